@@ -0,0 +1,63 @@
+//! Draw calls a cube-heavy scene needs with and without instancing.
+//! Run with `cargo bench --bench instancing`.
+
+use ZeroEngine::modules::ecs::entity::{MeshType, spawn_entity};
+use ZeroEngine::modules::ecs::world::World;
+use ZeroEngine::modules::render::batching::{BatchKey, batch_by_key};
+use glam::{Vec3, Vec4};
+use std::time::Instant;
+
+const ITERATIONS: u32 = 100;
+
+fn scene(entities: usize) -> World {
+    let mut world = World::new();
+    for i in 0..entities {
+        // Mostly cubes in a handful of colors, a few triangles
+        let mesh = if i % 10 == 0 {
+            MeshType::Triangle
+        } else {
+            MeshType::Cube
+        };
+        let hue = (i % 4) as f32 / 4.0;
+        let position = Vec3::new((i % 100) as f32, 0.0, (i / 100) as f32);
+        spawn_entity(
+            &mut world,
+            format!("entity {}", i),
+            position,
+            Vec3::ONE,
+            mesh,
+            Vec4::new(hue, 1.0 - hue, 0.5, 1.0),
+        );
+    }
+    world
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>16} {:>16} {:>14}",
+        "entities", "draws (naive)", "draws (batched)", "batching time"
+    );
+    for entities in [100, 1_000, 10_000, 50_000] {
+        let world = scene(entities);
+        let renderables = world.get_renderable_entities_with_ids();
+
+        let start = Instant::now();
+        let mut batches = Vec::new();
+        for _ in 0..ITERATIONS {
+            let keys = renderables
+                .iter()
+                .enumerate()
+                .map(|(index, (_, mesh, _, material))| (index, BatchKey::new(*mesh, material)));
+            batches = batch_by_key(keys, 0).1;
+        }
+        let elapsed = start.elapsed() / ITERATIONS;
+
+        println!(
+            "{:>10} {:>16} {:>16} {:>14?}",
+            entities,
+            renderables.len(),
+            batches.len(),
+            elapsed
+        );
+    }
+}
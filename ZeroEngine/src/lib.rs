@@ -1,8 +1,11 @@
+use crate::modules::assets::{AssetGroup, Assets, MeshHandle, SceneHandle};
+use crate::modules::console::Console;
 use crate::modules::ecs::entity::Entity;
 use crate::modules::ecs::entity::MeshType;
 use crate::modules::ecs::scripts::ScriptRegistry;
 use crate::modules::ecs::systems::init_scripts;
 use crate::modules::ecs::systems::update_and_render;
+use crate::modules::ecs::world::EntityId;
 use crate::modules::ecs::world::World;
 use crate::modules::state::State;
 use anyhow::Result;
@@ -17,6 +20,16 @@ pub mod modules; // any additional helpers
 pub struct Engine {
     pub world: World,
     pub scripts: ScriptRegistry,
+    pub assets: Assets,
+    /// Everything the current scene asked for, used for loading progress
+    pub scene_assets: AssetGroup,
+    /// Path the current scene was loaded from
+    pub scene_path: Option<String>,
+    /// Developer console commands
+    pub console: Console,
+    // glTF scenes waiting for their file, entities waiting for their mesh's material
+    pub(crate) pending_scenes: Vec<SceneHandle>,
+    pub(crate) pending_materials: Vec<(EntityId, MeshHandle)>,
 }
 
 impl Engine {
@@ -24,46 +37,80 @@ impl Engine {
     pub fn new() -> Self {
         let mut world = World::new();
         let scripts = ScriptRegistry::new();
+        let mut assets = Assets::new();
 
         // Example: spawn a cube (you can move this to a helper function)
         use glam::{Vec3, Vec4};
         use modules::ecs::entity::{Entity, MeshType};
 
         // Initialize scripts
-        if let Err(e) = modules::ecs::systems::init_scripts(&mut world, &mut scripts.clone()) {
-            eprintln!("Failed to initialize scripts: {}", e);
+        if let Err(e) =
+            modules::ecs::systems::init_scripts(&mut world, &mut scripts.clone(), &mut assets)
+        {
+            log_error!("Failed to initialize scripts: {}", e);
         }
 
-        Engine { world, scripts }
+        Engine {
+            world,
+            scripts,
+            assets,
+            scene_assets: AssetGroup::new(),
+            scene_path: None,
+            console: Console::new(),
+            pending_scenes: Vec::new(),
+            pending_materials: Vec::new(),
+        }
     }
 
     /// Update world and render; runtime/editor will provide `State` and delta time
 
     pub fn update_and_render(&mut self, state: &mut State, dt: f32) -> Result<(), String> {
-        modules::ecs::systems::update_and_render(&mut self.world, state, &mut self.scripts, dt)
-            .map_err(|e| e.to_string())
+        self.poll_assets();
+        modules::ecs::systems::update_and_render(
+            &mut self.world,
+            state,
+            &mut self.scripts,
+            &mut self.assets,
+            dt,
+        )
+        .map_err(|e| e.to_string())
     }
 
-    pub fn init_with_state(&mut self, state: &mut State, path: String) {
+    /// Start over with the scene at `path`. The world is set up even if the
+    /// scene fails to load, the error is returned afterwards.
+    pub fn init_with_state(&mut self, state: &mut State, path: String) -> Result<(), String> {
         self.world = World::new();
         self.scripts = ScriptRegistry::new();
+        self.reset_assets();
 
-        if let Err(e) = self.load_scene(path) {
-            eprintln!("failed to load scene: {}", e);
-        }
+        let loaded = self.load_scene(path);
+        state.sync_meshes(&mut self.assets);
+        state.sync_textures(&mut self.assets);
+        state.sync_cubemaps(&mut self.assets);
+        state.sync_shaders(&mut self.assets);
+        state.sync_fonts(&self.assets);
 
         // initialize scripts
-        if let Err(e) = init_scripts(&mut self.world, &mut self.scripts) {
-            eprintln!("Failed to init scripts: {}", e);
+        if let Err(e) = init_scripts(&mut self.world, &mut self.scripts, &mut self.assets) {
+            log_error!("Failed to init scripts: {}", e);
         }
 
         // finally, let State know to resize GPU buffers if needed
         state.resize(state.get_window().inner_size());
+        loaded
     }
 
     /// Optional: helper to reset or initialize world
     pub fn init_world(&mut self) {
         self.world = World::new();
         self.scripts = ScriptRegistry::new();
+        self.reset_assets();
+    }
+
+    fn reset_assets(&mut self) {
+        self.assets = Assets::new();
+        self.scene_assets.clear();
+        self.pending_scenes.clear();
+        self.pending_materials.clear();
     }
 }
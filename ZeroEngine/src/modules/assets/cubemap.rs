@@ -0,0 +1,139 @@
+use crate::modules::assets::CubemapAsset;
+use glam::{Vec3, Vec4};
+use std::f32::consts::PI;
+use std::path::Path;
+
+// ============================================================================
+// CUBEMAP IMPORT
+// ============================================================================
+
+/// Faces are stored in the order GPUs expect: +X, -X, +Y, -Y, +Z, -Z
+pub const FACE_NAMES: [&str; 6] = ["+x", "-x", "+y", "-y", "+z", "-z"];
+
+/// Largest face an equirectangular image is resampled to
+const MAX_FACE_SIZE: u32 = 1024;
+
+/// Direction through the texel at `(u, v)` of `face`, both in -1..1 with `v`
+/// pointing down the image
+pub fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    let direction = match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    };
+    direction.normalize()
+}
+
+/// Six face images, in `FACE_NAMES` order. Every face has to be the same
+/// square size; colors are sRGB and converted to linear.
+pub fn load_face_files(paths: &[impl AsRef<Path>; 6]) -> Result<CubemapAsset, String> {
+    let mut size = None;
+    let mut pixels = Vec::new();
+    for (path, name) in paths.iter().zip(FACE_NAMES) {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| format!("skybox face {} '{}': {}", name, path.display(), e))?
+            .to_rgba8();
+        let expected = *size.get_or_insert(image.width());
+        if image.width() != expected || image.height() != expected {
+            return Err(format!(
+                "skybox face {} '{}' is {}x{}, expected {}x{}",
+                name,
+                path.display(),
+                image.width(),
+                image.height(),
+                expected,
+                expected
+            ));
+        }
+        pixels.extend(image.pixels().map(|p| {
+            let [r, g, b, a] = p.0.map(|c| c as f32 / 255.0);
+            Vec4::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+        }));
+    }
+
+    Ok(CubemapAsset {
+        size: size.unwrap_or(1),
+        pixels,
+    })
+}
+
+/// Equirectangular panorama (usually `.hdr`), resampled into six faces a
+/// quarter of its width
+pub fn load_equirect_file(path: &Path) -> Result<CubemapAsset, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?;
+    let (width, height) = (image.width(), image.height());
+    let pixels = linear_pixels(&image);
+    let face_size = (width / 4).clamp(1, MAX_FACE_SIZE);
+    Ok(equirect_to_cube(width, height, &pixels, face_size))
+}
+
+/// Linear colors of an image. Float images (`.hdr`, `.exr`) already are,
+/// 8 and 16 bit ones are sRGB like skybox faces.
+pub fn linear_pixels(image: &image::DynamicImage) -> Vec<Vec4> {
+    let is_float = matches!(
+        image,
+        image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_)
+    );
+    image
+        .to_rgba32f()
+        .pixels()
+        .map(|p| {
+            let [r, g, b, a] = p.0;
+            if is_float {
+                Vec4::new(r, g, b, a)
+            } else {
+                Vec4::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+            }
+        })
+        .collect()
+}
+
+/// Resample a panorama with longitude across and latitude down into cube
+/// faces of `face_size` texels. -Z is the middle of the image.
+pub fn equirect_to_cube(width: u32, height: u32, pixels: &[Vec4], face_size: u32) -> CubemapAsset {
+    let texel = |x: i64, y: i64| {
+        let x = x.rem_euclid(width as i64) as u32;
+        let y = y.clamp(0, height as i64 - 1) as u32;
+        pixels[(y * width + x) as usize]
+    };
+
+    let mut out = Vec::with_capacity((6 * face_size * face_size) as usize);
+    for face in 0..6 {
+        for y in 0..face_size {
+            for x in 0..face_size {
+                let u = (x as f32 + 0.5) / face_size as f32 * 2.0 - 1.0;
+                let v = (y as f32 + 0.5) / face_size as f32 * 2.0 - 1.0;
+                let direction = face_direction(face, u, v);
+
+                // Bilinear lookup, wrapping around horizontally
+                let longitude = direction.x.atan2(-direction.z) / (2.0 * PI) + 0.5;
+                let latitude = direction.y.clamp(-1.0, 1.0).acos() / PI;
+                let sx = longitude * width as f32 - 0.5;
+                let sy = latitude * height as f32 - 0.5;
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = texel(x0, y0).lerp(texel(x0 + 1, y0), fx);
+                let bottom = texel(x0, y0 + 1).lerp(texel(x0 + 1, y0 + 1), fx);
+                out.push(top.lerp(bottom, fy));
+            }
+        }
+    }
+
+    CubemapAsset {
+        size: face_size,
+        pixels: out,
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
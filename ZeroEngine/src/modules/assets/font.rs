@@ -0,0 +1,144 @@
+use crate::modules::ecs::text::TextAlign;
+use ab_glyph::{Font as _, FontArc, GlyphId, ScaleFont};
+use glam::Vec2;
+use std::fmt;
+use std::path::Path;
+
+// ============================================================================
+// FONTS
+// ============================================================================
+
+/// Parsed TrueType or OpenType font. Cheap to clone, the font data is
+/// shared.
+#[derive(Clone)]
+pub struct Font {
+    font: FontArc,
+}
+
+impl fmt::Debug for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Font({} glyphs)", self.font.glyph_count())
+    }
+}
+
+/// Glyph placed by `Font::layout`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacedGlyph {
+    pub glyph: u16,
+    /// Pen position on the baseline in pixels from the top left of the
+    /// text's box, +Y down
+    pub position: Vec2,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PlacedGlyph>,
+    /// Width of the widest line and height of all lines, in pixels
+    pub size: Vec2,
+    /// Distance from the top of the box to the first baseline
+    pub baseline: f32,
+}
+
+/// Coverage of a rasterized glyph, one byte per pixel
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphBitmap {
+    pub width: u32,
+    pub height: u32,
+    /// Top left corner relative to the pen position, +Y down
+    pub offset: Vec2,
+    pub coverage: Vec<u8>,
+}
+
+impl Font {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        let font = FontArc::try_from_vec(bytes).map_err(|e| e.to_string())?;
+        Ok(Self { font })
+    }
+
+    /// Font that ships with the engine, used when a text names none
+    pub fn builtin() -> Self {
+        let font = FontArc::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+            .expect("built-in font is valid");
+        Self { font }
+    }
+
+    /// Distance between the baselines of two lines at `size`
+    pub fn line_height(&self, size: f32) -> f32 {
+        let scaled = self.font.as_scaled(size);
+        scaled.height() + scaled.line_gap()
+    }
+
+    /// Lay out `text` at `size` pixels per line, breaking lines at `\n`.
+    /// Characters the font lacks show its missing glyph.
+    pub fn layout(&self, text: &str, size: f32, align: TextAlign) -> TextLayout {
+        let scaled = self.font.as_scaled(size);
+        let line_height = scaled.height() + scaled.line_gap();
+
+        let mut lines = Vec::new();
+        for line in text.split('\n') {
+            let mut glyphs = Vec::new();
+            let mut x = 0.0;
+            let mut previous: Option<GlyphId> = None;
+            for c in line.chars().filter(|c| !c.is_control()) {
+                let id = scaled.glyph_id(c);
+                if let Some(previous) = previous {
+                    x += scaled.kern(previous, id);
+                }
+                glyphs.push((id, x));
+                x += scaled.h_advance(id);
+                previous = Some(id);
+            }
+            lines.push((glyphs, x));
+        }
+
+        let width = lines.iter().map(|(_, w)| *w).fold(0.0, f32::max);
+        let mut layout = TextLayout {
+            glyphs: Vec::new(),
+            size: Vec2::new(
+                width,
+                scaled.height() + line_height * (lines.len() - 1) as f32,
+            ),
+            baseline: scaled.ascent(),
+        };
+        for (index, (glyphs, line_width)) in lines.into_iter().enumerate() {
+            let start = (width - line_width) * align.fraction();
+            let baseline = scaled.ascent() + line_height * index as f32;
+            layout
+                .glyphs
+                .extend(glyphs.into_iter().map(|(id, x)| PlacedGlyph {
+                    glyph: id.0,
+                    position: Vec2::new(start + x, baseline),
+                }));
+        }
+        layout
+    }
+
+    /// Coverage of a glyph at `size` pixels per line, `None` for glyphs
+    /// without an outline such as spaces
+    pub fn rasterize(&self, glyph: u16, size: f32) -> Option<GlyphBitmap> {
+        let glyph = GlyphId(glyph).with_scale(size);
+        let outlined = self.font.outline_glyph(glyph)?;
+        let bounds = outlined.px_bounds();
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut coverage = vec![0; (width * height) as usize];
+        outlined.draw(|x, y, c| {
+            if x < width && y < height {
+                coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+        Some(GlyphBitmap {
+            width,
+            height,
+            offset: Vec2::new(bounds.min.x, bounds.min.y),
+            coverage,
+        })
+    }
+}
+
+pub fn load_font(path: impl AsRef<Path>) -> Result<Font, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    Font::from_bytes(bytes)
+}
@@ -0,0 +1,241 @@
+use crate::log_error;
+use crate::modules::assets::{MaterialData, MeshData};
+use crate::modules::ecs::entity::Projection;
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// IMPORTED TYPES
+// ============================================================================
+
+#[derive(Clone)]
+pub struct GltfCamera {
+    pub name: String,
+    pub fov: f32, // Vertical field of view in degrees
+    pub near: f32,
+    pub far: f32,
+    pub projection: Projection,
+}
+
+/// A glTF mesh with all of its triangle primitives merged
+pub struct GltfMesh {
+    pub name: String,
+    pub data: MeshData,
+    pub material: MaterialData,
+}
+
+#[derive(Clone)]
+pub struct GltfNode {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub translation: glam::Vec3,
+    pub rotation: glam::Quat,
+    pub scale: glam::Vec3,
+    pub mesh: Option<usize>,
+    pub camera: Option<GltfCamera>,
+}
+
+/// Flattened node list of the default scene. Parents always come before
+/// their children.
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    pub meshes: Vec<GltfMesh>,
+}
+
+impl GltfScene {
+    /// Mesh picked by index (`"0"`) or by name
+    pub fn find_mesh(&self, selector: &str) -> Option<usize> {
+        match selector.parse::<usize>() {
+            Ok(index) => (index < self.meshes.len()).then_some(index),
+            Err(_) => self.meshes.iter().position(|m| m.name == selector),
+        }
+    }
+}
+
+// ============================================================================
+// LOADING
+// ============================================================================
+
+/// Import a `.gltf` or `.glb` file
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<GltfScene> {
+    let path = path.as_ref();
+    let (document, buffers, _images) = ::gltf::import(path)
+        .with_context(|| format!("failed to import glTF '{}'", path.display()))?;
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        meshes.push(
+            read_mesh(&mesh, &buffers, path.parent())
+                .with_context(|| format!("in mesh {} of '{}'", mesh.index(), path.display()))?,
+        );
+    }
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next());
+
+    let mut nodes = Vec::new();
+    if let Some(scene) = scene {
+        for root in scene.nodes() {
+            flatten_node(&root, None, &mut nodes);
+        }
+    } else if meshes.is_empty() {
+        bail!("'{}' contains no scenes or meshes", path.display());
+    }
+
+    Ok(GltfScene { nodes, meshes })
+}
+
+fn flatten_node(node: &::gltf::Node, parent: Option<usize>, out: &mut Vec<GltfNode>) {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    let name = node
+        .name()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Node {}", node.index()));
+
+    let camera = node.camera().map(|camera| match camera.projection() {
+        ::gltf::camera::Projection::Perspective(p) => GltfCamera {
+            name: camera.name().unwrap_or(&name).to_string(),
+            fov: p.yfov().to_degrees(),
+            near: p.znear(),
+            far: p.zfar().unwrap_or(1000.0),
+            projection: Projection::Perspective,
+        },
+        // ymag is half the visible height
+        ::gltf::camera::Projection::Orthographic(o) => GltfCamera {
+            name: camera.name().unwrap_or(&name).to_string(),
+            fov: 60.0,
+            near: o.znear(),
+            far: o.zfar(),
+            projection: Projection::Orthographic {
+                size: o.ymag() * 2.0,
+            },
+        },
+    });
+
+    let index = out.len();
+    out.push(GltfNode {
+        name,
+        parent,
+        translation: glam::Vec3::from(translation),
+        rotation: glam::Quat::from_array(rotation),
+        scale: glam::Vec3::from(scale),
+        mesh: node.mesh().map(|m| m.index()),
+        camera,
+    });
+
+    for child in node.children() {
+        flatten_node(&child, Some(index), out);
+    }
+}
+
+fn read_mesh(
+    mesh: &::gltf::Mesh,
+    buffers: &[::gltf::buffer::Data],
+    base_dir: Option<&Path>,
+) -> Result<GltfMesh> {
+    let name = mesh
+        .name()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Mesh {}", mesh.index()));
+
+    let mut data = MeshData::default();
+    let mut material = None;
+
+    for primitive in mesh.primitives() {
+        if primitive.mode() != ::gltf::mesh::Mode::Triangles {
+            log_error!(
+                "Skipping non-triangle primitive {} in mesh '{}'",
+                primitive.index(),
+                name
+            );
+            continue;
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let positions: Vec<[f32; 3]> = reader
+            .read_positions()
+            .context("primitive has no POSITION attribute")?
+            .collect();
+
+        let normals: Vec<[f32; 3]> = reader
+            .read_normals()
+            .map(|n| n.collect())
+            .unwrap_or_default();
+
+        let uvs: Vec<[f32; 2]> = reader
+            .read_tex_coords(0)
+            .map(|t| t.into_f32().collect())
+            .unwrap_or_default();
+
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        data.append(&MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+        });
+
+        // The first primitive's material represents the whole mesh
+        if material.is_none() {
+            material = Some(read_material(&primitive.material(), &name, base_dir));
+        }
+    }
+
+    Ok(GltfMesh {
+        material: material.unwrap_or_else(|| MaterialData::new(&name)),
+        name,
+        data,
+    })
+}
+
+fn read_material(
+    source: &::gltf::Material,
+    mesh_name: &str,
+    base_dir: Option<&Path>,
+) -> MaterialData {
+    let pbr = source.pbr_metallic_roughness();
+    let mut material = MaterialData::new(source.name().unwrap_or(mesh_name));
+    material.diffuse = glam::Vec4::from(pbr.base_color_factor());
+    material.metallic = pbr.metallic_factor();
+    material.roughness = pbr.roughness_factor();
+    material.emissive = glam::Vec3::from(source.emissive_factor());
+
+    material.diffuse_texture = pbr
+        .base_color_texture()
+        .and_then(|t| texture_path(&t.texture(), base_dir));
+    material.metallic_roughness_texture = pbr
+        .metallic_roughness_texture()
+        .and_then(|t| texture_path(&t.texture(), base_dir));
+    material.emissive_texture = source
+        .emissive_texture()
+        .and_then(|t| texture_path(&t.texture(), base_dir));
+    material.normal_texture = source
+        .normal_texture()
+        .and_then(|t| texture_path(&t.texture(), base_dir));
+    material
+}
+
+/// File next to the glTF that a texture points at. Images embedded in
+/// buffers or data URIs are not supported yet.
+fn texture_path(texture: &::gltf::Texture, base_dir: Option<&Path>) -> Option<PathBuf> {
+    match texture.source().source() {
+        ::gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => {
+            Some(match base_dir {
+                Some(dir) => dir.join(uri),
+                None => PathBuf::from(uri),
+            })
+        }
+        _ => {
+            log_error!(
+                "Skipping embedded glTF image {}: only external image files are supported",
+                texture.source().index()
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::modules::assets::gltf::GltfNode;
+use crate::modules::assets::{CubemapAsset, MeshAsset, ScriptAsset, ShaderAsset, TextureAsset};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// ============================================================================
+// JOB RESULTS
+// ============================================================================
+
+/// Output of a background load, routed back to the matching store by `Assets::poll`
+pub enum Loaded {
+    Mesh {
+        /// `path#index` of a glTF mesh that was picked by name
+        alias: Option<String>,
+        result: Result<Box<MeshAsset>, String>,
+    },
+    Texture(Result<TextureAsset, String>),
+    Cubemap(Result<CubemapAsset, String>),
+    Script(Result<ScriptAsset, String>),
+    Shader(Result<ShaderAsset, String>),
+    GltfScene {
+        path: PathBuf,
+        result: Result<(Vec<GltfNode>, Vec<MeshAsset>), String>,
+    },
+}
+
+/// Store a job's result goes to, so a job that panics can still fail its
+/// handle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadKind {
+    Mesh,
+    Texture,
+    Cubemap,
+    Script,
+    Shader,
+    GltfScene,
+}
+
+impl Loaded {
+    pub fn failed(kind: LoadKind, error: String) -> Self {
+        match kind {
+            LoadKind::Mesh => Loaded::Mesh {
+                alias: None,
+                result: Err(error),
+            },
+            LoadKind::Texture => Loaded::Texture(Err(error)),
+            LoadKind::Cubemap => Loaded::Cubemap(Err(error)),
+            LoadKind::Script => Loaded::Script(Err(error)),
+            LoadKind::Shader => Loaded::Shader(Err(error)),
+            LoadKind::GltfScene => Loaded::GltfScene {
+                path: PathBuf::new(),
+                result: Err(error),
+            },
+        }
+    }
+}
+
+type Job = (u32, LoadKind, Box<dyn FnOnce() -> Loaded + Send>);
+
+// ============================================================================
+// WORKER POOL
+// ============================================================================
+
+/// Fixed pool of threads that run file IO and parsing off the main thread.
+/// Workers exit on their own once the loader is dropped and the job channel
+/// closes.
+pub struct AssetLoader {
+    jobs: Sender<Job>,
+    results: Receiver<(u32, Loaded)>,
+    in_flight: usize,
+}
+
+impl AssetLoader {
+    pub fn new(worker_count: usize) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        for index in 0..worker_count.max(1) {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            thread::Builder::new()
+                .name(format!("asset-loader-{}", index))
+                .spawn(move || worker_loop(job_rx, result_tx))
+                .expect("failed to spawn asset loader thread");
+        }
+
+        Self {
+            jobs: job_tx,
+            results: result_rx,
+            in_flight: 0,
+        }
+    }
+
+    /// Default pool size based on the available cores
+    pub fn default_worker_count() -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get().clamp(1, 4))
+            .unwrap_or(2)
+    }
+
+    pub fn submit(
+        &mut self,
+        kind: LoadKind,
+        id: u32,
+        job: impl FnOnce() -> Loaded + Send + 'static,
+    ) {
+        if self.jobs.send((id, kind, Box::new(job))).is_ok() {
+            self.in_flight += 1;
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Completed loads, without blocking
+    pub fn try_recv(&mut self) -> Option<(u32, Loaded)> {
+        let result = self.results.try_recv().ok();
+        if result.is_some() {
+            self.in_flight -= 1;
+        }
+        result
+    }
+
+    /// Block until one load completes or `timeout` passes. Returns `None`
+    /// right away when nothing is in flight.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<(u32, Loaded)> {
+        if self.in_flight == 0 {
+            return None;
+        }
+
+        match self.results.recv_timeout(timeout) {
+            Ok(result) => {
+                self.in_flight -= 1;
+                Some(result)
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
+
+fn worker_loop(jobs: Arc<Mutex<Receiver<Job>>>, results: Sender<(u32, Loaded)>) {
+    loop {
+        let next = match jobs.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+
+        let Ok((id, kind, job)) = next else {
+            return;
+        };
+
+        // A loader that panics fails its handle instead of taking the worker
+        // down and leaving the handle pending forever
+        let loaded = panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Loaded::failed(kind, format!("loader panicked: {}", message))
+        });
+
+        if results.send((id, loaded)).is_err() {
+            return;
+        }
+    }
+}
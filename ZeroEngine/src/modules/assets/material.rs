@@ -0,0 +1,148 @@
+use crate::modules::assets::RENDER_TARGET_PREFIX;
+use crate::modules::ecs::components::{BlendMode, SamplerSettings, TextureFilter, TextureWrap};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// MATERIAL FILES
+// ============================================================================
+
+/// A `.material` file: JSON description of a PBR material. Texture paths are
+/// relative to the file itself, `target:name` uses a camera's render target.
+///
+/// ```json
+/// {
+///   "base_color": [1.0, 1.0, 1.0, 1.0],
+///   "base_color_texture": "textures/brick.png",
+///   "metallic": 0.0,
+///   "roughness": 0.8,
+///   "normal_texture": "textures/brick_normal.png",
+///   "sampler": { "filter": "linear", "wrap": "repeat" },
+///   "shader": "shaders/brick.wgsl",
+///   "blend": "alpha"
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MaterialFile {
+    pub base_color: glam::Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: glam::Vec3,
+    pub normal_scale: f32,
+    pub base_color_texture: Option<PathBuf>,
+    pub metallic_roughness_texture: Option<PathBuf>,
+    pub emissive_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+    pub sampler: SamplerSettings,
+    /// Custom WGSL shader, see `render::shader` for the interface
+    pub shader: Option<PathBuf>,
+    /// `None` picks alpha blending if the base color is see-through
+    pub blend: Option<BlendMode>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMaterial {
+    #[serde(default = "white")]
+    base_color: [f32; 4],
+    #[serde(default)]
+    metallic: f32,
+    #[serde(default = "default_roughness")]
+    roughness: f32,
+    #[serde(default)]
+    emissive: [f32; 3],
+    #[serde(default = "one")]
+    normal_scale: f32,
+    base_color_texture: Option<String>,
+    metallic_roughness_texture: Option<String>,
+    emissive_texture: Option<String>,
+    normal_texture: Option<String>,
+    #[serde(default)]
+    sampler: RawSampler,
+    shader: Option<String>,
+    blend: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RawSampler {
+    filter: Option<String>,
+    wrap: Option<String>,
+}
+
+fn white() -> [f32; 4] {
+    [1.0; 4]
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Blend mode by its name in material and scene files
+pub fn parse_blend_mode(name: &str) -> Result<BlendMode, String> {
+    match name {
+        "opaque" => Ok(BlendMode::Opaque),
+        "alpha" => Ok(BlendMode::Alpha),
+        "additive" => Ok(BlendMode::Additive),
+        "multiply" => Ok(BlendMode::Multiply),
+        other => Err(format!("unknown blend mode '{}'", other)),
+    }
+}
+
+/// Texture filter by its name in material and sprite sheet files
+pub fn parse_texture_filter(name: &str) -> Result<TextureFilter, String> {
+    match name {
+        "linear" => Ok(TextureFilter::Linear),
+        "nearest" => Ok(TextureFilter::Nearest),
+        other => Err(format!("unknown texture filter '{}'", other)),
+    }
+}
+
+pub fn load_material(path: impl AsRef<Path>) -> Result<MaterialFile, String> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_material(&source, path.parent())
+}
+
+/// Parse a material file. `base_dir` is used to resolve texture paths.
+pub fn parse_material(source: &str, base_dir: Option<&Path>) -> Result<MaterialFile, String> {
+    let raw: RawMaterial = serde_json::from_str(source).map_err(|e| e.to_string())?;
+
+    let resolve = |file: Option<String>| {
+        file.map(|file| match base_dir {
+            Some(dir) if !file.starts_with(RENDER_TARGET_PREFIX) => dir.join(file),
+            _ => PathBuf::from(file),
+        })
+    };
+
+    let filter = match raw.sampler.filter.as_deref() {
+        Some(name) => parse_texture_filter(name)?,
+        None => TextureFilter::Linear,
+    };
+    let wrap = match raw.sampler.wrap.as_deref() {
+        None | Some("repeat") => TextureWrap::Repeat,
+        Some("mirror") => TextureWrap::MirrorRepeat,
+        Some("clamp") => TextureWrap::ClampToEdge,
+        Some(other) => return Err(format!("unknown texture wrap mode '{}'", other)),
+    };
+    let blend = raw.blend.as_deref().map(parse_blend_mode).transpose()?;
+
+    Ok(MaterialFile {
+        base_color: glam::Vec4::from(raw.base_color),
+        metallic: raw.metallic.clamp(0.0, 1.0),
+        roughness: raw.roughness.clamp(0.0, 1.0),
+        emissive: glam::Vec3::from(raw.emissive),
+        normal_scale: raw.normal_scale,
+        base_color_texture: resolve(raw.base_color_texture),
+        metallic_roughness_texture: resolve(raw.metallic_roughness_texture),
+        emissive_texture: resolve(raw.emissive_texture),
+        normal_texture: resolve(raw.normal_texture),
+        sampler: SamplerSettings { filter, wrap },
+        shader: resolve(raw.shader),
+        blend,
+    })
+}
@@ -0,0 +1,1204 @@
+pub mod cubemap;
+pub mod font;
+pub mod gltf;
+pub mod loader;
+pub mod material;
+pub mod obj;
+pub mod sprite_sheet;
+pub mod store;
+pub mod tiled;
+
+pub use store::{AssetEntry, AssetStatus, AssetStore, Handle};
+
+use font::Font;
+use loader::{AssetLoader, LoadKind, Loaded};
+use sprite_sheet::SpriteSheet;
+
+use crate::log_error;
+use crate::modules::ecs::components::{Background, BlendMode, Material};
+use crate::modules::ecs::world::World;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+// ============================================================================
+// CPU-SIDE ASSET DATA
+// ============================================================================
+
+/// Mesh geometry as it comes out of an importer, before it is uploaded to the GPU.
+/// `normals` and `uvs` are either empty or have one entry per position. UVs
+/// have their origin at the top left of the image, like glTF and wgpu.
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty() || self.indices.is_empty()
+    }
+
+    /// Smooth per-vertex normals from the triangle list, for meshes imported
+    /// without any. Faces contribute in proportion to their area.
+    pub fn smooth_normals(&self) -> Vec<[f32; 3]> {
+        let mut normals = vec![glam::Vec3::ZERO; self.positions.len()];
+        for tri in self.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+            if a.max(b).max(c) >= self.positions.len() {
+                continue;
+            }
+            let [pa, pb, pc] = [a, b, c].map(|i| glam::Vec3::from(self.positions[i]));
+            let face = (pb - pa).cross(pc - pa);
+            for i in [a, b, c] {
+                normals[i] += face;
+            }
+        }
+
+        normals
+            .into_iter()
+            .map(|n| n.try_normalize().unwrap_or(glam::Vec3::Y).to_array())
+            .collect()
+    }
+
+    /// Append another mesh, offsetting its indices. Normals and UVs are only
+    /// kept if both sides have them.
+    pub fn append(&mut self, other: &MeshData) {
+        let base = self.positions.len() as u32;
+        let was_empty = self.positions.is_empty();
+
+        if was_empty || (!self.normals.is_empty() && !other.normals.is_empty()) {
+            self.normals.extend_from_slice(&other.normals);
+        } else {
+            self.normals.clear();
+        }
+
+        if was_empty || (!self.uvs.is_empty() && !other.uvs.is_empty()) {
+            self.uvs.extend_from_slice(&other.uvs);
+        } else {
+            self.uvs.clear();
+        }
+
+        self.positions.extend_from_slice(&other.positions);
+        self.indices.extend(other.indices.iter().map(|i| i + base));
+    }
+}
+
+/// Surface description produced by importers (MTL and glTF)
+#[derive(Clone, Debug)]
+pub struct MaterialData {
+    pub name: String,
+    pub diffuse: glam::Vec4,
+    pub ambient: glam::Vec3,
+    pub shininess: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: glam::Vec3,
+    pub diffuse_texture: Option<PathBuf>,
+    pub metallic_roughness_texture: Option<PathBuf>,
+    pub emissive_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+}
+
+impl MaterialData {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            diffuse: glam::Vec4::ONE,
+            ambient: glam::Vec3::ZERO,
+            shininess: 0.0,
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: glam::Vec3::ZERO,
+            diffuse_texture: None,
+            metallic_roughness_texture: None,
+            emissive_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+// ============================================================================
+// ASSET TYPES
+// ============================================================================
+
+pub struct MeshAsset {
+    pub data: MeshData,
+    /// Default material that came with the mesh file
+    pub material: Option<MaterialData>,
+}
+
+/// Decoded RGBA8 image
+pub struct TextureAsset {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl TextureAsset {
+    /// Number of levels in a full mip chain down to 1x1
+    pub fn mip_level_count(&self) -> u32 {
+        32 - self.width.max(self.height).max(1).leading_zeros()
+    }
+
+    /// Next mip level: half the size (at least 1x1), each texel the average
+    /// of the 2x2 block above it
+    pub fn downsample(&self) -> TextureAsset {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+
+        let texel = |x: u32, y: u32, c: u32| {
+            let x = x.min(self.width - 1);
+            let y = y.min(self.height - 1);
+            self.pixels[((y * self.width + x) * 4 + c) as usize] as u32
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x * 2, y * 2);
+                for c in 0..4 {
+                    let sum = texel(sx, sy, c)
+                        + texel(sx + 1, sy, c)
+                        + texel(sx, sy + 1, c)
+                        + texel(sx + 1, sy + 1, c);
+                    pixels.push(((sum + 2) / 4) as u8);
+                }
+            }
+        }
+
+        TextureAsset {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Six square faces of linear HDR color, face after face in the order of
+/// `cubemap::FACE_NAMES`, rows top to bottom
+pub struct CubemapAsset {
+    pub size: u32,
+    pub pixels: Vec<glam::Vec4>,
+}
+
+/// Raw WASM module bytes, shared by every instance of the script
+pub struct ScriptAsset {
+    pub bytes: Arc<[u8]>,
+}
+
+/// WGSL source of a custom entity shader, already checked against the
+/// engine's shader interface
+pub struct ShaderAsset {
+    pub source: String,
+}
+
+pub enum SceneAsset {
+    Json(String),
+    Gltf {
+        nodes: Vec<gltf::GltfNode>,
+        /// Indexed like the glTF file's meshes
+        meshes: Vec<MeshHandle>,
+    },
+}
+
+pub type MeshHandle = Handle<MeshAsset>;
+pub type TextureHandle = Handle<TextureAsset>;
+pub type CubemapHandle = Handle<CubemapAsset>;
+pub type MaterialHandle = Handle<Material>;
+pub type SpriteSheetHandle = Handle<SpriteSheet>;
+pub type FontHandle = Handle<Font>;
+pub type ScriptHandle = Handle<ScriptAsset>;
+pub type ShaderHandle = Handle<ShaderAsset>;
+pub type SceneHandle = Handle<SceneAsset>;
+
+/// Ids 0 and 1 are the built-in triangle and cube meshes
+pub const TRIANGLE_MESH: MeshHandle = Handle::from_id(0);
+pub const CUBE_MESH: MeshHandle = Handle::from_id(1);
+
+/// Id 0 is the font that ships with the engine
+pub const DEFAULT_FONT: FontHandle = Handle::from_id(0);
+
+/// Name of the marker file at the top of a project directory
+pub const PROJECT_FILE: &str = "Project.zero";
+
+/// Texture references starting with this name a render target, not a file
+pub const RENDER_TARGET_PREFIX: &str = "target:";
+
+/// How often shader files are checked for changes
+const SHADER_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Untyped reference to an asset in any store
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetId {
+    Mesh(MeshHandle),
+    Texture(TextureHandle),
+    Cubemap(CubemapHandle),
+    Material(MaterialHandle),
+    SpriteSheet(SpriteSheetHandle),
+    Font(FontHandle),
+    Script(ScriptHandle),
+    Shader(ShaderHandle),
+    Scene(SceneHandle),
+}
+
+/// Set of assets that belong together, e.g. everything a scene needs
+#[derive(Clone, Debug, Default)]
+pub struct AssetGroup {
+    ids: Vec<AssetId>,
+}
+
+impl AssetGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, id: AssetId) {
+        if !self.ids.contains(&id) {
+            self.ids.push(id);
+        }
+    }
+
+    pub fn ids(&self) -> &[AssetId] {
+        &self.ids
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadProgress {
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    pub fn pending(&self) -> usize {
+        self.total - self.loaded - self.failed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Share of finished (loaded or failed) assets, 1.0 for an empty group
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.loaded + self.failed) as f32 / self.total as f32
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AssetError {
+    pub kind: &'static str,
+    pub key: String,
+    pub message: String,
+}
+
+// ============================================================================
+// ASSET MANAGER
+// ============================================================================
+
+pub struct Assets {
+    root: PathBuf,
+    pub meshes: AssetStore<MeshAsset>,
+    pub textures: AssetStore<TextureAsset>,
+    pub cubemaps: AssetStore<CubemapAsset>,
+    pub materials: AssetStore<Material>,
+    pub sprite_sheets: AssetStore<SpriteSheet>,
+    pub fonts: AssetStore<Font>,
+    pub scripts: AssetStore<ScriptAsset>,
+    pub shaders: AssetStore<ShaderAsset>,
+    pub scenes: AssetStore<SceneAsset>,
+
+    loader: AssetLoader,
+
+    // Hot reload: source file and last seen modification time per shader
+    watched_shaders: HashMap<u32, (PathBuf, Option<SystemTime>)>,
+    last_shader_check: Instant,
+
+    // GPU bookkeeping, drained by `State::sync_meshes`, `State::sync_textures`
+    // and `State::sync_cubemaps`
+    mesh_uploads: Vec<MeshHandle>,
+    freed_meshes: Vec<MeshHandle>,
+    texture_uploads: Vec<TextureHandle>,
+    freed_textures: Vec<TextureHandle>,
+    cubemap_uploads: Vec<CubemapHandle>,
+    freed_cubemaps: Vec<CubemapHandle>,
+    /// Shaders that were reloaded or freed since `State` last looked
+    shader_changes: Vec<ShaderHandle>,
+}
+
+impl Assets {
+    /// Asset manager rooted at the current working directory
+    pub fn new() -> Self {
+        Self::with_root(std::env::current_dir().unwrap_or_default())
+    }
+
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        let mut meshes = AssetStore::new();
+        meshes.insert_builtin(TRIANGLE_MESH.id(), "triangle", None);
+        meshes.insert_builtin(CUBE_MESH.id(), "cube", None);
+        let mut fonts = AssetStore::new();
+        fonts.insert_builtin(DEFAULT_FONT.id(), "font", Some(Font::builtin()));
+
+        Self {
+            root: root.into(),
+            meshes,
+            textures: AssetStore::new(),
+            cubemaps: AssetStore::new(),
+            materials: AssetStore::new(),
+            sprite_sheets: AssetStore::new(),
+            fonts,
+            scripts: AssetStore::new(),
+            shaders: AssetStore::new(),
+            scenes: AssetStore::new(),
+            loader: AssetLoader::new(AssetLoader::default_worker_count()),
+            watched_shaders: HashMap::new(),
+            last_shader_check: Instant::now(),
+            mesh_uploads: Vec::new(),
+            freed_meshes: Vec::new(),
+            texture_uploads: Vec::new(),
+            freed_textures: Vec::new(),
+            cubemap_uploads: Vec::new(),
+            freed_cubemaps: Vec::new(),
+            shader_changes: Vec::new(),
+        }
+    }
+
+    // ========================================================================
+    // PATHS
+    // ========================================================================
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn set_root(&mut self, root: impl Into<PathBuf>) {
+        self.root = root.into();
+    }
+
+    /// Resolve a project-relative path. Absolute paths are kept as they are.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        let joined = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        };
+        fs::canonicalize(&joined).unwrap_or(joined)
+    }
+
+    /// Deduplication key: resolved path plus the optional `#selector`
+    fn key_for(&self, reference: &str) -> (PathBuf, Option<String>, String) {
+        let (path, selector) = match reference.split_once('#') {
+            Some((path, selector)) => (path, Some(selector.to_string())),
+            None => (reference, None),
+        };
+
+        let resolved = self.resolve(path);
+        let key = match &selector {
+            Some(selector) => format!("{}#{}", resolved.display(), selector),
+            None => resolved.display().to_string(),
+        };
+        (resolved, selector, key)
+    }
+
+    // ========================================================================
+    // LOADING
+    // ========================================================================
+
+    /// Start loading an `.obj`, `.gltf` or `.glb` mesh in the background
+    /// (`file.glb#MeshName` or `file.glb#1` picks a mesh by name or index,
+    /// otherwise the first one is used). The handle is pending until `poll`
+    /// sees the result.
+    pub fn load_mesh(&mut self, reference: &str) -> MeshHandle {
+        match reference.to_lowercase().as_str() {
+            "triangle" => return TRIANGLE_MESH,
+            "cube" => return CUBE_MESH,
+            _ => {}
+        }
+
+        let (path, selector, key) = self.key_for(reference);
+        if let Some(handle) = self.meshes.find(&key) {
+            return handle;
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+
+        if !matches!(extension.as_str(), "obj" | "gltf" | "glb") {
+            let message = format!("unsupported mesh format '{}'", path.display());
+            return self.add_mesh(Some(key), Err(message));
+        }
+
+        let handle = self.meshes.insert_pending(Some(key));
+        self.loader.submit(LoadKind::Mesh, handle.id(), move || {
+            if extension == "obj" {
+                return Loaded::Mesh {
+                    alias: None,
+                    result: load_obj_mesh(&path).map(Box::new),
+                };
+            }
+
+            // glTF meshes picked by name are also found by index, like the
+            // meshes of an imported scene, once they have loaded
+            let by_name = selector
+                .as_ref()
+                .is_some_and(|s| s.parse::<usize>().is_err());
+            match load_gltf_mesh(&path, selector.as_deref()) {
+                Ok((mesh, index)) => Loaded::Mesh {
+                    alias: by_name.then(|| format!("{}#{}", path.display(), index)),
+                    result: Ok(Box::new(mesh)),
+                },
+                Err(e) => Loaded::Mesh {
+                    alias: None,
+                    result: Err(e),
+                },
+            }
+        });
+        handle
+    }
+
+    /// Register every mesh of a glTF file as `path#index`
+    fn register_gltf_meshes(&mut self, path: &Path, meshes: Vec<MeshAsset>) -> Vec<MeshHandle> {
+        meshes
+            .into_iter()
+            .enumerate()
+            .map(|(index, mesh)| {
+                let key = format!("{}#{}", path.display(), index);
+                if let Some(handle) = self.meshes.find(&key) {
+                    return handle;
+                }
+                self.add_mesh(Some(key), Ok(mesh))
+            })
+            .collect()
+    }
+
+    /// Add mesh data that did not come from a file (or a load result)
+    pub fn add_mesh(
+        &mut self,
+        key: Option<String>,
+        result: Result<MeshAsset, String>,
+    ) -> MeshHandle {
+        if let Err(e) = &result {
+            log_error!(
+                "Failed to load mesh '{}': {}",
+                key.as_deref().unwrap_or("<generated>"),
+                e
+            );
+        }
+
+        let loaded = result.is_ok();
+        let handle = self.meshes.insert(key, result);
+        if loaded {
+            self.mesh_uploads.push(handle);
+        }
+        handle
+    }
+
+    /// Start decoding a PNG or JPEG texture as RGBA8 in the background.
+    /// `target:name` references the render target of that name instead.
+    pub fn load_texture(&mut self, reference: &str) -> TextureHandle {
+        if let Some(name) = reference.strip_prefix(RENDER_TARGET_PREFIX) {
+            return self.render_target(name);
+        }
+
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.textures.find(&key) {
+            return handle;
+        }
+
+        let handle = self.textures.insert_pending(Some(key));
+        self.loader.submit(LoadKind::Texture, handle.id(), move || {
+            Loaded::Texture(load_texture_file(&path))
+        });
+        handle
+    }
+
+    /// Start loading a skybox from an equirectangular panorama, usually an
+    /// `.hdr` file
+    pub fn load_cubemap(&mut self, reference: &str) -> CubemapHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.cubemaps.find(&key) {
+            return handle;
+        }
+
+        let handle = self.cubemaps.insert_pending(Some(key));
+        self.loader.submit(LoadKind::Cubemap, handle.id(), move || {
+            Loaded::Cubemap(cubemap::load_equirect_file(&path))
+        });
+        handle
+    }
+
+    /// Start loading a skybox from six face images, in `cubemap::FACE_NAMES`
+    /// order
+    pub fn load_cubemap_faces(&mut self, references: [&str; 6]) -> CubemapHandle {
+        let resolved = references.map(|r| self.key_for(r));
+        let key = resolved
+            .iter()
+            .map(|(_, _, key)| key.as_str())
+            .collect::<Vec<_>>()
+            .join("|");
+        if let Some(handle) = self.cubemaps.find(&key) {
+            return handle;
+        }
+
+        let paths = resolved.map(|(path, _, _)| path);
+        let handle = self.cubemaps.insert_pending(Some(key));
+        self.loader.submit(LoadKind::Cubemap, handle.id(), move || {
+            Loaded::Cubemap(cubemap::load_face_files(&paths))
+        });
+        handle
+    }
+
+    /// Texture handle of a named render target. The texture has no image
+    /// data, the renderer fills it from the cameras drawing into it. Targets
+    /// are never collected.
+    pub fn render_target(&mut self, name: &str) -> TextureHandle {
+        let key = format!("{}{}", RENDER_TARGET_PREFIX, name);
+        if let Some(handle) = self.textures.find(&key) {
+            return handle;
+        }
+
+        let empty = TextureAsset {
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+        };
+        let handle = self.textures.insert(Some(key), Ok(empty));
+        self.textures.pin(handle);
+        handle
+    }
+
+    /// Load a `.material` file. The file itself is read right away; its
+    /// textures load in the background.
+    pub fn load_material(&mut self, reference: &str) -> MaterialHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.materials.find(&key) {
+            return handle;
+        }
+
+        let result = material::load_material(&path).map(|file| {
+            let shader = file
+                .shader
+                .as_ref()
+                .map(|p| self.load_shader(&p.to_string_lossy()));
+            let mut texture =
+                |path: Option<PathBuf>| path.map(|p| self.load_texture(&p.to_string_lossy()));
+            Material {
+                color: file.base_color,
+                metallic: file.metallic,
+                roughness: file.roughness,
+                emissive: file.emissive,
+                normal_scale: file.normal_scale,
+                base_color_texture: texture(file.base_color_texture),
+                metallic_roughness_texture: texture(file.metallic_roughness_texture),
+                emissive_texture: texture(file.emissive_texture),
+                normal_texture: texture(file.normal_texture),
+                sampler: file.sampler,
+                shader,
+                blend: file
+                    .blend
+                    .unwrap_or_else(|| BlendMode::for_alpha(file.base_color.w)),
+            }
+        });
+
+        if let Err(e) = &result {
+            log_error!("Failed to load material '{}': {}", key, e);
+        }
+        self.materials.insert(Some(key), result)
+    }
+
+    /// Material for the description that came with a mesh file, loading the
+    /// textures it names
+    pub fn material_from_data(&mut self, data: &MaterialData) -> Material {
+        let mut texture = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|p| self.load_texture(&p.to_string_lossy()))
+        };
+        Material {
+            base_color_texture: texture(&data.diffuse_texture),
+            metallic_roughness_texture: texture(&data.metallic_roughness_texture),
+            emissive_texture: texture(&data.emissive_texture),
+            normal_texture: texture(&data.normal_texture),
+            ..Material::from_data(data)
+        }
+    }
+
+    /// Load a `.sprites` sheet. The file itself is read right away; its
+    /// texture loads in the background.
+    pub fn load_sprite_sheet(&mut self, reference: &str) -> SpriteSheetHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.sprite_sheets.find(&key) {
+            return handle;
+        }
+
+        let result = sprite_sheet::load_sprite_sheet(&path).map(|file| SpriteSheet {
+            texture: file
+                .texture
+                .map(|p| self.load_texture(&p.to_string_lossy())),
+            filter: file.filter,
+            frames: file.frames,
+            animations: file.animations,
+        });
+
+        if let Err(e) = &result {
+            log_error!("Failed to load sprite sheet '{}': {}", key, e);
+        }
+        self.sprite_sheets.insert(Some(key), result)
+    }
+
+    /// Load a TTF or OTF font. Fonts are small, so the file is read right
+    /// away.
+    pub fn load_font(&mut self, reference: &str) -> FontHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.fonts.find(&key) {
+            return handle;
+        }
+
+        let result = font::load_font(&path);
+        if let Err(e) = &result {
+            log_error!("Failed to load font '{}': {}", key, e);
+        }
+        self.fonts.insert(Some(key), result)
+    }
+
+    /// Start loading a WGSL shader in the background. The file is validated
+    /// against the engine's shader interface and watched for changes.
+    pub fn load_shader(&mut self, reference: &str) -> ShaderHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.shaders.find(&key) {
+            return handle;
+        }
+
+        let handle = self.shaders.insert_pending(Some(key));
+        self.watched_shaders
+            .insert(handle.id(), (path.clone(), modified_time(&path)));
+        self.loader.submit(LoadKind::Shader, handle.id(), move || {
+            Loaded::Shader(load_shader_file(&path))
+        });
+        handle
+    }
+
+    /// Reload shaders whose files changed on disk. Cheap to call every frame,
+    /// the files are only checked a couple of times per second.
+    pub fn reload_changed_shaders(&mut self) {
+        if self.last_shader_check.elapsed() < SHADER_WATCH_INTERVAL {
+            return;
+        }
+        self.last_shader_check = Instant::now();
+
+        for (&id, (path, last_modified)) in &mut self.watched_shaders {
+            let modified = modified_time(path);
+            if modified == *last_modified {
+                continue;
+            }
+            *last_modified = modified;
+
+            let path = path.clone();
+            self.loader.submit(LoadKind::Shader, id, move || {
+                Loaded::Shader(load_shader_file(&path))
+            });
+        }
+    }
+
+    /// Start reading a WASM script module in the background; every instance
+    /// shares the bytes
+    pub fn load_script(&mut self, reference: &str) -> ScriptHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.scripts.find(&key) {
+            return handle;
+        }
+
+        let handle = self.scripts.insert_pending(Some(key));
+        self.loader.submit(LoadKind::Script, handle.id(), move || {
+            Loaded::Script(
+                fs::read(&path)
+                    .map(|bytes| ScriptAsset {
+                        bytes: bytes.into(),
+                    })
+                    .map_err(|e| e.to_string()),
+            )
+        });
+        handle
+    }
+
+    /// Load a JSON scene (read right away, it is small) or start loading a
+    /// glTF file used as a scene. Meshes of glTF scenes are registered when
+    /// the file finishes loading.
+    pub fn load_scene(&mut self, reference: &str) -> SceneHandle {
+        let (path, _, key) = self.key_for(reference);
+        if let Some(handle) = self.scenes.find(&key) {
+            return handle;
+        }
+
+        if is_gltf_path(reference) {
+            let handle = self.scenes.insert_pending(Some(key));
+            let job_path = path.clone();
+            self.loader
+                .submit(LoadKind::GltfScene, handle.id(), move || {
+                    Loaded::GltfScene {
+                        result: load_gltf_scene(&job_path),
+                        path: job_path,
+                    }
+                });
+            return handle;
+        }
+
+        let result = fs::read_to_string(&path)
+            .map(SceneAsset::Json)
+            .map_err(|e| e.to_string());
+
+        if let Err(e) = &result {
+            log_error!("Failed to load scene '{}': {}", key, e);
+        }
+        self.scenes.insert(Some(key), result)
+    }
+
+    // ========================================================================
+    // BACKGROUND RESULTS
+    // ========================================================================
+
+    /// Apply every background load that finished since the last call
+    pub fn poll(&mut self) {
+        while let Some((id, loaded)) = self.loader.try_recv() {
+            self.apply_loaded(id, loaded);
+        }
+    }
+
+    /// Block until every asset in `group` has loaded or failed
+    pub fn wait_for(&mut self, group: &AssetGroup) {
+        self.poll();
+        while !self.progress(group).is_done() {
+            match self.loader.recv_timeout(Duration::from_millis(100)) {
+                Some((id, loaded)) => self.apply_loaded(id, loaded),
+                // Nothing in flight means nothing can change anymore
+                None if self.loader.in_flight() == 0 => break,
+                None => {}
+            }
+        }
+    }
+
+    /// Block until every queued load has finished
+    pub fn wait_all(&mut self) {
+        while self.loader.in_flight() > 0 {
+            if let Some((id, loaded)) = self.loader.recv_timeout(Duration::from_millis(100)) {
+                self.apply_loaded(id, loaded);
+            }
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.loader.in_flight()
+    }
+
+    fn apply_loaded(&mut self, id: u32, loaded: Loaded) {
+        match loaded {
+            Loaded::Mesh { alias, result } => {
+                let result = result.map(|mesh| *mesh);
+                let handle = MeshHandle::from_id(id);
+                if let Some(alias) = alias
+                    && self.meshes.find(&alias).is_none()
+                {
+                    self.meshes.alias(alias, handle);
+                }
+                report_failure("mesh", self.meshes.entry(handle), &result);
+                let ok = result.is_ok();
+                if self.meshes.complete(handle, result) && ok {
+                    self.mesh_uploads.push(handle);
+                }
+            }
+            Loaded::Texture(result) => {
+                let handle = TextureHandle::from_id(id);
+                report_failure("texture", self.textures.entry(handle), &result);
+                let ok = result.is_ok();
+                if self.textures.complete(handle, result) && ok {
+                    self.texture_uploads.push(handle);
+                }
+            }
+            Loaded::Cubemap(result) => {
+                let handle = CubemapHandle::from_id(id);
+                report_failure("cubemap", self.cubemaps.entry(handle), &result);
+                let ok = result.is_ok();
+                if self.cubemaps.complete(handle, result) && ok {
+                    self.cubemap_uploads.push(handle);
+                }
+            }
+            Loaded::Script(result) => {
+                let handle = ScriptHandle::from_id(id);
+                report_failure("script", self.scripts.entry(handle), &result);
+                self.scripts.complete(handle, result);
+            }
+            Loaded::Shader(result) => {
+                let handle = ShaderHandle::from_id(id);
+                report_failure("shader", self.shaders.entry(handle), &result);
+                match result {
+                    Ok(shader) => {
+                        if self.shaders.complete(handle, Ok(shader)) {
+                            self.shader_changes.push(handle);
+                        }
+                    }
+                    // A broken edit keeps the last working version on screen
+                    Err(e) if self.shaders.get(handle).is_some() => {
+                        self.shaders.fail_keeping_data(handle, e);
+                    }
+                    Err(e) => {
+                        self.shaders.complete(handle, Err(e));
+                    }
+                }
+            }
+            Loaded::GltfScene { path, result } => {
+                let handle = SceneHandle::from_id(id);
+                report_failure("scene", self.scenes.entry(handle), &result);
+                let result = result.map(|(nodes, meshes)| SceneAsset::Gltf {
+                    meshes: self.register_gltf_meshes(&path, meshes),
+                    nodes,
+                });
+                self.scenes.complete(handle, result);
+            }
+        }
+    }
+
+    // ========================================================================
+    // STATUS
+    // ========================================================================
+
+    pub fn status(&self, id: AssetId) -> Option<&AssetStatus> {
+        match id {
+            AssetId::Mesh(h) => self.meshes.status(h),
+            AssetId::Texture(h) => self.textures.status(h),
+            AssetId::Cubemap(h) => self.cubemaps.status(h),
+            AssetId::Material(h) => self.materials.status(h),
+            AssetId::SpriteSheet(h) => self.sprite_sheets.status(h),
+            AssetId::Font(h) => self.fonts.status(h),
+            AssetId::Script(h) => self.scripts.status(h),
+            AssetId::Shader(h) => self.shaders.status(h),
+            AssetId::Scene(h) => self.scenes.status(h),
+        }
+    }
+
+    /// How far along the assets of a group are. Scenes, materials and sprite
+    /// sheets also count the meshes and textures they contain once they are
+    /// loaded.
+    pub fn progress(&self, group: &AssetGroup) -> LoadProgress {
+        let mut progress = LoadProgress::default();
+        let mut count = |status: Option<&AssetStatus>| {
+            progress.total += 1;
+            match status {
+                Some(AssetStatus::Loaded) => progress.loaded += 1,
+                Some(AssetStatus::Failed(_)) | None => progress.failed += 1,
+                Some(AssetStatus::Pending) => {}
+            }
+        };
+
+        for &id in group.ids() {
+            count(self.status(id));
+            let scene = match id {
+                AssetId::Scene(scene) => self.scenes.get(scene),
+                _ => None,
+            };
+            if let Some(SceneAsset::Gltf { meshes, .. }) = scene {
+                for &mesh in meshes {
+                    count(self.meshes.status(mesh));
+                }
+            }
+
+            let material = match id {
+                AssetId::Material(material) => self.materials.get(material),
+                _ => None,
+            };
+            for texture in material.iter().flat_map(|m| m.textures()) {
+                count(self.textures.status(texture));
+            }
+            if let Some(shader) = material.and_then(|m| m.shader) {
+                count(self.shaders.status(shader));
+            }
+
+            let sheet_texture = match id {
+                AssetId::SpriteSheet(sheet) => {
+                    self.sprite_sheets.get(sheet).and_then(|s| s.texture)
+                }
+                _ => None,
+            };
+            if let Some(texture) = sheet_texture {
+                count(self.textures.status(texture));
+            }
+        }
+        progress
+    }
+
+    /// Every asset that failed to load
+    pub fn errors(&self) -> Vec<AssetError> {
+        fn collect<T>(kind: &'static str, store: &AssetStore<T>, out: &mut Vec<AssetError>) {
+            for (_, entry) in store.iter() {
+                if let AssetStatus::Failed(message) = &entry.status {
+                    out.push(AssetError {
+                        kind,
+                        key: entry.key.clone().unwrap_or_default(),
+                        message: message.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut errors = Vec::new();
+        collect("mesh", &self.meshes, &mut errors);
+        collect("texture", &self.textures, &mut errors);
+        collect("cubemap", &self.cubemaps, &mut errors);
+        collect("material", &self.materials, &mut errors);
+        collect("sprite sheet", &self.sprite_sheets, &mut errors);
+        collect("font", &self.fonts, &mut errors);
+        collect("script", &self.scripts, &mut errors);
+        collect("shader", &self.shaders, &mut errors);
+        collect("scene", &self.scenes, &mut errors);
+        errors
+    }
+
+    // ========================================================================
+    // REFERENCE COUNTING
+    // ========================================================================
+
+    /// Count how many entities use each mesh, texture, shader and script.
+    /// Sprites, tilesets and color grading LUTs of the scene and cameras
+    /// count as texture users, the world's skybox as the only cubemap user.
+    pub fn update_ref_counts(&mut self, world: &World) {
+        let mut mesh_counts: HashMap<u32, usize> = HashMap::new();
+        let mut texture_counts: HashMap<u32, usize> = HashMap::new();
+        let mut script_counts: HashMap<u32, usize> = HashMap::new();
+        let mut shader_counts: HashMap<u32, usize> = HashMap::new();
+
+        for (_, entity) in world.iter_entities() {
+            if let Some(mesh) = &entity.mesh_handle {
+                *mesh_counts.entry(mesh.id()).or_default() += 1;
+            }
+            for texture in entity.material.iter().flat_map(|m| m.textures()) {
+                *texture_counts.entry(texture.id()).or_default() += 1;
+            }
+            for handle in entity.scripts.iter().flatten().filter_map(|s| s.handle) {
+                *script_counts.entry(handle.id()).or_default() += 1;
+            }
+            if let Some(shader) = entity.material.and_then(|m| m.shader) {
+                *shader_counts.entry(shader.id()).or_default() += 1;
+            }
+            if let Some(texture) = entity.sprite.and_then(|s| s.texture) {
+                *texture_counts.entry(texture.id()).or_default() += 1;
+            }
+            for tileset in entity.tilemap.iter().flat_map(|t| t.tilesets.iter()) {
+                if let Some(texture) = tileset.texture {
+                    *texture_counts.entry(texture.id()).or_default() += 1;
+                }
+            }
+            if let Some(lut) = entity
+                .camera
+                .as_ref()
+                .and_then(|c| c.post_process)
+                .and_then(|p| p.color_grading.lut)
+            {
+                *texture_counts.entry(lut.id()).or_default() += 1;
+            }
+        }
+        if let Some(lut) = world.post_process.color_grading.lut {
+            *texture_counts.entry(lut.id()).or_default() += 1;
+        }
+
+        let mut cubemap_counts: HashMap<u32, usize> = HashMap::new();
+        if let Some(Background::Skybox(cubemap)) = world.background {
+            cubemap_counts.insert(cubemap.id(), 1);
+        }
+
+        self.meshes.set_ref_counts(&mesh_counts);
+        self.textures.set_ref_counts(&texture_counts);
+        self.cubemaps.set_ref_counts(&cubemap_counts);
+        self.scripts.set_ref_counts(&script_counts);
+        self.shaders.set_ref_counts(&shader_counts);
+    }
+
+    /// Drop assets nothing refers to anymore. Freed meshes, textures,
+    /// cubemaps and shaders are queued so `State` can release their GPU
+    /// resources.
+    pub fn collect_unused(&mut self) {
+        let freed = self.meshes.collect_unused();
+        self.mesh_uploads.retain(|h| !freed.contains(h));
+        self.freed_meshes.extend(freed);
+
+        let freed = self.textures.collect_unused();
+        self.texture_uploads.retain(|h| !freed.contains(h));
+        self.freed_textures.extend(freed);
+
+        let freed = self.cubemaps.collect_unused();
+        self.cubemap_uploads.retain(|h| !freed.contains(h));
+        self.freed_cubemaps.extend(freed);
+
+        for shader in self.shaders.collect_unused() {
+            self.watched_shaders.remove(&shader.id());
+            self.shader_changes.push(shader);
+        }
+
+        self.scripts.collect_unused();
+    }
+
+    pub fn take_mesh_uploads(&mut self) -> Vec<MeshHandle> {
+        std::mem::take(&mut self.mesh_uploads)
+    }
+
+    pub fn take_freed_meshes(&mut self) -> Vec<MeshHandle> {
+        std::mem::take(&mut self.freed_meshes)
+    }
+
+    pub fn take_texture_uploads(&mut self) -> Vec<TextureHandle> {
+        std::mem::take(&mut self.texture_uploads)
+    }
+
+    pub fn take_freed_textures(&mut self) -> Vec<TextureHandle> {
+        std::mem::take(&mut self.freed_textures)
+    }
+
+    pub fn take_cubemap_uploads(&mut self) -> Vec<CubemapHandle> {
+        std::mem::take(&mut self.cubemap_uploads)
+    }
+
+    pub fn take_freed_cubemaps(&mut self) -> Vec<CubemapHandle> {
+        std::mem::take(&mut self.freed_cubemaps)
+    }
+
+    pub fn take_shader_changes(&mut self) -> Vec<ShaderHandle> {
+        std::mem::take(&mut self.shader_changes)
+    }
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// LOAD JOBS (run on worker threads)
+// ============================================================================
+
+fn load_obj_mesh(path: &Path) -> Result<MeshAsset, String> {
+    let model = obj::load_obj(path).map_err(|e| e.to_string())?;
+    let material = model.meshes.first().and_then(|m| {
+        m.material
+            .as_ref()
+            .and_then(|name| model.materials.get(name))
+            .cloned()
+    });
+
+    Ok(MeshAsset {
+        data: model.merged(),
+        material,
+    })
+}
+
+/// A mesh of a glTF file and its index there
+fn load_gltf_mesh(path: &Path, selector: Option<&str>) -> Result<(MeshAsset, usize), String> {
+    let scene = gltf::load_gltf(path).map_err(|e| format!("{:#}", e))?;
+
+    let index = match selector {
+        Some(name) => scene.find_mesh(name),
+        None => (!scene.meshes.is_empty()).then_some(0),
+    }
+    .ok_or_else(|| {
+        format!(
+            "no mesh '{}' in '{}'",
+            selector.unwrap_or(""),
+            path.display()
+        )
+    })?;
+
+    let mesh = scene
+        .meshes
+        .into_iter()
+        .nth(index)
+        .expect("index was checked");
+    let mesh = MeshAsset {
+        data: mesh.data,
+        material: Some(mesh.material),
+    };
+    Ok((mesh, index))
+}
+
+fn load_gltf_scene(path: &Path) -> Result<(Vec<gltf::GltfNode>, Vec<MeshAsset>), String> {
+    let scene = gltf::load_gltf(path).map_err(|e| format!("{:#}", e))?;
+    let meshes = scene
+        .meshes
+        .into_iter()
+        .map(|mesh| MeshAsset {
+            data: mesh.data,
+            material: Some(mesh.material),
+        })
+        .collect();
+    Ok((scene.nodes, meshes))
+}
+
+fn load_texture_file(path: &Path) -> Result<TextureAsset, String> {
+    let rgba = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+    Ok(TextureAsset {
+        width: rgba.width(),
+        height: rgba.height(),
+        pixels: rgba.into_raw(),
+    })
+}
+
+fn load_shader_file(path: &Path) -> Result<ShaderAsset, String> {
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    crate::modules::render::shader::validate(&source)?;
+    Ok(ShaderAsset { source })
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn report_failure<T, D>(kind: &str, entry: Option<&AssetEntry<T>>, result: &Result<D, String>) {
+    if let (Some(entry), Err(e)) = (entry, result) {
+        log_error!(
+            "Failed to load {} '{}': {}",
+            kind,
+            entry.key.as_deref().unwrap_or("<generated>"),
+            e
+        );
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+pub fn is_gltf_path(path: &str) -> bool {
+    let path = path.split('#').next().unwrap_or(path).to_lowercase();
+    path.ends_with(".gltf") || path.ends_with(".glb")
+}
+
+/// Closest ancestor of `scene_path` that holds a `Project.zero` file, or the
+/// scene's own directory when there is none
+pub fn find_project_root(scene_path: impl AsRef<Path>) -> PathBuf {
+    let scene_path = scene_path.as_ref();
+    let absolute = fs::canonicalize(scene_path).unwrap_or_else(|_| scene_path.to_path_buf());
+    let scene_dir = absolute.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    scene_dir
+        .ancestors()
+        .find(|dir| dir.join(PROJECT_FILE).is_file())
+        .map(Path::to_path_buf)
+        .unwrap_or(scene_dir)
+}
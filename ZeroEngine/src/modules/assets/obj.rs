@@ -0,0 +1,448 @@
+use crate::modules::assets::{MaterialData, MeshData};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ObjError {
+    pub path: Option<PathBuf>,
+    /// 1-based line number, 0 when the error is not tied to a line (e.g. IO)
+    pub line: usize,
+    pub message: String,
+}
+
+impl ObjError {
+    fn at(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn with_path(mut self, path: &Path) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.to_path_buf());
+        }
+        self
+    }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<obj>".to_string());
+
+        if self.line > 0 {
+            write!(f, "{}:{}: {}", path, self.line, self.message)
+        } else {
+            write!(f, "{}: {}", path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+// ============================================================================
+// MODEL TYPES
+// ============================================================================
+
+/// One `o`/`g`/`usemtl` section of an OBJ file
+pub struct ObjMesh {
+    pub name: String,
+    pub material: Option<String>,
+    pub data: MeshData,
+}
+
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: HashMap<String, MaterialData>,
+}
+
+impl ObjModel {
+    /// All sections combined into a single mesh
+    pub fn merged(&self) -> MeshData {
+        let mut data = MeshData::default();
+        for mesh in &self.meshes {
+            data.append(&mesh.data);
+        }
+        data
+    }
+}
+
+// ============================================================================
+// LOADING
+// ============================================================================
+
+/// Load an OBJ file and any MTL libraries it references (relative to the OBJ)
+pub fn load_obj(path: impl AsRef<Path>) -> Result<ObjModel, ObjError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)
+        .map_err(|e| ObjError::at(0, format!("failed to read file: {}", e)).with_path(path))?;
+
+    parse_obj(&source, path.parent()).map_err(|e| e.with_path(path))
+}
+
+/// Load an MTL file on its own
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<HashMap<String, MaterialData>, ObjError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)
+        .map_err(|e| ObjError::at(0, format!("failed to read file: {}", e)).with_path(path))?;
+
+    parse_mtl(&source, path.parent()).map_err(|e| e.with_path(path))
+}
+
+/// Parse OBJ source. `base_dir` is used to resolve `mtllib` references; when it
+/// is `None`, material libraries are skipped.
+pub fn parse_obj(source: &str, base_dir: Option<&Path>) -> Result<ObjModel, ObjError> {
+    let mut parser = ObjParser::default();
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let line = strip_comment(raw_line);
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let v = parse_floats::<3>(&args, line_no, "vertex position")?;
+                parser.positions.push(v);
+            }
+            "vn" => {
+                let n = parse_floats::<3>(&args, line_no, "vertex normal")?;
+                parser.normals.push(n);
+            }
+            "vt" => {
+                // The optional third (w) coordinate is ignored. OBJ puts the
+                // origin at the bottom left, `MeshData` at the top left.
+                let [u, v] = parse_floats::<2>(&args, line_no, "texture coordinate")?;
+                parser.uvs.push([u, 1.0 - v]);
+            }
+            "f" => parser.add_face(&args, line_no)?,
+            "o" | "g" => parser.start_section(args.join(" "), None),
+            "usemtl" => {
+                if args.is_empty() {
+                    return Err(ObjError::at(line_no, "usemtl requires a material name"));
+                }
+                let name = parser.current_name.clone();
+                parser.start_section(name, Some(args.join(" ")));
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(ObjError::at(line_no, "mtllib requires a file name"));
+                }
+                if let Some(dir) = base_dir {
+                    let mtl_path = dir.join(args.join(" "));
+                    let materials = load_mtl(&mtl_path)?;
+                    parser.materials.extend(materials);
+                }
+            }
+            // Smoothing groups, lines and points are not used by the renderer
+            "s" | "l" | "p" => {}
+            other => {
+                return Err(ObjError::at(
+                    line_no,
+                    format!("unsupported statement '{}'", other),
+                ));
+            }
+        }
+    }
+
+    parser.finish_section();
+
+    Ok(ObjModel {
+        meshes: parser.meshes,
+        materials: parser.materials,
+    })
+}
+
+/// Parse MTL source. `base_dir` is used to resolve texture paths.
+pub fn parse_mtl(
+    source: &str,
+    base_dir: Option<&Path>,
+) -> Result<HashMap<String, MaterialData>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<MaterialData> = None;
+    // `Pr` wins over the roughness derived from `Ns`, whatever the order
+    let mut explicit_roughness = false;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let line = strip_comment(raw_line);
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(ObjError::at(line_no, "newmtl requires a material name"));
+            }
+            if let Some(done) = current.take() {
+                materials.insert(done.name.clone(), done);
+            }
+            current = Some(MaterialData::new(args.join(" ")));
+            explicit_roughness = false;
+            continue;
+        }
+
+        let Some(material) = current.as_mut() else {
+            return Err(ObjError::at(
+                line_no,
+                format!("'{}' appears before any newmtl", keyword),
+            ));
+        };
+
+        match keyword {
+            "Kd" => {
+                let [r, g, b] = parse_floats::<3>(&args, line_no, "diffuse color")?;
+                material.diffuse = glam::Vec4::new(r, g, b, material.diffuse.w);
+            }
+            "Ka" => {
+                material.ambient = parse_floats::<3>(&args, line_no, "ambient color")?.into();
+            }
+            "Ns" => {
+                material.shininess = parse_floats::<1>(&args, line_no, "shininess")?[0];
+                if !explicit_roughness {
+                    material.roughness = (2.0 / (material.shininess.max(0.0) + 2.0)).sqrt();
+                }
+            }
+            // PBR extension statements
+            "Pr" => {
+                material.roughness = parse_floats::<1>(&args, line_no, "roughness")?[0];
+                explicit_roughness = true;
+            }
+            "Pm" => {
+                material.metallic = parse_floats::<1>(&args, line_no, "metallic")?[0];
+            }
+            "Ke" => {
+                material.emissive = parse_floats::<3>(&args, line_no, "emissive color")?.into();
+            }
+            "d" => {
+                material.diffuse.w = parse_floats::<1>(&args, line_no, "dissolve")?[0];
+            }
+            "Tr" => {
+                material.diffuse.w = 1.0 - parse_floats::<1>(&args, line_no, "transparency")?[0];
+            }
+            "map_Kd" | "map_Ke" | "norm" | "map_Bump" | "bump" => {
+                // Options such as `-s 1 1 1` come before the file name
+                let Some(file) = args.last() else {
+                    return Err(ObjError::at(
+                        line_no,
+                        format!("{} requires a file name", keyword),
+                    ));
+                };
+                let path = Some(match base_dir {
+                    Some(dir) => dir.join(file),
+                    None => PathBuf::from(file),
+                });
+                match keyword {
+                    "map_Kd" => material.diffuse_texture = path,
+                    "map_Ke" => material.emissive_texture = path,
+                    // Bump maps are assumed to be tangent-space normal maps
+                    _ => material.normal_texture = path,
+                }
+            }
+            // Everything else (Ks, illum, Ni, other maps) is accepted but
+            // unused, PBR materials have no separate specular color
+            _ => {}
+        }
+    }
+
+    if let Some(done) = current.take() {
+        materials.insert(done.name.clone(), done);
+    }
+
+    Ok(materials)
+}
+
+// ============================================================================
+// PARSER STATE
+// ============================================================================
+
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    materials: HashMap<String, MaterialData>,
+    meshes: Vec<ObjMesh>,
+
+    // Current section
+    current_name: String,
+    current_material: Option<String>,
+    current: MeshData,
+    current_has_normals: bool,
+    current_has_uvs: bool,
+    vertex_cache: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+}
+
+impl ObjParser {
+    fn start_section(&mut self, name: String, material: Option<String>) {
+        self.finish_section();
+        self.current_name = name;
+        if material.is_some() {
+            self.current_material = material;
+        }
+    }
+
+    fn finish_section(&mut self) {
+        self.vertex_cache.clear();
+        if self.current.is_empty() {
+            self.current = MeshData::default();
+            return;
+        }
+
+        let mut data = std::mem::take(&mut self.current);
+        if !self.current_has_normals {
+            data.normals.clear();
+        }
+        if !self.current_has_uvs {
+            data.uvs.clear();
+        }
+
+        self.meshes.push(ObjMesh {
+            name: self.current_name.clone(),
+            material: self.current_material.clone(),
+            data,
+        });
+    }
+
+    fn add_face(&mut self, args: &[&str], line_no: usize) -> Result<(), ObjError> {
+        if args.len() < 3 {
+            return Err(ObjError::at(
+                line_no,
+                format!("face needs at least 3 vertices, found {}", args.len()),
+            ));
+        }
+
+        let mut corners = Vec::with_capacity(args.len());
+        for token in args {
+            let key = self.parse_corner(token, line_no)?;
+            corners.push(self.vertex_index(key));
+        }
+
+        // Fan triangulation for quads and n-gons
+        for i in 1..corners.len() - 1 {
+            self.current
+                .indices
+                .extend_from_slice(&[corners[0], corners[i], corners[i + 1]]);
+        }
+        Ok(())
+    }
+
+    fn parse_corner(
+        &self,
+        token: &str,
+        line_no: usize,
+    ) -> Result<(usize, Option<usize>, Option<usize>), ObjError> {
+        let mut parts = token.split('/');
+        let v = parts.next().unwrap_or("");
+        let vt = parts.next().filter(|s| !s.is_empty());
+        let vn = parts.next().filter(|s| !s.is_empty());
+
+        let v = resolve_index(v, self.positions.len(), line_no, "position")?;
+        let vt = vt
+            .map(|s| resolve_index(s, self.uvs.len(), line_no, "texture coordinate"))
+            .transpose()?;
+        let vn = vn
+            .map(|s| resolve_index(s, self.normals.len(), line_no, "normal"))
+            .transpose()?;
+
+        Ok((v, vt, vn))
+    }
+
+    fn vertex_index(&mut self, key: (usize, Option<usize>, Option<usize>)) -> u32 {
+        if let Some(&index) = self.vertex_cache.get(&key) {
+            return index;
+        }
+
+        let (v, vt, vn) = key;
+        let index = self.current.positions.len() as u32;
+        self.current.positions.push(self.positions[v]);
+
+        if self.current.positions.len() == 1 {
+            self.current_has_normals = vn.is_some();
+            self.current_has_uvs = vt.is_some();
+        }
+        self.current_has_normals &= vn.is_some();
+        self.current_has_uvs &= vt.is_some();
+
+        self.current
+            .normals
+            .push(vn.map(|i| self.normals[i]).unwrap_or([0.0; 3]));
+        self.current
+            .uvs
+            .push(vt.map(|i| self.uvs[i]).unwrap_or([0.0; 2]));
+
+        self.vertex_cache.insert(key, index);
+        index
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+}
+
+fn parse_floats<const N: usize>(
+    args: &[&str],
+    line_no: usize,
+    what: &str,
+) -> Result<[f32; N], ObjError> {
+    if args.len() < N {
+        return Err(ObjError::at(
+            line_no,
+            format!("{} needs {} values, found {}", what, N, args.len()),
+        ));
+    }
+
+    let mut out = [0.0; N];
+    for (slot, token) in out.iter_mut().zip(args) {
+        *slot = token.parse().map_err(|_| {
+            ObjError::at(line_no, format!("invalid number '{}' in {}", token, what))
+        })?;
+    }
+    Ok(out)
+}
+
+/// Convert a 1-based (or negative, relative) OBJ index into a 0-based index
+fn resolve_index(token: &str, count: usize, line_no: usize, what: &str) -> Result<usize, ObjError> {
+    let raw: i64 = token
+        .parse()
+        .map_err(|_| ObjError::at(line_no, format!("invalid {} index '{}'", what, token)))?;
+
+    let resolved = if raw > 0 {
+        raw - 1
+    } else if raw < 0 {
+        count as i64 + raw
+    } else {
+        return Err(ObjError::at(line_no, format!("{} index cannot be 0", what)));
+    };
+
+    if resolved < 0 || resolved as usize >= count {
+        return Err(ObjError::at(
+            line_no,
+            format!("{} index {} out of range ({} defined)", what, raw, count),
+        ));
+    }
+    Ok(resolved as usize)
+}
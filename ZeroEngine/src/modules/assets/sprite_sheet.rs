@@ -0,0 +1,177 @@
+use crate::modules::assets::RENDER_TARGET_PREFIX;
+use crate::modules::assets::TextureHandle;
+use crate::modules::assets::material::parse_texture_filter;
+use crate::modules::ecs::components::TextureFilter;
+use crate::modules::ecs::sprites::{SpriteClip, SpriteRegion};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// SPRITE SHEETS
+// ============================================================================
+
+/// A `.sprites` file: a texture atlas with named frames and the animations
+/// made of them. Frames are pixel rectangles `[x, y, width, height]` from the
+/// top left; a grid adds frames named "0", "1", ... row by row.
+///
+/// ```json
+/// {
+///   "texture": "hero.png",
+///   "filter": "nearest",
+///   "grid": { "frame_size": [32, 32], "columns": 8, "count": 16 },
+///   "frames": { "jump": [0, 64, 32, 48] },
+///   "animations": {
+///     "run": { "frames": [8, 9, 10, 11], "fps": 12 },
+///     "jump": { "frames": ["jump"], "loop": false }
+///   }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct SpriteSheetFile {
+    pub texture: Option<PathBuf>,
+    pub filter: TextureFilter,
+    pub frames: HashMap<String, SpriteRegion>,
+    pub animations: HashMap<String, SpriteClip>,
+}
+
+/// Loaded sprite sheet, see `SpriteSheetFile`
+#[derive(Clone, Debug, Default)]
+pub struct SpriteSheet {
+    pub texture: Option<TextureHandle>,
+    pub filter: TextureFilter,
+    pub frames: HashMap<String, SpriteRegion>,
+    pub animations: HashMap<String, SpriteClip>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSheet {
+    texture: Option<String>,
+    filter: Option<String>,
+    grid: Option<RawGrid>,
+    #[serde(default)]
+    frames: HashMap<String, [f32; 4]>,
+    #[serde(default)]
+    animations: HashMap<String, RawClip>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawGrid {
+    frame_size: [f32; 2],
+    columns: u32,
+    count: u32,
+    /// Top left of the first frame
+    #[serde(default)]
+    offset: [f32; 2],
+    /// Gap between frames
+    #[serde(default)]
+    spacing: [f32; 2],
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawClip {
+    frames: Vec<FrameRef>,
+    #[serde(default = "default_fps")]
+    fps: f32,
+    #[serde(default = "yes", rename = "loop")]
+    looping: bool,
+}
+
+/// Grid frames can be referred to by number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FrameRef {
+    Index(u32),
+    Name(String),
+}
+
+fn default_fps() -> f32 {
+    10.0
+}
+
+fn yes() -> bool {
+    true
+}
+
+pub fn load_sprite_sheet(path: impl AsRef<Path>) -> Result<SpriteSheetFile, String> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_sprite_sheet(&source, path.parent())
+}
+
+/// Parse a sprite sheet file. `base_dir` is used to resolve the texture path.
+pub fn parse_sprite_sheet(
+    source: &str,
+    base_dir: Option<&Path>,
+) -> Result<SpriteSheetFile, String> {
+    let raw: RawSheet = serde_json::from_str(source).map_err(|e| e.to_string())?;
+
+    let texture = raw.texture.map(|file| match base_dir {
+        Some(dir) if !file.starts_with(RENDER_TARGET_PREFIX) => dir.join(file),
+        _ => PathBuf::from(file),
+    });
+    let filter = match raw.filter.as_deref() {
+        Some(name) => parse_texture_filter(name)?,
+        None => TextureFilter::Nearest,
+    };
+
+    let mut frames = HashMap::new();
+    if let Some(grid) = &raw.grid {
+        if grid.columns == 0 {
+            return Err("grid needs at least one column".into());
+        }
+        let [width, height] = grid.frame_size;
+        for index in 0..grid.count {
+            let (column, row) = ((index % grid.columns) as f32, (index / grid.columns) as f32);
+            let region = SpriteRegion::new(
+                grid.offset[0] + column * (width + grid.spacing[0]),
+                grid.offset[1] + row * (height + grid.spacing[1]),
+                width,
+                height,
+            );
+            frames.insert(index.to_string(), region);
+        }
+    }
+    for (name, [x, y, width, height]) in raw.frames {
+        frames.insert(name, SpriteRegion::new(x, y, width, height));
+    }
+
+    let mut animations = HashMap::new();
+    for (name, clip) in raw.animations {
+        let regions = clip
+            .frames
+            .iter()
+            .map(|frame| {
+                let key = match frame {
+                    FrameRef::Index(index) => index.to_string(),
+                    FrameRef::Name(name) => name.clone(),
+                };
+                frames
+                    .get(&key)
+                    .copied()
+                    .ok_or_else(|| format!("animation '{}': unknown frame '{}'", name, key))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if regions.is_empty() {
+            return Err(format!("animation '{}' has no frames", name));
+        }
+        animations.insert(
+            name,
+            SpriteClip {
+                frames: regions,
+                fps: clip.fps.max(0.0),
+                looping: clip.looping,
+            },
+        );
+    }
+
+    Ok(SpriteSheetFile {
+        texture,
+        filter,
+        frames,
+        animations,
+    })
+}
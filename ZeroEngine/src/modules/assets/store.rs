@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+// ============================================================================
+// TYPED HANDLES
+// ============================================================================
+
+/// Typed id of an asset. Copying a handle does not affect reference counts;
+/// those are derived from what the world actually uses.
+pub struct Handle<T> {
+    id: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub const fn from_id(id: u32) -> Self {
+        Self {
+            id,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({})", self.id)
+    }
+}
+
+// ============================================================================
+// ASSET STORE
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetStatus {
+    /// Queued or being loaded on a worker thread
+    Pending,
+    Loaded,
+    Failed(String),
+}
+
+pub struct AssetEntry<T> {
+    /// Resolved source key (path plus optional `#selector`), `None` for built-ins
+    pub key: Option<String>,
+    pub status: AssetStatus,
+    pub data: Option<T>,
+    pub ref_count: usize,
+    /// Built-ins and explicitly pinned assets are never collected
+    pub pinned: bool,
+    was_referenced: bool,
+}
+
+/// All assets of one type, deduplicated by key
+pub struct AssetStore<T> {
+    entries: HashMap<u32, AssetEntry<T>>,
+    by_key: HashMap<String, u32>,
+    next_id: u32,
+}
+
+impl<T> AssetStore<T> {
+    pub fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Leave ids below `first_id` free for built-ins
+    pub fn starting_at(first_id: u32) -> Self {
+        Self {
+            entries: HashMap::new(),
+            by_key: HashMap::new(),
+            next_id: first_id,
+        }
+    }
+
+    /// Register a built-in asset under a fixed id
+    pub fn insert_builtin(&mut self, id: u32, name: &str, data: Option<T>) -> Handle<T> {
+        self.entries.insert(
+            id,
+            AssetEntry {
+                key: None,
+                status: AssetStatus::Loaded,
+                data,
+                ref_count: 0,
+                pinned: true,
+                was_referenced: false,
+            },
+        );
+        self.by_key.insert(format!("builtin:{}", name), id);
+        self.next_id = self.next_id.max(id + 1);
+        Handle::from_id(id)
+    }
+
+    pub fn insert(&mut self, key: Option<String>, result: Result<T, String>) -> Handle<T> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let (status, data) = match result {
+            Ok(data) => (AssetStatus::Loaded, Some(data)),
+            Err(e) => (AssetStatus::Failed(e), None),
+        };
+
+        if let Some(key) = &key {
+            self.by_key.insert(key.clone(), id);
+        }
+        self.entries.insert(
+            id,
+            AssetEntry {
+                key,
+                status,
+                data,
+                ref_count: 0,
+                pinned: false,
+                was_referenced: false,
+            },
+        );
+        Handle::from_id(id)
+    }
+
+    /// Reserve a handle for an asset that is still loading
+    pub fn insert_pending(&mut self, key: Option<String>) -> Handle<T> {
+        let handle = self.insert(key, Err(String::new()));
+        if let Some(entry) = self.entries.get_mut(&handle.id) {
+            entry.status = AssetStatus::Pending;
+        }
+        handle
+    }
+
+    /// Store the result of a background load. Returns false if the asset was
+    /// dropped in the meantime.
+    pub fn complete(&mut self, handle: Handle<T>, result: Result<T, String>) -> bool {
+        let Some(entry) = self.entries.get_mut(&handle.id) else {
+            return false;
+        };
+
+        match result {
+            Ok(data) => {
+                entry.status = AssetStatus::Loaded;
+                entry.data = Some(data);
+            }
+            Err(e) => {
+                entry.status = AssetStatus::Failed(e);
+                entry.data = None;
+            }
+        }
+        true
+    }
+
+    /// Mark an asset as failed but keep its last good data, so a broken
+    /// reload doesn't take the asset away from whatever uses it
+    pub fn fail_keeping_data(&mut self, handle: Handle<T>, message: String) -> bool {
+        let Some(entry) = self.entries.get_mut(&handle.id) else {
+            return false;
+        };
+        entry.status = AssetStatus::Failed(message);
+        true
+    }
+
+    pub fn is_pending(&self, handle: Handle<T>) -> bool {
+        matches!(self.status(handle), Some(AssetStatus::Pending))
+    }
+
+    /// Make `key` resolve to an existing asset as well
+    pub fn alias(&mut self, key: impl Into<String>, handle: Handle<T>) {
+        self.by_key.insert(key.into(), handle.id);
+    }
+
+    pub fn find(&self, key: &str) -> Option<Handle<T>> {
+        self.by_key.get(key).map(|&id| Handle::from_id(id))
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.entries.get(&handle.id)?.data.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.entries.get_mut(&handle.id)?.data.as_mut()
+    }
+
+    pub fn entry(&self, handle: Handle<T>) -> Option<&AssetEntry<T>> {
+        self.entries.get(&handle.id)
+    }
+
+    pub fn status(&self, handle: Handle<T>) -> Option<&AssetStatus> {
+        self.entries.get(&handle.id).map(|e| &e.status)
+    }
+
+    pub fn is_loaded(&self, handle: Handle<T>) -> bool {
+        matches!(self.status(handle), Some(AssetStatus::Loaded))
+    }
+
+    pub fn ref_count(&self, handle: Handle<T>) -> usize {
+        self.entries.get(&handle.id).map_or(0, |e| e.ref_count)
+    }
+
+    pub fn pin(&mut self, handle: Handle<T>) {
+        if let Some(entry) = self.entries.get_mut(&handle.id) {
+            entry.pinned = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &AssetEntry<T>)> {
+        self.entries.iter().map(|(&id, e)| (Handle::from_id(id), e))
+    }
+
+    /// Replace all reference counts with freshly counted ones
+    pub fn set_ref_counts(&mut self, counts: &HashMap<u32, usize>) {
+        for (id, entry) in &mut self.entries {
+            entry.ref_count = counts.get(id).copied().unwrap_or(0);
+            entry.was_referenced |= entry.ref_count > 0;
+        }
+    }
+
+    /// Remove assets that were used at some point and no longer are
+    pub fn collect_unused(&mut self) -> Vec<Handle<T>> {
+        let unused: Vec<u32> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| !e.pinned && e.was_referenced && e.ref_count == 0)
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in &unused {
+            self.entries.remove(id);
+            self.by_key.retain(|_, v| v != id);
+        }
+
+        unused.into_iter().map(Handle::from_id).collect()
+    }
+}
+
+impl<T> Default for AssetStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,679 @@
+use base64::Engine as _;
+use quick_xml::events::{BytesStart, Event};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// TILED MAPS
+// ============================================================================
+
+/// Orthogonal, finite map from the Tiled editor, read from a `.tmx` (XML) or
+/// `.tmj` (JSON) file. External `.tsx`/`.tsj` tilesets are read along with
+/// it; image paths are resolved against the file that names them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledMap {
+    /// Size in cells
+    pub width: u32,
+    pub height: u32,
+    /// Cell size in pixels
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    /// Group layers are flattened, their offsets added up
+    pub layers: Vec<TiledLayer>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTileset {
+    pub name: String,
+    pub first_gid: u32,
+    pub image: Option<PathBuf>,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// Collision flags by local tile id: an integer `collision` property, or
+    /// 1 for a true `solid` property or for tiles with collision shapes
+    pub collision: HashMap<u32, u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledLayer {
+    Tiles {
+        name: String,
+        width: u32,
+        height: u32,
+        /// Gids with Tiled's flip flags, row by row
+        data: Vec<u32>,
+        visible: bool,
+        opacity: f32,
+        /// Pixels, +Y down
+        offset: [f32; 2],
+    },
+    Objects {
+        name: String,
+        objects: Vec<TiledObject>,
+        visible: bool,
+        offset: [f32; 2],
+    },
+}
+
+/// Object of an object layer, e.g. a spawn point or trigger area
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledObject {
+    pub name: String,
+    /// Class (formerly type) set in the editor
+    pub class: String,
+    /// Top left in pixels, +Y down
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub fn load_tiled_map(path: impl AsRef<Path>) -> Result<TiledMap, String> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    match extension(path).as_str() {
+        "tmx" => parse_tmx(&source, base_dir),
+        "tmj" | "json" => parse_tmj(&source, base_dir),
+        other => Err(format!("unsupported map format '{}'", other)),
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Tilesets in another file start where the map says, everything else comes
+/// from that file
+fn load_external_tileset(path: &Path, first_gid: u32) -> Result<TiledTileset, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("tileset '{}': {}", path.display(), e))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let tileset = match extension(path).as_str() {
+        "tsx" => tileset_from_xml(&parse_xml(&source)?, base_dir)?,
+        "tsj" | "json" => {
+            let json: JsonTileset = serde_json::from_str(&source).map_err(|e| e.to_string())?;
+            tileset_from_json(json, base_dir)?
+        }
+        other => return Err(format!("unsupported tileset format '{}'", other)),
+    };
+    Ok(TiledTileset {
+        first_gid,
+        ..tileset
+    })
+}
+
+/// Raw layer data: CSV or base64, optionally zlib or gzip compressed
+fn decode_data(text: &str, encoding: &str, compression: &str) -> Result<Vec<u32>, String> {
+    match encoding {
+        "csv" => text
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse::<u32>()
+                    .map_err(|e| format!("bad tile '{}': {}", s, e))
+            })
+            .collect(),
+        "base64" => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map_err(|e| e.to_string())?;
+            let mut raw = Vec::new();
+            match compression {
+                "" => raw = bytes,
+                "zlib" => {
+                    flate2::read::ZlibDecoder::new(&bytes[..])
+                        .read_to_end(&mut raw)
+                        .map_err(|e| e.to_string())?;
+                }
+                "gzip" => {
+                    flate2::read::GzDecoder::new(&bytes[..])
+                        .read_to_end(&mut raw)
+                        .map_err(|e| e.to_string())?;
+                }
+                other => return Err(format!("unsupported compression '{}'", other)),
+            }
+            Ok(raw
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect())
+        }
+        other => Err(format!("unsupported encoding '{}'", other)),
+    }
+}
+
+fn check_layer_size(name: &str, data: &[u32], width: u32, height: u32) -> Result<(), String> {
+    // In u64 so sizes from the file can't overflow
+    if data.len() as u64 != width as u64 * height as u64 {
+        return Err(format!(
+            "layer '{}' has {} tiles, expected {}x{}",
+            name,
+            data.len(),
+            width,
+            height
+        ));
+    }
+    Ok(())
+}
+
+fn check_map(orientation: &str, infinite: bool) -> Result<(), String> {
+    if !orientation.is_empty() && orientation != "orthogonal" {
+        return Err(format!("unsupported orientation '{}'", orientation));
+    }
+    if infinite {
+        return Err("infinite maps are not supported".into());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TMX (XML)
+// ============================================================================
+
+/// Just enough of an XML tree for Tiled files
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<XmlNode>,
+    text: String,
+}
+
+impl XmlNode {
+    fn attr(&self, name: &str) -> &str {
+        self.attributes.get(name).map(String::as_str).unwrap_or("")
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.attributes.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("<{}> {}='{}' is not a number", self.name, name, value)),
+            None => Ok(default),
+        }
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn child<'a>(&'a self, name: &'a str) -> Option<&'a XmlNode> {
+        self.children(name).next()
+    }
+}
+
+fn parse_xml(source: &str) -> Result<XmlNode, String> {
+    fn open(start: &BytesStart) -> Result<XmlNode, String> {
+        let mut node = XmlNode {
+            name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+            ..Default::default()
+        };
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(|e| e.to_string())?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+            node.attributes.insert(key, value.into_owned());
+        }
+        Ok(node)
+    }
+
+    let mut reader = quick_xml::Reader::from_str(source);
+    reader.config_mut().trim_text(true);
+    // Open elements, the root's parent at the bottom
+    let mut stack = vec![XmlNode::default()];
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(start) => stack.push(open(&start)?),
+            Event::Empty(start) => {
+                let node = open(&start)?;
+                stack.last_mut().unwrap().children.push(node);
+            }
+            Event::End(_) => {
+                let node = stack.pop().unwrap();
+                stack
+                    .last_mut()
+                    .ok_or("unbalanced XML")?
+                    .children
+                    .push(node);
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                stack.last_mut().unwrap().text.push_str(&text);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| "empty XML document".to_string())
+}
+
+/// Parse a `.tmx` map. `base_dir` is used to resolve tileset and image
+/// paths.
+pub fn parse_tmx(source: &str, base_dir: &Path) -> Result<TiledMap, String> {
+    let root = parse_xml(source)?;
+    if root.name != "map" {
+        return Err(format!("expected <map>, found <{}>", root.name));
+    }
+    check_map(root.attr("orientation"), root.attr("infinite") == "1")?;
+
+    let mut tilesets = Vec::new();
+    for node in root.children("tileset") {
+        let first_gid = node.number("firstgid", 1)?;
+        let tileset = match node.attributes.get("source") {
+            Some(source) => load_external_tileset(&base_dir.join(source), first_gid)?,
+            None => TiledTileset {
+                first_gid,
+                ..tileset_from_xml(node, base_dir)?
+            },
+        };
+        tilesets.push(tileset);
+    }
+
+    let mut layers = Vec::new();
+    xml_layers(&root, [0.0, 0.0], true, &mut layers)?;
+
+    Ok(TiledMap {
+        width: root.number("width", 0)?,
+        height: root.number("height", 0)?,
+        tile_width: root.number("tilewidth", 0)?,
+        tile_height: root.number("tileheight", 0)?,
+        tilesets,
+        layers,
+    })
+}
+
+fn xml_layers(
+    parent: &XmlNode,
+    offset: [f32; 2],
+    visible: bool,
+    out: &mut Vec<TiledLayer>,
+) -> Result<(), String> {
+    for node in &parent.children {
+        let offset = [
+            offset[0] + node.number("offsetx", 0.0)?,
+            offset[1] + node.number("offsety", 0.0)?,
+        ];
+        let visible = visible && node.attr("visible") != "0";
+        let name = node.attr("name").to_string();
+        match node.name.as_str() {
+            "layer" => {
+                let width = node.number("width", 0)?;
+                let height = node.number("height", 0)?;
+                let data_node = node
+                    .child("data")
+                    .ok_or_else(|| format!("layer '{}' has no data", name))?;
+                let data = match data_node.attr("encoding") {
+                    // Oldest format: one <tile> element per cell
+                    "" => data_node
+                        .children("tile")
+                        .map(|tile| tile.number("gid", 0))
+                        .collect::<Result<Vec<u32>, _>>()?,
+                    encoding => {
+                        decode_data(&data_node.text, encoding, data_node.attr("compression"))?
+                    }
+                };
+                check_layer_size(&name, &data, width, height)?;
+                out.push(TiledLayer::Tiles {
+                    name,
+                    width,
+                    height,
+                    data,
+                    visible,
+                    opacity: node.number("opacity", 1.0)?,
+                    offset,
+                });
+            }
+            "objectgroup" => {
+                let objects = node
+                    .children("object")
+                    .map(|object| {
+                        Ok(TiledObject {
+                            name: object.attr("name").to_string(),
+                            class: match object.attr("class") {
+                                "" => object.attr("type").to_string(),
+                                class => class.to_string(),
+                            },
+                            x: object.number("x", 0.0)?,
+                            y: object.number("y", 0.0)?,
+                            width: object.number("width", 0.0)?,
+                            height: object.number("height", 0.0)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+                out.push(TiledLayer::Objects {
+                    name,
+                    objects,
+                    visible,
+                    offset,
+                });
+            }
+            "group" => xml_layers(node, offset, visible, out)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn tileset_from_xml(node: &XmlNode, base_dir: &Path) -> Result<TiledTileset, String> {
+    let name = node.attr("name").to_string();
+    let image = node
+        .child("image")
+        .map(|image| base_dir.join(image.attr("source")));
+    if image.is_none()
+        && node
+            .child("tile")
+            .is_some_and(|t| t.child("image").is_some())
+    {
+        return Err(format!(
+            "tileset '{}': image collections are not supported",
+            name
+        ));
+    }
+
+    let mut collision = HashMap::new();
+    for tile in node.children("tile") {
+        let mut flags = 0;
+        let mut explicit = None;
+        for property in tile
+            .child("properties")
+            .iter()
+            .flat_map(|p| p.children("property"))
+        {
+            match (property.attr("name"), property.attr("value")) {
+                ("collision", value) => {
+                    explicit = Some(value.parse().map_err(|_| {
+                        format!("tileset '{}': collision '{}' is not a number", name, value)
+                    })?)
+                }
+                ("solid" | "collides", "true") => flags |= 1,
+                _ => {}
+            }
+        }
+        if tile.child("objectgroup").is_some() {
+            flags |= 1;
+        }
+        let flags = explicit.unwrap_or(flags);
+        if flags != 0 {
+            collision.insert(tile.number("id", 0)?, flags);
+        }
+    }
+
+    Ok(TiledTileset {
+        name,
+        first_gid: 1,
+        image,
+        tile_width: node.number("tilewidth", 0)?,
+        tile_height: node.number("tileheight", 0)?,
+        columns: node.number("columns", 0)?,
+        tile_count: node.number("tilecount", 0)?,
+        margin: node.number("margin", 0)?,
+        spacing: node.number("spacing", 0)?,
+        collision,
+    })
+}
+
+// ============================================================================
+// TMJ (JSON)
+// ============================================================================
+
+#[derive(Deserialize)]
+struct JsonMap {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    /// Array of gids, or a base64 string
+    data: Option<serde_json::Value>,
+    #[serde(default)]
+    encoding: String,
+    #[serde(default)]
+    compression: String,
+    #[serde(default = "yes")]
+    visible: bool,
+    #[serde(default = "one")]
+    opacity: f32,
+    #[serde(default)]
+    offsetx: f32,
+    #[serde(default)]
+    offsety: f32,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    class: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    firstgid: Option<u32>,
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    image: Option<String>,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    image: Option<String>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    objectgroup: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Parse a `.tmj` map. `base_dir` is used to resolve tileset and image
+/// paths.
+pub fn parse_tmj(source: &str, base_dir: &Path) -> Result<TiledMap, String> {
+    let map: JsonMap = serde_json::from_str(source).map_err(|e| e.to_string())?;
+    check_map(&map.orientation, map.infinite)?;
+
+    let mut tilesets = Vec::new();
+    for json in map.tilesets {
+        let first_gid = json.firstgid.unwrap_or(1);
+        let tileset = match &json.source {
+            Some(source) => load_external_tileset(&base_dir.join(source), first_gid)?,
+            None => TiledTileset {
+                first_gid,
+                ..tileset_from_json(json, base_dir)?
+            },
+        };
+        tilesets.push(tileset);
+    }
+
+    let mut layers = Vec::new();
+    json_layers(map.layers, [0.0, 0.0], true, &mut layers)?;
+
+    Ok(TiledMap {
+        width: map.width,
+        height: map.height,
+        tile_width: map.tilewidth,
+        tile_height: map.tileheight,
+        tilesets,
+        layers,
+    })
+}
+
+fn json_layers(
+    layers: Vec<JsonLayer>,
+    offset: [f32; 2],
+    visible: bool,
+    out: &mut Vec<TiledLayer>,
+) -> Result<(), String> {
+    for layer in layers {
+        let offset = [offset[0] + layer.offsetx, offset[1] + layer.offsety];
+        let visible = visible && layer.visible;
+        match layer.kind.as_str() {
+            "tilelayer" => {
+                let data = match &layer.data {
+                    Some(serde_json::Value::String(text)) => {
+                        decode_data(text, &layer.encoding, &layer.compression)?
+                    }
+                    Some(value) => serde_json::from_value(value.clone())
+                        .map_err(|e| format!("layer '{}': {}", layer.name, e))?,
+                    None => return Err(format!("layer '{}' has no data", layer.name)),
+                };
+                check_layer_size(&layer.name, &data, layer.width, layer.height)?;
+                out.push(TiledLayer::Tiles {
+                    name: layer.name,
+                    width: layer.width,
+                    height: layer.height,
+                    data,
+                    visible,
+                    opacity: layer.opacity,
+                    offset,
+                });
+            }
+            "objectgroup" => out.push(TiledLayer::Objects {
+                name: layer.name,
+                objects: layer
+                    .objects
+                    .into_iter()
+                    .map(|object| TiledObject {
+                        name: object.name,
+                        class: if object.class.is_empty() {
+                            object.kind
+                        } else {
+                            object.class
+                        },
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                    })
+                    .collect(),
+                visible,
+                offset,
+            }),
+            "group" => json_layers(layer.layers, offset, visible, out)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn tileset_from_json(json: JsonTileset, base_dir: &Path) -> Result<TiledTileset, String> {
+    if json.image.is_none() && json.tiles.iter().any(|t| t.image.is_some()) {
+        return Err(format!(
+            "tileset '{}': image collections are not supported",
+            json.name
+        ));
+    }
+
+    let mut collision = HashMap::new();
+    for tile in &json.tiles {
+        let mut flags = 0;
+        let mut explicit = None;
+        for property in &tile.properties {
+            match (property.name.as_str(), &property.value) {
+                ("collision", value) => {
+                    explicit = Some(value.as_u64().ok_or_else(|| {
+                        format!(
+                            "tileset '{}': collision {} is not a number",
+                            json.name, value
+                        )
+                    })? as u32)
+                }
+                ("solid" | "collides", serde_json::Value::Bool(true)) => flags |= 1,
+                _ => {}
+            }
+        }
+        if tile.objectgroup.is_some() {
+            flags |= 1;
+        }
+        let flags = explicit.unwrap_or(flags);
+        if flags != 0 {
+            collision.insert(tile.id, flags);
+        }
+    }
+
+    Ok(TiledTileset {
+        name: json.name,
+        first_gid: 1,
+        image: json.image.map(|image| base_dir.join(image)),
+        tile_width: json.tilewidth,
+        tile_height: json.tileheight,
+        columns: json.columns,
+        tile_count: json.tilecount,
+        margin: json.margin,
+        spacing: json.spacing,
+        collision,
+    })
+}
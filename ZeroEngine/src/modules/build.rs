@@ -1,16 +1,35 @@
 use crate::Engine;
-use crate::modules::ecs::components::{Material, MeshHandle, Transform};
-use crate::modules::ecs::entity::{Camera, Entity, MeshType};
+use crate::log_error;
+use crate::modules::assets::gltf::GltfNode;
+use crate::modules::assets::material::{parse_blend_mode, parse_texture_filter};
+use crate::modules::assets::tiled::{self, TiledLayer, TiledMap};
+use crate::modules::assets::{
+    AssetId, AssetStatus, Assets, FontHandle, LoadProgress, SceneAsset, find_project_root,
+};
+use crate::modules::ecs::components::{
+    Background, BlendMode, Fog, FogMode, Light, Material, MeshHandle, PostProcess, Tonemapper,
+    Transform,
+};
+use crate::modules::ecs::entity::{
+    Camera, Entity, MeshType, Projection, RenderTarget, TargetSize, Viewport,
+};
 use crate::modules::ecs::entity::{set_active_camera, spawn_camera};
+use crate::modules::ecs::particles::{ParticleBurst, ParticleEmitter};
 use crate::modules::ecs::scripts::Script;
 use crate::modules::ecs::scripts::ScriptRegistry;
+use crate::modules::ecs::sprites::{Sprite, SpriteAnimator, SpriteRegion};
+use crate::modules::ecs::text::{HudText, Text, parse_text_align};
+use crate::modules::ecs::tilemap::{Tile, Tilemap, Tileset};
+use crate::modules::ecs::ui::{UiPanel, UiWidget, UiWidgetKind, parse_ui_anchor};
 use crate::modules::ecs::world::EntityId;
 use crate::modules::ecs::world::World;
+use crate::modules::render::msaa::SAMPLE_COUNTS;
 
-use glam::{Vec3, Vec4};
+use glam::{EulerRot, Quat, Vec2, Vec3, Vec4};
 use serde::Deserialize;
 
 use std::fs;
+use std::sync::Arc;
 
 #[derive(Deserialize)]
 struct SceneEntity {
@@ -19,20 +38,655 @@ struct SceneEntity {
     position: Vec3,
     #[serde(deserialize_with = "vec3_from_array")]
     scale: Vec3,
+    /// XYZ Euler angles in radians
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
     mesh: String,
-    #[serde(deserialize_with = "vec4_from_array")]
-    color: Vec4,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    color: Option<Vec4>,
+    /// Path to a `.material` file
+    material: Option<String>,
+    /// "opaque", "alpha", "additive" or "multiply"
+    blend: Option<String>,
     camera: Option<CameraData>,
+    light: Option<LightData>,
+    particles: Option<EmitterData>,
+    casts_shadows: Option<bool>,
+    receives_shadows: Option<bool>,
     scripts: Option<Vec<String>>,
     tags: Option<Vec<String>>,
 }
 
-#[derive(Copy, Clone, Deserialize)]
+#[derive(Clone, Deserialize)]
 struct CameraData {
     fov: f32,
     near: f32,
     far: f32,
     active: bool,
+    post_process: Option<PostProcessData>,
+    #[serde(flatten)]
+    view: CameraViewData,
+}
+
+/// Projection and screen placement shared by entity and scene cameras. Every
+/// active camera is drawn, so split-screen is two cameras with half viewports:
+///
+/// ```json
+/// "projection": "orthographic",
+/// "size": 20.0,
+/// "viewport": [0.75, 0.0, 0.25, 0.25],
+/// "clear_color": [0.1, 0.1, 0.1, 1.0],
+/// "order": 1
+/// ```
+///
+/// A `target` draws into a texture instead, which materials use as
+/// `"target:monitor"`. It has a fixed `width` and `height` or a `scale` of
+/// the window size:
+///
+/// ```json
+/// "target": { "name": "monitor", "width": 512, "height": 256 }
+/// ```
+#[derive(Clone, Default, Deserialize)]
+struct CameraViewData {
+    /// "perspective" or "orthographic"
+    projection: Option<String>,
+    /// Height in world units an orthographic camera shows
+    size: Option<f32>,
+    /// x, y, width and height as fractions of the window, from the top left
+    viewport: Option<[f32; 4]>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    clear_color: Option<Vec4>,
+    /// `false` draws over the cameras before this one
+    clear: Option<bool>,
+    order: Option<i32>,
+    target: Option<TargetData>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TargetData {
+    name: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    /// Fraction of the window size, when there is no fixed size
+    scale: Option<f32>,
+}
+
+impl CameraViewData {
+    fn apply(&self, camera: &mut Camera, assets: &mut Assets) -> Result<(), String> {
+        camera.projection = match self.projection.as_deref().unwrap_or("perspective") {
+            "perspective" => Projection::Perspective,
+            "orthographic" => Projection::Orthographic {
+                size: self.size.unwrap_or(DEFAULT_ORTHOGRAPHIC_SIZE),
+            },
+            other => return Err(format!("unknown projection '{}'", other)),
+        };
+        if let Some([x, y, width, height]) = self.viewport {
+            camera.viewport = Viewport::new(x, y, width, height);
+        }
+        if let Some(color) = self.clear_color {
+            camera.clear_color = Some(color);
+        }
+        if self.clear == Some(false) {
+            camera.clear_color = None;
+        }
+        camera.order = self.order.unwrap_or(0);
+        if let Some(target) = &self.target {
+            let size = match (target.width, target.height, target.scale) {
+                (Some(width), Some(height), None) => TargetSize::Fixed { width, height },
+                (None, None, scale) => TargetSize::Window {
+                    scale: scale.unwrap_or(1.0),
+                },
+                _ => {
+                    return Err(format!(
+                        "render target '{}' needs a width and height or a scale",
+                        target.name
+                    ));
+                }
+            };
+            camera.target = Some(RenderTarget {
+                texture: assets.render_target(&target.name),
+                size,
+            });
+        }
+        Ok(())
+    }
+}
+
+const DEFAULT_ORTHOGRAPHIC_SIZE: f32 = 10.0;
+
+/// Post-processing of a scene or camera. Listing an effect turns it on
+/// unless it says `"enabled": false`; missing values keep their defaults.
+///
+/// ```json
+/// "post_process": {
+///   "exposure": 1.2,
+///   "tonemapper": "aces",
+///   "bloom": { "threshold": 1.0, "intensity": 0.4 },
+///   "vignette": { "intensity": 0.3, "smoothness": 0.5 },
+///   "color_grading": { "lut": "luts/warm.png", "strength": 0.8 },
+///   "fxaa": true
+/// }
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PostProcessData {
+    exposure: Option<f32>,
+    /// "none", "reinhard" or "aces"
+    tonemapper: Option<String>,
+    bloom: Option<BloomData>,
+    vignette: Option<VignetteData>,
+    color_grading: Option<ColorGradingData>,
+    fxaa: Option<bool>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BloomData {
+    enabled: Option<bool>,
+    threshold: Option<f32>,
+    intensity: Option<f32>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VignetteData {
+    enabled: Option<bool>,
+    intensity: Option<f32>,
+    smoothness: Option<f32>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColorGradingData {
+    enabled: Option<bool>,
+    /// Path to the LUT strip image
+    lut: Option<String>,
+    strength: Option<f32>,
+}
+
+/// What the scene shows behind everything, exactly one of the forms:
+///
+/// ```json
+/// "background": { "color": [0.1, 0.1, 0.15] }
+/// "background": { "gradient": { "top": [0.2, 0.4, 0.9], "bottom": [0.8, 0.9, 1.0] } }
+/// "background": { "skybox": "skies/sunset.hdr" }
+/// "background": { "skybox": ["px.png", "nx.png", "py.png", "ny.png", "pz.png", "nz.png"] }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackgroundData {
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    color: Option<Vec3>,
+    gradient: Option<GradientData>,
+    skybox: Option<SkyboxData>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GradientData {
+    #[serde(deserialize_with = "vec3_from_array")]
+    top: Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    bottom: Vec3,
+}
+
+/// An equirectangular panorama, or six faces in +X, -X, +Y, -Y, +Z, -Z order
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SkyboxData {
+    Panorama(String),
+    Faces(Vec<String>),
+}
+
+/// Scene fog. Listing it turns it on unless it says `"enabled": false`;
+/// missing values keep their defaults.
+///
+/// ```json
+/// "fog": { "mode": "linear", "color": [0.6, 0.7, 0.8], "start": 20, "end": 150 }
+/// "fog": { "mode": "height", "density": 0.05, "height": 0, "height_falloff": 0.3 }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FogData {
+    enabled: Option<bool>,
+    /// "linear", "exponential", "exponential_squared" or "height"
+    mode: Option<String>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    color: Option<Vec3>,
+    start: Option<f32>,
+    end: Option<f32>,
+    density: Option<f32>,
+    height: Option<f32>,
+    height_falloff: Option<f32>,
+}
+
+impl FogData {
+    fn to_fog(&self) -> Result<Fog, String> {
+        let mut fog = Fog {
+            enabled: self.enabled.unwrap_or(true),
+            ..Fog::default()
+        };
+        if let Some(mode) = &self.mode {
+            fog.mode = match mode.as_str() {
+                "linear" => FogMode::Linear,
+                "exponential" => FogMode::Exponential,
+                "exponential_squared" => FogMode::ExponentialSquared,
+                "height" => FogMode::Height,
+                other => return Err(format!("unknown fog mode '{}'", other)),
+            };
+        }
+        fog.color = self.color.unwrap_or(fog.color);
+        fog.start = self.start.unwrap_or(fog.start);
+        fog.end = self.end.unwrap_or(fog.end);
+        fog.density = self.density.unwrap_or(fog.density);
+        fog.height = self.height.unwrap_or(fog.height);
+        fog.height_falloff = self.height_falloff.unwrap_or(fog.height_falloff);
+        Ok(fog)
+    }
+}
+
+/// Particle emitter settings on top of the defaults, on an entity as
+/// "particles" or meshless under "emitters"
+///
+/// ```json
+/// "particles": { "rate": 0, "bursts": [{ "time": 0.5, "count": 200 }],
+///                "speed": [4, 6], "cone_angle": 180, "gravity": [0, -9.8, 0],
+///                "end_color": [1, 0.2, 0, 0], "blend": "additive" }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmitterData {
+    enabled: Option<bool>,
+    rate: Option<f32>,
+    #[serde(default)]
+    bursts: Vec<BurstData>,
+    max_particles: Option<u32>,
+    /// Seconds, [min, max]
+    lifetime: Option<[f32; 2]>,
+    speed: Option<[f32; 2]>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    direction: Option<Vec3>,
+    /// Degrees
+    cone_angle: Option<f32>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    gravity: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    start_color: Option<Vec4>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    end_color: Option<Vec4>,
+    start_size: Option<f32>,
+    end_size: Option<f32>,
+    /// "opaque", "alpha", "additive" or "multiply"
+    blend: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BurstData {
+    time: f32,
+    count: u32,
+}
+
+/// Emitter without a mesh, listed under "emitters" in the scene file
+#[derive(Deserialize)]
+struct SceneEmitter {
+    name: String,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    position: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
+    #[serde(flatten)]
+    emitter: EmitterData,
+}
+
+impl EmitterData {
+    fn to_emitter(&self) -> Result<ParticleEmitter, String> {
+        let mut emitter = ParticleEmitter::default();
+        emitter.enabled = self.enabled.unwrap_or(true);
+        emitter.rate = self.rate.unwrap_or(emitter.rate);
+        emitter.bursts = self
+            .bursts
+            .iter()
+            .map(|b| ParticleBurst {
+                time: b.time,
+                count: b.count,
+            })
+            .collect();
+        emitter.bursts.sort_by(|a, b| a.time.total_cmp(&b.time));
+        emitter.max_particles = self.max_particles.unwrap_or(emitter.max_particles);
+        if let Some([low, high]) = self.lifetime {
+            emitter.lifetime = (low, high);
+        }
+        if let Some([low, high]) = self.speed {
+            emitter.speed = (low, high);
+        }
+        emitter.direction = self.direction.unwrap_or(emitter.direction);
+        emitter.cone_angle = self.cone_angle.unwrap_or(emitter.cone_angle);
+        emitter.gravity = self.gravity.unwrap_or(emitter.gravity);
+        emitter.start_color = self.start_color.unwrap_or(emitter.start_color);
+        emitter.end_color = self.end_color.unwrap_or(emitter.end_color);
+        emitter.start_size = self.start_size.unwrap_or(emitter.start_size);
+        emitter.end_size = self.end_size.unwrap_or(emitter.end_size);
+        if let Some(blend) = &self.blend {
+            emitter.blend = parse_blend_mode(blend)?;
+        }
+        Ok(emitter)
+    }
+}
+
+/// Sprite on a meshless entity, listed under "sprites" in the scene file.
+/// `texture` shows a plain image; `sheet` takes the texture, frames and
+/// animations from a `.sprites` file.
+///
+/// ```json
+/// { "name": "Hero", "position": [0, 0, 0], "sheet": "hero.sprites",
+///   "animation": "run", "size": [1, 1.5], "pivot": [0.5, 0], "layer": 2,
+///   "scripts": ["scripts/hero.wasm"] }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneSprite {
+    name: String,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    position: Option<Vec3>,
+    /// XYZ Euler angles in radians
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    scale: Option<Vec3>,
+    texture: Option<String>,
+    sheet: Option<String>,
+    /// Pixel rectangle `[x, y, width, height]` of the texture
+    region: Option<[f32; 4]>,
+    /// Named frame of the sheet
+    frame: Option<String>,
+    /// Clip of the sheet to start playing
+    animation: Option<String>,
+    /// World units, defaults to 1x1
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    size: Option<Vec2>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    tint: Option<Vec4>,
+    #[serde(default)]
+    flip_x: bool,
+    #[serde(default)]
+    flip_y: bool,
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    pivot: Option<Vec2>,
+    #[serde(default)]
+    layer: i32,
+    /// "nearest" or "linear", defaults to the sheet's filter or nearest
+    filter: Option<String>,
+    scripts: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+}
+
+/// Map made in the Tiled editor, listed under "tilemaps" in the scene file.
+/// The entity holds one child per layer: tile layers get a `Tilemap`,
+/// object layers one child entity per object, tagged with its class. Tile
+/// layers sort on `layer`, `layer + 1`, ... in the map's order.
+///
+/// ```json
+/// { "name": "Level1", "path": "maps/level1.tmx", "position": [-10, 6, 0],
+///   "cell_size": [1, 1], "layer": -10 }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneTilemap {
+    name: String,
+    /// `.tmx` or `.tmj` file
+    path: String,
+    /// Top left corner of the map
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    position: Option<Vec3>,
+    /// XYZ Euler angles in radians
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    scale: Option<Vec3>,
+    /// World size of a cell, defaults to 1 unit wide keeping the map's
+    /// aspect
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    cell_size: Option<Vec2>,
+    #[serde(default)]
+    layer: i32,
+    /// "nearest" or "linear", defaults to nearest
+    filter: Option<String>,
+    scripts: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+}
+
+/// World-space text on a meshless entity, listed under "texts" in the
+/// scene file. `font` is a `.ttf` or `.otf` file, the built-in font when
+/// left out.
+///
+/// ```json
+/// { "name": "Sign", "position": [0, 2, 0], "text": "Shop\nOpen",
+///   "font": "fonts/title.ttf", "size": 0.5, "align": "center",
+///   "billboard": true }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneText {
+    name: String,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    position: Option<Vec3>,
+    /// XYZ Euler angles in radians
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    scale: Option<Vec3>,
+    text: String,
+    font: Option<String>,
+    /// Line height in world units, defaults to 1
+    size: Option<f32>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    color: Option<Vec4>,
+    /// "left", "center" or "right"
+    align: Option<String>,
+    #[serde(default)]
+    billboard: bool,
+    scripts: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+}
+
+/// Screen-space label, listed under "hud" in the scene file. Scripts
+/// change it through its name.
+///
+/// ```json
+/// { "name": "score", "text": "Score: 0", "size": 32,
+///   "anchor": [1, 0], "offset": [-16, 16], "align": "right" }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneHudText {
+    name: String,
+    #[serde(default)]
+    text: String,
+    font: Option<String>,
+    /// Line height in pixels, defaults to 24
+    size: Option<f32>,
+    #[serde(default, deserialize_with = "opt_vec4_from_array")]
+    color: Option<Vec4>,
+    /// "left", "center" or "right"
+    align: Option<String>,
+    /// Point of the window from (0, 0) top left to (1, 1) bottom right
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    anchor: Option<Vec2>,
+    /// Pixels from the anchor, +Y down
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    offset: Option<Vec2>,
+}
+
+/// Game UI panel drawn with egui, listed under "ui" in the scene file.
+/// Widgets are "label", "button" or "progress"; scripts change them and
+/// hear about clicks through their `id`.
+///
+/// ```json
+/// { "name": "status", "anchor": "top_right", "offset": [-10, 10],
+///   "title": "Player", "width": 180,
+///   "widgets": [
+///     { "type": "progress", "id": "health", "value": 1, "text": "HP" },
+///     { "type": "button", "id": "pause", "text": "Pause" }
+///   ] }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneUiPanel {
+    name: String,
+    title: Option<String>,
+    /// "top_left", "top", "top_right", "left", "center", "right",
+    /// "bottom_left", "bottom" or "bottom_right"
+    anchor: Option<String>,
+    /// Points from the anchor, +Y down
+    #[serde(default, deserialize_with = "opt_vec2_from_array")]
+    offset: Option<Vec2>,
+    width: Option<f32>,
+    #[serde(default)]
+    horizontal: bool,
+    /// Defaults to true
+    visible: Option<bool>,
+    #[serde(default)]
+    widgets: Vec<SceneUiWidget>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneUiWidget {
+    #[serde(rename = "type")]
+    kind: String,
+    id: String,
+    text: Option<String>,
+    /// Progress bars, 0 to 1
+    value: Option<f32>,
+    /// Buttons, defaults to true
+    enabled: Option<bool>,
+}
+
+impl SceneUiPanel {
+    fn to_panel(&self) -> Result<UiPanel, String> {
+        let mut panel = UiPanel {
+            title: self.title.clone(),
+            offset: self.offset.unwrap_or(Vec2::ZERO),
+            width: self.width,
+            horizontal: self.horizontal,
+            visible: self.visible.unwrap_or(true),
+            ..UiPanel::default()
+        };
+        if let Some(anchor) = &self.anchor {
+            panel.anchor = parse_ui_anchor(anchor)?;
+        }
+        for widget in &self.widgets {
+            panel.widgets.push(widget.to_widget()?);
+        }
+        Ok(panel)
+    }
+}
+
+impl SceneUiWidget {
+    fn to_widget(&self) -> Result<UiWidget, String> {
+        let text = self.text.clone();
+        let mut widget = match self.kind.as_str() {
+            "label" => UiWidget::label(&self.id, text.unwrap_or_default()),
+            "button" => UiWidget::button(&self.id, text.unwrap_or_default()),
+            "progress" => {
+                let mut bar = UiWidget::progress_bar(&self.id, self.value.unwrap_or(0.0));
+                if let Some(text) = text {
+                    bar.set_text(text);
+                }
+                bar
+            }
+            other => return Err(format!("unknown ui widget '{}'", other)),
+        };
+        if let (UiWidgetKind::Button { enabled, .. }, Some(value)) =
+            (&mut widget.kind, self.enabled)
+        {
+            *enabled = value;
+        }
+        Ok(widget)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct LightData {
+    /// "directional", "point" or "spot"
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default = "white", deserialize_with = "vec3_from_array")]
+    color: Vec3,
+    #[serde(default = "one")]
+    intensity: f32,
+    #[serde(default = "default_light_range")]
+    range: f32,
+    #[serde(default = "default_inner_angle")]
+    inner_angle: f32,
+    #[serde(default = "default_outer_angle")]
+    outer_angle: f32,
+    /// Convenience for directional and spot lights instead of a rotation
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    direction: Option<Vec3>,
+    casts_shadows: Option<bool>,
+}
+
+/// Light without a mesh, listed under "lights" in the scene file
+#[derive(Deserialize)]
+struct SceneLight {
+    name: String,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    position: Option<Vec3>,
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    rotation: Option<Vec3>,
+    #[serde(flatten)]
+    light: LightData,
+}
+
+impl LightData {
+    fn to_light(&self) -> Result<Light, String> {
+        let mut light = match self.kind.as_str() {
+            "directional" => Light::directional(self.color, self.intensity),
+            "point" => Light::point(self.color, self.intensity, self.range),
+            "spot" => Light::spot(
+                self.color,
+                self.intensity,
+                self.range,
+                self.inner_angle,
+                self.outer_angle,
+            ),
+            other => return Err(format!("unknown light type '{}'", other)),
+        };
+        if let Some(casts_shadows) = self.casts_shadows {
+            light.casts_shadows = casts_shadows;
+        }
+        Ok(light)
+    }
+
+    /// Rotation that points the light's forward (-Z) axis along `direction`
+    fn rotation(&self) -> Option<Vec3> {
+        let direction = self.direction?.try_normalize()?;
+        let (x, y, z) = Quat::from_rotation_arc(-Vec3::Z, direction).to_euler(EulerRot::XYZ);
+        Some(Vec3::new(x, y, z))
+    }
+}
+
+fn white() -> Vec3 {
+    Vec3::ONE
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+fn default_light_range() -> f32 {
+    10.0
+}
+
+fn default_inner_angle() -> f32 {
+    20.0
+}
+
+fn default_outer_angle() -> f32 {
+    30.0
 }
 
 #[derive(Deserialize)]
@@ -55,39 +709,195 @@ pub struct SceneCamera {
     pub far: f32,
     pub active: bool,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub post_process: Option<PostProcessData>,
+    #[serde(flatten)]
+    view: CameraViewData,
 }
 #[derive(Deserialize)]
 
 struct SceneFile {
     entities: Vec<SceneEntity>,
     cameras: Vec<SceneCamera>,
+    #[serde(default)]
+    lights: Vec<SceneLight>,
+    #[serde(default)]
+    emitters: Vec<SceneEmitter>,
+    #[serde(default)]
+    sprites: Vec<SceneSprite>,
+    #[serde(default)]
+    tilemaps: Vec<SceneTilemap>,
+    #[serde(default)]
+    texts: Vec<SceneText>,
+    #[serde(default)]
+    hud: Vec<SceneHudText>,
+    #[serde(default)]
+    ui: Vec<SceneUiPanel>,
+    /// Ambient light color, defaults to a dim grey
+    #[serde(default, deserialize_with = "opt_vec3_from_array")]
+    ambient: Option<Vec3>,
+    post_process: Option<PostProcessData>,
+    /// MSAA samples per pixel: 1, 2, 4 or 8
+    msaa: Option<u32>,
+    background: Option<BackgroundData>,
+    fog: Option<FogData>,
 }
 
 impl Engine {
+    /// Load a JSON scene, or a `.gltf`/`.glb` file as a whole scene. Asset paths
+    /// inside the scene are resolved against its project root.
+    ///
+    /// Meshes, scripts and glTF scenes load in the background; JSON entities are
+    /// spawned right away and glTF nodes once their file is ready. Use
+    /// `scene_progress` or `wait_for_scene` to track completion.
     pub fn load_scene(&mut self, path: String) -> Result<(), String> {
-        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        self.scene_path = Some(path.clone());
+        let scene_path = fs::canonicalize(&path).map_err(|e| format!("scene '{}': {}", path, e))?;
+        self.assets.set_root(find_project_root(&scene_path));
+
+        let handle = self.assets.load_scene(&scene_path.to_string_lossy());
+        self.scene_assets.add(AssetId::Scene(handle));
+
+        match self.assets.scenes.status(handle) {
+            Some(AssetStatus::Failed(e)) => return Err(e.clone()),
+            Some(AssetStatus::Pending) => {
+                self.pending_scenes.push(handle);
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        match self.assets.scenes.get(handle) {
+            Some(SceneAsset::Json(data)) => {
+                let data = data.clone();
+                self.spawn_json_scene(&data)
+            }
+            Some(SceneAsset::Gltf { nodes, meshes }) => {
+                let (nodes, meshes) = (nodes.clone(), meshes.clone());
+                spawn_gltf_scene(&mut self.world, &mut self.assets, &nodes, &meshes);
+                Ok(())
+            }
+            None => Err(format!("scene '{}' has no data", path)),
+        }
+    }
+
+    /// Apply finished background loads: spawn glTF scenes whose file arrived
+    /// and give entities the default material of meshes that just loaded.
+    /// Edited shader files are reloaded here too.
+    pub fn poll_assets(&mut self) {
+        self.assets.reload_changed_shaders();
+        self.assets.poll();
+
+        let (pending, ready): (Vec<_>, Vec<_>) = self
+            .pending_scenes
+            .drain(..)
+            .partition(|&handle| self.assets.scenes.is_pending(handle));
+        self.pending_scenes = pending;
+        for handle in ready {
+            if let Some(SceneAsset::Gltf { nodes, meshes }) = self.assets.scenes.get(handle) {
+                let (nodes, meshes) = (nodes.clone(), meshes.clone());
+                spawn_gltf_scene(&mut self.world, &mut self.assets, &nodes, &meshes);
+            }
+        }
+
+        let (pending, ready): (Vec<_>, Vec<_>) = self
+            .pending_materials
+            .drain(..)
+            .partition(|&(_, mesh)| self.assets.meshes.is_pending(mesh));
+        self.pending_materials = pending;
+        for (entity_id, mesh) in ready {
+            let Some(material) = mesh_material(&mut self.assets, mesh) else {
+                continue;
+            };
+            if let Some(entity) = self.world.get_entity_mut(entity_id) {
+                entity.add_material(material);
+            }
+        }
+    }
+
+    /// Loading progress of everything the current scene references
+    pub fn scene_progress(&self) -> LoadProgress {
+        self.assets.progress(&self.scene_assets)
+    }
 
-        let scene: SceneFile = serde_json::from_str(&data).map_err(|e| {
-            eprintln!("Serde error: {:?}", e);
+    /// Block until every asset of the current scene has loaded or failed
+    pub fn wait_for_scene(&mut self) {
+        self.assets.wait_for(&self.scene_assets);
+        self.poll_assets();
+    }
+
+    fn spawn_json_scene(&mut self, data: &str) -> Result<(), String> {
+        let scene: SceneFile = serde_json::from_str(data).map_err(|e| {
+            log_error!("Serde error: {:?}", e);
             e.to_string()
         })?;
 
         for e in scene.entities {
+            let mesh = self.assets.load_mesh(&e.mesh);
+            self.scene_assets.add(AssetId::Mesh(mesh));
+
+            for script in e.scripts.iter().flatten() {
+                let handle = self.assets.load_script(script);
+                self.scene_assets.add(AssetId::Script(handle));
+            }
+
+            // A material file wins over the material that came with the mesh
+            // file, and an explicit scene color wins over both
+            let mut material = match &e.material {
+                Some(path) => {
+                    let handle = self.assets.load_material(path);
+                    self.scene_assets.add(AssetId::Material(handle));
+                    self.assets.materials.get(handle).cloned()
+                }
+                None => mesh_material(&mut self.assets, mesh),
+            }
+            .unwrap_or_default();
+            if let Some(color) = e.color {
+                material.color = color;
+                if color.w < 1.0 && material.blend == BlendMode::Opaque {
+                    material.blend = BlendMode::Alpha;
+                }
+            }
+            if let Some(blend) = &e.blend {
+                material.blend = parse_blend_mode(blend)?;
+            }
+            let awaiting_material =
+                e.color.is_none() && e.material.is_none() && self.assets.meshes.is_pending(mesh);
+            let light = e.light.as_ref().map(LightData::to_light).transpose()?;
+            let emitter = e
+                .particles
+                .as_ref()
+                .map(EmitterData::to_emitter)
+                .transpose()
+                .map_err(|err| format!("particles of '{}': {}", e.name, err))?;
+            let rotation = e
+                .rotation
+                .or_else(|| e.light.as_ref().and_then(LightData::rotation));
+
             let mut builder = Entity::builder_with_world(
                 &mut self.world,
                 e.name,
                 Vec3::from(e.position),
                 Vec3::from(e.scale),
-                match e.mesh.to_lowercase().as_str() {
-                    "cube" => MeshType::Cube,
-                    "triangle" => MeshType::Triangle,
-                    _ => MeshType::Custom(0),
-                },
-                Vec4::from(e.color),
+                MeshType::Custom(mesh.id()),
+                material.color,
                 e.scripts.as_ref().and_then(|s| s.get(0).cloned()),
             );
 
-            if let Some(cam) = e.camera {
+            if let Some(rotation) = rotation {
+                builder = builder.with_rotation(rotation);
+            }
+
+            if let Some(light) = light {
+                builder = builder.with_light(light);
+            }
+
+            builder = builder.with_shadows(
+                e.casts_shadows.unwrap_or(true),
+                e.receives_shadows.unwrap_or(true),
+            );
+
+            if let Some(cam) = &e.camera {
                 builder = builder.with_camera(cam.fov, cam.near, cam.far);
             }
 
@@ -95,18 +905,37 @@ impl Engine {
                 builder = builder.with_tags(tags);
             }
 
-            let entity_id = builder.build();
-
-            // If camera is marked active, set it
+            let post_process = e
+                .camera
+                .as_ref()
+                .and_then(|cam| cam.post_process.as_ref())
+                .map(|data| self.load_post_process(data))
+                .transpose()?;
 
-            if let Some(cam) = &e.camera {
-                if cam.active {
-                    set_active_camera(&mut self.world, entity_id);
+            let entity_id = builder.build();
+            if let Some(entity) = self.world.get_entity_mut(entity_id) {
+                entity.add_material(material);
+                if let Some(emitter) = emitter {
+                    entity.add_particle_emitter(emitter);
                 }
+                if let (Some(camera), Some(data)) = (&mut entity.camera, &e.camera) {
+                    camera.post_process = post_process;
+                    // Every camera marked active is drawn
+                    camera.is_active = data.active;
+                    data.view.apply(camera, &mut self.assets)?;
+                }
+            }
+            if awaiting_material {
+                self.pending_materials.push((entity_id, mesh));
             }
         }
 
         for c in scene.cameras {
+            let post_process = c
+                .post_process
+                .as_ref()
+                .map(|data| self.load_post_process(data))
+                .transpose()?;
             let camera_entity = crate::modules::ecs::entity::spawn_camera(
                 &mut self.world,
                 c.name,
@@ -117,13 +946,514 @@ impl Engine {
                 c.far,
             );
 
-            if c.active {
-                set_active_camera(&mut self.world, camera_entity); // marks this as the active camera
+            if let Some(camera) = self
+                .world
+                .get_entity_mut(camera_entity)
+                .and_then(|e| e.camera.as_mut())
+            {
+                camera.post_process = post_process;
+                camera.is_active = c.active;
+                c.view.apply(camera, &mut self.assets)?;
             }
         }
 
+        for l in scene.lights {
+            let light = l
+                .light
+                .to_light()
+                .map_err(|e| format!("light '{}': {}", l.name, e))?;
+            let rotation = l
+                .rotation
+                .or_else(|| l.light.rotation())
+                .unwrap_or(Vec3::ZERO);
+
+            let light_entity = self.world.create_entity(l.name);
+            if let Some(entity) = self.world.get_entity_mut(light_entity) {
+                entity.add_transform(Transform {
+                    position: l.position.unwrap_or(Vec3::ZERO),
+                    rotation,
+                    scale: Vec3::ONE,
+                });
+                entity.add_light(light);
+            }
+        }
+
+        for em in scene.emitters {
+            let emitter = em
+                .emitter
+                .to_emitter()
+                .map_err(|e| format!("emitter '{}': {}", em.name, e))?;
+
+            let emitter_entity = self.world.create_entity(em.name);
+            if let Some(entity) = self.world.get_entity_mut(emitter_entity) {
+                entity.add_transform(Transform {
+                    position: em.position.unwrap_or(Vec3::ZERO),
+                    rotation: em.rotation.unwrap_or(Vec3::ZERO),
+                    scale: Vec3::ONE,
+                });
+                entity.add_particle_emitter(emitter);
+            }
+        }
+
+        for sp in scene.sprites {
+            let (sprite, animator) = self
+                .load_sprite(&sp)
+                .map_err(|e| format!("sprite '{}': {}", sp.name, e))?;
+
+            let sprite_entity = self.world.create_entity(sp.name);
+            if let Some(entity) = self.world.get_entity_mut(sprite_entity) {
+                entity.add_transform(Transform {
+                    position: sp.position.unwrap_or(Vec3::ZERO),
+                    rotation: sp.rotation.unwrap_or(Vec3::ZERO),
+                    scale: sp.scale.unwrap_or(Vec3::ONE),
+                });
+                entity.add_sprite(sprite);
+                if let Some(animator) = animator {
+                    entity.add_sprite_animator(animator);
+                }
+                if let Some(scripts) = sp.scripts {
+                    entity.scripts = Some(scripts.into_iter().map(Script::new).collect());
+                }
+                if let Some(tags) = sp.tags {
+                    entity.tags = tags;
+                }
+            }
+        }
+
+        for tm in scene.tilemaps {
+            self.load_tilemap(&tm)
+                .map_err(|e| format!("tilemap '{}': {}", tm.name, e))?;
+        }
+
+        for tx in scene.texts {
+            let text = self
+                .load_text(&tx)
+                .map_err(|e| format!("text '{}': {}", tx.name, e))?;
+
+            let text_entity = self.world.create_entity(tx.name);
+            if let Some(entity) = self.world.get_entity_mut(text_entity) {
+                entity.add_transform(Transform {
+                    position: tx.position.unwrap_or(Vec3::ZERO),
+                    rotation: tx.rotation.unwrap_or(Vec3::ZERO),
+                    scale: tx.scale.unwrap_or(Vec3::ONE),
+                });
+                entity.add_text(text);
+                if let Some(scripts) = tx.scripts {
+                    entity.scripts = Some(scripts.into_iter().map(Script::new).collect());
+                }
+                if let Some(tags) = tx.tags {
+                    entity.tags = tags;
+                }
+            }
+        }
+
+        for label in &scene.hud {
+            let hud_text = self
+                .load_hud_text(label)
+                .map_err(|e| format!("hud '{}': {}", label.name, e))?;
+            self.world.hud.insert(label.name.clone(), hud_text);
+        }
+
+        for panel in &scene.ui {
+            let ui_panel = panel
+                .to_panel()
+                .map_err(|e| format!("ui panel '{}': {}", panel.name, e))?;
+            self.world.ui.insert_panel(panel.name.clone(), ui_panel);
+        }
+
+        if let Some(ambient) = scene.ambient {
+            self.world.ambient_light = ambient;
+        }
+        if let Some(data) = &scene.post_process {
+            self.world.post_process = self.load_post_process(data)?;
+        }
+        if let Some(samples) = scene.msaa {
+            if !SAMPLE_COUNTS.contains(&samples) {
+                return Err(format!("msaa must be 1, 2, 4 or 8, got {}", samples));
+            }
+            self.world.msaa_samples = samples;
+        }
+        if let Some(data) = &scene.background {
+            self.world.background = Some(self.load_background(data)?);
+        }
+        if let Some(data) = &scene.fog {
+            self.world.fog = data.to_fog()?;
+        }
+
         Ok(())
     }
+
+    /// Sprite from the scene file; its texture or sheet starts loading as
+    /// part of the scene
+    fn load_sprite(
+        &mut self,
+        data: &SceneSprite,
+    ) -> Result<(Sprite, Option<SpriteAnimator>), String> {
+        if data.sheet.is_none() && (data.frame.is_some() || data.animation.is_some()) {
+            return Err("frame and animation need a sheet".into());
+        }
+        let mut sprite = Sprite {
+            size: data.size.unwrap_or(Vec2::ONE),
+            tint: data.tint.unwrap_or(Vec4::ONE),
+            flip_x: data.flip_x,
+            flip_y: data.flip_y,
+            pivot: data.pivot.unwrap_or(Vec2::splat(0.5)),
+            layer: data.layer,
+            region: data
+                .region
+                .map(|[x, y, width, height]| SpriteRegion::new(x, y, width, height)),
+            ..Sprite::default()
+        };
+
+        let mut animator = None;
+        match (&data.texture, &data.sheet) {
+            (Some(_), Some(_)) => return Err("needs at most one of texture or sheet".into()),
+            (Some(path), None) => {
+                let texture = self.assets.load_texture(path);
+                self.scene_assets.add(AssetId::Texture(texture));
+                sprite.texture = Some(texture);
+            }
+            (None, Some(path)) => {
+                let handle = self.assets.load_sprite_sheet(path);
+                self.scene_assets.add(AssetId::SpriteSheet(handle));
+                if let Some(sheet) = self.assets.sprite_sheets.get(handle) {
+                    sprite.texture = sheet.texture;
+                    sprite.sampler.filter = sheet.filter;
+                    if let Some(frame) = &data.frame {
+                        let region = sheet
+                            .frames
+                            .get(frame)
+                            .ok_or_else(|| format!("unknown frame '{}'", frame))?;
+                        sprite.region = Some(*region);
+                    }
+                    if let Some(clip) = &data.animation {
+                        let first = sheet
+                            .animations
+                            .get(clip)
+                            .ok_or_else(|| format!("unknown animation '{}'", clip))?
+                            .frames[0];
+                        sprite.region = Some(first);
+                    }
+                }
+                let mut sheet_animator = SpriteAnimator::new(handle);
+                if let Some(clip) = &data.animation {
+                    sheet_animator.play(clip);
+                }
+                animator = Some(sheet_animator);
+            }
+            (None, None) => {}
+        }
+        if let Some(filter) = &data.filter {
+            sprite.sampler.filter = parse_texture_filter(filter)?;
+        }
+        Ok((sprite, animator))
+    }
+
+    /// Text from the scene file, its font loaded right away
+    fn load_text(&mut self, data: &SceneText) -> Result<Text, String> {
+        let mut text = Text {
+            text: data.text.clone(),
+            size: data.size.unwrap_or(1.0),
+            color: data.color.unwrap_or(Vec4::ONE),
+            billboard: data.billboard,
+            ..Text::default()
+        };
+        if let Some(align) = &data.align {
+            text.align = parse_text_align(align)?;
+        }
+        if let Some(path) = &data.font {
+            text.font = self.load_scene_font(path);
+        }
+        Ok(text)
+    }
+
+    fn load_hud_text(&mut self, data: &SceneHudText) -> Result<HudText, String> {
+        let mut label = HudText {
+            text: data.text.clone(),
+            anchor: data.anchor.unwrap_or(Vec2::ZERO),
+            offset: data.offset.unwrap_or(Vec2::ZERO),
+            ..HudText::default()
+        };
+        if let Some(size) = data.size {
+            label.size = size;
+        }
+        if let Some(color) = data.color {
+            label.color = color;
+        }
+        if let Some(align) = &data.align {
+            label.align = parse_text_align(align)?;
+        }
+        if let Some(path) = &data.font {
+            label.font = self.load_scene_font(path);
+        }
+        Ok(label)
+    }
+
+    fn load_scene_font(&mut self, path: &str) -> FontHandle {
+        let handle = self.assets.load_font(path);
+        self.scene_assets.add(AssetId::Font(handle));
+        handle
+    }
+
+    /// Spawn a Tiled map and its layers; tileset images start loading as
+    /// part of the scene
+    fn load_tilemap(&mut self, data: &SceneTilemap) -> Result<EntityId, String> {
+        let map = tiled::load_tiled_map(self.assets.resolve(&data.path))?;
+        if map.tile_width == 0 || map.tile_height == 0 {
+            return Err("map has no tile size".into());
+        }
+        let sampler_filter = match &data.filter {
+            Some(filter) => Some(parse_texture_filter(filter)?),
+            None => None,
+        };
+        let cell_pixels = Vec2::new(map.tile_width as f32, map.tile_height as f32);
+        let cell_size = data
+            .cell_size
+            .unwrap_or(Vec2::new(1.0, cell_pixels.y / cell_pixels.x));
+        // Tiled pixels, +Y down, to local units
+        let to_local = |[x, y]: [f32; 2]| {
+            Vec3::new(
+                x / cell_pixels.x * cell_size.x,
+                -y / cell_pixels.y * cell_size.y,
+                0.0,
+            )
+        };
+        let tilesets = self.load_tilesets(&map);
+
+        let root = self.world.create_entity(data.name.clone());
+        if let Some(entity) = self.world.get_entity_mut(root) {
+            entity.add_transform(Transform {
+                position: data.position.unwrap_or(Vec3::ZERO),
+                rotation: data.rotation.unwrap_or(Vec3::ZERO),
+                scale: data.scale.unwrap_or(Vec3::ONE),
+            });
+            if let Some(scripts) = &data.scripts {
+                entity.scripts = Some(scripts.iter().cloned().map(Script::new).collect());
+            }
+            if let Some(tags) = &data.tags {
+                entity.tags = tags.clone();
+            }
+        }
+
+        let mut sort_layer = data.layer;
+        for layer in map.layers {
+            match layer {
+                TiledLayer::Tiles {
+                    name,
+                    width,
+                    height,
+                    data: gids,
+                    visible,
+                    opacity,
+                    offset,
+                } => {
+                    let mut tilemap = Tilemap::new(width, height, cell_pixels, tilesets.clone());
+                    tilemap.cell_size = cell_size;
+                    tilemap.tint.w = opacity;
+                    tilemap.layer = sort_layer;
+                    tilemap.visible = visible;
+                    if let Some(filter) = sampler_filter {
+                        tilemap.sampler.filter = filter;
+                    }
+                    for (index, &gid) in gids.iter().enumerate() {
+                        let (x, y) = (index as u32 % width, index as u32 / width);
+                        tilemap.set_tile(x, y, Tile::from_raw(gid));
+                    }
+                    sort_layer += 1;
+
+                    let layer_entity = self.world.create_entity(name);
+                    if let Some(entity) = self.world.get_entity_mut(layer_entity) {
+                        entity.add_transform(Transform {
+                            position: to_local(offset),
+                            ..Transform::default()
+                        });
+                        entity.add_tilemap(tilemap);
+                    }
+                    self.world.set_parent(layer_entity, root);
+                }
+                TiledLayer::Objects {
+                    name,
+                    objects,
+                    offset,
+                    ..
+                } => {
+                    let layer_entity = self.world.create_entity(name);
+                    if let Some(entity) = self.world.get_entity_mut(layer_entity) {
+                        entity.add_transform(Transform {
+                            position: to_local(offset),
+                            ..Transform::default()
+                        });
+                    }
+                    self.world.set_parent(layer_entity, root);
+
+                    for object in objects {
+                        let object_entity = self.world.create_entity(object.name);
+                        if let Some(entity) = self.world.get_entity_mut(object_entity) {
+                            entity.add_transform(Transform {
+                                position: to_local([object.x, object.y]),
+                                ..Transform::default()
+                            });
+                            if !object.class.is_empty() {
+                                entity.tags = vec![object.class];
+                            }
+                        }
+                        self.world.set_parent(object_entity, layer_entity);
+                    }
+                }
+            }
+        }
+        Ok(root)
+    }
+
+    /// Tilesets of a map with their images queued for loading
+    fn load_tilesets(&mut self, map: &TiledMap) -> Arc<[Tileset]> {
+        map.tilesets
+            .iter()
+            .map(|tileset| {
+                let texture = tileset.image.as_ref().map(|image| {
+                    let texture = self.assets.load_texture(&image.to_string_lossy());
+                    self.scene_assets.add(AssetId::Texture(texture));
+                    texture
+                });
+                Tileset {
+                    name: tileset.name.clone(),
+                    first_gid: tileset.first_gid,
+                    texture,
+                    tile_width: tileset.tile_width,
+                    tile_height: tileset.tile_height,
+                    columns: tileset.columns,
+                    tile_count: tileset.tile_count,
+                    margin: tileset.margin,
+                    spacing: tileset.spacing,
+                    collision: tileset.collision.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Background from the scene file; skyboxes start loading as part of the
+    /// scene
+    fn load_background(&mut self, data: &BackgroundData) -> Result<Background, String> {
+        let background = match (data.color, &data.gradient, &data.skybox) {
+            (Some(color), None, None) => Background::Color(color),
+            (None, Some(gradient), None) => Background::Gradient {
+                top: gradient.top,
+                bottom: gradient.bottom,
+            },
+            (None, None, Some(SkyboxData::Panorama(path))) => {
+                Background::Skybox(self.assets.load_cubemap(path))
+            }
+            (None, None, Some(SkyboxData::Faces(paths))) => {
+                let faces: [&str; 6] = paths
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .map_err(|_| format!("skybox needs 6 faces, got {}", paths.len()))?;
+                Background::Skybox(self.assets.load_cubemap_faces(faces))
+            }
+            _ => return Err("background needs exactly one of color, gradient or skybox".into()),
+        };
+        if let Background::Skybox(cubemap) = background {
+            self.scene_assets.add(AssetId::Cubemap(cubemap));
+        }
+        Ok(background)
+    }
+
+    /// Settings from the scene file on top of the defaults; the color grading
+    /// LUT starts loading as part of the scene
+    fn load_post_process(&mut self, data: &PostProcessData) -> Result<PostProcess, String> {
+        let mut post = PostProcess::default();
+        if let Some(exposure) = data.exposure {
+            post.exposure = exposure;
+        }
+        if let Some(name) = &data.tonemapper {
+            post.tonemapper = match name.as_str() {
+                "none" => Tonemapper::None,
+                "reinhard" => Tonemapper::Reinhard,
+                "aces" => Tonemapper::Aces,
+                other => return Err(format!("unknown tonemapper '{}'", other)),
+            };
+        }
+        if let Some(bloom) = &data.bloom {
+            post.bloom.enabled = bloom.enabled.unwrap_or(true);
+            post.bloom.threshold = bloom.threshold.unwrap_or(post.bloom.threshold);
+            post.bloom.intensity = bloom.intensity.unwrap_or(post.bloom.intensity);
+        }
+        if let Some(vignette) = &data.vignette {
+            post.vignette.enabled = vignette.enabled.unwrap_or(true);
+            post.vignette.intensity = vignette.intensity.unwrap_or(post.vignette.intensity);
+            post.vignette.smoothness = vignette.smoothness.unwrap_or(post.vignette.smoothness);
+        }
+        if let Some(grading) = &data.color_grading {
+            post.color_grading.enabled = grading.enabled.unwrap_or(true);
+            post.color_grading.strength = grading.strength.unwrap_or(1.0);
+            if let Some(path) = &grading.lut {
+                let lut = self.assets.load_texture(path);
+                self.scene_assets.add(AssetId::Texture(lut));
+                post.color_grading.lut = Some(lut);
+            }
+        }
+        post.fxaa = data.fxaa.unwrap_or(post.fxaa);
+        Ok(post)
+    }
+}
+
+/// Material that came with a mesh file, with its textures queued for loading
+fn mesh_material(assets: &mut Assets, mesh: MeshHandle) -> Option<Material> {
+    let data = assets.meshes.get(mesh)?.material.clone()?;
+    Some(assets.material_from_data(&data))
+}
+
+/// Spawn one entity per glTF node, keeping the node hierarchy
+fn spawn_gltf_scene(
+    world: &mut World,
+    assets: &mut Assets,
+    nodes: &[GltfNode],
+    meshes: &[MeshHandle],
+) {
+    let mut node_entities: Vec<EntityId> = Vec::with_capacity(nodes.len());
+    let mut first_camera = None;
+
+    for node in nodes {
+        let entity_id = world.create_entity(node.name.clone());
+        let (x, y, z) = node.rotation.to_euler(EulerRot::XYZ);
+
+        if let Some(entity) = world.get_entity_mut(entity_id) {
+            entity.add_transform(Transform {
+                position: node.translation,
+                rotation: Vec3::new(x, y, z),
+                scale: node.scale,
+            });
+
+            if let Some(mesh) = node.mesh.map(|index| meshes[index]) {
+                let material = mesh_material(assets, mesh).unwrap_or_default();
+
+                entity.add_mesh_handle(mesh);
+                entity.add_material(material);
+            }
+
+            if let Some(cam) = &node.camera {
+                entity.add_camera(Camera {
+                    fov: cam.fov,
+                    near: cam.near,
+                    far: cam.far,
+                    projection: cam.projection,
+                    ..Default::default()
+                });
+                first_camera.get_or_insert(entity_id);
+            }
+        }
+
+        if let Some(parent) = node.parent {
+            world.set_parent(entity_id, node_entities[parent]);
+        }
+        node_entities.push(entity_id);
+    }
+
+    if let Some(camera) = first_camera {
+        set_active_camera(world, camera);
+    }
 }
 
 trait IntoVec3 {
@@ -152,10 +1482,26 @@ where
     Ok(Vec3::new(arr[0], arr[1], arr[2]))
 }
 
-fn vec4_from_array<'de, D>(deserializer: D) -> Result<Vec4, D::Error>
+fn opt_vec3_from_array<'de, D>(deserializer: D) -> Result<Option<Vec3>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let arr = <Option<[f32; 3]>>::deserialize(deserializer)?;
+    Ok(arr.map(Vec3::from))
+}
+
+fn opt_vec2_from_array<'de, D>(deserializer: D) -> Result<Option<Vec2>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let arr = <Option<[f32; 2]>>::deserialize(deserializer)?;
+    Ok(arr.map(Vec2::from))
+}
+
+fn opt_vec4_from_array<'de, D>(deserializer: D) -> Result<Option<Vec4>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let arr = <[f32; 4]>::deserialize(deserializer)?;
-    Ok(Vec4::new(arr[0], arr[1], arr[2], arr[3]))
+    let arr = <Option<[f32; 4]>>::deserialize(deserializer)?;
+    Ok(arr.map(|a| Vec4::new(a[0], a[1], a[2], a[3])))
 }
@@ -0,0 +1,494 @@
+use crate::Engine;
+use crate::modules::assets::{CUBE_MESH, TRIANGLE_MESH};
+use crate::modules::ecs::entity::{MeshType, spawn_entity};
+use crate::modules::ecs::world::EntityId;
+use crate::modules::render::modes::RenderMode;
+use crate::modules::state::State;
+use glam::{Vec3, Vec4};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+
+// ============================================================================
+// LOG
+// ============================================================================
+
+/// Lines kept for the console, older ones are dropped
+pub const LOG_CAPACITY: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+    /// `console.log` of a script
+    Script,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogLine {
+    pub level: LogLevel,
+    pub text: String,
+}
+
+static LOG: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// Print a line and keep it for the console. Warnings and errors go to
+/// stderr.
+pub fn log(level: LogLevel, text: impl Into<String>) {
+    let text = text.into();
+    match level {
+        LogLevel::Warn | LogLevel::Error => eprintln!("{}", text),
+        LogLevel::Info | LogLevel::Script => println!("{}", text),
+    }
+    let mut lines = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if lines.len() == LOG_CAPACITY {
+        lines.pop_front();
+    }
+    lines.push_back(LogLine { level, text });
+}
+
+/// Kept lines, oldest first
+pub fn log_lines() -> Vec<LogLine> {
+    let lines = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    lines.iter().cloned().collect()
+}
+
+pub fn clear_log() {
+    LOG.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// `println!` that also shows in the console
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::modules::console::log($crate::modules::console::LogLevel::Info, format!($($arg)*))
+    };
+}
+
+/// `eprintln!` that shows in the console as a warning
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::modules::console::log($crate::modules::console::LogLevel::Warn, format!($($arg)*))
+    };
+}
+
+/// `eprintln!` that also shows in the console
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::modules::console::log($crate::modules::console::LogLevel::Error, format!($($arg)*))
+    };
+}
+
+// ============================================================================
+// COMMANDS
+// ============================================================================
+
+/// What a command can reach. `state` is `None` without a window, e.g. in
+/// tests; commands that need the renderer fail then.
+pub struct ConsoleContext<'a> {
+    pub engine: &'a mut Engine,
+    pub state: Option<&'a mut State>,
+}
+
+/// Console command. `args` are the words after the command's name; the
+/// returned text is shown in the console. Closures with the same signature
+/// are commands too.
+pub trait ConsoleCommand {
+    fn run(&self, args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String>;
+}
+
+impl<F> ConsoleCommand for F
+where
+    F: Fn(&[&str], &mut ConsoleContext) -> Result<String, String>,
+{
+    fn run(&self, args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+        self(args, ctx)
+    }
+}
+
+struct RegisteredCommand {
+    usage: String,
+    command: Box<dyn ConsoleCommand>,
+}
+
+/// Commands of the developer console by name. Starts with the built-in
+/// ones; games add their own with `register`.
+///
+/// ```ignore
+/// engine.console.register("god", "god - toggle invulnerability", |_: &[&str], ctx: &mut ConsoleContext| {
+///     ctx.engine.world.hud.set_text("god", "GOD MODE");
+///     Ok("god mode on".to_string())
+/// });
+/// ```
+#[derive(Default)]
+pub struct Console {
+    commands: BTreeMap<String, RegisteredCommand>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let mut console = Self::default();
+        console.register("entities", "entities - list entities", list_entities);
+        console.register(
+            "inspect",
+            "inspect <entity> - show an entity's components",
+            inspect,
+        );
+        console.register(
+            "set",
+            "set <entity> <field> <values> - change position, rotation, scale, color or text",
+            set_field,
+        );
+        console.register(
+            "spawn",
+            "spawn <cube|triangle|mesh file> [x y z] - add a mesh",
+            spawn,
+        );
+        console.register("reload", "reload - load the current scene again", reload);
+        console.register(
+            "timescale",
+            "timescale [scale] - show or set the time scale",
+            time_scale,
+        );
+        console.register(
+            "rendermode",
+            "rendermode [name] - next or named render mode",
+            render_mode,
+        );
+        console.register(
+            "clear",
+            "clear - empty the console",
+            |_: &[&str], _: &mut ConsoleContext| {
+                clear_log();
+                Ok(String::new())
+            },
+        );
+        console
+    }
+
+    /// Add a command, replacing any of that name. `usage` is shown by
+    /// `help`.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        usage: impl Into<String>,
+        command: impl ConsoleCommand + 'static,
+    ) {
+        self.commands.insert(
+            name.into(),
+            RegisteredCommand {
+                usage: usage.into(),
+                command: Box::new(command),
+            },
+        );
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Names and usage lines, by name
+    pub fn commands(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.commands
+            .iter()
+            .map(|(name, registered)| (name.as_str(), registered.usage.as_str()))
+    }
+
+    /// Run a command line such as `set Player position 0 1 0`. `help` lists
+    /// the commands.
+    pub fn run(&self, line: &str, ctx: &mut ConsoleContext) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            return Ok(String::new());
+        };
+        if name == "help" {
+            let mut lines = vec!["help - list commands".to_string()];
+            lines.extend(self.commands().map(|(_, usage)| usage.to_string()));
+            return Ok(lines.join("\n"));
+        }
+        match self.commands.get(name) {
+            Some(registered) => registered.command.run(args, ctx),
+            None => Err(format!("unknown command '{}', try help", name)),
+        }
+    }
+}
+
+impl Engine {
+    /// Run a console command and log it with its output
+    pub fn run_console_command(
+        &mut self,
+        state: Option<&mut State>,
+        line: &str,
+    ) -> Result<String, String> {
+        log(LogLevel::Info, format!("> {}", line));
+        // Commands get the whole engine, console included, so it is taken
+        // out while one runs
+        let console = std::mem::take(&mut self.console);
+        let result = console.run(
+            line,
+            &mut ConsoleContext {
+                engine: self,
+                state,
+            },
+        );
+        self.console = console;
+
+        match &result {
+            Ok(output) if !output.is_empty() => log(LogLevel::Info, output.clone()),
+            Ok(_) => {}
+            Err(e) => log(LogLevel::Error, e.clone()),
+        }
+        result
+    }
+}
+
+// ============================================================================
+// BUILT-IN COMMANDS
+// ============================================================================
+
+/// First entity called `name`
+fn find_entity(engine: &Engine, name: &str) -> Result<EntityId, String> {
+    engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == name)
+        .map(|(id, _)| id)
+        .ok_or_else(|| format!("no entity '{}'", name))
+}
+
+fn parse_floats<const N: usize>(args: &[&str]) -> Result<[f32; N], String> {
+    if args.len() != N {
+        return Err(format!("expected {} numbers, got {}", N, args.len()));
+    }
+    let mut values = [0.0; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", arg))?;
+    }
+    Ok(values)
+}
+
+fn list_entities(_: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let lines: Vec<String> = ctx
+        .engine
+        .world
+        .iter_entities()
+        .map(|(id, e)| {
+            let mut components = Vec::new();
+            for (name, present) in [
+                ("mesh", e.mesh_handle.is_some()),
+                ("camera", e.camera.is_some()),
+                ("light", e.light.is_some()),
+                ("particles", e.particle_emitter.is_some()),
+                ("sprite", e.sprite.is_some()),
+                ("tilemap", e.tilemap.is_some()),
+                ("text", e.text.is_some()),
+                ("scripts", e.scripts.is_some()),
+            ] {
+                if present {
+                    components.push(name);
+                }
+            }
+            format!("{} {:?} [{}]", e.name, id, components.join(", "))
+        })
+        .collect();
+    Ok(format!("{} entities\n{}", lines.len(), lines.join("\n")))
+}
+
+fn inspect(args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: inspect <entity>".into());
+    };
+    let world = &ctx.engine.world;
+    let id = find_entity(ctx.engine, name)?;
+    let Some(e) = world.get_entity(id) else {
+        return Err(format!("no entity '{}'", name));
+    };
+
+    let mut lines = vec![format!("{} {:?}", e.name, id)];
+    if let Some(parent) = e.parent.and_then(|p| world.get_entity(p)) {
+        lines.push(format!("parent: {}", parent.name));
+    }
+    if let Some(children) = &e.children {
+        lines.push(format!("children: {}", children.len()));
+    }
+    if !e.tags.is_empty() {
+        lines.push(format!("tags: {}", e.tags.join(", ")));
+    }
+    if let Some(t) = &e.transform {
+        lines.push(format!(
+            "position: {}  rotation: {}  scale: {}",
+            t.position, t.rotation, t.scale
+        ));
+    }
+    if let Some(mesh) = e.mesh_handle {
+        lines.push(format!("mesh: {}", mesh.id()));
+    }
+    if let Some(material) = &e.material {
+        lines.push(format!(
+            "material: color {} metallic {} roughness {}",
+            material.color, material.metallic, material.roughness
+        ));
+    }
+    if let Some(light) = &e.light {
+        lines.push(format!("light: {:?}", light));
+    }
+    if let Some(camera) = &e.camera {
+        lines.push(format!(
+            "camera: fov {} near {} far {} active {}",
+            camera.fov, camera.near, camera.far, camera.is_active
+        ));
+    }
+    if let Some(emitter) = &e.particle_emitter {
+        lines.push(format!(
+            "particles: rate {} enabled {}",
+            emitter.rate, emitter.enabled
+        ));
+    }
+    if let Some(sprite) = &e.sprite {
+        lines.push(format!(
+            "sprite: size {} tint {} layer {}",
+            sprite.size, sprite.tint, sprite.layer
+        ));
+    }
+    if let Some(tilemap) = &e.tilemap {
+        lines.push(format!(
+            "tilemap: {}x{} layer {}",
+            tilemap.width, tilemap.height, tilemap.layer
+        ));
+    }
+    if let Some(text) = &e.text {
+        lines.push(format!("text: {:?} size {}", text.text, text.size));
+    }
+    if let Some(scripts) = &e.scripts {
+        let paths: Vec<&str> = scripts.iter().map(|s| s.script_path.as_str()).collect();
+        lines.push(format!("scripts: {}", paths.join(", ")));
+    }
+    Ok(lines.join("\n"))
+}
+
+fn set_field(args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let [name, field, values @ ..] = args else {
+        return Err("usage: set <entity> <field> <values>".into());
+    };
+    let id = find_entity(ctx.engine, name)?;
+    let Some(e) = ctx.engine.world.get_entity_mut(id) else {
+        return Err(format!("no entity '{}'", name));
+    };
+
+    match *field {
+        "position" | "rotation" | "scale" => {
+            let value = Vec3::from(parse_floats::<3>(values)?);
+            let transform = e
+                .transform
+                .as_mut()
+                .ok_or_else(|| format!("'{}' has no transform", name))?;
+            match *field {
+                "position" => transform.position = value,
+                "rotation" => transform.rotation = value,
+                _ => transform.scale = value,
+            }
+        }
+        // The first of material, sprite, text and light the entity has
+        "color" => {
+            let color = match values.len() {
+                3 => Vec3::from(parse_floats::<3>(values)?).extend(1.0),
+                _ => Vec4::from(parse_floats::<4>(values)?),
+            };
+            if let Some(material) = &mut e.material {
+                material.color = color;
+            } else if let Some(sprite) = &mut e.sprite {
+                sprite.tint = color;
+            } else if let Some(text) = &mut e.text {
+                text.color = color;
+            } else if let Some(light) = &mut e.light {
+                light.color = color.truncate();
+            } else {
+                return Err(format!("'{}' has nothing colored", name));
+            }
+        }
+        "text" => {
+            let text = e
+                .text
+                .as_mut()
+                .ok_or_else(|| format!("'{}' has no text", name))?;
+            text.text = values.join(" ");
+        }
+        other => return Err(format!("unknown field '{}'", other)),
+    }
+    Ok(format!("{}.{} set", name, field))
+}
+
+fn spawn(args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let [mesh, position @ ..] = args else {
+        return Err("usage: spawn <cube|triangle|mesh file> [x y z]".into());
+    };
+    let position = match position {
+        [] => Vec3::ZERO,
+        _ => Vec3::from(parse_floats::<3>(position)?),
+    };
+    let (name, handle) = match *mesh {
+        "cube" => ("Cube".to_string(), CUBE_MESH),
+        "triangle" => ("Triangle".to_string(), TRIANGLE_MESH),
+        path => {
+            let name = Path::new(path)
+                .file_stem()
+                .map_or_else(|| path.to_string(), |s| s.to_string_lossy().into_owned());
+            (name, ctx.engine.assets.load_mesh(path))
+        }
+    };
+    spawn_entity(
+        &mut ctx.engine.world,
+        name.clone(),
+        position,
+        Vec3::ONE,
+        MeshType::Custom(handle.id()),
+        Vec4::ONE,
+    );
+    Ok(format!("spawned '{}' at {}", name, position))
+}
+
+fn reload(_: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let path = ctx.engine.scene_path.clone().ok_or("no scene loaded")?;
+    match ctx.state.as_deref_mut() {
+        Some(state) => ctx.engine.init_with_state(state, path.clone())?,
+        None => {
+            ctx.engine.init_world();
+            ctx.engine.load_scene(path.clone())?;
+        }
+    }
+    Ok(format!("reloaded '{}'", path))
+}
+
+fn time_scale(args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let world = &mut ctx.engine.world;
+    if let [value] = args {
+        let scale: f32 = value
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", value))?;
+        if scale.is_nan() || scale < 0.0 {
+            return Err("time scale can't be negative".into());
+        }
+        world.time_scale = scale;
+    }
+    Ok(format!("time scale {}", world.time_scale))
+}
+
+fn render_mode(args: &[&str], ctx: &mut ConsoleContext) -> Result<String, String> {
+    let state = ctx.state.as_deref_mut().ok_or("no renderer")?;
+    let mode = match args {
+        [] => state.render_mode().next(),
+        [name] => RenderMode::from_name(name)?,
+        _ => return Err("usage: rendermode [name]".into()),
+    };
+    state.set_render_mode(mode);
+    Ok(format!("render mode {}", mode.name()))
+}
@@ -19,10 +19,372 @@ impl Default for Transform {
     }
 }
 
-pub struct MeshHandle(pub u32); // index into GPU buffer
+impl Transform {
+    /// Rotation is stored as XYZ Euler angles in radians
+    pub fn rotation_quat(&self) -> glam::Quat {
+        glam::Quat::from_euler(
+            glam::EulerRot::XYZ,
+            self.rotation.x,
+            self.rotation.y,
+            self.rotation.z,
+        )
+    }
+
+    /// Local-to-parent matrix
+    pub fn matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(self.scale, self.rotation_quat(), self.position)
+    }
+}
 
+pub use crate::modules::assets::MeshHandle; // key into State::meshes
+use crate::modules::assets::{CubemapHandle, MaterialData, ShaderHandle, TextureHandle};
 
-#[derive(Copy, Clone)]
+
+/// PBR metallic-roughness material. Factors multiply the matching texture;
+/// missing textures behave like plain white (or a flat normal).
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Material {
+    /// Base color factor (linear RGBA)
     pub color: glam::Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: glam::Vec3,
+    pub normal_scale: f32,
+    pub base_color_texture: Option<TextureHandle>,
+    /// Roughness in the green channel, metallic in blue (glTF layout)
+    pub metallic_roughness_texture: Option<TextureHandle>,
+    pub emissive_texture: Option<TextureHandle>,
+    /// Tangent-space normal map
+    pub normal_texture: Option<TextureHandle>,
+    pub sampler: SamplerSettings,
+    /// Custom WGSL shader, `None` for the engine's PBR shader
+    pub shader: Option<ShaderHandle>,
+    /// How the material mixes with what's behind it
+    pub blend: BlendMode,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(Vec4::ONE)
+    }
+}
+
+impl Material {
+    pub fn new(color: glam::Vec4) -> Self {
+        Self {
+            color,
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive: Vec3::ZERO,
+            normal_scale: 1.0,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            emissive_texture: None,
+            normal_texture: None,
+            sampler: SamplerSettings::default(),
+            shader: None,
+            blend: BlendMode::for_alpha(color.w),
+        }
+    }
+
+    /// Factors of a material described by an imported mesh file. Textures are
+    /// attached by `Assets::material_from_data`.
+    pub fn from_data(data: &MaterialData) -> Self {
+        Self {
+            color: data.diffuse,
+            metallic: data.metallic,
+            roughness: data.roughness,
+            emissive: data.emissive,
+            blend: BlendMode::for_alpha(data.diffuse.w),
+            ..Self::default()
+        }
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = TextureHandle> {
+        [
+            self.base_color_texture,
+            self.metallic_roughness_texture,
+            self.emissive_texture,
+            self.normal_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureFilter {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureWrap {
+    #[default]
+    Repeat,
+    MirrorRepeat,
+    ClampToEdge,
+}
+
+/// How a material's textures are sampled
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SamplerSettings {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+}
+
+/// Blending of a material with the frame behind it. Everything but `Opaque`
+/// is drawn after the opaque geometry, back to front, without writing depth.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    #[default]
+    Opaque,
+    /// Mixed by the alpha of the base color
+    Alpha,
+    /// Added on top, scaled by alpha (glows, fire)
+    Additive,
+    /// Darkens what's behind by the color (tinted glass, decals)
+    Multiply,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 4] = [
+        BlendMode::Opaque,
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+    ];
+
+    /// Alpha blending for a see-through color, opaque otherwise
+    pub fn for_alpha(alpha: f32) -> Self {
+        if alpha < 1.0 {
+            BlendMode::Alpha
+        } else {
+            BlendMode::Opaque
+        }
+    }
+
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+}
+
+// ============================================================================
+// LIGHTS
+// ============================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightKind {
+    /// Shines along the entity's forward (-Z) axis from infinitely far away
+    Directional,
+    /// Radiates in all directions from the entity's position
+    Point,
+    /// Cone along the entity's forward (-Z) axis
+    Spot,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: glam::Vec3,
+    pub intensity: f32,
+    /// Distance at which point and spot lights fade out completely
+    pub range: f32,
+    /// Spot cone half-angles in degrees; full intensity inside `inner_angle`
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    /// Render a shadow map for this light. Only directional and spot lights
+    /// support shadows; point lights ignore the flag.
+    pub casts_shadows: bool,
+}
+
+impl Light {
+    pub fn directional(color: glam::Vec3, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color,
+            intensity,
+            range: f32::INFINITY,
+            inner_angle: 0.0,
+            outer_angle: 0.0,
+            casts_shadows: true,
+        }
+    }
+
+    pub fn point(color: glam::Vec3, intensity: f32, range: f32) -> Self {
+        Self {
+            kind: LightKind::Point,
+            color,
+            intensity,
+            range,
+            inner_angle: 0.0,
+            outer_angle: 0.0,
+            casts_shadows: false,
+        }
+    }
+
+    pub fn spot(
+        color: glam::Vec3,
+        intensity: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot,
+            color,
+            intensity,
+            range,
+            inner_angle: inner_angle.min(outer_angle),
+            outer_angle,
+            casts_shadows: true,
+        }
+    }
+}
+
+// ============================================================================
+// BACKGROUND
+// ============================================================================
+
+/// What the scene shows where no geometry was drawn. It is drawn after the
+/// opaque pass, so only uncovered pixels pay for it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Background {
+    Color(Vec3),
+    /// Blend from `top` at the top of each viewport to `bottom` at its foot
+    Gradient {
+        top: Vec3,
+        bottom: Vec3,
+    },
+    Skybox(CubemapHandle),
+}
+
+// ============================================================================
+// FOG
+// ============================================================================
+
+/// How fog thickens with view-space depth
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FogMode {
+    /// From none at `start` to full at `end`
+    Linear,
+    #[default]
+    Exponential,
+    /// Clear near the camera, then closing in faster than `Exponential`
+    ExponentialSquared,
+    /// Exponential, densest at `height` and thinning out above it
+    Height,
+}
+
+/// Fog blended over lit surfaces in the entity shader. Scripts can animate
+/// it at runtime through the `fog_*` host functions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fog {
+    pub enabled: bool,
+    pub mode: FogMode,
+    pub color: Vec3,
+    /// Linear fog range along the view direction
+    pub start: f32,
+    pub end: f32,
+    /// Thickness of the exponential and height modes
+    pub density: f32,
+    /// Height fog: world height of the fog floor and how quickly it fades
+    /// above it
+    pub height: f32,
+    pub height_falloff: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: FogMode::Exponential,
+            color: Vec3::new(0.5, 0.6, 0.7),
+            start: 10.0,
+            end: 100.0,
+            density: 0.02,
+            height: 0.0,
+            height_falloff: 0.2,
+        }
+    }
+}
+
+// ============================================================================
+// POST-PROCESSING
+// ============================================================================
+
+/// Curve mapping HDR scene color into displayable range
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Tonemapper {
+    /// Clamp only
+    None,
+    Reinhard,
+    #[default]
+    Aces,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bloom {
+    pub enabled: bool,
+    /// Brightness above which pixels start to glow
+    pub threshold: f32,
+    pub intensity: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vignette {
+    pub enabled: bool,
+    /// How dark the corners get, 0 to 1
+    pub intensity: f32,
+    /// Width of the falloff towards the corners
+    pub smoothness: f32,
+}
+
+/// Color grading through a lookup table. The LUT is a horizontal strip of
+/// N slices of N x N pixels (e.g. 256 x 16), blue selecting the slice.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorGrading {
+    pub enabled: bool,
+    pub lut: Option<TextureHandle>,
+    /// Blend between the original (0) and graded (1) colors
+    pub strength: f32,
+}
+
+/// Post-process chain applied to the HDR frame, in order: bloom, exposure
+/// and tonemapping, color grading, vignette, FXAA. Every effect can be
+/// switched on and off at runtime through its `enabled` flag.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PostProcess {
+    pub exposure: f32,
+    pub tonemapper: Tonemapper,
+    pub bloom: Bloom,
+    pub vignette: Vignette,
+    pub color_grading: ColorGrading,
+    pub fxaa: bool,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            tonemapper: Tonemapper::Aces,
+            bloom: Bloom {
+                enabled: false,
+                threshold: 1.0,
+                intensity: 0.3,
+            },
+            vignette: Vignette {
+                enabled: false,
+                intensity: 0.3,
+                smoothness: 0.5,
+            },
+            color_grading: ColorGrading {
+                enabled: false,
+                lut: None,
+                strength: 1.0,
+            },
+            fxaa: false,
+        }
+    }
 }
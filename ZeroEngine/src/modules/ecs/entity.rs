@@ -1,4 +1,9 @@
+use crate::modules::assets::TextureHandle;
 use crate::modules::ecs::components::*;
+use crate::modules::ecs::particles::ParticleEmitter;
+use crate::modules::ecs::sprites::{Sprite, SpriteAnimator};
+use crate::modules::ecs::text::Text;
+use crate::modules::ecs::tilemap::Tilemap;
 use crate::modules::ecs::scripts::*;
 use crate::modules::ecs::world::*;
 use glam::*;
@@ -8,10 +13,123 @@ use glam::*;
 // ============================================================================
 
 pub struct Camera {
-    pub fov: f32,        // Field of view in degrees
+    pub fov: f32,        // Vertical field of view in degrees, perspective only
     pub near: f32,       // Near clipping plane
     pub far: f32,        // Far clipping plane
-    pub is_active: bool, // Only one camera can be active at a time
+    pub is_active: bool, // Every active camera is drawn into its viewport
+    /// Overrides the world's post-processing while this camera is the first
+    /// one drawn
+    pub post_process: Option<PostProcess>,
+    pub projection: Projection,
+    /// Part of the window this camera draws into
+    pub viewport: Viewport,
+    /// Fills the viewport before drawing, `None` draws over the cameras
+    /// before it
+    pub clear_color: Option<Vec4>,
+    /// Cameras are drawn in ascending order, later ones on top
+    pub order: i32,
+    /// Offscreen texture drawn into instead of the window
+    pub target: Option<RenderTarget>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            fov: 60.0,
+            near: 0.1,
+            far: 100.0,
+            is_active: false,
+            post_process: None,
+            projection: Projection::Perspective,
+            viewport: Viewport::FULL,
+            clear_color: Some(Vec4::new(0.0, 0.0, 0.0, 1.0)),
+            order: 0,
+            target: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel projection showing `size` world units from bottom to top
+    Orthographic { size: f32 },
+}
+
+/// Rectangle of the window in 0..1 coordinates, from the top left corner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub const FULL: Viewport = Viewport {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Pixel rectangle `(x, y, width, height)` inside a target of the given
+    /// size, clamped to it. `None` when nothing of it is left.
+    pub fn to_pixels(&self, target_width: u32, target_height: u32) -> Option<[f32; 4]> {
+        let (w, h) = (target_width as f32, target_height as f32);
+        let left = (self.x.clamp(0.0, 1.0) * w).round();
+        let top = (self.y.clamp(0.0, 1.0) * h).round();
+        let right = ((self.x + self.width).clamp(0.0, 1.0) * w).round();
+        let bottom = ((self.y + self.height).clamp(0.0, 1.0) * h).round();
+        (right > left && bottom > top).then_some([left, top, right - left, bottom - top])
+    }
+}
+
+/// Texture a camera draws into, usable by materials like any loaded texture.
+/// Get the handle with `Assets::render_target`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderTarget {
+    pub texture: TextureHandle,
+    pub size: TargetSize,
+}
+
+/// How big a render target is, and so when it has to be recreated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetSize {
+    /// Always this many pixels
+    Fixed { width: u32, height: u32 },
+    /// A fraction of the window, following it when it is resized
+    Window { scale: f32 },
+}
+
+impl TargetSize {
+    /// Size in pixels for a window of the given size, at least one pixel
+    pub fn resolve(&self, window_width: u32, window_height: u32) -> (u32, u32) {
+        let (width, height) = match *self {
+            TargetSize::Fixed { width, height } => (width, height),
+            TargetSize::Window { scale } => (
+                (window_width as f32 * scale).round() as u32,
+                (window_height as f32 * scale).round() as u32,
+            ),
+        };
+        (width.max(1), height.max(1))
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL
+    }
 }
 
 pub struct Entity {
@@ -20,10 +138,18 @@ pub struct Entity {
     pub mesh_handle: Option<MeshHandle>,
     pub material: Option<Material>,
     pub camera: Option<Camera>,
+    pub light: Option<Light>,
+    pub particle_emitter: Option<ParticleEmitter>,
+    pub sprite: Option<Sprite>,
+    pub sprite_animator: Option<SpriteAnimator>,
+    pub tilemap: Option<Tilemap>,
+    pub text: Option<Text>,
     pub scripts: Option<Vec<Script>>,
     pub children: Option<Vec<EntityId>>,
     pub parent: Option<EntityId>,
     pub tags: Vec<String>,
+    pub casts_shadows: bool,
+    pub receives_shadows: bool,
 }
 
 pub struct EntityBuilder {
@@ -34,10 +160,13 @@ pub struct EntityBuilder {
     mesh_handle: Option<MeshHandle>,
     material: Option<Material>,
     camera: Option<Camera>,
+    light: Option<Light>,
     scripts: Option<Vec<Script>>,
     children: Option<Vec<EntityId>>,
     parent: Option<EntityId>,
     tags: Vec<String>,
+    casts_shadows: bool,
+    receives_shadows: bool,
 }
 
 pub enum MeshType {
@@ -75,13 +204,16 @@ impl EntityBuilder {
                 rotation: Vec3::ZERO,
                 scale,
             }),
-            mesh_handle: Some(MeshHandle(Self::mesh_type_to_id(mesh))),
-            material: Some(Material { color }),
+            mesh_handle: Some(MeshHandle::from_id(Self::mesh_type_to_id(mesh))),
+            material: Some(Material::new(color)),
             camera: None,
+            light: None,
             scripts,
             children: None,
             parent: None,
             tags: vec![],
+            casts_shadows: true,
+            receives_shadows: true,
         }
     }
 
@@ -112,10 +244,32 @@ impl EntityBuilder {
             near,
             far,
             is_active: false, // Set to false by default, use set_active_camera later
+            ..Default::default()
         });
         self
     }
 
+    /// Add a light component
+    pub fn with_light(mut self, light: Light) -> Self {
+        self.light = Some(light);
+        self
+    }
+
+    /// Set the initial rotation (XYZ Euler, radians)
+    pub fn with_rotation(mut self, rotation: Vec3) -> Self {
+        if let Some(transform) = &mut self.transform {
+            transform.rotation = rotation;
+        }
+        self
+    }
+
+    /// Choose whether the entity casts and receives shadows (both on by default)
+    pub fn with_shadows(mut self, casts: bool, receives: bool) -> Self {
+        self.casts_shadows = casts;
+        self.receives_shadows = receives;
+        self
+    }
+
     /// Add tags to the entity
     pub fn with_tags(mut self, tags: Vec<String>) -> Self {
         self.tags.extend(tags);
@@ -144,10 +298,13 @@ impl EntityBuilder {
             entity.mesh_handle = self.mesh_handle;
             entity.material = self.material;
             entity.camera = self.camera;
+            entity.light = self.light;
             entity.scripts = self.scripts;
             entity.children = self.children;
             entity.parent = self.parent;
             entity.tags = self.tags;
+            entity.casts_shadows = self.casts_shadows;
+            entity.receives_shadows = self.receives_shadows;
         }
 
         entity_id
@@ -177,8 +334,16 @@ impl Entity {
             children: None,
             parent: None,
             camera: None,
+            light: None,
+            particle_emitter: None,
+            sprite: None,
+            sprite_animator: None,
+            tilemap: None,
+            text: None,
             scripts: None,
             tags: Vec::new(),
+            casts_shadows: true,
+            receives_shadows: true,
         }
     }
 
@@ -192,10 +357,13 @@ impl Entity {
             mesh_handle: None,
             material: None,
             camera: None,
+            light: None,
             scripts: None,
             children: None,
             parent: None,
             tags: vec![],
+            casts_shadows: true,
+            receives_shadows: true,
         }
     }
 
@@ -221,13 +389,16 @@ impl Entity {
                 rotation: Vec3::ZERO,
                 scale,
             }),
-            mesh_handle: Some(MeshHandle(EntityBuilder::mesh_type_to_id(mesh))),
-            material: Some(Material { color }),
+            mesh_handle: Some(MeshHandle::from_id(EntityBuilder::mesh_type_to_id(mesh))),
+            material: Some(Material::new(color)),
             camera: None,
+            light: None,
             scripts: None,
             children: None,
             parent: None,
             tags: vec![],
+            casts_shadows: true,
+            receives_shadows: true,
         };
 
         if let Some(path) = script_path {
@@ -246,6 +417,30 @@ impl Entity {
         self.camera = Some(camera);
     }
 
+    pub fn add_light(&mut self, light: Light) {
+        self.light = Some(light);
+    }
+
+    pub fn add_particle_emitter(&mut self, emitter: ParticleEmitter) {
+        self.particle_emitter = Some(emitter);
+    }
+
+    pub fn add_sprite(&mut self, sprite: Sprite) {
+        self.sprite = Some(sprite);
+    }
+
+    pub fn add_sprite_animator(&mut self, animator: SpriteAnimator) {
+        self.sprite_animator = Some(animator);
+    }
+
+    pub fn add_tilemap(&mut self, tilemap: Tilemap) {
+        self.tilemap = Some(tilemap);
+    }
+
+    pub fn add_text(&mut self, text: Text) {
+        self.text = Some(text);
+    }
+
     pub fn add_parent(&mut self, parent: EntityId) {
         self.parent = Some(parent);
     }
@@ -300,8 +495,8 @@ pub fn spawn_entity(
         });
 
         let mesh_id = EntityBuilder::mesh_type_to_id(mesh);
-        entity.add_mesh_handle(MeshHandle(mesh_id));
-        entity.add_material(Material { color });
+        entity.add_mesh_handle(MeshHandle::from_id(mesh_id));
+        entity.add_material(Material::new(color));
     }
 
     entity_id
@@ -372,6 +567,7 @@ pub fn spawn_camera(
             near,
             far,
             is_active: true, // Default the first one to active
+            ..Default::default()
         });
     }
 
@@ -407,15 +603,31 @@ pub fn camera_view_proj(camera: &Camera, transform: &Transform, aspect_ratio: f3
     // View matrix
     let view = Mat4::look_at_rh(eye, target, up);
 
-    // Projection matrix (perspective)
-    let proj = Mat4::perspective_rh_gl(
-        camera.fov.to_radians(),
-        aspect_ratio,
-        camera.near,
-        camera.far,
-    );
+    camera_projection(camera, aspect_ratio) * view
+}
 
-    proj * view
+/// Projection matrix of a camera, with 0..1 depth as wgpu expects
+pub fn camera_projection(camera: &Camera, aspect_ratio: f32) -> Mat4 {
+    match camera.projection {
+        Projection::Perspective => Mat4::perspective_rh(
+            camera.fov.to_radians(),
+            aspect_ratio,
+            camera.near,
+            camera.far,
+        ),
+        Projection::Orthographic { size } => {
+            let half_height = size * 0.5;
+            let half_width = half_height * aspect_ratio;
+            Mat4::orthographic_rh(
+                -half_width,
+                half_width,
+                -half_height,
+                half_height,
+                camera.near,
+                camera.far,
+            )
+        }
+    }
 }
 
 // ============================================================================
@@ -1,5 +1,10 @@
 pub mod entity;
 pub mod components;
 pub mod world;
+pub mod particles;
+pub mod sprites;
+pub mod text;
+pub mod tilemap;
+pub mod ui;
 pub mod systems;
 pub mod scripts;
@@ -0,0 +1,283 @@
+use crate::modules::ecs::components::BlendMode;
+use crate::modules::ecs::world::{EntityId, World};
+use glam::{Mat4, Vec3, Vec4};
+use std::ops::Range;
+
+// ============================================================================
+// PARTICLE EMITTER
+// ============================================================================
+
+/// One particle as the GPU stores it. A particle is dead once its age
+/// reaches its lifetime; dead slots are reused oldest first.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Particle {
+    /// xyz world position, w age in seconds
+    pub position_age: [f32; 4],
+    /// xyz world velocity, w lifetime in seconds
+    pub velocity_lifetime: [f32; 4],
+}
+unsafe impl bytemuck::Pod for Particle {}
+unsafe impl bytemuck::Zeroable for Particle {}
+
+impl Particle {
+    pub fn position(&self) -> Vec3 {
+        Vec3::from_slice(&self.position_age)
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::from_slice(&self.velocity_lifetime)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.position_age[3] < self.velocity_lifetime[3]
+    }
+}
+
+/// A batch of particles released at once, `time` seconds after the emitter
+/// started
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleBurst {
+    pub time: f32,
+    pub count: u32,
+}
+
+/// Where particle motion is integrated. The CPU path exists for devices
+/// without compute shaders and for headless tests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParticleSimulation {
+    #[default]
+    Gpu,
+    Cpu,
+}
+
+/// Spawns billboarded particles from the entity's position into world
+/// space. Settings can change at any time; `max_particles` only takes
+/// effect for an emitter that hasn't spawned yet.
+///
+/// ```ignore
+/// let mut sparks = ParticleEmitter::default();
+/// sparks.rate = 0.0;
+/// sparks.burst(200);
+/// entity.add_particle_emitter(sparks);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParticleEmitter {
+    /// Continuous and scheduled spawning; live particles finish either way
+    pub enabled: bool,
+    /// Particles per second
+    pub rate: f32,
+    /// Sorted by time
+    pub bursts: Vec<ParticleBurst>,
+    pub max_particles: u32,
+    /// Seconds, picked between the two for every particle
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    /// Local emission axis, turned with the entity
+    pub direction: Vec3,
+    /// Half-angle of the emission cone in degrees, 180 for every direction
+    pub cone_angle: f32,
+    /// World-space acceleration
+    pub gravity: Vec3,
+    /// Linear RGBA at birth and death, blended over each particle's life
+    pub start_color: Vec4,
+    pub end_color: Vec4,
+    /// Billboard width in world units at birth and death
+    pub start_size: f32,
+    pub end_size: f32,
+    pub blend: BlendMode,
+    state: EmitterState,
+}
+
+/// Spawning progress of one emitter and the CPU copy of its particles
+#[derive(Clone, Debug, Default)]
+struct EmitterState {
+    elapsed: f32,
+    /// Fraction of a particle owed to `rate`
+    carry: f32,
+    /// Bursts already due
+    bursts_fired: usize,
+    /// Requested through `burst`, released on the next update
+    pending: u32,
+    rng: u32,
+    next_slot: u32,
+    particles: Vec<Particle>,
+    /// Slots written by the last update, for the GPU copy
+    spawned: Vec<Range<u32>>,
+    /// Seconds the last update advanced, for the GPU simulation step
+    step: f32,
+    /// Counts updates, so a renderer can tell a new step from one it ran
+    updates: u64,
+}
+
+const DEFAULT_SEED: u32 = 0x9e37_79b9;
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate: 20.0,
+            bursts: Vec::new(),
+            max_particles: 1000,
+            lifetime: (1.0, 2.0),
+            speed: (1.0, 2.0),
+            direction: Vec3::Y,
+            cone_angle: 25.0,
+            gravity: Vec3::ZERO,
+            start_color: Vec4::ONE,
+            end_color: Vec4::new(1.0, 1.0, 1.0, 0.0),
+            start_size: 0.2,
+            end_size: 0.2,
+            blend: BlendMode::Alpha,
+            state: EmitterState {
+                rng: DEFAULT_SEED,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl ParticleEmitter {
+    /// Release `count` particles on the next update, even while disabled
+    pub fn burst(&mut self, count: u32) {
+        self.state.pending = self.state.pending.saturating_add(count);
+    }
+
+    /// Seconds since the emitter started
+    pub fn elapsed(&self) -> f32 {
+        self.state.elapsed
+    }
+
+    /// CPU copy of every slot, live or dead. Positions only move here with
+    /// `ParticleSimulation::Cpu`; otherwise the GPU holds the current ones.
+    pub fn particles(&self) -> &[Particle] {
+        &self.state.particles
+    }
+
+    pub fn alive_count(&self) -> usize {
+        self.state.particles.iter().filter(|p| p.is_alive()).count()
+    }
+
+    /// Slots the last update spawned into
+    pub fn spawned(&self) -> &[Range<u32>] {
+        &self.state.spawned
+    }
+
+    /// Seconds the last update advanced and its number
+    pub fn last_step(&self) -> (f32, u64) {
+        (self.state.step, self.state.updates)
+    }
+
+    /// Spawn this frame's particles at `transform` and, on the CPU path,
+    /// move every particle by `dt`
+    pub fn update(&mut self, transform: &Mat4, dt: f32, simulation: ParticleSimulation) {
+        let capacity = self.max_particles.max(1);
+        if self.state.particles.is_empty() {
+            self.state.particles = vec![Particle::default(); capacity as usize];
+        }
+        self.state.spawned.clear();
+        self.state.step = dt;
+        self.state.updates += 1;
+
+        // Scheduled bursts pass by while the emitter is disabled
+        self.state.elapsed += dt;
+        let mut count = std::mem::take(&mut self.state.pending);
+        for burst in self.bursts.iter().skip(self.state.bursts_fired) {
+            if burst.time > self.state.elapsed {
+                break;
+            }
+            if self.enabled {
+                count = count.saturating_add(burst.count);
+            }
+            self.state.bursts_fired += 1;
+        }
+        if self.enabled {
+            self.state.carry += self.rate.max(0.0) * dt;
+            let whole = self.state.carry.floor();
+            self.state.carry -= whole;
+            count = count.saturating_add(whole as u32);
+        }
+
+        // More than fit would only overwrite themselves
+        let count = count.min(self.state.particles.len() as u32);
+        let origin = transform.transform_point3(Vec3::ZERO);
+        let axis = transform
+            .transform_vector3(self.direction)
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+        for _ in 0..count {
+            let particle = self.spawn_particle(origin, axis);
+            let slot = self.state.next_slot % self.state.particles.len() as u32;
+            self.state.particles[slot as usize] = particle;
+            self.state.next_slot = slot + 1;
+            match self.state.spawned.last_mut() {
+                Some(range) if range.end == slot => range.end += 1,
+                _ => self.state.spawned.push(slot..slot + 1),
+            }
+        }
+
+        if simulation == ParticleSimulation::Cpu {
+            simulate_particles(&mut self.state.particles, self.gravity, dt);
+        }
+    }
+
+    fn spawn_particle(&mut self, origin: Vec3, axis: Vec3) -> Particle {
+        let lifetime = self.random_between(self.lifetime);
+        let speed = self.random_between(self.speed);
+
+        // Uniform over the spherical cap around the axis
+        let cos_max = self.cone_angle.clamp(0.0, 180.0).to_radians().cos();
+        let cos_theta = 1.0 + (cos_max - 1.0) * self.random();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = std::f32::consts::TAU * self.random();
+        let (side, up) = axis.any_orthonormal_pair();
+        let direction = axis * cos_theta + (side * phi.cos() + up * phi.sin()) * sin_theta;
+
+        Particle {
+            position_age: origin.extend(0.0).to_array(),
+            velocity_lifetime: (direction * speed).extend(lifetime).to_array(),
+        }
+    }
+
+    fn random_between(&mut self, (low, high): (f32, f32)) -> f32 {
+        low + (high - low) * self.random()
+    }
+
+    /// xorshift32 in 0..1, seeded the same for every emitter so runs repeat
+    fn random(&mut self) -> f32 {
+        let mut x = self.state.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state.rng = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+/// Age every particle by `dt` and move the live ones. `PARTICLE_SIMULATE`
+/// does the same on the GPU.
+pub fn simulate_particles(particles: &mut [Particle], gravity: Vec3, dt: f32) {
+    for particle in particles.iter_mut().filter(|p| p.is_alive()) {
+        let velocity = particle.velocity() + gravity * dt;
+        let position = particle.position() + velocity * dt;
+        particle.position_age = position.extend(particle.position_age[3] + dt).to_array();
+        particle.velocity_lifetime = velocity.extend(particle.velocity_lifetime[3]).to_array();
+    }
+}
+
+/// Advance every emitter in the world by `dt`
+pub fn update_particles(world: &mut World, dt: f32, simulation: ParticleSimulation) {
+    let emitters: Vec<(EntityId, Mat4)> = world
+        .iter_entities()
+        .filter(|(_, e)| e.particle_emitter.is_some())
+        .map(|(id, _)| (id, world.world_matrix(id)))
+        .collect();
+    for (id, transform) in emitters {
+        if let Some(emitter) = world
+            .get_entity_mut(id)
+            .and_then(|e| e.particle_emitter.as_mut())
+        {
+            emitter.update(&transform, dt, simulation);
+        }
+    }
+}
@@ -1,6 +1,17 @@
+use crate::log_error;
+use crate::modules::assets::{AssetStatus, Assets, ScriptHandle};
+use crate::modules::console::{LogLevel, log};
+use crate::modules::ecs::components::Fog;
 use crate::modules::ecs::entity::*;
+use crate::modules::ecs::particles::ParticleEmitter;
+use crate::modules::ecs::sprites::{Sprite, SpriteAnimator};
+use crate::modules::ecs::text::{Hud, HudText, Text};
+use crate::modules::ecs::tilemap::{Tile, Tilemap};
+use crate::modules::ecs::ui::{UiLayer, UiPanel, UiWidget, UiWidgetKind, parse_ui_anchor};
 use crate::modules::ecs::world::*;
-use anyhow::Result;
+use crate::modules::render::debug_draw::DebugDraw;
+use anyhow::{Result, anyhow, bail};
+use glam::{Mat4, Vec2, Vec3, Vec4};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use wasmtime::*;
@@ -42,6 +53,8 @@ impl ScriptTagRegistry {
 
 pub struct Script {
     pub script_path: String,
+    /// Module asset, filled in once the script is initialized
+    pub handle: Option<ScriptHandle>,
     pub is_initialized: bool,
     pub script_data: HashMap<String, f32>,
 }
@@ -50,6 +63,7 @@ impl Script {
     pub fn new(script_path: impl Into<String>) -> Self {
         Self {
             script_path: script_path.into(),
+            handle: None,
             is_initialized: false,
             script_data: HashMap::new(),
         }
@@ -108,6 +122,8 @@ pub struct ScriptContext {
 pub struct ScriptRuntime {
     engine: Engine,
     instances: HashMap<ScriptInstanceId, Instance>,
+    // Compiled once per script asset and shared by all of its instances
+    modules: HashMap<ScriptHandle, Module>,
     stores: HashMap<ScriptInstanceId, Store<ScriptContext>>,
     world_ptr: Option<*mut World>,
     // Map EntityId to a simple u32 for WASM communication
@@ -121,6 +137,7 @@ impl ScriptRuntime {
         Self {
             engine: Engine::default(),
             instances: HashMap::new(),
+            modules: HashMap::new(),
             stores: HashMap::new(),
             world_ptr: None,
             entity_id_map: HashMap::new(),
@@ -149,12 +166,27 @@ impl ScriptRuntime {
         self.reverse_entity_id_map.get(&handle).copied()
     }
 
+    fn get_or_compile_module(
+        &mut self,
+        script: ScriptHandle,
+        bytes: &[u8],
+    ) -> Result<Module, Box<dyn std::error::Error>> {
+        if let Some(module) = self.modules.get(&script) {
+            return Ok(module.clone());
+        }
+
+        let module = Module::new(&self.engine, bytes)?;
+        self.modules.insert(script, module.clone());
+        Ok(module)
+    }
+
     pub fn init_script_instance(
         &mut self,
         instance_id: ScriptInstanceId,
-        wasm_path: &str,
+        script: ScriptHandle,
+        bytes: &[u8],
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let module = Module::from_file(&self.engine, wasm_path)?;
+        let module = self.get_or_compile_module(script, bytes)?;
         let entity_handle = self.get_or_create_entity_handle(instance_id.entity_id);
 
         // Create store with context that includes the entity handle
@@ -206,6 +238,24 @@ impl ScriptRuntime {
         Ok(())
     }
 
+    /// Call the script's `dispatchUiEvents` export, if it has one, so it can
+    /// run its click handlers
+    pub fn dispatch_ui_events(
+        &mut self,
+        instance_id: ScriptInstanceId,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(store), Some(instance)) = (
+            self.stores.get_mut(&instance_id),
+            self.instances.get(&instance_id),
+        ) else {
+            return Ok(());
+        };
+        if let Ok(dispatch) = instance.get_typed_func::<(), ()>(&mut *store, "dispatchUiEvents") {
+            dispatch.call(&mut *store, ())?;
+        }
+        Ok(())
+    }
+
     // ========================================================================
     // HOST FUNCTION REGISTRATION
     // ========================================================================
@@ -244,29 +294,86 @@ impl ScriptRuntime {
         linker.func_wrap("context", "get_entity_scale_y", Self::get_scale_y)?;
         linker.func_wrap("context", "get_entity_scale_z", Self::get_scale_z)?;
 
+        // Debug drawing, colors are RGBA and durations in seconds
+        linker.func_wrap("context", "debug_line", Self::debug_line)?;
+        linker.func_wrap("context", "debug_aabb", Self::debug_aabb)?;
+        linker.func_wrap("context", "debug_sphere", Self::debug_sphere)?;
+        linker.func_wrap("context", "debug_axes", Self::debug_axes)?;
+        linker.func_wrap("context", "debug_grid", Self::debug_grid)?;
+        linker.func_wrap("context", "debug_text_3d", Self::debug_text_3d)?;
+        linker.func_wrap("context", "debug_set_enabled", Self::debug_set_enabled)?;
+
+        // Scene fog, colors are linear RGB
+        linker.func_wrap("context", "fog_set_enabled", Self::fog_set_enabled)?;
+        linker.func_wrap("context", "fog_set_color", Self::fog_set_color)?;
+        linker.func_wrap("context", "fog_get_density", Self::fog_get_density)?;
+        linker.func_wrap("context", "fog_set_density", Self::fog_set_density)?;
+        linker.func_wrap("context", "fog_set_range", Self::fog_set_range)?;
+        linker.func_wrap("context", "fog_set_height", Self::fog_set_height)?;
+
+        // Particle emitter on the calling entity
+        linker.func_wrap("context", "particles_burst", Self::particles_burst)?;
+        linker.func_wrap(
+            "context",
+            "particles_set_enabled",
+            Self::particles_set_enabled,
+        )?;
+        linker.func_wrap("context", "particles_set_rate", Self::particles_set_rate)?;
+
+        // Sprite and sprite animation on the calling entity
+        linker.func_wrap("context", "sprite_play", Self::sprite_play)?;
+        linker.func_wrap("context", "sprite_stop", Self::sprite_stop)?;
+        linker.func_wrap("context", "sprite_set_flip", Self::sprite_set_flip)?;
+        linker.func_wrap("context", "sprite_set_tint", Self::sprite_set_tint)?;
+
+        // Tilemap layers by entity name, "" for the calling entity
+        linker.func_wrap("context", "tilemap_get_tile", Self::tilemap_get_tile)?;
+        linker.func_wrap("context", "tilemap_set_tile", Self::tilemap_set_tile)?;
+        linker.func_wrap(
+            "context",
+            "tilemap_get_collision",
+            Self::tilemap_get_collision,
+        )?;
+        linker.func_wrap(
+            "context",
+            "tilemap_collision_at",
+            Self::tilemap_collision_at,
+        )?;
+
+        // Text on the calling entity and HUD labels by name
+        linker.func_wrap("context", "text_set", Self::text_set)?;
+        linker.func_wrap("context", "text_set_color", Self::text_set_color)?;
+        linker.func_wrap("context", "hud_set_text", Self::hud_set_text)?;
+        linker.func_wrap("context", "hud_set_position", Self::hud_set_position)?;
+        linker.func_wrap("context", "hud_set_color", Self::hud_set_color)?;
+        linker.func_wrap("context", "hud_set_size", Self::hud_set_size)?;
+        linker.func_wrap("context", "hud_set_visible", Self::hud_set_visible)?;
+        linker.func_wrap("context", "hud_remove", Self::hud_remove)?;
+
+        // Game UI panels and widgets by name
+        linker.func_wrap("context", "ui_add_panel", Self::ui_add_panel)?;
+        linker.func_wrap("context", "ui_remove_panel", Self::ui_remove_panel)?;
+        linker.func_wrap(
+            "context",
+            "ui_set_panel_visible",
+            Self::ui_set_panel_visible,
+        )?;
+        linker.func_wrap("context", "ui_add_label", Self::ui_add_label)?;
+        linker.func_wrap("context", "ui_add_button", Self::ui_add_button)?;
+        linker.func_wrap("context", "ui_add_progress", Self::ui_add_progress)?;
+        linker.func_wrap("context", "ui_set_text", Self::ui_set_text)?;
+        linker.func_wrap("context", "ui_set_progress", Self::ui_set_progress)?;
+        linker.func_wrap("context", "ui_set_enabled", Self::ui_set_enabled)?;
+        linker.func_wrap("context", "ui_remove_widget", Self::ui_remove_widget)?;
+        linker.func_wrap("context", "ui_was_clicked", Self::ui_was_clicked)?;
+
         linker.func_wrap(
             "env",
             "console.log",
             |mut caller: Caller<'_, ScriptContext>, ptr: i32| {
-                let memory = caller
-                    .get_export("memory")
-                    .and_then(|e| e.into_memory())
-                    .expect("WASM must export memory");
-
-                // AssemblyScript strings are UTF-16, starting with length at ptr-4
-                let data = memory.data(&caller);
-                let len =
-                    u32::from_le_bytes(data[(ptr - 4) as usize..ptr as usize].try_into().unwrap());
-                let bytes = &data[ptr as usize..(ptr as usize + len as usize * 2)];
-
-                // Decode UTF-16
-                let utf16: Vec<u16> = bytes
-                    .chunks(2)
-                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
-                    .collect();
-
-                let string = String::from_utf16(&utf16).unwrap_or("<utf16 error>".to_string());
-                println!("[WASM] {}", string);
+                let line = Self::read_string(&mut caller, ptr)?;
+                log(LogLevel::Script, format!("[WASM] {}", line));
+                Ok(())
             },
         )?;
 
@@ -472,6 +579,617 @@ impl ScriptRuntime {
         }
     }
 
+    // Debug draw functions
+    #[allow(clippy::too_many_arguments)]
+    fn debug_line(
+        x1: f32,
+        y1: f32,
+        z1: f32,
+        x2: f32,
+        y2: f32,
+        z2: f32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+        duration: f32,
+    ) {
+        Self::with_debug_draw(|debug| {
+            debug.line(
+                Vec3::new(x1, y1, z1),
+                Vec3::new(x2, y2, z2),
+                Vec4::new(r, g, b, a),
+                duration,
+            )
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn debug_aabb(
+        min_x: f32,
+        min_y: f32,
+        min_z: f32,
+        max_x: f32,
+        max_y: f32,
+        max_z: f32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+        duration: f32,
+    ) {
+        Self::with_debug_draw(|debug| {
+            debug.aabb(
+                Vec3::new(min_x, min_y, min_z),
+                Vec3::new(max_x, max_y, max_z),
+                Vec4::new(r, g, b, a),
+                duration,
+            )
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn debug_sphere(
+        x: f32,
+        y: f32,
+        z: f32,
+        radius: f32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+        duration: f32,
+    ) {
+        Self::with_debug_draw(|debug| {
+            debug.sphere(Vec3::new(x, y, z), radius, Vec4::new(r, g, b, a), duration)
+        });
+    }
+
+    /// Axes of the calling entity's world transform
+    fn debug_axes(caller: Caller<'_, ScriptContext>, size: f32, duration: f32) {
+        let Some(entity_id) = caller.data().current_entity_id else {
+            return;
+        };
+        unsafe {
+            if let Some(world_ptr) = MAIN_WORLD_PTR {
+                let world = &mut *world_ptr;
+                let transform = world.world_matrix(entity_id);
+                world.debug_draw.axes(transform, size, duration);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn debug_grid(
+        x: f32,
+        y: f32,
+        z: f32,
+        size: f32,
+        divisions: u32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+        duration: f32,
+    ) {
+        Self::with_debug_draw(|debug| {
+            debug.grid(
+                Vec3::new(x, y, z),
+                size,
+                divisions,
+                Vec4::new(r, g, b, a),
+                duration,
+            )
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn debug_text_3d(
+        mut caller: Caller<'_, ScriptContext>,
+        text_ptr: i32,
+        x: f32,
+        y: f32,
+        z: f32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+        duration: f32,
+    ) -> Result<()> {
+        let text = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_debug_draw(|debug| {
+            debug.text_3d(Vec3::new(x, y, z), text, Vec4::new(r, g, b, a), duration)
+        });
+        Ok(())
+    }
+
+    fn debug_set_enabled(enabled: i32) {
+        Self::with_debug_draw(|debug| debug.set_enabled(enabled != 0));
+    }
+
+    fn with_debug_draw(f: impl FnOnce(&mut DebugDraw)) {
+        unsafe {
+            if let Some(world_ptr) = MAIN_WORLD_PTR {
+                let world = &mut *world_ptr;
+                f(&mut world.debug_draw);
+            }
+        }
+    }
+
+    // Fog functions
+    fn fog_set_enabled(enabled: i32) {
+        Self::with_fog(|fog| fog.enabled = enabled != 0);
+    }
+
+    fn fog_set_color(r: f32, g: f32, b: f32) {
+        Self::with_fog(|fog| fog.color = Vec3::new(r, g, b));
+    }
+
+    fn fog_get_density() -> f32 {
+        Self::with_fog(|fog| fog.density).unwrap_or(0.0)
+    }
+
+    fn fog_set_density(density: f32) {
+        Self::with_fog(|fog| fog.density = density.max(0.0));
+    }
+
+    /// Linear fog distances
+    fn fog_set_range(start: f32, end: f32) {
+        Self::with_fog(|fog| {
+            fog.start = start;
+            fog.end = end;
+        });
+    }
+
+    fn fog_set_height(height: f32, falloff: f32) {
+        Self::with_fog(|fog| {
+            fog.height = height;
+            fog.height_falloff = falloff;
+        });
+    }
+
+    fn with_fog<R>(f: impl FnOnce(&mut Fog) -> R) -> Option<R> {
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            Some(f(&mut world.fog))
+        }
+    }
+
+    fn particles_burst(caller: Caller<'_, ScriptContext>, count: i32) {
+        Self::with_emitter(&caller, |emitter| emitter.burst(count.max(0) as u32));
+    }
+
+    fn particles_set_enabled(caller: Caller<'_, ScriptContext>, enabled: i32) {
+        Self::with_emitter(&caller, |emitter| emitter.enabled = enabled != 0);
+    }
+
+    fn particles_set_rate(caller: Caller<'_, ScriptContext>, rate: f32) {
+        Self::with_emitter(&caller, |emitter| emitter.rate = rate.max(0.0));
+    }
+
+    fn with_emitter<R>(
+        caller: &Caller<'_, ScriptContext>,
+        f: impl FnOnce(&mut ParticleEmitter) -> R,
+    ) -> Option<R> {
+        let entity_id = caller.data().current_entity_id?;
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            let emitter = world.get_entity_mut(entity_id)?.particle_emitter.as_mut()?;
+            Some(f(emitter))
+        }
+    }
+
+    /// Start a clip of the entity's sprite sheet, unless it's already playing
+    fn sprite_play(mut caller: Caller<'_, ScriptContext>, name_ptr: i32) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_animator(&caller, |animator| animator.play(&name));
+        Ok(())
+    }
+
+    fn sprite_stop(caller: Caller<'_, ScriptContext>) {
+        Self::with_animator(&caller, SpriteAnimator::stop);
+    }
+
+    fn sprite_set_flip(caller: Caller<'_, ScriptContext>, flip_x: i32, flip_y: i32) {
+        Self::with_sprite(&caller, |sprite| {
+            sprite.flip_x = flip_x != 0;
+            sprite.flip_y = flip_y != 0;
+        });
+    }
+
+    fn sprite_set_tint(caller: Caller<'_, ScriptContext>, r: f32, g: f32, b: f32, a: f32) {
+        Self::with_sprite(&caller, |sprite| sprite.tint = Vec4::new(r, g, b, a));
+    }
+
+    fn with_sprite<R>(
+        caller: &Caller<'_, ScriptContext>,
+        f: impl FnOnce(&mut Sprite) -> R,
+    ) -> Option<R> {
+        let entity_id = caller.data().current_entity_id?;
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            let sprite = world.get_entity_mut(entity_id)?.sprite.as_mut()?;
+            Some(f(sprite))
+        }
+    }
+
+    fn with_animator<R>(
+        caller: &Caller<'_, ScriptContext>,
+        f: impl FnOnce(&mut SpriteAnimator) -> R,
+    ) -> Option<R> {
+        let entity_id = caller.data().current_entity_id?;
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            let animator = world.get_entity_mut(entity_id)?.sprite_animator.as_mut()?;
+            Some(f(animator))
+        }
+    }
+
+    /// Gid of a cell without its flip flags, 0 when empty and -1 outside the
+    /// map or without such a layer
+    fn tilemap_get_tile(
+        mut caller: Caller<'_, ScriptContext>,
+        layer_ptr: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<i32> {
+        let layer = Self::read_string(&mut caller, layer_ptr)?;
+        let tile = Self::with_tilemap(&caller, &layer, |tilemap, _| {
+            tilemap.tile(x.try_into().ok()?, y.try_into().ok()?)
+        });
+        Ok(tile.flatten().map_or(-1, |tile| tile.gid as i32))
+    }
+
+    /// Set a cell to a gid, Tiled's flip flags allowed; 0 clears it
+    fn tilemap_set_tile(
+        mut caller: Caller<'_, ScriptContext>,
+        layer_ptr: i32,
+        x: i32,
+        y: i32,
+        gid: i32,
+    ) -> Result<()> {
+        let layer = Self::read_string(&mut caller, layer_ptr)?;
+        Self::with_tilemap(&caller, &layer, |tilemap, _| {
+            if let (Ok(x), Ok(y)) = (x.try_into(), y.try_into()) {
+                tilemap.set_tile(x, y, Tile::from_raw(gid as u32));
+            }
+        });
+        Ok(())
+    }
+
+    fn tilemap_get_collision(
+        mut caller: Caller<'_, ScriptContext>,
+        layer_ptr: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<i32> {
+        let layer = Self::read_string(&mut caller, layer_ptr)?;
+        let collision = Self::with_tilemap(&caller, &layer, |tilemap, _| {
+            match (x.try_into(), y.try_into()) {
+                (Ok(x), Ok(y)) => tilemap.collision(x, y) as i32,
+                _ => 0,
+            }
+        });
+        Ok(collision.unwrap_or(0))
+    }
+
+    /// Collision flags of the cell under a point in the world's XY plane
+    fn tilemap_collision_at(
+        mut caller: Caller<'_, ScriptContext>,
+        layer_ptr: i32,
+        world_x: f32,
+        world_y: f32,
+    ) -> Result<i32> {
+        let layer = Self::read_string(&mut caller, layer_ptr)?;
+        let collision = Self::with_tilemap(&caller, &layer, |tilemap, matrix| {
+            let point = Vec3::new(world_x, world_y, matrix.w_axis.z);
+            let local = matrix.inverse().transform_point3(point);
+            let (x, y) = tilemap.cell_at(local.truncate())?;
+            Some(tilemap.collision(x, y) as i32)
+        });
+        Ok(collision.flatten().unwrap_or(0))
+    }
+
+    /// Run `f` on the first tilemap entity called `layer`, or the calling
+    /// entity's tilemap if `layer` is empty, with its world matrix
+    fn with_tilemap<R>(
+        caller: &Caller<'_, ScriptContext>,
+        layer: &str,
+        f: impl FnOnce(&mut Tilemap, Mat4) -> R,
+    ) -> Option<R> {
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            let entity_id = if layer.is_empty() {
+                caller.data().current_entity_id?
+            } else {
+                world
+                    .iter_entities()
+                    .find(|(_, e)| e.name == layer && e.tilemap.is_some())?
+                    .0
+            };
+            let matrix = world.world_matrix(entity_id);
+            let tilemap = world.get_entity_mut(entity_id)?.tilemap.as_mut()?;
+            Some(f(tilemap, matrix))
+        }
+    }
+
+    fn text_set(mut caller: Caller<'_, ScriptContext>, text_ptr: i32) -> Result<()> {
+        let content = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_text(&caller, |text| text.text = content);
+        Ok(())
+    }
+
+    fn text_set_color(caller: Caller<'_, ScriptContext>, r: f32, g: f32, b: f32, a: f32) {
+        Self::with_text(&caller, |text| text.color = Vec4::new(r, g, b, a));
+    }
+
+    fn with_text<R>(
+        caller: &Caller<'_, ScriptContext>,
+        f: impl FnOnce(&mut Text) -> R,
+    ) -> Option<R> {
+        let entity_id = caller.data().current_entity_id?;
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            let text = world.get_entity_mut(entity_id)?.text.as_mut()?;
+            Some(f(text))
+        }
+    }
+
+    /// Change a label's text, adding the label if there is none of that name
+    fn hud_set_text(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        text_ptr: i32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        let text = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_hud(|hud| hud.set_text(&name, text));
+        Ok(())
+    }
+
+    fn hud_set_position(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        anchor_x: f32,
+        anchor_y: f32,
+        offset_x: f32,
+        offset_y: f32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_hud_label(&name, |label| {
+            label.anchor = Vec2::new(anchor_x, anchor_y);
+            label.offset = Vec2::new(offset_x, offset_y);
+        });
+        Ok(())
+    }
+
+    fn hud_set_color(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_hud_label(&name, |label| label.color = Vec4::new(r, g, b, a));
+        Ok(())
+    }
+
+    fn hud_set_size(mut caller: Caller<'_, ScriptContext>, name_ptr: i32, size: f32) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_hud_label(&name, |label| label.size = size.max(1.0));
+        Ok(())
+    }
+
+    fn hud_set_visible(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        visible: i32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_hud_label(&name, |label| label.visible = visible != 0);
+        Ok(())
+    }
+
+    fn hud_remove(mut caller: Caller<'_, ScriptContext>, name_ptr: i32) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_hud(|hud| hud.remove(&name));
+        Ok(())
+    }
+
+    fn with_hud<R>(f: impl FnOnce(&mut Hud) -> R) -> Option<R> {
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            Some(f(&mut world.hud))
+        }
+    }
+
+    /// Run `f` on an existing label, `hud_set_text` adds them
+    fn with_hud_label<R>(name: &str, f: impl FnOnce(&mut HudText) -> R) -> Option<R> {
+        Self::with_hud(|hud| hud.get_mut(name).map(f)).flatten()
+    }
+
+    /// Add an empty panel, replacing any of that name. `anchor` is e.g.
+    /// "top_left" or "center", see `parse_ui_anchor`.
+    fn ui_add_panel(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        anchor_ptr: i32,
+        offset_x: f32,
+        offset_y: f32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        let anchor = Self::read_string(&mut caller, anchor_ptr)?;
+        let anchor = match parse_ui_anchor(&anchor) {
+            Ok(anchor) => anchor,
+            Err(e) => {
+                log_error!("[WASM] ui panel '{}': {}", name, e);
+                return Ok(());
+            }
+        };
+        let panel = UiPanel {
+            offset: Vec2::new(offset_x, offset_y),
+            ..UiPanel::new(anchor)
+        };
+        Self::with_ui(|ui| ui.insert_panel(name, panel));
+        Ok(())
+    }
+
+    fn ui_remove_panel(mut caller: Caller<'_, ScriptContext>, name_ptr: i32) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_ui(|ui| ui.remove_panel(&name));
+        Ok(())
+    }
+
+    fn ui_set_panel_visible(
+        mut caller: Caller<'_, ScriptContext>,
+        name_ptr: i32,
+        visible: i32,
+    ) -> Result<()> {
+        let name = Self::read_string(&mut caller, name_ptr)?;
+        Self::with_ui(|ui| {
+            if let Some(panel) = ui.panel_mut(&name) {
+                panel.visible = visible != 0;
+            }
+        });
+        Ok(())
+    }
+
+    fn ui_add_label(
+        mut caller: Caller<'_, ScriptContext>,
+        panel_ptr: i32,
+        id_ptr: i32,
+        text_ptr: i32,
+    ) -> Result<()> {
+        let panel = Self::read_string(&mut caller, panel_ptr)?;
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        let text = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_ui(|ui| ui.add_widget(&panel, UiWidget::label(id, text)));
+        Ok(())
+    }
+
+    fn ui_add_button(
+        mut caller: Caller<'_, ScriptContext>,
+        panel_ptr: i32,
+        id_ptr: i32,
+        text_ptr: i32,
+    ) -> Result<()> {
+        let panel = Self::read_string(&mut caller, panel_ptr)?;
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        let text = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_ui(|ui| ui.add_widget(&panel, UiWidget::button(id, text)));
+        Ok(())
+    }
+
+    fn ui_add_progress(
+        mut caller: Caller<'_, ScriptContext>,
+        panel_ptr: i32,
+        id_ptr: i32,
+        value: f32,
+    ) -> Result<()> {
+        let panel = Self::read_string(&mut caller, panel_ptr)?;
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        Self::with_ui(|ui| ui.add_widget(&panel, UiWidget::progress_bar(id, value)));
+        Ok(())
+    }
+
+    fn ui_set_text(
+        mut caller: Caller<'_, ScriptContext>,
+        id_ptr: i32,
+        text_ptr: i32,
+    ) -> Result<()> {
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        let text = Self::read_string(&mut caller, text_ptr)?;
+        Self::with_ui(|ui| ui.widget_mut(&id).map(|widget| widget.set_text(text)));
+        Ok(())
+    }
+
+    fn ui_set_progress(
+        mut caller: Caller<'_, ScriptContext>,
+        id_ptr: i32,
+        new_value: f32,
+    ) -> Result<()> {
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        Self::with_ui(|ui| {
+            if let Some(UiWidgetKind::ProgressBar { value, .. }) =
+                ui.widget_mut(&id).map(|widget| &mut widget.kind)
+            {
+                *value = new_value.clamp(0.0, 1.0);
+            }
+        });
+        Ok(())
+    }
+
+    fn ui_set_enabled(
+        mut caller: Caller<'_, ScriptContext>,
+        id_ptr: i32,
+        new_enabled: i32,
+    ) -> Result<()> {
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        Self::with_ui(|ui| {
+            if let Some(UiWidgetKind::Button { enabled, .. }) =
+                ui.widget_mut(&id).map(|widget| &mut widget.kind)
+            {
+                *enabled = new_enabled != 0;
+            }
+        });
+        Ok(())
+    }
+
+    fn ui_remove_widget(mut caller: Caller<'_, ScriptContext>, id_ptr: i32) -> Result<()> {
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        Self::with_ui(|ui| ui.remove_widget(&id));
+        Ok(())
+    }
+
+    /// Whether a button was clicked in the last frame
+    fn ui_was_clicked(mut caller: Caller<'_, ScriptContext>, id_ptr: i32) -> Result<i32> {
+        let id = Self::read_string(&mut caller, id_ptr)?;
+        Ok(Self::with_ui(|ui| ui.was_clicked(&id)).unwrap_or(false) as i32)
+    }
+
+    fn with_ui<R>(f: impl FnOnce(&mut UiLayer) -> R) -> Option<R> {
+        unsafe {
+            let world_ptr = MAIN_WORLD_PTR?;
+            let world = &mut *world_ptr;
+            Some(f(&mut world.ui))
+        }
+    }
+
+    /// AssemblyScript strings are UTF-16, their size in bytes stored at ptr-4.
+    /// Pointers outside the script's memory trap instead of reading past it.
+    fn read_string(caller: &mut Caller<'_, ScriptContext>, ptr: i32) -> Result<String> {
+        let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else {
+            bail!("script does not export memory");
+        };
+
+        let data = memory.data(&caller);
+        let start = ptr as u32 as usize;
+        let size = start
+            .checked_sub(4)
+            .and_then(|header| data.get(header..start))
+            .map(|header| u32::from_le_bytes(header.try_into().unwrap()) as usize);
+        let bytes = size
+            .and_then(|size| data.get(start..start.checked_add(size)?))
+            .ok_or_else(|| anyhow!("string at {} is outside script memory", ptr))?;
+
+        // Decode UTF-16
+        let utf16: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+
+        Ok(String::from_utf16(&utf16).unwrap_or("<utf16 error>".to_string()))
+    }
+
     fn abort_handler(msg_ptr: i32, file_ptr: i32, line: i32, col: i32) {
         panic!(
             "WASM called abort at {}:{} (msg ptr {}) col {}",
@@ -509,6 +1227,7 @@ pub fn set_script_world_reference(world: &mut World) {
 pub fn run_script_system(
     world: &mut World,
     registry: &mut ScriptRegistry,
+    assets: &mut Assets,
     delta_time: f32,
 ) -> anyhow::Result<()> {
     // Set the world reference first
@@ -516,6 +1235,7 @@ pub fn run_script_system(
 
     // Collect all entity-script combinations
     let script_instances = collect_script_instances(world);
+    let has_ui_events = !world.ui.events().is_empty();
 
     SCRIPT_RUNTIME.with(|runtime| -> anyhow::Result<()> {
         let mut runtime = runtime.borrow_mut();
@@ -525,24 +1245,47 @@ pub fn run_script_system(
             let instance_id = ScriptInstanceId::new(entity_id, script_id);
 
             if !is_initialized {
+                // Scripts whose module failed to load were already reported by Assets
+                let script = assets.load_script(&script_path);
+                if let Some(AssetStatus::Failed(_)) = assets.scripts.status(script) {
+                    continue;
+                }
+                let Some(bytes) = assets.scripts.get(script).map(|s| s.bytes.clone()) else {
+                    continue;
+                };
+
                 // Initialize the script instance
-                if let Err(e) = runtime.init_script_instance(instance_id, &script_path) {
-                    eprintln!(
+                if let Err(e) = runtime.init_script_instance(instance_id, script, &bytes) {
+                    log_error!(
                         "Failed to initialize script '{}' for entity {:?}: {}",
-                        script_path, entity_id, e
+                        script_path,
+                        entity_id,
+                        e
                     );
                     continue;
                 }
 
                 // Mark as initialized in the world
-                mark_script_initialized(entity_id, script_index);
+                mark_script_initialized(entity_id, script_index, script);
             }
 
             // Update the script instance
             if let Err(e) = runtime.update_script_instance(instance_id, delta_time) {
-                eprintln!(
+                log_error!(
                     "Failed to update script '{}' for entity {:?}: {}",
-                    script_path, entity_id, e
+                    script_path,
+                    entity_id,
+                    e
+                );
+            }
+
+            // Button clicks of the last frame
+            if has_ui_events && let Err(e) = runtime.dispatch_ui_events(instance_id) {
+                log_error!(
+                    "Failed to dispatch ui events to '{}' for entity {:?}: {}",
+                    script_path,
+                    entity_id,
+                    e
                 );
             }
         }
@@ -577,7 +1320,7 @@ fn collect_script_instances(world: &World) -> Vec<(EntityId, usize, String, bool
         .collect()
 }
 
-fn mark_script_initialized(entity_id: EntityId, script_index: usize) {
+fn mark_script_initialized(entity_id: EntityId, script_index: usize, handle: ScriptHandle) {
     unsafe {
         if let Some(world_ptr) = MAIN_WORLD_PTR {
             let world = &mut *(world_ptr as *mut World);
@@ -585,6 +1328,7 @@ fn mark_script_initialized(entity_id: EntityId, script_index: usize) {
                 if let Some(scripts) = &mut entity.scripts {
                     if let Some(script) = scripts.get_mut(script_index) {
                         script.is_initialized = true;
+                        script.handle = Some(handle);
                     }
                 }
             }
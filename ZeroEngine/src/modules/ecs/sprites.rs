@@ -0,0 +1,213 @@
+use crate::modules::assets::{Assets, SpriteSheetHandle, TextureHandle};
+use crate::modules::ecs::components::{SamplerSettings, TextureFilter, TextureWrap};
+use crate::modules::ecs::world::World;
+use glam::{Vec2, Vec4};
+
+// ============================================================================
+// SPRITES
+// ============================================================================
+
+/// Rectangle of a texture in pixels, origin at the top left
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpriteRegion {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl SpriteRegion {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Texture coordinates of the top left and bottom right corner
+    pub fn uv_rect(&self, texture_width: u32, texture_height: u32) -> Vec4 {
+        let size = Vec2::new(texture_width.max(1) as f32, texture_height.max(1) as f32);
+        let min = Vec2::new(self.x, self.y) / size;
+        let max = Vec2::new(self.x + self.width, self.y + self.height) / size;
+        Vec4::new(min.x, min.y, max.x, max.y)
+    }
+}
+
+/// Textured quad in the entity's XY plane, facing +Z. Sprites draw after
+/// opaque geometry, lower layers first and within a layer lower world Z
+/// first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite {
+    /// `None` draws a solid rectangle of the tint color
+    pub texture: Option<TextureHandle>,
+    /// Part of the texture to show, the whole texture if `None`
+    pub region: Option<SpriteRegion>,
+    /// Size in world units before the entity's scale
+    pub size: Vec2,
+    /// Linear RGBA the texture is multiplied by
+    pub tint: Vec4,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Point of the quad at the entity's position, (0, 0) bottom left and
+    /// (1, 1) top right
+    pub pivot: Vec2,
+    /// Sorting layer
+    pub layer: i32,
+    pub sampler: SamplerSettings,
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            region: None,
+            size: Vec2::ONE,
+            tint: Vec4::ONE,
+            flip_x: false,
+            flip_y: false,
+            pivot: Vec2::splat(0.5),
+            layer: 0,
+            // Crisp pixel art without bleeding at the region edges
+            sampler: SamplerSettings {
+                filter: TextureFilter::Nearest,
+                wrap: TextureWrap::ClampToEdge,
+            },
+        }
+    }
+}
+
+impl Sprite {
+    pub fn new(texture: TextureHandle) -> Self {
+        Self {
+            texture: Some(texture),
+            ..Default::default()
+        }
+    }
+
+    /// Texture coordinates of the corners at the quad's top left and bottom
+    /// right, flips applied
+    pub fn uv_rect(&self, texture_width: u32, texture_height: u32) -> Vec4 {
+        let mut uv = match self.region {
+            Some(region) => region.uv_rect(texture_width, texture_height),
+            None => Vec4::new(0.0, 0.0, 1.0, 1.0),
+        };
+        if self.flip_x {
+            uv = Vec4::new(uv.z, uv.y, uv.x, uv.w);
+        }
+        if self.flip_y {
+            uv = Vec4::new(uv.x, uv.w, uv.z, uv.y);
+        }
+        uv
+    }
+}
+
+// ============================================================================
+// SPRITE ANIMATION
+// ============================================================================
+
+/// Frames of one animation in a sprite sheet
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteClip {
+    pub frames: Vec<SpriteRegion>,
+    /// Frames per second
+    pub fps: f32,
+    pub looping: bool,
+}
+
+impl SpriteClip {
+    /// Frame shown `time` seconds into the clip
+    pub fn frame_at(&self, time: f32) -> usize {
+        let count = self.frames.len().max(1);
+        let frame = (time.max(0.0) * self.fps).floor() as usize;
+        if self.looping {
+            frame % count
+        } else {
+            frame.min(count - 1)
+        }
+    }
+
+    /// Seconds a single pass through the clip takes
+    pub fn duration(&self) -> f32 {
+        if self.fps > 0.0 {
+            self.frames.len() as f32 / self.fps
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// Plays the clips of a sprite sheet on the entity's `Sprite`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteAnimator {
+    pub sheet: SpriteSheetHandle,
+    /// Name of the clip playing or last played
+    pub clip: Option<String>,
+    /// Playback rate, 1 for the clip's own fps
+    pub speed: f32,
+    pub playing: bool,
+    time: f32,
+}
+
+impl SpriteAnimator {
+    pub fn new(sheet: SpriteSheetHandle) -> Self {
+        Self {
+            sheet,
+            clip: None,
+            speed: 1.0,
+            playing: false,
+            time: 0.0,
+        }
+    }
+
+    /// Start `clip` from its first frame, unless it's already playing
+    pub fn play(&mut self, clip: &str) {
+        if self.playing && self.clip.as_deref() == Some(clip) {
+            return;
+        }
+        self.clip = Some(clip.to_string());
+        self.time = 0.0;
+        self.playing = true;
+    }
+
+    /// Hold the current frame
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Seconds into the current clip
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
+
+/// Advance every sprite animation by `dt` and show its current frame.
+/// Clips that don't loop stop on their last frame.
+pub fn update_sprite_animations(world: &mut World, assets: &Assets, dt: f32) {
+    for (_, entity) in world.iter_entities_mut() {
+        let (Some(animator), Some(sprite)) = (&mut entity.sprite_animator, &mut entity.sprite)
+        else {
+            continue;
+        };
+        let Some(sheet) = assets.sprite_sheets.get(animator.sheet) else {
+            continue;
+        };
+        if sprite.texture.is_none() {
+            sprite.texture = sheet.texture;
+        }
+        let Some(clip) = animator.clip.as_ref().and_then(|c| sheet.animations.get(c)) else {
+            continue;
+        };
+
+        if animator.playing {
+            animator.time += dt * animator.speed;
+            if !clip.looping && animator.time >= clip.duration() {
+                animator.playing = false;
+            }
+        }
+        if let Some(frame) = clip.frames.get(clip.frame_at(animator.time)) {
+            sprite.region = Some(*frame);
+        }
+    }
+}
@@ -1,15 +1,23 @@
+use crate::modules::assets::Assets;
 use crate::modules::ecs::world::*;
+use crate::modules::ecs::particles::update_particles;
+use crate::modules::ecs::sprites::update_sprite_animations;
 use crate::modules::ecs::scripts::*;
 use crate::modules::state::State;
+use crate::{log_info, log_warn};
 use anyhow::{Context, Result};
 
 /// Initialize all script instances for entities that have scripts
-pub fn init_scripts(world: &mut World, registry: &mut ScriptRegistry) -> Result<()> {
+pub fn init_scripts(
+    world: &mut World,
+    registry: &mut ScriptRegistry,
+    assets: &mut Assets,
+) -> Result<()> {
     // Use the new unified script system
-    run_script_system(world, registry, 0.0)
+    run_script_system(world, registry, assets, 0.0)
         .context("Failed to initialize scripts")?;
     
-    println!("Script initialization completed");
+    log_info!("Script initialization completed");
     Ok(())
 }
 
@@ -17,14 +25,15 @@ pub fn init_scripts(world: &mut World, registry: &mut ScriptRegistry) -> Result<
 pub fn handle_scripts(
     world: &mut World,
     registry: &mut ScriptRegistry,
+    assets: &mut Assets,
     dt: f32,
 ) -> Result<()> {
     if dt < 0.0 || dt > 1.0 {
-        eprintln!("Warning: Unusual delta time: {:.6}s", dt);
+        log_warn!("Unusual delta time: {:.6}s", dt);
     }
 
     // Use the new unified script system
-    run_script_system(world, registry, dt)
+    run_script_system(world, registry, assets, dt)
         .context("Failed to update scripts")?;
 
     Ok(())
@@ -35,17 +44,37 @@ pub fn update_and_render(
     world: &mut World,
     state: &mut State,
     registry: &mut ScriptRegistry,
+    assets: &mut Assets,
     delta_time: f32,
 ) -> Result<()> {
+    // Scripts, particles and animations run on scaled time
+    let delta_time = delta_time * world.time_scale;
+
     // 1. Update all scripts
-    handle_scripts(world, registry, delta_time)
+    handle_scripts(world, registry, assets, delta_time)
         .context("Failed to update scripts")?;
 
     // 2. Apply any pending world changes (if you have a system for this)
     // world.apply_pending_changes();
 
+    // Spawn particles, bursts scripts asked for included
+    update_particles(world, delta_time, state.particle_simulation());
+    update_sprite_animations(world, assets, delta_time);
+
+    // Free assets no entity uses anymore and upload new ones
+    assets.update_ref_counts(world);
+    assets.collect_unused();
+    state.sync_meshes(assets);
+    state.sync_textures(assets);
+    state.sync_cubemaps(assets);
+    state.sync_shaders(assets);
+    state.sync_fonts(assets);
+
     // 3. Render the current world state
     state.render(world);
+    // Clicks reach scripts in the next update
+    world.ui.set_events(state.take_ui_events());
+    world.debug_draw.end_frame(delta_time);
 state.get_window().request_redraw();
 
     Ok(())
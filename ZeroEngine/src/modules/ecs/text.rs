@@ -0,0 +1,168 @@
+use crate::modules::assets::{DEFAULT_FONT, FontHandle};
+use glam::{Vec2, Vec4};
+use std::collections::BTreeMap;
+
+// ============================================================================
+// TEXT
+// ============================================================================
+
+/// Horizontal alignment of the lines of a text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    /// Share of the text's width left of the alignment point
+    pub fn fraction(&self) -> f32 {
+        match self {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => 0.5,
+            TextAlign::Right => 1.0,
+        }
+    }
+}
+
+pub fn parse_text_align(name: &str) -> Result<TextAlign, String> {
+    match name {
+        "left" => Ok(TextAlign::Left),
+        "center" => Ok(TextAlign::Center),
+        "right" => Ok(TextAlign::Right),
+        other => Err(format!("unknown text align '{}'", other)),
+    }
+}
+
+/// Text in the entity's XY plane, facing +Z. The entity sits on the first
+/// line's baseline, at its left end, middle or right end depending on
+/// `align`. Drawn after sprites, in the entity's scale and rotation unless
+/// it is a billboard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Text {
+    pub text: String,
+    pub font: FontHandle,
+    /// Line height in world units
+    pub size: f32,
+    /// Linear RGBA
+    pub color: Vec4,
+    pub align: TextAlign,
+    /// Always face the camera, e.g. for labels and damage numbers
+    pub billboard: bool,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font: DEFAULT_FONT,
+            size: 1.0,
+            color: Vec4::ONE,
+            align: TextAlign::Left,
+            billboard: false,
+        }
+    }
+}
+
+impl Text {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+// ============================================================================
+// HUD
+// ============================================================================
+
+/// Screen-space label drawn over the finished frame. `anchor` picks a point
+/// of the window, (0, 0) top left and (1, 1) bottom right; the same point
+/// of the text's box is placed there, moved by `offset` pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HudText {
+    pub text: String,
+    pub font: FontHandle,
+    /// Line height in pixels
+    pub size: f32,
+    /// Linear RGBA
+    pub color: Vec4,
+    pub align: TextAlign,
+    pub anchor: Vec2,
+    /// Pixels, +Y down
+    pub offset: Vec2,
+    pub visible: bool,
+}
+
+impl Default for HudText {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font: DEFAULT_FONT,
+            size: 24.0,
+            color: Vec4::ONE,
+            align: TextAlign::Left,
+            anchor: Vec2::ZERO,
+            offset: Vec2::ZERO,
+            visible: true,
+        }
+    }
+}
+
+/// Named HUD labels of the world, drawn in name order
+///
+/// ```ignore
+/// world.hud.set_text("score", format!("Score: {}", score));
+/// if let Some(label) = world.hud.get_mut("score") {
+///     label.anchor = Vec2::new(1.0, 0.0);
+///     label.offset = Vec2::new(-16.0, 16.0);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hud {
+    labels: BTreeMap<String, HudText>,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change the text of a label, adding it with default settings if there
+    /// is none of that name
+    pub fn set_text(&mut self, name: &str, text: impl Into<String>) {
+        self.labels.entry(name.to_string()).or_default().text = text.into();
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, label: HudText) {
+        self.labels.insert(name.into(), label);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HudText> {
+        self.labels.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut HudText> {
+        self.labels.get_mut(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<HudText> {
+        self.labels.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &HudText)> {
+        self.labels
+            .iter()
+            .map(|(name, label)| (name.as_str(), label))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+}
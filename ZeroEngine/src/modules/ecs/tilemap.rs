@@ -0,0 +1,223 @@
+use crate::modules::assets::TextureHandle;
+use crate::modules::ecs::components::{SamplerSettings, TextureFilter, TextureWrap};
+use crate::modules::ecs::sprites::SpriteRegion;
+use glam::{Vec2, Vec4};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// ============================================================================
+// TILES
+// ============================================================================
+
+/// Side of the square chunks a tilemap is drawn and updated in, in cells
+pub const CHUNK_SIZE: u32 = 16;
+
+const FLIP_X: u32 = 0x8000_0000;
+const FLIP_Y: u32 = 0x4000_0000;
+const FLIP_DIAGONAL: u32 = 0x2000_0000;
+const GID_MASK: u32 = 0x1fff_ffff;
+
+/// One cell of a tilemap. `gid` numbers tiles across all tilesets of the
+/// map like Tiled does, 0 is an empty cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tile {
+    pub gid: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Swaps the tile's X and Y axes, applied before the other flips
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    pub const EMPTY: Tile = Tile {
+        gid: 0,
+        flip_x: false,
+        flip_y: false,
+        flip_diagonal: false,
+    };
+
+    pub fn new(gid: u32) -> Self {
+        Self {
+            gid: gid & GID_MASK,
+            ..Self::EMPTY
+        }
+    }
+
+    /// Tile from a Tiled gid with its flip flags in the top bits
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            gid: raw & GID_MASK,
+            flip_x: raw & FLIP_X != 0,
+            flip_y: raw & FLIP_Y != 0,
+            flip_diagonal: raw & FLIP_DIAGONAL != 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gid == 0
+    }
+}
+
+/// Tile atlas: a grid of equally sized tiles in one texture
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tileset {
+    pub name: String,
+    /// Gid of the first tile, the rest follow in atlas order
+    pub first_gid: u32,
+    pub texture: Option<TextureHandle>,
+    /// Tile size in pixels
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// Pixels around the atlas and between its tiles
+    pub margin: u32,
+    pub spacing: u32,
+    /// Collision flags by local tile id, tiles not listed have none
+    pub collision: HashMap<u32, u32>,
+}
+
+impl Tileset {
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.tile_count
+    }
+
+    /// Atlas rectangle of the tile `local` tiles after the first
+    pub fn region(&self, local: u32) -> SpriteRegion {
+        let columns = self.columns.max(1);
+        let (column, row) = (local % columns, local / columns);
+        SpriteRegion::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as f32,
+            (self.margin + row * (self.tile_height + self.spacing)) as f32,
+            self.tile_width as f32,
+            self.tile_height as f32,
+        )
+    }
+}
+
+// ============================================================================
+// TILEMAP
+// ============================================================================
+
+/// Grid of tiles in the entity's XY plane. Cell (0, 0) is at the top left,
+/// touching the entity's position; rows go down -Y like in Tiled. Tiles
+/// taller or wider than a cell stick out up and to the right. Tilemaps draw
+/// with sprites and sort the same way, by `layer` first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tilemap {
+    pub width: u32,
+    pub height: u32,
+    /// World size of one cell
+    pub cell_size: Vec2,
+    /// Size of one cell in the tilesets' pixels
+    pub cell_pixels: Vec2,
+    /// Shared by every layer of a map
+    pub tilesets: Arc<[Tileset]>,
+    /// Linear RGBA every tile is multiplied by
+    pub tint: Vec4,
+    /// Sorting layer, see `Sprite::layer`
+    pub layer: i32,
+    pub visible: bool,
+    pub sampler: SamplerSettings,
+    tiles: Vec<Tile>,
+    /// Bumped whenever a tile of the chunk changes
+    revisions: Vec<u32>,
+}
+
+impl Tilemap {
+    /// Empty map with 1x1 world unit cells of `cell_pixels` tileset pixels
+    pub fn new(width: u32, height: u32, cell_pixels: Vec2, tilesets: Arc<[Tileset]>) -> Self {
+        let chunks = width.div_ceil(CHUNK_SIZE) * height.div_ceil(CHUNK_SIZE);
+        Self {
+            width,
+            height,
+            cell_size: Vec2::ONE,
+            cell_pixels,
+            tilesets,
+            tint: Vec4::ONE,
+            layer: 0,
+            visible: true,
+            sampler: SamplerSettings {
+                filter: TextureFilter::Nearest,
+                wrap: TextureWrap::ClampToEdge,
+            },
+            tiles: vec![Tile::EMPTY; (width * height) as usize],
+            revisions: vec![0; chunks as usize],
+        }
+    }
+
+    pub fn tile(&self, x: u32, y: u32) -> Option<Tile> {
+        self.index(x, y).map(|i| self.tiles[i])
+    }
+
+    /// Change a cell; returns false outside the map
+    pub fn set_tile(&mut self, x: u32, y: u32, tile: Tile) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false;
+        };
+        if self.tiles[index] != tile {
+            self.tiles[index] = tile;
+            let (cx, cy) = (x / CHUNK_SIZE, y / CHUNK_SIZE);
+            let chunk = (cy * self.chunks_x() + cx) as usize;
+            self.revisions[chunk] = self.revisions[chunk].wrapping_add(1);
+        }
+        true
+    }
+
+    /// Collision flags of the tile in a cell, 0 for empty cells and cells
+    /// outside the map
+    pub fn collision(&self, x: u32, y: u32) -> u32 {
+        let Some(tile) = self.tile(x, y).filter(|t| !t.is_empty()) else {
+            return 0;
+        };
+        self.tileset_for(tile.gid)
+            .and_then(|(_, tileset)| tileset.collision.get(&(tile.gid - tileset.first_gid)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Cell under a point in the entity's local space
+    pub fn cell_at(&self, local: Vec2) -> Option<(u32, u32)> {
+        let x = (local.x / self.cell_size.x).floor();
+        let y = (-local.y / self.cell_size.y).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+
+    /// Tileset a gid belongs to and its index in `tilesets`
+    pub fn tileset_for(&self, gid: u32) -> Option<(usize, &Tileset)> {
+        self.tilesets
+            .iter()
+            .enumerate()
+            .find(|(_, tileset)| tileset.contains(gid))
+    }
+
+    /// Number of chunks across and down
+    pub fn chunks(&self) -> (u32, u32) {
+        (self.chunks_x(), self.height.div_ceil(CHUNK_SIZE))
+    }
+
+    /// Changes whenever a tile of chunk (`cx`, `cy`) does
+    pub fn chunk_revision(&self, cx: u32, cy: u32) -> u32 {
+        self.revisions[(cy * self.chunks_x() + cx) as usize]
+    }
+
+    /// Cells of a chunk with their tiles, empty ones skipped
+    pub fn chunk_tiles(&self, cx: u32, cy: u32) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        let xs = cx * CHUNK_SIZE..((cx + 1) * CHUNK_SIZE).min(self.width);
+        let ys = cy * CHUNK_SIZE..((cy + 1) * CHUNK_SIZE).min(self.height);
+        ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+            .map(|(x, y)| (x, y, self.tiles[(y * self.width + x) as usize]))
+            .filter(|(_, _, tile)| !tile.is_empty())
+    }
+
+    fn chunks_x(&self) -> u32 {
+        self.width.div_ceil(CHUNK_SIZE)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+}
@@ -0,0 +1,253 @@
+use glam::Vec2;
+use std::collections::BTreeMap;
+
+// ============================================================================
+// UI LAYER
+// ============================================================================
+
+/// Corner, edge middle or center of the window a panel sticks to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UiAnchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+pub fn parse_ui_anchor(name: &str) -> Result<UiAnchor, String> {
+    match name {
+        "top_left" => Ok(UiAnchor::TopLeft),
+        "top" => Ok(UiAnchor::Top),
+        "top_right" => Ok(UiAnchor::TopRight),
+        "left" => Ok(UiAnchor::Left),
+        "center" => Ok(UiAnchor::Center),
+        "right" => Ok(UiAnchor::Right),
+        "bottom_left" => Ok(UiAnchor::BottomLeft),
+        "bottom" => Ok(UiAnchor::Bottom),
+        "bottom_right" => Ok(UiAnchor::BottomRight),
+        other => Err(format!("unknown ui anchor '{}'", other)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiWidgetKind {
+    Label {
+        text: String,
+    },
+    Button {
+        text: String,
+        enabled: bool,
+    },
+    /// `value` from 0 to 1, `text` drawn on the bar
+    ProgressBar {
+        value: f32,
+        text: Option<String>,
+    },
+}
+
+/// Widget of a panel. Scripts find widgets by `id`, which should be unique
+/// across panels; clicks are reported with it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiWidget {
+    pub id: String,
+    pub kind: UiWidgetKind,
+}
+
+impl UiWidget {
+    pub fn label(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            kind: UiWidgetKind::Label { text: text.into() },
+        }
+    }
+
+    pub fn button(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            kind: UiWidgetKind::Button {
+                text: text.into(),
+                enabled: true,
+            },
+        }
+    }
+
+    pub fn progress_bar(id: impl Into<String>, value: f32) -> Self {
+        Self {
+            id: id.into(),
+            kind: UiWidgetKind::ProgressBar {
+                value: value.clamp(0.0, 1.0),
+                text: None,
+            },
+        }
+    }
+
+    /// Change the text of a label, button or progress bar
+    pub fn set_text(&mut self, new_text: impl Into<String>) {
+        match &mut self.kind {
+            UiWidgetKind::Label { text } | UiWidgetKind::Button { text, .. } => {
+                *text = new_text.into()
+            }
+            UiWidgetKind::ProgressBar { text, .. } => *text = Some(new_text.into()),
+        }
+    }
+}
+
+/// Box of widgets stuck to a point of the window, laid out top to bottom
+/// or left to right. Panels with a title get a frame with a title bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiPanel {
+    pub title: Option<String>,
+    pub anchor: UiAnchor,
+    /// Points from the anchor, +Y down
+    pub offset: Vec2,
+    /// Points, `None` to fit the widgets
+    pub width: Option<f32>,
+    pub horizontal: bool,
+    pub visible: bool,
+    pub widgets: Vec<UiWidget>,
+}
+
+impl Default for UiPanel {
+    fn default() -> Self {
+        Self {
+            title: None,
+            anchor: UiAnchor::TopLeft,
+            offset: Vec2::ZERO,
+            width: None,
+            horizontal: false,
+            visible: true,
+            widgets: Vec::new(),
+        }
+    }
+}
+
+impl UiPanel {
+    pub fn new(anchor: UiAnchor) -> Self {
+        Self {
+            anchor,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UiEvent {
+    /// A button was clicked, with its widget id
+    Clicked(String),
+}
+
+/// Game UI drawn with egui over the frame, after HUD text. Panels are
+/// drawn in name order. Events of the last drawn frame stay until the
+/// next one replaces them, so scripts see each click in exactly one
+/// update.
+///
+/// ```ignore
+/// let mut panel = UiPanel::new(UiAnchor::TopRight);
+/// panel.widgets.push(UiWidget::progress_bar("health", 1.0));
+/// panel.widgets.push(UiWidget::button("pause", "Pause"));
+/// world.ui.insert_panel("status", panel);
+/// if world.ui.was_clicked("pause") { ... }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UiLayer {
+    panels: BTreeMap<String, UiPanel>,
+    events: Vec<UiEvent>,
+}
+
+impl UiLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert_panel(&mut self, name: impl Into<String>, panel: UiPanel) {
+        self.panels.insert(name.into(), panel);
+    }
+
+    pub fn panel(&self, name: &str) -> Option<&UiPanel> {
+        self.panels.get(name)
+    }
+
+    pub fn panel_mut(&mut self, name: &str) -> Option<&mut UiPanel> {
+        self.panels.get_mut(name)
+    }
+
+    pub fn remove_panel(&mut self, name: &str) -> Option<UiPanel> {
+        self.panels.remove(name)
+    }
+
+    pub fn panels(&self) -> impl Iterator<Item = (&str, &UiPanel)> {
+        self.panels
+            .iter()
+            .map(|(name, panel)| (name.as_str(), panel))
+    }
+
+    /// Add a widget to the end of a panel, or replace the panel's widget
+    /// with the same id. `false` if there is no such panel.
+    pub fn add_widget(&mut self, panel: &str, widget: UiWidget) -> bool {
+        let Some(panel) = self.panels.get_mut(panel) else {
+            return false;
+        };
+        match panel.widgets.iter_mut().find(|w| w.id == widget.id) {
+            Some(existing) => *existing = widget,
+            None => panel.widgets.push(widget),
+        }
+        true
+    }
+
+    /// First widget called `id` in any panel
+    pub fn widget(&self, id: &str) -> Option<&UiWidget> {
+        self.panels
+            .values()
+            .flat_map(|panel| &panel.widgets)
+            .find(|widget| widget.id == id)
+    }
+
+    pub fn widget_mut(&mut self, id: &str) -> Option<&mut UiWidget> {
+        self.panels
+            .values_mut()
+            .flat_map(|panel| &mut panel.widgets)
+            .find(|widget| widget.id == id)
+    }
+
+    /// Remove the first widget called `id`, returning whether there was one
+    pub fn remove_widget(&mut self, id: &str) -> bool {
+        for panel in self.panels.values_mut() {
+            if let Some(index) = panel.widgets.iter().position(|w| w.id == id) {
+                panel.widgets.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.panels.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.panels.clear();
+        self.events.clear();
+    }
+
+    /// Events of the last drawn frame
+    pub fn events(&self) -> &[UiEvent] {
+        &self.events
+    }
+
+    /// Replace the events with those of a newly drawn frame
+    pub fn set_events(&mut self, events: Vec<UiEvent>) {
+        self.events = events;
+    }
+
+    pub fn was_clicked(&self, id: &str) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, UiEvent::Clicked(clicked) if clicked == id))
+    }
+}
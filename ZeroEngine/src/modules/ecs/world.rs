@@ -1,11 +1,18 @@
 use crate::modules::ecs::components::*;
 use crate::modules::ecs::entity::Entity;
 use crate::modules::ecs::entity::*;
+use crate::modules::ecs::text::Hud;
+use crate::modules::ecs::ui::UiLayer;
+use crate::modules::render::debug_draw::DebugDraw;
+use crate::modules::render::msaa::DEFAULT_SAMPLE_COUNT;
 use slotmap::{SlotMap, new_key_type};
 use std::collections::HashMap;
 new_key_type! { pub struct EntityKey; }
 pub type EntityId = EntityKey;
 
+/// Upper bound when walking parent links
+const MAX_HIERARCHY_DEPTH: usize = 256;
+
 // Tag interned as u32
 pub type TagId = u32;
 
@@ -46,6 +53,24 @@ pub struct World {
     entities: SlotMap<EntityId, Entity>,
     tags: TagRegistry,
     tag_index: HashMap<TagId, Vec<EntityId>>, // speeds up queries
+    /// Light applied to every surface regardless of `Light` components
+    pub ambient_light: glam::Vec3,
+    /// Scene-wide post-processing, cameras can override it
+    pub post_process: PostProcess,
+    /// MSAA samples per pixel: 1, 2, 4 or 8 if the GPU supports it
+    pub msaa_samples: u32,
+    /// Lines drawn over the scene for debugging, filled by systems and scripts
+    pub debug_draw: DebugDraw,
+    /// Screen-space labels drawn over the frame, filled by scenes and scripts
+    pub hud: Hud,
+    /// egui panels, buttons and progress bars over the HUD
+    pub ui: UiLayer,
+    /// Drawn behind everything; `None` leaves the cameras' clear colors
+    pub background: Option<Background>,
+    pub fog: Fog,
+    /// Multiplies the frame time scripts, particles and animations see,
+    /// 0 pauses them
+    pub time_scale: f32,
 }
 
 impl World {
@@ -54,18 +79,158 @@ impl World {
             entities: SlotMap::with_key(),
             tags: TagRegistry::new(),
             tag_index: HashMap::new(),
+            ambient_light: glam::Vec3::splat(0.1),
+            post_process: PostProcess::default(),
+            msaa_samples: DEFAULT_SAMPLE_COUNT,
+            debug_draw: DebugDraw::new(),
+            hud: Hud::new(),
+            ui: UiLayer::new(),
+            background: None,
+            fog: Fog::default(),
+            time_scale: 1.0,
         }
     }
 
     pub fn active_camera_matrix(&self, aspect: f32) -> Option<glam::Mat4> {
-        for (_, entity) in &self.entities {
-            if let (Some(cam), Some(t)) = (&entity.camera, &entity.transform) {
-                if cam.is_active {
-                    return Some(camera_view_proj(cam, t, aspect));
-                }
+        self.active_camera()
+            .map(|(cam, t)| camera_view_proj(cam, &t, aspect))
+    }
+
+    /// Active camera together with its transform in world space. With
+    /// several active cameras this is the first one drawn.
+    pub fn active_camera(&self) -> Option<(&Camera, Transform)> {
+        self.active_cameras()
+            .into_iter()
+            .next()
+            .map(|(_, cam, t)| (cam, t))
+    }
+
+    /// Every active camera with its transform in world space, in the order
+    /// they are drawn. Equal orders keep their creation order.
+    pub fn active_cameras(&self) -> Vec<(EntityId, &Camera, Transform)> {
+        let mut cameras: Vec<_> = self
+            .entities
+            .iter()
+            .filter_map(|(id, entity)| {
+                let cam = entity.camera.as_ref().filter(|c| c.is_active)?;
+                let t = entity.transform.as_ref()?;
+                Some((id, cam, self.transform_in_world(id, t)))
+            })
+            .collect();
+        cameras.sort_by_key(|(_, cam, _)| cam.order);
+        cameras
+    }
+
+    /// Post-processing of the active camera, or the scene's if it has none
+    pub fn active_post_process(&self) -> &PostProcess {
+        self.active_camera()
+            .and_then(|(cam, _)| cam.post_process.as_ref())
+            .unwrap_or(&self.post_process)
+    }
+
+    /// World-space position of the active camera
+    pub fn active_camera_position(&self) -> Option<glam::Vec3> {
+        self.active_cameras()
+            .first()
+            .map(|(id, _, _)| self.world_matrix(*id).transform_point3(glam::Vec3::ZERO))
+    }
+
+    /// Every light with its world-space position and forward (-Z) direction
+    pub fn get_lights(&self) -> Vec<(Light, glam::Vec3, glam::Vec3)> {
+        self.entities
+            .iter()
+            .filter_map(|(id, entity)| {
+                let light = entity.light?;
+                let matrix = self.world_matrix(id);
+                let position = matrix.transform_point3(glam::Vec3::ZERO);
+                let direction = matrix
+                    .transform_vector3(-glam::Vec3::Z)
+                    .try_normalize()
+                    .unwrap_or(-glam::Vec3::Z);
+                Some((light, position, direction))
+            })
+            .collect()
+    }
+
+    // ========================================================================
+    // HIERARCHY
+    // ========================================================================
+
+    /// Attach `child` under `parent`, detaching it from any previous parent
+    pub fn set_parent(&mut self, child: EntityId, parent: EntityId) {
+        if child == parent {
+            return;
+        }
+
+        let old_parent = self.entities.get(child).and_then(|e| e.parent);
+        if let Some(children) = old_parent
+            .and_then(|p| self.entities.get_mut(p))
+            .and_then(|e| e.children.as_mut())
+        {
+            children.retain(|&c| c != child);
+        }
+
+        if let Some(entity) = self.entities.get_mut(child) {
+            entity.add_parent(parent);
+        }
+        if let Some(entity) = self.entities.get_mut(parent) {
+            entity.add_child(child);
+        }
+    }
+
+    /// Product of all ancestor transforms (identity for root entities)
+    pub fn parent_matrix(&self, id: EntityId) -> glam::Mat4 {
+        let mut matrix = glam::Mat4::IDENTITY;
+        let mut current = self.entities.get(id).and_then(|e| e.parent);
+        let mut depth = 0;
+
+        while let Some(parent_id) = current {
+            // Guard against accidental cycles
+            depth += 1;
+            if depth > MAX_HIERARCHY_DEPTH {
+                break;
             }
+
+            let Some(parent) = self.entities.get(parent_id) else {
+                break;
+            };
+            if let Some(t) = &parent.transform {
+                matrix = t.matrix() * matrix;
+            }
+            current = parent.parent;
+        }
+
+        matrix
+    }
+
+    /// Local-to-world matrix of an entity
+    pub fn world_matrix(&self, id: EntityId) -> glam::Mat4 {
+        let local = self
+            .entities
+            .get(id)
+            .and_then(|e| e.transform)
+            .map(|t| t.matrix())
+            .unwrap_or(glam::Mat4::IDENTITY);
+        self.parent_matrix(id) * local
+    }
+
+    /// Position and rotation of `transform` expressed in world space. Scale is
+    /// left local since cameras ignore it.
+    fn transform_in_world(&self, id: EntityId, transform: &Transform) -> Transform {
+        let parent = self.parent_matrix(id);
+        if parent == glam::Mat4::IDENTITY {
+            return *transform;
+        }
+
+        let (_, parent_rotation, _) = parent.to_scale_rotation_translation();
+        let rotation = parent_rotation * transform.rotation_quat();
+        let (x, y, z) = rotation.to_euler(glam::EulerRot::XYZ);
+
+        Transform {
+            position: parent.transform_point3(transform.position),
+            rotation: glam::Vec3::new(x, y, z),
+            scale: transform.scale,
         }
-        None
     }
 
     pub fn create_entity(&mut self, name: impl Into<String>) -> EntityId {
@@ -77,14 +242,15 @@ impl World {
         self.entities.get(id)
     }
 
-    pub fn get_renderable_entities(&self) -> Vec<(u32, Transform, Material)> {
+    /// Mesh id, world matrix and material of everything that can be drawn
+    pub fn get_renderable_entities(&self) -> Vec<(MeshHandle, glam::Mat4, Material)> {
         self.entities
-            .values()
-            .filter_map(|entity| {
-                if let (Some(mesh_handle), Some(material), Some(transform)) =
+            .iter()
+            .filter_map(|(entity_id, entity)| {
+                if let (Some(mesh_handle), Some(material), Some(_)) =
                     (&entity.mesh_handle, &entity.material, &entity.transform)
                 {
-                    Some((mesh_handle.0, *transform, *material))
+                    Some((*mesh_handle, self.world_matrix(entity_id), *material))
                 } else {
                     None
                 }
@@ -92,14 +258,21 @@ impl World {
             .collect()
     }
 
-    pub fn get_renderable_entities_with_ids(&self) -> Vec<(EntityId, u32, Transform, Material)> {
+    pub fn get_renderable_entities_with_ids(
+        &self,
+    ) -> Vec<(EntityId, MeshHandle, glam::Mat4, Material)> {
         self.entities
             .iter()
             .filter_map(|(entity_id, entity)| {
-                if let (Some(mesh_handle), Some(material), Some(transform)) =
+                if let (Some(mesh_handle), Some(material), Some(_)) =
                     (&entity.mesh_handle, &entity.material, &entity.transform)
                 {
-                    Some((entity_id, mesh_handle.0, *transform, *material))
+                    Some((
+                        entity_id,
+                        *mesh_handle,
+                        self.world_matrix(entity_id),
+                        *material,
+                    ))
                 } else {
                     None
                 }
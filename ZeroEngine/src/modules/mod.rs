@@ -1,5 +1,6 @@
 pub mod build;
+pub mod console;
 pub mod state;
 pub mod ecs;
-
-
+pub mod assets;
+pub mod render;
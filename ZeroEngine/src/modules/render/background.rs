@@ -0,0 +1,397 @@
+use crate::modules::assets::{Assets, CubemapAsset, CubemapHandle};
+use crate::modules::ecs::components::Background;
+use glam::Vec3;
+use std::collections::HashMap;
+
+// ============================================================================
+// BACKGROUND
+// ============================================================================
+
+/// Fullscreen triangle on the far plane. Drawn after the opaque pass with
+/// `LessEqual`, so it only lands on pixels no geometry covered. Skyboxes
+/// turn each pixel back into a world-space view ray through the inverse
+/// view-projection.
+pub const BACKGROUND_SHADER: &str = r#"
+struct Frame {
+    view_proj: mat4x4<f32>,
+    inverse_view_proj: mat4x4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> frame: Frame;
+
+// x 0 gradient (a solid color has equal ends), 1 skybox
+struct Background {
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+    mode: vec4<u32>,
+}
+@group(1) @binding(0)
+var<uniform> background: Background;
+@group(1) @binding(1)
+var sky_texture: texture_cube<f32>;
+@group(1) @binding(2)
+var sky_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.ndc = uv * 2.0 - 1.0;
+    out.clip_position = vec4<f32>(out.ndc, 1.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let near = frame.inverse_view_proj * vec4<f32>(in.ndc, 0.0, 1.0);
+    let far = frame.inverse_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w - near.xyz / near.w);
+    let sky = textureSampleLevel(sky_texture, sky_sampler, direction, 0.0).rgb;
+
+    let gradient = mix(background.bottom.rgb, background.top.rgb, in.ndc.y * 0.5 + 0.5);
+    return vec4<f32>(select(gradient, sky, background.mode.x == 1u), 1.0);
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct BackgroundUniformData {
+    top: [f32; 4],
+    bottom: [f32; 4],
+    mode: [u32; 4],
+}
+unsafe impl bytemuck::Pod for BackgroundUniformData {}
+unsafe impl bytemuck::Zeroable for BackgroundUniformData {}
+
+const UNIFORM_SIZE: u64 = std::mem::size_of::<BackgroundUniformData>() as u64;
+
+/// Draws the world's `Background` into the main pass and owns the GPU side
+/// of every loaded cubemap. Skyboxes still loading show black.
+pub struct BackgroundRenderer {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    /// 1x1 black cube bound while no skybox is ready
+    fallback: wgpu::TextureView,
+    cubemaps: HashMap<CubemapHandle, wgpu::TextureView>,
+    bind_group: wgpu::BindGroup,
+    /// Cubemap `bind_group` shows, `None` for the fallback
+    bound: Option<CubemapHandle>,
+    enabled: bool,
+}
+
+impl BackgroundRenderer {
+    /// `frame_layout` is the main pass's group 0, its first binding starts
+    /// with the view-projection and its inverse
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Background Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(UNIFORM_SIZE),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Background Pipeline Layout"),
+            bind_group_layouts: &[frame_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background Shader"),
+            source: wgpu::ShaderSource::Wgsl(BACKGROUND_SHADER.into()),
+        });
+        let pipeline =
+            Self::create_pipeline(device, &layout, &shader, format, depth_format, sample_count);
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Background Uniform Buffer"),
+            size: UNIFORM_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Background Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let black = CubemapAsset {
+            size: 1,
+            pixels: vec![glam::Vec4::W; 6],
+        };
+        let fallback = Self::upload_cubemap(device, queue, &black, "Background Fallback Cube");
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            &uniform_buffer,
+            &fallback,
+            &sampler,
+        );
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            sampler,
+            fallback,
+            cubemaps: HashMap::new(),
+            bind_group,
+            bound: None,
+            enabled: false,
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.pipeline = Self::create_pipeline(
+            device,
+            &self.layout,
+            &self.shader,
+            self.format,
+            self.depth_format,
+            sample_count,
+        );
+    }
+
+    /// Upload newly loaded cubemaps and release unused ones
+    pub fn sync(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, assets: &mut Assets) {
+        for handle in assets.take_freed_cubemaps() {
+            self.cubemaps.remove(&handle);
+            if self.bound == Some(handle) {
+                self.bind(device, None);
+            }
+        }
+
+        for handle in assets.take_cubemap_uploads() {
+            let Some(cubemap) = assets.cubemaps.get(handle) else {
+                continue;
+            };
+            let label = format!("Cubemap {}", handle.id());
+            let view = Self::upload_cubemap(device, queue, cubemap, &label);
+            self.cubemaps.insert(handle, view);
+            // A reloaded cubemap needs a fresh bind group
+            if self.bound == Some(handle) {
+                self.bind(device, Some(handle));
+            }
+        }
+    }
+
+    /// Set up this frame's background, `None` to skip drawing it
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        background: Option<&Background>,
+    ) {
+        self.enabled = background.is_some();
+        let (top, bottom, cubemap) = match background {
+            None => return,
+            Some(Background::Color(color)) => (*color, *color, None),
+            Some(Background::Gradient { top, bottom }) => (*top, *bottom, None),
+            Some(Background::Skybox(handle)) => (Vec3::ZERO, Vec3::ZERO, Some(*handle)),
+        };
+
+        let data = BackgroundUniformData {
+            top: top.extend(1.0).to_array(),
+            bottom: bottom.extend(1.0).to_array(),
+            mode: [cubemap.is_some() as u32, 0, 0, 0],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&data));
+
+        let cubemap = cubemap.filter(|handle| self.cubemaps.contains_key(handle));
+        if cubemap != self.bound {
+            self.bind(device, cubemap);
+        }
+    }
+
+    /// Draw into a pass that has the frame bind group at group 0. Does
+    /// nothing when the world has no background.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass) {
+        if !self.enabled {
+            return;
+        }
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    fn bind(&mut self, device: &wgpu::Device, cubemap: Option<CubemapHandle>) {
+        let view = cubemap
+            .and_then(|handle| self.cubemaps.get(&handle))
+            .unwrap_or(&self.fallback);
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            &self.uniform_buffer,
+            view,
+            &self.sampler,
+        );
+        self.bound = cubemap;
+    }
+
+    /// Six layer half-float texture, viewed as a cube
+    fn upload_cubemap(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cubemap: &CubemapAsset,
+        label: &str,
+    ) -> wgpu::TextureView {
+        let size = wgpu::Extent3d {
+            width: cubemap.size,
+            height: cubemap.size,
+            depth_or_array_layers: 6,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let texels: Vec<u16> = cubemap
+            .pixels
+            .iter()
+            .flat_map(|p| p.to_array())
+            .map(|c| half::f16::from_f32(c).to_bits())
+            .collect();
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(8 * cubemap.size),
+                rows_per_image: Some(cubemap.size),
+            },
+            size,
+        );
+
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Background Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,101 @@
+use crate::modules::assets::MeshHandle;
+use crate::modules::ecs::components::Material;
+use crate::modules::render::material::MaterialKey;
+use crate::modules::render::pipelines::PipelineKey;
+use glam::Vec3;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+// ============================================================================
+// INSTANCED DRAW BATCHES
+// ============================================================================
+
+/// What entities have to share to be drawn as instances of one draw call.
+/// Transforms and material factors are per instance, so only the mesh, the
+/// bound textures and the shader matter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BatchKey {
+    pub mesh: MeshHandle,
+    pub material: MaterialKey,
+    pub pipeline: PipelineKey,
+}
+
+impl BatchKey {
+    pub fn new(mesh: MeshHandle, material: &Material) -> Self {
+        Self {
+            mesh,
+            material: MaterialKey::from(material),
+            pipeline: PipelineKey::new(material.shader, material.blend),
+        }
+    }
+}
+
+/// One instanced draw over a contiguous range of the instance buffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawBatch<K> {
+    pub key: K,
+    pub instances: Range<u32>,
+}
+
+/// Group items by key, keeping the order in which keys first appear.
+/// Returns the item indices in instance order and one batch per key, with
+/// instance ranges starting at `first_instance`.
+pub fn batch_by_key<K: Copy + Eq + Hash>(
+    items: impl IntoIterator<Item = (usize, K)>,
+    first_instance: u32,
+) -> (Vec<usize>, Vec<DrawBatch<K>>) {
+    let mut group_of: HashMap<K, usize> = HashMap::new();
+    let mut groups: Vec<(K, Vec<usize>)> = Vec::new();
+    for (item, key) in items {
+        let group = *group_of.entry(key).or_insert_with(|| {
+            groups.push((key, Vec::new()));
+            groups.len() - 1
+        });
+        groups[group].1.push(item);
+    }
+
+    let mut order = Vec::new();
+    let mut batches = Vec::with_capacity(groups.len());
+    for (key, items) in groups {
+        let start = first_instance + order.len() as u32;
+        order.extend(items);
+        batches.push(DrawBatch {
+            key,
+            instances: start..first_instance + order.len() as u32,
+        });
+    }
+    (order, batches)
+}
+
+/// Group runs of equal keys without reordering, for draws whose order matters.
+/// Returns the same shape as `batch_by_key`.
+pub fn batch_consecutive<K: Copy + Eq>(
+    items: impl IntoIterator<Item = (usize, K)>,
+    first_instance: u32,
+) -> (Vec<usize>, Vec<DrawBatch<K>>) {
+    let mut order = Vec::new();
+    let mut batches: Vec<DrawBatch<K>> = Vec::new();
+    for (item, key) in items {
+        let instance = first_instance + order.len() as u32;
+        order.push(item);
+        match batches.last_mut() {
+            Some(batch) if batch.key == key => batch.instances.end = instance + 1,
+            _ => batches.push(DrawBatch {
+                key,
+                instances: instance..instance + 1,
+            }),
+        }
+    }
+    (order, batches)
+}
+
+/// Sort items farthest from the camera first, so blended surfaces are drawn
+/// over what's behind them. Equal distances keep their order.
+pub fn sort_back_to_front<T>(items: &mut [T], camera: Vec3, position: impl Fn(&T) -> Vec3) {
+    items.sort_by(|a, b| {
+        let a = camera.distance_squared(position(a));
+        let b = camera.distance_squared(position(b));
+        b.total_cmp(&a)
+    });
+}
@@ -0,0 +1,241 @@
+use crate::modules::assets::TextureHandle;
+use crate::modules::ecs::components::Transform;
+use crate::modules::ecs::entity::{Camera, RenderTarget, camera_view_proj};
+use crate::modules::ecs::world::World;
+use crate::modules::render::shader::compose;
+use glam::{Mat4, Quat, Vec3, Vec4};
+
+// ============================================================================
+// CAMERA VIEWS
+// ============================================================================
+
+/// An active camera resolved against the render target for one frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraView {
+    pub view_proj: Mat4,
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Pixel rectangle `(x, y, width, height)` the camera draws into
+    pub viewport: [f32; 4],
+    /// Width over height of the viewport
+    pub aspect: f32,
+    pub clear_color: Option<Vec4>,
+    /// Render target texture, `None` for the window
+    pub target: Option<TextureHandle>,
+}
+
+impl CameraView {
+    /// `None` when the camera's viewport lies outside the target, which is
+    /// `width` by `height` pixels
+    pub fn new(camera: &Camera, transform: &Transform, width: u32, height: u32) -> Option<Self> {
+        let viewport = camera.viewport.to_pixels(width, height)?;
+        let aspect = viewport[2] / viewport[3];
+        Some(Self {
+            view_proj: camera_view_proj(camera, transform, aspect),
+            position: transform.position,
+            rotation: transform.rotation_quat(),
+            viewport,
+            aspect,
+            clear_color: camera.clear_color,
+            target: camera.target.map(|t| t.texture),
+        })
+    }
+
+    /// The whole window seen through the identity matrix, for worlds
+    /// without an active camera drawing into it
+    pub fn fallback(width: u32, height: u32) -> Self {
+        Self {
+            view_proj: Mat4::IDENTITY,
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            viewport: [0.0, 0.0, width as f32, height as f32],
+            aspect: width as f32 / height as f32,
+            clear_color: Some(Vec4::W),
+            target: None,
+        }
+    }
+}
+
+/// Views of every active camera in the order they are drawn, for a window
+/// of `width` by `height` pixels. Render target cameras are sized by their
+/// target.
+pub fn camera_views(world: &World, width: u32, height: u32) -> Vec<CameraView> {
+    let cameras = world.active_cameras();
+    let mut views: Vec<CameraView> = cameras
+        .iter()
+        .filter_map(|(_, camera, transform)| {
+            let (width, height) = match camera.target {
+                Some(target) => target.size.resolve(width, height),
+                None => (width, height),
+            };
+            CameraView::new(camera, transform, width, height)
+        })
+        .collect();
+    if cameras.iter().all(|(_, camera, _)| camera.target.is_some()) {
+        views.push(CameraView::fallback(width, height));
+    }
+    views
+}
+
+/// Render target of every camera, active or not, so targets keep their
+/// last image while their camera is switched off
+pub fn camera_targets(world: &World) -> Vec<RenderTarget> {
+    let mut targets: Vec<RenderTarget> = Vec::new();
+    for (_, entity) in world.iter_entities() {
+        let Some(target) = entity.camera.as_ref().and_then(|c| c.target) else {
+            continue;
+        };
+        if !targets.iter().any(|t| t.texture == target.texture) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+// ============================================================================
+// VIEWPORT CLEAR
+// ============================================================================
+
+/// Fullscreen triangle on the far plane, limited to the viewport. A render
+/// pass load op would clear the whole target, this only clears one camera's
+/// part of it.
+pub const CLEAR_SHADER: &str = r#"
+@vertex
+fn vs_clear(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 1.0, 1.0);
+}
+
+@fragment
+fn fs_clear() -> @location(0) vec4<f32> {
+    return frame.clear_color;
+}
+"#;
+
+/// Resets depth inside the current viewport and, for cameras with a clear
+/// color, the color as well
+pub struct ViewportClear {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    color: wgpu::RenderPipeline,
+    depth_only: wgpu::RenderPipeline,
+}
+
+impl ViewportClear {
+    /// `frame_layout` is the main pass's group 0
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Viewport Clear Pipeline Layout"),
+            bind_group_layouts: &[frame_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Viewport Clear Shader"),
+            source: wgpu::ShaderSource::Wgsl(compose(CLEAR_SHADER).into()),
+        });
+        let build = |write_mask| {
+            Self::create_pipeline(
+                device,
+                &layout,
+                &shader,
+                format,
+                depth_format,
+                sample_count,
+                write_mask,
+            )
+        };
+        let color = build(wgpu::ColorWrites::ALL);
+        let depth_only = build(wgpu::ColorWrites::empty());
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            color,
+            depth_only,
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        let build = |write_mask| {
+            Self::create_pipeline(
+                device,
+                &self.layout,
+                &self.shader,
+                self.format,
+                self.depth_format,
+                sample_count,
+                write_mask,
+            )
+        };
+        let color = build(wgpu::ColorWrites::ALL);
+        let depth_only = build(wgpu::ColorWrites::empty());
+        self.color = color;
+        self.depth_only = depth_only;
+    }
+
+    /// Clear the pass's current viewport. The frame bind group at group 0
+    /// has to be the camera's, it holds the clear color.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, clear_color: bool) {
+        pass.set_pipeline(if clear_color {
+            &self.color
+        } else {
+            &self.depth_only
+        });
+        pass.draw(0..3, 0..1);
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        write_mask: wgpu::ColorWrites,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Viewport Clear Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_clear"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_clear"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,161 @@
+use glam::{Mat4, Vec3, Vec4};
+
+// ============================================================================
+// BOUNDING VOLUMES
+// ============================================================================
+
+/// Axis-aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |aabb, p| Self {
+                min: aabb.min.min(p),
+                max: aabb.max.max(p),
+            },
+        ))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Box enclosing this one after `transform`, still axis-aligned
+    pub fn transformed(&self, transform: &Mat4) -> Self {
+        let center = transform.transform_point3(self.center());
+        let half = self.half_extents();
+        let extents = transform.x_axis.truncate().abs() * half.x
+            + transform.y_axis.truncate().abs() * half.y
+            + transform.z_axis.truncate().abs() * half.z;
+        Self {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Sphere after `transform`; non-uniform scale grows it by the largest axis
+    pub fn transformed(&self, transform: &Mat4) -> Self {
+        let scale = transform
+            .x_axis
+            .truncate()
+            .length()
+            .max(transform.y_axis.truncate().length())
+            .max(transform.z_axis.truncate().length());
+        Self {
+            center: transform.transform_point3(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/// Local-space bounds of a mesh, computed once when it is registered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshBounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+
+impl MeshBounds {
+    pub fn from_positions(positions: &[[f32; 3]]) -> Option<Self> {
+        let aabb = Aabb::from_points(positions.iter().copied().map(Vec3::from))?;
+        // Centered on the box but only as large as the farthest vertex needs
+        let center = aabb.center();
+        let radius = positions
+            .iter()
+            .map(|&p| Vec3::from(p).distance(center))
+            .fold(0.0, f32::max);
+        Some(Self {
+            aabb,
+            sphere: BoundingSphere { center, radius },
+        })
+    }
+}
+
+// ============================================================================
+// FRUSTUM
+// ============================================================================
+
+/// Six planes of a view-projection matrix, normals pointing inwards
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Planes of a view-projection with wgpu's 0..1 clip depth
+    pub fn from_view_proj(view_proj: &Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().length();
+            if length > f32::EPSILON {
+                plane / length
+            } else {
+                plane
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Corner farthest along the plane normal
+            let normal = plane.truncate();
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+
+    /// Cheap sphere test first, then the tighter box
+    pub fn is_visible(&self, bounds: &MeshBounds, transform: &Mat4) -> bool {
+        self.intersects_sphere(&bounds.sphere.transformed(transform))
+            && self.intersects_aabb(&bounds.aabb.transformed(transform))
+    }
+}
+
+// ============================================================================
+// STATS
+// ============================================================================
+
+/// What the last frame drew. Entity counts add up over all cameras.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Entities drawn in the main pass
+    pub drawn: usize,
+    /// Entities skipped because they were outside a camera frustum
+    pub culled: usize,
+    /// Drawn entities with a transparent blend mode
+    pub transparent: usize,
+    /// Instanced draw calls of the main pass, one per mesh and material
+    pub draw_calls: usize,
+    /// Instanced draw calls across all shadow map layers
+    pub shadow_draws: usize,
+}
@@ -0,0 +1,524 @@
+use glam::{Mat4, Vec3, Vec4};
+
+// ============================================================================
+// DEBUG DRAW
+// ============================================================================
+
+/// Immediate-mode lines for visualizing colliders, paths, frusta and the
+/// like. Shapes are drawn for `duration` seconds, or for the next frame only
+/// when it is zero. Calls made while disabled are dropped.
+///
+/// ```ignore
+/// world.debug_draw.aabb(Vec3::splat(-1.0), Vec3::ONE, Vec4::new(0.0, 1.0, 0.0, 1.0), 0.0);
+/// world.debug_draw.text_3d(Vec3::Y * 2.0, "SPAWN", Vec4::ONE, 5.0);
+/// ```
+#[derive(Debug)]
+pub struct DebugDraw {
+    pub enabled: bool,
+    lines: Vec<DebugLine>,
+    texts: Vec<DebugText>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DebugLine {
+    from: Vec3,
+    to: Vec3,
+    color: Vec4,
+    remaining: f32,
+}
+
+#[derive(Clone, Debug)]
+struct DebugText {
+    position: Vec3,
+    text: String,
+    color: Vec4,
+    remaining: f32,
+}
+
+/// Segments per circle of a debug sphere
+const SPHERE_SEGMENTS: usize = 24;
+
+/// World-space height of a `text_3d` character
+pub const TEXT_HEIGHT: f32 = 0.25;
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            lines: Vec::new(),
+            texts: Vec::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_enabled(!self.enabled);
+    }
+
+    /// Disabling also drops shapes that were still waiting out their duration
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.texts.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.texts.is_empty()
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: Vec4, duration: f32) {
+        if self.enabled {
+            self.lines.push(DebugLine {
+                from,
+                to,
+                color,
+                remaining: duration,
+            });
+        }
+    }
+
+    /// The twelve edges of an axis-aligned box
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: Vec4, duration: f32) {
+        let corner = |i: usize| {
+            Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        // Corners that differ in exactly one bit share an edge
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color, duration);
+                }
+            }
+        }
+    }
+
+    /// Three circles around the axes
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: Vec4, duration: f32) {
+        let axes = [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)];
+        for (u, v) in axes {
+            let point = |i: usize| {
+                let angle = i as f32 / SPHERE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            for i in 0..SPHERE_SEGMENTS {
+                self.line(point(i), point(i + 1), color, duration);
+            }
+        }
+    }
+
+    /// Red, green and blue lines along the X, Y and Z axes of `transform`
+    pub fn axes(&mut self, transform: Mat4, size: f32, duration: f32) {
+        let origin = transform.transform_point3(Vec3::ZERO);
+        let colors = [
+            Vec4::new(1.0, 0.0, 0.0, 1.0),
+            Vec4::new(0.0, 1.0, 0.0, 1.0),
+            Vec4::new(0.0, 0.0, 1.0, 1.0),
+        ];
+        for (axis, color) in [Vec3::X, Vec3::Y, Vec3::Z].into_iter().zip(colors) {
+            let end = transform.transform_point3(axis * size);
+            self.line(origin, end, color, duration);
+        }
+    }
+
+    /// Square grid on the XZ plane around `center`, `divisions` cells a side
+    pub fn grid(&mut self, center: Vec3, size: f32, divisions: u32, color: Vec4, duration: f32) {
+        let divisions = divisions.max(1);
+        let half = size * 0.5;
+        for i in 0..=divisions {
+            let offset = -half + size * i as f32 / divisions as f32;
+            self.line(
+                center + Vec3::new(offset, 0.0, -half),
+                center + Vec3::new(offset, 0.0, half),
+                color,
+                duration,
+            );
+            self.line(
+                center + Vec3::new(-half, 0.0, offset),
+                center + Vec3::new(half, 0.0, offset),
+                color,
+                duration,
+            );
+        }
+    }
+
+    /// Text facing the camera, centered on `position`. Drawn with a
+    /// sixteen-segment line font: letters, digits and a few symbols.
+    pub fn text_3d(&mut self, position: Vec3, text: impl Into<String>, color: Vec4, duration: f32) {
+        if self.enabled {
+            self.texts.push(DebugText {
+                position,
+                text: text.into(),
+                color,
+                remaining: duration,
+            });
+        }
+    }
+
+    /// Line list of everything queued. Text is laid out along the camera's
+    /// right and up vectors.
+    pub fn vertices(&self, camera_right: Vec3, camera_up: Vec3) -> Vec<DebugVertex> {
+        let mut vertices = Vec::with_capacity(self.lines.len() * 2);
+        let mut push = |from: Vec3, to: Vec3, color: Vec4| {
+            vertices.push(DebugVertex::new(from, color));
+            vertices.push(DebugVertex::new(to, color));
+        };
+
+        for line in &self.lines {
+            push(line.from, line.to, line.color);
+        }
+        for text in &self.texts {
+            for (from, to) in text_segments(&text.text) {
+                let place = |p: [f32; 2]| {
+                    text.position + (camera_right * p[0] + camera_up * p[1]) * TEXT_HEIGHT
+                };
+                push(place(from), place(to), text.color);
+            }
+        }
+        vertices
+    }
+
+    /// Age shapes by a frame and drop the expired ones. Call after rendering.
+    pub fn end_frame(&mut self, dt: f32) {
+        self.lines.retain_mut(|line| {
+            line.remaining -= dt;
+            line.remaining > 0.0
+        });
+        self.texts.retain_mut(|text| {
+            text.remaining -= dt;
+            text.remaining > 0.0
+        });
+    }
+}
+
+// ============================================================================
+// SEGMENT FONT
+// ============================================================================
+
+// Sixteen-segment display layout on a 1x2 cell, origin bottom-left
+const SEGMENTS: [([f32; 2], [f32; 2]); 16] = [
+    ([0.0, 2.0], [0.5, 2.0]), // A1
+    ([0.5, 2.0], [1.0, 2.0]), // A2
+    ([1.0, 2.0], [1.0, 1.0]), // B
+    ([1.0, 1.0], [1.0, 0.0]), // C
+    ([1.0, 0.0], [0.5, 0.0]), // D2
+    ([0.5, 0.0], [0.0, 0.0]), // D1
+    ([0.0, 0.0], [0.0, 1.0]), // E
+    ([0.0, 1.0], [0.0, 2.0]), // F
+    ([0.0, 1.0], [0.5, 1.0]), // G1
+    ([0.5, 1.0], [1.0, 1.0]), // G2
+    ([0.0, 2.0], [0.5, 1.0]), // H
+    ([0.5, 2.0], [0.5, 1.0]), // I
+    ([1.0, 2.0], [0.5, 1.0]), // J
+    ([0.5, 1.0], [1.0, 0.0]), // K
+    ([0.5, 1.0], [0.5, 0.0]), // L
+    ([0.5, 1.0], [0.0, 0.0]), // M
+];
+
+const A1: u16 = 1 << 0;
+const A2: u16 = 1 << 1;
+const B: u16 = 1 << 2;
+const C: u16 = 1 << 3;
+const D2: u16 = 1 << 4;
+const D1: u16 = 1 << 5;
+const E: u16 = 1 << 6;
+const F: u16 = 1 << 7;
+const G1: u16 = 1 << 8;
+const G2: u16 = 1 << 9;
+const H: u16 = 1 << 10;
+const I: u16 = 1 << 11;
+const J: u16 = 1 << 12;
+const K: u16 = 1 << 13;
+const L: u16 = 1 << 14;
+const M: u16 = 1 << 15;
+const A: u16 = A1 | A2;
+const D: u16 = D1 | D2;
+const G: u16 = G1 | G2;
+
+/// Lit segments of a character; lowercase draws as uppercase and unknown
+/// characters as a blank
+pub fn glyph(c: char) -> u16 {
+    match c.to_ascii_uppercase() {
+        '0' => A | B | C | D | E | F | J | M,
+        '1' => B | C | J,
+        '2' => A | B | G | E | D,
+        '3' => A | B | G2 | C | D,
+        '4' => F | G | B | C,
+        '5' => A | F | G | C | D,
+        '6' => A | F | G | E | C | D,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' => A | B | C | E | F | G,
+        'B' => A | B | C | D | G2 | I | L,
+        'C' => A | F | E | D,
+        'D' => A | B | C | D | I | L,
+        'E' => A | F | E | D | G1,
+        'F' => A | F | E | G1,
+        'G' => A | F | E | D | C | G2,
+        'H' => F | E | B | C | G,
+        'I' => A | D | I | L,
+        'J' => B | C | D | E,
+        'K' => F | E | G1 | J | K,
+        'L' => F | E | D,
+        'M' => F | E | B | C | H | J,
+        'N' => F | E | B | C | H | K,
+        'O' => A | B | C | D | E | F,
+        'P' => A | B | F | E | G,
+        'Q' => A | B | C | D | E | F | K,
+        'R' => A | B | F | E | G | K,
+        'S' => A | F | G | C | D,
+        'T' => A | I | L,
+        'U' => F | E | D | C | B,
+        'V' => F | E | M | J,
+        'W' => F | E | M | K | C | B,
+        'X' => H | J | K | M,
+        'Y' => H | J | L,
+        'Z' => A | J | M | D,
+        '-' => G,
+        '+' => G | I | L,
+        '=' => G | D,
+        '_' => D,
+        '/' => J | M,
+        '\\' => H | K,
+        '(' => J | K,
+        ')' => H | M,
+        '*' => G | H | I | J | K | L | M,
+        '.' => D1,
+        _ => 0,
+    }
+}
+
+/// Line segments of a string in character-height units, centered on the
+/// origin. Characters are half as wide as they are high.
+pub fn text_segments(text: &str) -> Vec<([f32; 2], [f32; 2])> {
+    const ADVANCE: f32 = 0.8;
+    let count = text.chars().count() as f32;
+    let left = -(count * ADVANCE - (ADVANCE - 0.5)) * 0.5;
+
+    let mut segments = Vec::new();
+    for (index, c) in text.chars().enumerate() {
+        let x = left + index as f32 * ADVANCE;
+        let bits = glyph(c);
+        for (bit, (from, to)) in SEGMENTS.iter().enumerate() {
+            if bits & (1 << bit) != 0 {
+                // The cell is 1x2, scaled down to 0.5x1
+                let place = |p: [f32; 2]| [x + p[0] * 0.5, p[1] * 0.5 - 0.5];
+                segments.push((place(*from), place(*to)));
+            }
+        }
+    }
+    segments
+}
+
+// ============================================================================
+// GPU LINES
+// ============================================================================
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebugVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+unsafe impl bytemuck::Pod for DebugVertex {}
+unsafe impl bytemuck::Zeroable for DebugVertex {}
+
+impl DebugVertex {
+    fn new(position: Vec3, color: Vec4) -> Self {
+        Self {
+            position: position.to_array(),
+            color: color.to_array(),
+        }
+    }
+}
+
+/// Vertices the line buffer holds before it has to grow
+const MIN_DEBUG_VERTICES: usize = 1024;
+
+pub const DEBUG_SHADER: &str = r#"
+struct Frame {
+    view_proj: mat4x4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> frame: Frame;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = frame.view_proj * vec4<f32>(position, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+/// Line-list pipeline drawing `DebugDraw` into the main pass. Lines are
+/// depth tested against the scene but don't write depth.
+pub struct DebugRenderer {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    capacity: usize,
+    vertex_count: u32,
+}
+
+impl DebugRenderer {
+    /// `frame_layout` is the main pass's group 0, its first binding starts
+    /// with the view-projection
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug Draw Pipeline Layout"),
+            bind_group_layouts: &[frame_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Debug Draw Shader"),
+            source: wgpu::ShaderSource::Wgsl(DEBUG_SHADER.into()),
+        });
+        let pipeline =
+            Self::create_pipeline(device, &layout, &shader, format, depth_format, sample_count);
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, MIN_DEBUG_VERTICES),
+            capacity: MIN_DEBUG_VERTICES,
+            vertex_count: 0,
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.pipeline = Self::create_pipeline(
+            device,
+            &self.layout,
+            &self.shader,
+            self.format,
+            self.depth_format,
+            sample_count,
+        );
+    }
+
+    /// Upload this frame's lines
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[DebugVertex],
+    ) {
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    /// Draw into a pass that has the frame bind group at group 0
+    pub fn draw(&self, pass: &mut wgpu::RenderPass) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        pass.set_pipeline(&self.pipeline);
+        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        pass.draw(0..self.vertex_count, 0..1);
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug Line Buffer"),
+            size: (capacity * std::mem::size_of::<DebugVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Debug Draw Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<DebugVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &ATTRIBUTES,
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,226 @@
+use crate::log_error;
+use crate::modules::assets::{Assets, TextureHandle};
+use crate::modules::ecs::components::{Material, SamplerSettings};
+use crate::modules::render::texture::{GpuTexture, create_sampler};
+use std::collections::HashMap;
+
+// ============================================================================
+// MATERIAL BIND GROUPS
+// ============================================================================
+
+/// Identifies the textures and sampler a material binds. Factors live in the
+/// per-instance data, so materials that only differ in factors share a
+/// bind group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialKey {
+    base_color: Option<TextureHandle>,
+    metallic_roughness: Option<TextureHandle>,
+    emissive: Option<TextureHandle>,
+    normal: Option<TextureHandle>,
+    sampler: SamplerSettings,
+}
+
+impl MaterialKey {
+    /// Whether any of the material's textures is `texture`
+    pub fn uses_texture(&self, texture: TextureHandle) -> bool {
+        [
+            self.base_color,
+            self.metallic_roughness,
+            self.emissive,
+            self.normal,
+        ]
+        .contains(&Some(texture))
+    }
+}
+
+impl From<&Material> for MaterialKey {
+    fn from(material: &Material) -> Self {
+        Self {
+            base_color: material.base_color_texture,
+            metallic_roughness: material.metallic_roughness_texture,
+            emissive: material.emissive_texture,
+            normal: material.normal_texture,
+            sampler: material.sampler,
+        }
+    }
+}
+
+/// GPU side of materials: uploaded textures, samplers and one bind group
+/// (group 2 of the entity pipeline) per distinct texture set
+pub struct MaterialBindings {
+    pub layout: wgpu::BindGroupLayout,
+    textures: HashMap<TextureHandle, GpuTexture>,
+    samplers: HashMap<SamplerSettings, wgpu::Sampler>,
+    bind_groups: HashMap<MaterialKey, wgpu::BindGroup>,
+    // Stand-ins for missing or still loading textures
+    white: GpuTexture,
+    flat_normal: GpuTexture,
+}
+
+impl MaterialBindings {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        Self {
+            layout: Self::create_layout(device),
+            textures: HashMap::new(),
+            samplers: HashMap::new(),
+            bind_groups: HashMap::new(),
+            white: GpuTexture::solid(device, queue, [255; 4], "White Texture"),
+            flat_normal: GpuTexture::solid(device, queue, [128, 128, 255, 255], "Flat Normal"),
+        }
+    }
+
+    fn create_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let texture = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                texture(0), // base color
+                texture(1), // metallic-roughness
+                texture(2), // emissive
+                texture(3), // normal
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("material_bind_group_layout"),
+        })
+    }
+
+    /// Upload newly loaded textures and release unused ones. Bind groups are
+    /// rebuilt lazily afterwards so materials pick up textures that just
+    /// finished loading.
+    pub fn sync(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, assets: &mut Assets) {
+        let freed = assets.take_freed_textures();
+        let uploads = assets.take_texture_uploads();
+        if freed.is_empty() && uploads.is_empty() {
+            return;
+        }
+
+        for handle in freed {
+            self.textures.remove(&handle);
+        }
+        for handle in uploads {
+            let Some(asset) = assets.textures.get(handle) else {
+                continue;
+            };
+            if asset.width == 0 || asset.height == 0 {
+                log_error!("Skipping empty texture {:?}", handle);
+                continue;
+            }
+            let label = format!("Texture {}", handle.id());
+            let texture = GpuTexture::from_asset(device, queue, asset, &label);
+            self.textures.insert(handle, texture);
+        }
+
+        self.bind_groups.clear();
+    }
+
+    /// Make sure the bind group for `material` exists and return its key
+    pub fn prepare(&mut self, device: &wgpu::Device, material: &Material) -> MaterialKey {
+        let key = MaterialKey::from(material);
+        if self.bind_groups.contains_key(&key) {
+            return key;
+        }
+
+        self.samplers
+            .entry(key.sampler)
+            .or_insert_with(|| create_sampler(device, key.sampler));
+        let sampler = &self.samplers[&key.sampler];
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(self.view(
+                        key.base_color,
+                        &self.white,
+                        true,
+                    )),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(self.view(
+                        key.metallic_roughness,
+                        &self.white,
+                        false,
+                    )),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(self.view(
+                        key.emissive,
+                        &self.white,
+                        true,
+                    )),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(self.view(
+                        key.normal,
+                        &self.flat_normal,
+                        false,
+                    )),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("material_bind_group"),
+        });
+
+        self.bind_groups.insert(key, bind_group);
+        key
+    }
+
+    fn view<'a>(
+        &'a self,
+        handle: Option<TextureHandle>,
+        fallback: &'a GpuTexture,
+        srgb: bool,
+    ) -> &'a wgpu::TextureView {
+        let texture = handle
+            .and_then(|h| self.textures.get(&h))
+            .unwrap_or(fallback);
+        if srgb {
+            &texture.srgb_view
+        } else {
+            &texture.linear_view
+        }
+    }
+
+    /// Put a render target's texture behind `handle`, replacing what was
+    /// there before
+    pub fn set_render_target(&mut self, handle: TextureHandle, texture: GpuTexture) {
+        self.textures.insert(handle, texture);
+        self.bind_groups.clear();
+    }
+
+    /// Drop a render target's texture, materials fall back to white
+    pub fn remove_render_target(&mut self, handle: TextureHandle) {
+        if self.textures.remove(&handle).is_some() {
+            self.bind_groups.clear();
+        }
+    }
+
+    pub fn bind_group(&self, key: &MaterialKey) -> Option<&wgpu::BindGroup> {
+        self.bind_groups.get(key)
+    }
+
+    pub fn texture(&self, handle: TextureHandle) -> Option<&GpuTexture> {
+        self.textures.get(&handle)
+    }
+}
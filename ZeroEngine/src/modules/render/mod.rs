@@ -0,0 +1,18 @@
+pub mod background;
+pub mod batching;
+pub mod cameras;
+pub mod culling;
+pub mod debug_draw;
+pub mod material;
+pub mod modes;
+pub mod msaa;
+pub mod particles;
+pub mod pipelines;
+pub mod post;
+pub mod shader;
+pub mod shadows;
+pub mod sprites;
+pub mod targets;
+pub mod text;
+pub mod texture;
+pub mod ui;
@@ -0,0 +1,261 @@
+use crate::modules::render::shader::compose;
+
+// ============================================================================
+// RENDER MODES
+// ============================================================================
+
+/// What the main pass shows. Everything but `Lit` replaces the materials with
+/// a debug shader and skips the scene's post-processing effects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    #[default]
+    Lit,
+    /// Triangle edges only
+    Wireframe,
+    /// World-space normals as RGB
+    Normals,
+    /// Distance from the camera, white up close
+    Depth,
+    /// Heatmap of how many surfaces cover each pixel
+    Overdraw,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 5] = [
+        RenderMode::Lit,
+        RenderMode::Wireframe,
+        RenderMode::Normals,
+        RenderMode::Depth,
+        RenderMode::Overdraw,
+    ];
+
+    /// The mode after this one, wrapping around; used by the mode key
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Lit => "lit",
+            RenderMode::Wireframe => "wireframe",
+            RenderMode::Normals => "normals",
+            RenderMode::Depth => "depth",
+            RenderMode::Overdraw => "overdraw",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| format!("unknown render mode '{}'", name))
+    }
+}
+
+/// Entry points of the debug render modes, composed with the entity prelude
+pub const MODES_SHADER: &str = include_str!("shaders/modes.wgsl");
+
+/// Expand an indexed triangle list so every three vertices form a triangle,
+/// for the barycentric wireframe
+pub fn unindex<T: Copy>(vertices: &[T], indices: &[u32]) -> Vec<T> {
+    indices.iter().map(|&i| vertices[i as usize]).collect()
+}
+
+/// One pipeline per debug mode, sharing the entity pipeline layout so the
+/// frame, instance and material bind groups stay bound
+pub struct RenderModePipelines {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    vertex_layout: wgpu::VertexBufferLayout<'static>,
+    /// Whether the device can rasterize polygons as lines
+    pub line_polygons: bool,
+    wireframe: wgpu::RenderPipeline,
+    normals: wgpu::RenderPipeline,
+    depth: wgpu::RenderPipeline,
+    overdraw: wgpu::RenderPipeline,
+}
+
+impl RenderModePipelines {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        vertex_layout: wgpu::VertexBufferLayout<'static>,
+    ) -> Self {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Mode Pipeline Layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Render Mode Shader"),
+            source: wgpu::ShaderSource::Wgsl(compose(MODES_SHADER).into()),
+        });
+        let line_polygons = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+
+        let build = |mode| {
+            Self::create_pipeline(
+                device,
+                &layout,
+                &shader,
+                format,
+                depth_format,
+                &vertex_layout,
+                sample_count,
+                mode,
+                line_polygons,
+            )
+        };
+        let wireframe = build(RenderMode::Wireframe);
+        let normals = build(RenderMode::Normals);
+        let depth = build(RenderMode::Depth);
+        let overdraw = build(RenderMode::Overdraw);
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            vertex_layout,
+            line_polygons,
+            wireframe,
+            normals,
+            depth,
+            overdraw,
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        let build = |mode| {
+            Self::create_pipeline(
+                device,
+                &self.layout,
+                &self.shader,
+                self.format,
+                self.depth_format,
+                &self.vertex_layout,
+                sample_count,
+                mode,
+                self.line_polygons,
+            )
+        };
+        let wireframe = build(RenderMode::Wireframe);
+        let normals = build(RenderMode::Normals);
+        let depth = build(RenderMode::Depth);
+        let overdraw = build(RenderMode::Overdraw);
+        self.wireframe = wireframe;
+        self.normals = normals;
+        self.depth = depth;
+        self.overdraw = overdraw;
+    }
+
+    /// Pipeline replacing the materials, `None` when they are used as is
+    pub fn get(&self, mode: RenderMode) -> Option<&wgpu::RenderPipeline> {
+        match mode {
+            RenderMode::Lit => None,
+            RenderMode::Wireframe => Some(&self.wireframe),
+            RenderMode::Normals => Some(&self.normals),
+            RenderMode::Depth => Some(&self.depth),
+            RenderMode::Overdraw => Some(&self.overdraw),
+        }
+    }
+
+    /// Whether meshes have to be drawn from their unindexed copy
+    pub fn needs_unindexed(&self, mode: RenderMode) -> bool {
+        mode == RenderMode::Wireframe && !self.line_polygons
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        vertex_layout: &wgpu::VertexBufferLayout<'static>,
+        sample_count: u32,
+        mode: RenderMode,
+        line_polygons: bool,
+    ) -> wgpu::RenderPipeline {
+        let (vertex_entry, fragment_entry) = match mode {
+            RenderMode::Wireframe if line_polygons => ("vs_main", "fs_line"),
+            RenderMode::Wireframe => ("vs_barycentric", "fs_barycentric"),
+            RenderMode::Normals => ("vs_main", "fs_normals"),
+            RenderMode::Depth => ("vs_main", "fs_depth"),
+            RenderMode::Lit | RenderMode::Overdraw => ("vs_main", "fs_overdraw"),
+        };
+        let overdraw = mode == RenderMode::Overdraw;
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(mode.name()),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some(vertex_entry),
+                buffers: std::slice::from_ref(vertex_layout),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some(fragment_entry),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(if overdraw {
+                        wgpu::BlendState {
+                            color: additive,
+                            alpha: additive,
+                        }
+                    } else {
+                        wgpu::BlendState::REPLACE
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                // Wireframes show back edges too, overdraw counts every face
+                cull_mode: match mode {
+                    RenderMode::Wireframe | RenderMode::Overdraw => None,
+                    _ => Some(wgpu::Face::Back),
+                },
+                polygon_mode: if mode == RenderMode::Wireframe && line_polygons {
+                    wgpu::PolygonMode::Line
+                } else {
+                    wgpu::PolygonMode::Fill
+                },
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: !overdraw,
+                depth_compare: if overdraw {
+                    wgpu::CompareFunction::Always
+                } else {
+                    wgpu::CompareFunction::Less
+                },
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
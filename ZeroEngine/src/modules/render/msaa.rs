@@ -0,0 +1,137 @@
+use crate::modules::render::post::HDR_FORMAT;
+
+// ============================================================================
+// MULTISAMPLING
+// ============================================================================
+
+/// Sample counts the engine accepts
+pub const SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// Sample count of new worlds. Four samples are supported everywhere.
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+
+/// Sample counts every one of `formats` supports on this device. Counts other
+/// than 1 and 4 need the adapter-specific format feature.
+pub fn supported_sample_counts(
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    formats: &[wgpu::TextureFormat],
+) -> Vec<u32> {
+    let adapter_specific = device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+    SAMPLE_COUNTS
+        .into_iter()
+        .filter(|&count| {
+            formats.iter().all(|&format| {
+                let features = if adapter_specific {
+                    adapter.get_texture_format_features(format)
+                } else {
+                    format.guaranteed_format_features(device.features())
+                };
+                features.flags.sample_count_supported(count)
+            })
+        })
+        .collect()
+}
+
+/// Check a requested sample count against what the device supports
+pub fn validate_sample_count(requested: u32, supported: &[u32]) -> Result<u32, String> {
+    if !SAMPLE_COUNTS.contains(&requested) {
+        return Err(format!(
+            "MSAA sample count must be 1, 2, 4 or 8, got {}",
+            requested
+        ));
+    }
+    if !supported.contains(&requested) {
+        return Err(format!(
+            "MSAA sample count {} isn't supported by this GPU (supported: {:?})",
+            requested, supported
+        ));
+    }
+    Ok(requested)
+}
+
+/// Color and depth targets of the main pass. With more than one sample the
+/// scene is drawn into a multisampled color target that resolves into the
+/// HDR target.
+pub struct MsaaTargets {
+    pub sample_count: u32,
+    color_view: Option<wgpu::TextureView>,
+    pub depth_view: wgpu::TextureView,
+}
+
+impl MsaaTargets {
+    pub fn new(
+        device: &wgpu::Device,
+        size: winit::dpi::PhysicalSize<u32>,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let target = |label, format, usage| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: size.width.max(1),
+                        height: size.height.max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let color_view = (sample_count > 1).then(|| {
+            target(
+                "MSAA Color Target",
+                HDR_FORMAT,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+        });
+        let depth_view = target(
+            "Depth Texture",
+            depth_format,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+
+        Self {
+            sample_count,
+            color_view,
+            depth_view,
+        }
+    }
+
+    /// Color attachment drawing into `resolve`, through the multisampled
+    /// target if there is one
+    pub fn color_attachment<'a>(
+        &'a self,
+        resolve: &'a wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        match &self.color_view {
+            Some(view) => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: Some(resolve),
+                ops: wgpu::Operations {
+                    load,
+                    // Only the resolved image is needed afterwards
+                    store: wgpu::StoreOp::Discard,
+                },
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view: resolve,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            },
+        }
+    }
+}
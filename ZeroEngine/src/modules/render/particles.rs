@@ -0,0 +1,523 @@
+use crate::modules::ecs::components::BlendMode;
+use crate::modules::ecs::particles::{Particle, ParticleEmitter, ParticleSimulation};
+use crate::modules::ecs::world::{EntityId, World};
+use crate::modules::render::pipelines::blend_state;
+use crate::modules::render::shader::compose;
+use std::collections::HashMap;
+
+// ============================================================================
+// PARTICLE SHADERS
+// ============================================================================
+
+/// One simulation step for every slot of an emitter, the GPU side of
+/// `simulate_particles`
+pub const PARTICLE_SIMULATE: &str = r#"
+struct Particle {
+    position_age: vec4<f32>,
+    velocity_lifetime: vec4<f32>,
+}
+
+// xyz gravity, w seconds to advance
+struct Step {
+    gravity_dt: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> step: Step;
+@group(0) @binding(1)
+var<storage, read_write> particles: array<Particle>;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&particles) {
+        return;
+    }
+    var particle = particles[id.x];
+    if particle.position_age.w >= particle.velocity_lifetime.w {
+        return;
+    }
+    let dt = step.gravity_dt.w;
+    let velocity = particle.velocity_lifetime.xyz + step.gravity_dt.xyz * dt;
+    particle.position_age = vec4<f32>(particle.position_age.xyz + velocity * dt, particle.position_age.w + dt);
+    particle.velocity_lifetime = vec4<f32>(velocity, particle.velocity_lifetime.w);
+    particles[id.x] = particle;
+}
+"#;
+
+/// Camera-facing quads, one instance per slot. Dead slots collapse to a
+/// point. Composed with the prelude for the frame uniforms and fog; group 1
+/// starts at binding 1 so it doesn't collide with `instances`.
+pub const PARTICLE_SHADER: &str = r#"
+struct Particle {
+    position_age: vec4<f32>,
+    velocity_lifetime: vec4<f32>,
+}
+
+// x start size, y end size, z 1.0 for additive blending
+struct ParticleStyle {
+    start_color: vec4<f32>,
+    end_color: vec4<f32>,
+    size: vec4<f32>,
+}
+@group(1) @binding(1)
+var<uniform> style: ParticleStyle;
+@group(1) @binding(2)
+var<storage, read> particles: array<Particle>;
+
+struct ParticleOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) corner: vec2<f32>,
+}
+
+@vertex
+fn vs_particle(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> ParticleOutput {
+    var out: ParticleOutput;
+    let particle = particles[instance_index];
+    let age = particle.position_age.w;
+    let lifetime = particle.velocity_lifetime.w;
+    if age >= lifetime {
+        return out;
+    }
+    let t = age / lifetime;
+
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    // The view-projection's first two rows point along the camera's right
+    // and up axes, for perspective and orthographic cameras alike
+    let m = frame.view_proj;
+    let right = normalize(vec3<f32>(m[0].x, m[1].x, m[2].x));
+    let up = normalize(vec3<f32>(m[0].y, m[1].y, m[2].y));
+    let half_size = mix(style.size.x, style.size.y, t) * 0.5;
+    let world_position = particle.position_age.xyz + (right * corner.x + up * corner.y) * half_size;
+
+    out.clip_position = frame.view_proj * vec4<f32>(world_position, 1.0);
+    out.world_position = world_position;
+    out.color = mix(style.start_color, style.end_color, t);
+    out.corner = corner;
+    return out;
+}
+
+@fragment
+fn fs_particle(in: ParticleOutput) -> @location(0) vec4<f32> {
+    // Soft round sprite
+    let alpha = in.color.a * (1.0 - smoothstep(0.5, 1.0, length(in.corner)));
+    // Added light fades out in fog instead of turning fog colored
+    if style.size.z > 0.5 {
+        return vec4<f32>(in.color.rgb, alpha * (1.0 - fog_factor(in.world_position)));
+    }
+    return vec4<f32>(apply_fog(in.color.rgb, in.world_position), alpha);
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ParticleStyleData {
+    start_color: [f32; 4],
+    end_color: [f32; 4],
+    size: [f32; 4],
+}
+unsafe impl bytemuck::Pod for ParticleStyleData {}
+unsafe impl bytemuck::Zeroable for ParticleStyleData {}
+
+impl ParticleStyleData {
+    fn new(emitter: &ParticleEmitter) -> Self {
+        let additive = emitter.blend == BlendMode::Additive;
+        Self {
+            start_color: emitter.start_color.to_array(),
+            end_color: emitter.end_color.to_array(),
+            size: [
+                emitter.start_size,
+                emitter.end_size,
+                if additive { 1.0 } else { 0.0 },
+                0.0,
+            ],
+        }
+    }
+}
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// ============================================================================
+// PARTICLE RENDERER
+// ============================================================================
+
+/// GPU copy of one emitter's slots
+struct GpuEmitter {
+    capacity: u32,
+    particles: wgpu::Buffer,
+    style: wgpu::Buffer,
+    step: wgpu::Buffer,
+    render_bind_group: wgpu::BindGroup,
+    compute_bind_group: Option<wgpu::BindGroup>,
+    blend: BlendMode,
+    /// Emitter update last applied, see `ParticleEmitter::last_step`
+    last_update: u64,
+}
+
+struct ParticleCompute {
+    pipeline: wgpu::ComputePipeline,
+    layout: wgpu::BindGroupLayout,
+}
+
+/// Simulates particle emitters in a compute pass and draws them as
+/// billboards after the transparent entities. Devices without compute
+/// shaders get the CPU simulation, uploaded every frame.
+pub struct ParticleRenderer {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    pipelines: HashMap<BlendMode, wgpu::RenderPipeline>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    compute: Option<ParticleCompute>,
+    emitters: HashMap<EntityId, GpuEmitter>,
+    /// Emitters to draw this frame, in world order
+    drawn: Vec<EntityId>,
+}
+
+impl ParticleRenderer {
+    /// `frame_layout` is the main pass's group 0
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+        simulation: ParticleSimulation,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Particle Pipeline Layout"),
+            bind_group_layouts: &[frame_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Shader"),
+            source: wgpu::ShaderSource::Wgsl(compose(PARTICLE_SHADER).into()),
+        });
+        let pipelines =
+            Self::create_pipelines(device, &layout, &shader, format, depth_format, sample_count);
+
+        let compute = (simulation == ParticleSimulation::Gpu).then(|| Self::create_compute(device));
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            pipelines,
+            bind_group_layout,
+            compute,
+            emitters: HashMap::new(),
+            drawn: Vec::new(),
+        }
+    }
+
+    /// Where emitters should integrate their particles for this renderer
+    pub fn simulation(&self) -> ParticleSimulation {
+        if self.compute.is_some() {
+            ParticleSimulation::Gpu
+        } else {
+            ParticleSimulation::Cpu
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.pipelines = Self::create_pipelines(
+            device,
+            &self.layout,
+            &self.shader,
+            self.format,
+            self.depth_format,
+            sample_count,
+        );
+    }
+
+    /// Upload what the emitters spawned since the last frame and record
+    /// their simulation step into `encoder`. Buffers of removed emitters are
+    /// released.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        world: &World,
+    ) {
+        let emitters: Vec<(EntityId, &ParticleEmitter)> = world
+            .iter_entities()
+            .filter_map(|(id, e)| Some((id, e.particle_emitter.as_ref()?)))
+            .filter(|(_, emitter)| !emitter.particles().is_empty())
+            .collect();
+        self.emitters
+            .retain(|id, _| emitters.iter().any(|(emitter_id, _)| emitter_id == id));
+        self.drawn = emitters.iter().map(|(id, _)| *id).collect();
+
+        let mut steps = Vec::new();
+        for (id, emitter) in emitters {
+            let capacity = emitter.particles().len() as u32;
+            if self
+                .emitters
+                .get(&id)
+                .is_none_or(|gpu| gpu.capacity != capacity)
+            {
+                let gpu = self.create_emitter(device, capacity);
+                self.emitters.insert(id, gpu);
+            }
+            let gpu = self.emitters.get_mut(&id).unwrap();
+            gpu.blend = emitter.blend;
+            queue.write_buffer(
+                &gpu.style,
+                0,
+                bytemuck::bytes_of(&ParticleStyleData::new(emitter)),
+            );
+
+            let (dt, update) = emitter.last_step();
+            if gpu.last_update == update {
+                continue;
+            }
+            gpu.last_update = update;
+
+            if gpu.compute_bind_group.is_none() {
+                // CPU simulation: the whole copy is current
+                queue.write_buffer(&gpu.particles, 0, bytemuck::cast_slice(emitter.particles()));
+                continue;
+            }
+            let stride = std::mem::size_of::<Particle>() as u64;
+            for range in emitter.spawned() {
+                let spawned = &emitter.particles()[range.start as usize..range.end as usize];
+                queue.write_buffer(
+                    &gpu.particles,
+                    range.start as u64 * stride,
+                    bytemuck::cast_slice(spawned),
+                );
+            }
+            let step = emitter.gravity.extend(dt).to_array();
+            queue.write_buffer(&gpu.step, 0, bytemuck::cast_slice(&step));
+            steps.push(id);
+        }
+
+        let Some(compute) = &self.compute else {
+            return;
+        };
+        if steps.is_empty() {
+            return;
+        }
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Simulation Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&compute.pipeline);
+        for id in steps {
+            let gpu = &self.emitters[&id];
+            pass.set_bind_group(0, gpu.compute_bind_group.as_ref(), &[]);
+            pass.dispatch_workgroups(gpu.capacity.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+    }
+
+    /// Draw every emitter into a pass that has the frame bind group at
+    /// group 0. Emitters aren't sorted against each other.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass) {
+        for id in &self.drawn {
+            let Some(gpu) = self.emitters.get(id) else {
+                continue;
+            };
+            pass.set_pipeline(&self.pipelines[&gpu.blend]);
+            pass.set_bind_group(1, &gpu.render_bind_group, &[]);
+            pass.draw(0..6, 0..gpu.capacity);
+        }
+    }
+
+    fn create_emitter(&self, device: &wgpu::Device, capacity: u32) -> GpuEmitter {
+        let particles = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Buffer"),
+            size: capacity as u64 * std::mem::size_of::<Particle>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let style = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Style Buffer"),
+            size: std::mem::size_of::<ParticleStyleData>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let step = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Step Buffer"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: style.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: particles.as_entire_binding(),
+                },
+            ],
+        });
+        let compute_bind_group = self.compute.as_ref().map(|compute| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Particle Simulation Bind Group"),
+                layout: &compute.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: step.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: particles.as_entire_binding(),
+                    },
+                ],
+            })
+        });
+
+        GpuEmitter {
+            capacity,
+            particles,
+            style,
+            step,
+            render_bind_group,
+            compute_bind_group,
+            blend: BlendMode::Alpha,
+            last_update: 0,
+        }
+    }
+
+    fn create_compute(device: &wgpu::Device) -> ParticleCompute {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Simulation Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Particle Simulation Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Simulation Shader"),
+            source: wgpu::ShaderSource::Wgsl(PARTICLE_SIMULATE.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particle Simulation Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        ParticleCompute { pipeline, layout }
+    }
+
+    /// One pipeline per blend mode. Particles never write depth, so they
+    /// don't hide each other.
+    fn create_pipelines(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> HashMap<BlendMode, wgpu::RenderPipeline> {
+        BlendMode::ALL
+            .into_iter()
+            .map(|blend| {
+                let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Particle Pipeline"),
+                    layout: Some(layout),
+                    vertex: wgpu::VertexState {
+                        module: shader,
+                        entry_point: Some("vs_particle"),
+                        buffers: &[],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: shader,
+                        entry_point: Some("fs_particle"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(blend_state(blend)),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: depth_format,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::Less,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    multiview: None,
+                    cache: None,
+                });
+                (blend, pipeline)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,265 @@
+use crate::log_error;
+use crate::modules::assets::{Assets, ShaderHandle};
+use crate::modules::ecs::components::BlendMode;
+use crate::modules::render::shader::{DEFAULT_SHADER, compose};
+use std::collections::HashMap;
+
+// ============================================================================
+// PIPELINE CACHE
+// ============================================================================
+
+/// Vertex buffer layouts entity pipelines are built for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VertexLayout {
+    /// position, normal, uv
+    Standard,
+}
+
+/// A pipeline is built once per shader, blend mode and vertex layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    /// `None` is the engine's default shader
+    pub shader: Option<ShaderHandle>,
+    pub blend: BlendMode,
+    pub vertex_layout: VertexLayout,
+}
+
+impl PipelineKey {
+    pub fn new(shader: Option<ShaderHandle>, blend: BlendMode) -> Self {
+        Self {
+            shader,
+            blend,
+            vertex_layout: VertexLayout::Standard,
+        }
+    }
+}
+
+/// Color blending of a blend mode. The HDR target's alpha isn't read later,
+/// so transparent modes leave it alone.
+pub fn blend_state(blend: BlendMode) -> wgpu::BlendState {
+    let keep_alpha = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+    let color = |src_factor, dst_factor| wgpu::BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: wgpu::BlendOperation::Add,
+    };
+    match blend {
+        BlendMode::Opaque => wgpu::BlendState::REPLACE,
+        BlendMode::Alpha => wgpu::BlendState {
+            color: color(
+                wgpu::BlendFactor::SrcAlpha,
+                wgpu::BlendFactor::OneMinusSrcAlpha,
+            ),
+            alpha: keep_alpha,
+        },
+        BlendMode::Additive => wgpu::BlendState {
+            color: color(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+            alpha: keep_alpha,
+        },
+        BlendMode::Multiply => wgpu::BlendState {
+            color: color(wgpu::BlendFactor::Dst, wgpu::BlendFactor::Zero),
+            alpha: keep_alpha,
+        },
+    }
+}
+
+/// Entity render pipelines. Custom shaders that fail to build fall back to
+/// the default pipeline instead of taking the renderer down.
+pub struct PipelineCache {
+    layout: wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    sample_count: u32,
+    standard_vertex: wgpu::VertexBufferLayout<'static>,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    /// Sources of custom shaders, kept to rebuild them for a new sample count
+    sources: HashMap<ShaderHandle, String>,
+    /// Last build error of each shader, cleared when the file changes
+    failed: HashMap<ShaderHandle, String>,
+}
+
+impl PipelineCache {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        standard_vertex: wgpu::VertexBufferLayout<'static>,
+    ) -> Self {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ECS Entity Pipeline Layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        let mut cache = Self {
+            layout,
+            format,
+            depth_format,
+            sample_count,
+            standard_vertex,
+            pipelines: HashMap::new(),
+            sources: HashMap::new(),
+            failed: HashMap::new(),
+        };
+        cache.build_default(device);
+        cache
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Rebuild every pipeline for a new MSAA sample count
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        if sample_count == self.sample_count {
+            return;
+        }
+        self.sample_count = sample_count;
+        self.pipelines.clear();
+        self.failed.clear();
+        self.build_default(device);
+
+        let sources: Vec<_> = self.sources.drain().collect();
+        for (handle, source) in sources {
+            self.build_custom(device, handle, source);
+        }
+    }
+
+    fn build_default(&mut self, device: &wgpu::Device) {
+        for blend in BlendMode::ALL {
+            let key = PipelineKey::new(None, blend);
+            let pipeline = self.create_pipeline(device, "ECS Entity Pipeline", DEFAULT_SHADER, key);
+            self.pipelines.insert(key, pipeline);
+        }
+    }
+
+    /// Build every blend mode of a custom shader; the first error stops it
+    fn build_custom(&mut self, device: &wgpu::Device, handle: ShaderHandle, source: String) {
+        for blend in BlendMode::ALL {
+            let key = PipelineKey::new(Some(handle), blend);
+            match self.build(device, &source, key) {
+                Ok(pipeline) => {
+                    self.pipelines.insert(key, pipeline);
+                }
+                Err(e) => {
+                    log_error!("Failed to build shader {:?}: {}", handle, e);
+                    self.pipelines.retain(|key, _| key.shader != Some(handle));
+                    self.failed.insert(handle, e);
+                    break;
+                }
+            }
+        }
+        self.sources.insert(handle, source);
+    }
+
+    /// Rebuild pipelines of shaders that were reloaded and drop the ones of
+    /// freed shaders
+    pub fn sync(&mut self, device: &wgpu::Device, assets: &mut Assets) {
+        for handle in assets.take_shader_changes() {
+            self.pipelines.retain(|key, _| key.shader != Some(handle));
+            self.sources.remove(&handle);
+            self.failed.remove(&handle);
+
+            if let Some(shader) = assets.shaders.get(handle) {
+                self.build_custom(device, handle, shader.source.clone());
+            }
+        }
+    }
+
+    /// Pipeline for a key, or the default pipeline with the same blending
+    /// if that shader isn't loaded or failed to build
+    pub fn get(&self, key: PipelineKey) -> &wgpu::RenderPipeline {
+        self.pipelines
+            .get(&key)
+            .or_else(|| self.pipelines.get(&PipelineKey::new(None, key.blend)))
+            .expect("default pipelines are built on creation")
+    }
+
+    /// Error of a shader that failed to build on the GPU
+    pub fn error(&self, shader: ShaderHandle) -> Option<&str> {
+        self.failed.get(&shader).map(String::as_str)
+    }
+
+    /// Build a custom pipeline, catching validation errors so a broken shader
+    /// only costs its own pipeline
+    fn build(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+        key: PipelineKey,
+    ) -> Result<wgpu::RenderPipeline, String> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = self.create_pipeline(device, "Custom Entity Pipeline", source, key);
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(error) => Err(error.to_string()),
+            None => Ok(pipeline),
+        }
+    }
+
+    fn create_pipeline(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        source: &str,
+        key: PipelineKey,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(compose(source).into()),
+        });
+        let vertex_layout = match key.vertex_layout {
+            VertexLayout::Standard => self.standard_vertex.clone(),
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[vertex_layout],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.format,
+                    blend: Some(blend_state(key.blend)),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: self.depth_format,
+                // Transparent surfaces are sorted instead, and must not hide
+                // each other
+                depth_write_enabled: !key.blend.is_transparent(),
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: self.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
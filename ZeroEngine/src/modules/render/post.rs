@@ -0,0 +1,541 @@
+use crate::modules::assets::TextureHandle;
+use crate::modules::ecs::components::{PostProcess, Tonemapper};
+use crate::modules::render::texture::GpuTexture;
+
+// ============================================================================
+// POST-PROCESSING
+// ============================================================================
+
+/// Format of the intermediate target the scene is rendered into
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Upper bound on bloom mip levels, the first one at half resolution
+pub const MAX_BLOOM_MIPS: u32 = 6;
+
+pub const POST_SHADER: &str = include_str!("shaders/post.wgsl");
+
+/// Bloom levels for a frame size, stopping before a level gets tiny
+pub fn bloom_mip_count(width: u32, height: u32) -> u32 {
+    let smallest = (width.min(height) / 2).max(1);
+    // Keep the last level at least 8 pixels high
+    let levels = smallest.max(8).ilog2() - 2;
+    levels.clamp(1, MAX_BLOOM_MIPS)
+}
+
+/// Settings uniform shared by every post pass
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostUniformData {
+    /// x exposure, y tonemapper, z bloom intensity (0 when off), w threshold
+    pub exposure_tonemap_bloom: [f32; 4],
+    /// x vignette intensity (0 when off), y smoothness, z LUT strength (0
+    /// when off), w LUT size
+    pub vignette_grading: [f32; 4],
+}
+unsafe impl bytemuck::Pod for PostUniformData {}
+unsafe impl bytemuck::Zeroable for PostUniformData {}
+
+impl PostUniformData {
+    /// Disabled effects are encoded as zero strength. Grading needs the size
+    /// of a LUT that is already on the GPU.
+    pub fn new(settings: &PostProcess, lut_size: Option<u32>) -> Self {
+        let tonemapper = match settings.tonemapper {
+            Tonemapper::None => 0.0,
+            Tonemapper::Reinhard => 1.0,
+            Tonemapper::Aces => 2.0,
+        };
+        let bloom = &settings.bloom;
+        let vignette = &settings.vignette;
+        let grading = &settings.color_grading;
+        let lut_size = lut_size.filter(|_| grading.enabled);
+        Self {
+            exposure_tonemap_bloom: [
+                settings.exposure.max(0.0),
+                tonemapper,
+                if bloom.enabled { bloom.intensity } else { 0.0 },
+                bloom.threshold.max(0.0),
+            ],
+            vignette_grading: [
+                if vignette.enabled {
+                    vignette.intensity.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                },
+                vignette.smoothness.clamp(0.01, 1.0),
+                if lut_size.is_some() {
+                    grading.strength.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                },
+                lut_size.unwrap_or(1) as f32,
+            ],
+        }
+    }
+}
+
+/// Size-dependent targets, rebuilt on resize
+struct PostTargets {
+    hdr_view: wgpu::TextureView,
+    hdr_bind_group: wgpu::BindGroup,
+    /// One view per bloom mip, largest first
+    bloom_views: Vec<wgpu::TextureView>,
+    bloom_bind_groups: Vec<wgpu::BindGroup>,
+    /// Composite output when FXAA runs afterwards
+    ldr_view: wgpu::TextureView,
+    ldr_bind_group: wgpu::BindGroup,
+}
+
+/// HDR scene target and the chain of passes that resolves it into the
+/// swapchain: bloom, tonemapping with grading and vignette, then FXAA
+pub struct PostProcessor {
+    output_format: wgpu::TextureFormat,
+    pass_layout: wgpu::BindGroupLayout,
+    composite_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    settings_buffer: wgpu::Buffer,
+    /// Bound in place of a LUT that isn't loaded yet
+    fallback_lut: GpuTexture,
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    fxaa_pipeline: wgpu::RenderPipeline,
+    targets: PostTargets,
+    /// Composite bind group for the LUT it was built with
+    composite_bind_group: Option<(Option<TextureHandle>, wgpu::BindGroup)>,
+}
+
+impl PostProcessor {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        output_format: wgpu::TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let pass_entries = [
+            texture_entry(0),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ];
+        let pass_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &pass_entries,
+            label: Some("post_pass_bind_group_layout"),
+        });
+        let composite_entries = [
+            pass_entries[0],
+            pass_entries[1],
+            pass_entries[2],
+            texture_entry(3),
+            texture_entry(4),
+        ];
+        let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &composite_entries,
+            label: Some("post_composite_bind_group_layout"),
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let settings_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Process Settings Buffer"),
+            size: std::mem::size_of::<PostUniformData>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let fallback_lut = GpuTexture::solid(device, queue, [255; 4], "Fallback LUT");
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Process Shader"),
+            source: wgpu::ShaderSource::Wgsl(POST_SHADER.into()),
+        });
+        let pass_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pass Pipeline Layout"),
+            bind_group_layouts: &[&pass_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post Composite Pipeline Layout"),
+                bind_group_layouts: &[&composite_layout],
+                push_constant_ranges: &[],
+            });
+
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let pipeline = |label, layout, entry_point, format, blend| {
+            create_fullscreen_pipeline(device, &shader, label, layout, entry_point, format, blend)
+        };
+        let prefilter_pipeline = pipeline(
+            "Bloom Prefilter Pipeline",
+            &pass_pipeline_layout,
+            "fs_bloom_prefilter",
+            HDR_FORMAT,
+            wgpu::BlendState::REPLACE,
+        );
+        let downsample_pipeline = pipeline(
+            "Bloom Downsample Pipeline",
+            &pass_pipeline_layout,
+            "fs_bloom_downsample",
+            HDR_FORMAT,
+            wgpu::BlendState::REPLACE,
+        );
+        let upsample_pipeline = pipeline(
+            "Bloom Upsample Pipeline",
+            &pass_pipeline_layout,
+            "fs_bloom_upsample",
+            HDR_FORMAT,
+            additive,
+        );
+        let composite_pipeline = pipeline(
+            "Post Composite Pipeline",
+            &composite_pipeline_layout,
+            "fs_composite",
+            output_format,
+            wgpu::BlendState::REPLACE,
+        );
+        let fxaa_pipeline = pipeline(
+            "FXAA Pipeline",
+            &pass_pipeline_layout,
+            "fs_fxaa",
+            output_format,
+            wgpu::BlendState::REPLACE,
+        );
+
+        let targets = Self::create_targets(
+            device,
+            &pass_layout,
+            &sampler,
+            &settings_buffer,
+            output_format,
+            size,
+        );
+
+        Self {
+            output_format,
+            pass_layout,
+            composite_layout,
+            sampler,
+            settings_buffer,
+            fallback_lut,
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+            fxaa_pipeline,
+            targets,
+            composite_bind_group: None,
+        }
+    }
+
+    /// Target the scene is rendered into
+    pub fn hdr_view(&self) -> &wgpu::TextureView {
+        &self.targets.hdr_view
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) {
+        self.targets = Self::create_targets(
+            device,
+            &self.pass_layout,
+            &self.sampler,
+            &self.settings_buffer,
+            self.output_format,
+            size,
+        );
+        self.composite_bind_group = None;
+    }
+
+    /// Resolve the HDR target into `output`. `lut` is the color grading
+    /// texture if it has been uploaded.
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        settings: &PostProcess,
+        lut: Option<(TextureHandle, &GpuTexture)>,
+        output: &wgpu::TextureView,
+    ) {
+        let lut_size = lut.map(|(_, texture)| texture.texture.height());
+        let uniforms = PostUniformData::new(settings, lut_size);
+        queue.write_buffer(&self.settings_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        if settings.bloom.enabled {
+            self.run_bloom(encoder);
+        }
+
+        let lut_handle = lut.map(|(handle, _)| handle);
+        if self
+            .composite_bind_group
+            .as_ref()
+            .is_none_or(|(handle, _)| *handle != lut_handle)
+        {
+            let lut_view = lut.map_or(&self.fallback_lut.linear_view, |(_, t)| &t.linear_view);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.composite_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&self.targets.hdr_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.settings_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&self.targets.bloom_views[0]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(lut_view),
+                    },
+                ],
+                label: Some("post_composite_bind_group"),
+            });
+            self.composite_bind_group = Some((lut_handle, bind_group));
+        }
+        let Some((_, composite_bind_group)) = &self.composite_bind_group else {
+            return;
+        };
+
+        let composite_target = if settings.fxaa {
+            &self.targets.ldr_view
+        } else {
+            output
+        };
+        fullscreen_pass(
+            encoder,
+            "Post Composite Pass",
+            composite_target,
+            wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+            &self.composite_pipeline,
+            composite_bind_group,
+        );
+
+        if settings.fxaa {
+            fullscreen_pass(
+                encoder,
+                "FXAA Pass",
+                output,
+                wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                &self.fxaa_pipeline,
+                &self.targets.ldr_bind_group,
+            );
+        }
+    }
+
+    /// Bright parts of the frame blurred down the mip chain and added back up
+    /// into the first mip
+    fn run_bloom(&self, encoder: &mut wgpu::CommandEncoder) {
+        let targets = &self.targets;
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+
+        fullscreen_pass(
+            encoder,
+            "Bloom Prefilter Pass",
+            &targets.bloom_views[0],
+            clear,
+            &self.prefilter_pipeline,
+            &targets.hdr_bind_group,
+        );
+        for mip in 1..targets.bloom_views.len() {
+            fullscreen_pass(
+                encoder,
+                "Bloom Downsample Pass",
+                &targets.bloom_views[mip],
+                clear,
+                &self.downsample_pipeline,
+                &targets.bloom_bind_groups[mip - 1],
+            );
+        }
+        for mip in (1..targets.bloom_views.len()).rev() {
+            fullscreen_pass(
+                encoder,
+                "Bloom Upsample Pass",
+                &targets.bloom_views[mip - 1],
+                wgpu::LoadOp::Load,
+                &self.upsample_pipeline,
+                &targets.bloom_bind_groups[mip],
+            );
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        settings_buffer: &wgpu::Buffer,
+        output_format: wgpu::TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> PostTargets {
+        let (width, height) = (size.width.max(1), size.height.max(1));
+        let target = |label, width: u32, height: u32, mips, format| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: width.max(1),
+                    height: height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: mips,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        };
+        let bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: settings_buffer.as_entire_binding(),
+                    },
+                ],
+                label: Some("post_pass_bind_group"),
+            })
+        };
+
+        let hdr_view = target("HDR Target", width, height, 1, HDR_FORMAT)
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mips = bloom_mip_count(width, height);
+        let bloom = target("Bloom Chain", width / 2, height / 2, mips, HDR_FORMAT);
+        let bloom_views: Vec<_> = (0..mips)
+            .map(|mip| {
+                bloom.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Bloom Mip"),
+                    base_mip_level: mip,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let ldr_view = target("LDR Target", width, height, 1, output_format)
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        PostTargets {
+            hdr_bind_group: bind_group(&hdr_view),
+            bloom_bind_groups: bloom_views.iter().map(bind_group).collect(),
+            ldr_bind_group: bind_group(&ldr_view),
+            hdr_view,
+            bloom_views,
+            ldr_view,
+        }
+    }
+}
+
+fn create_fullscreen_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    entry_point: &str,
+    format: wgpu::TextureFormat,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_fullscreen"),
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(entry_point),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+fn fullscreen_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    label: &str,
+    target: &wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+    pipeline: &wgpu::RenderPipeline,
+    bind_group: &wgpu::BindGroup,
+) {
+    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    pass.set_pipeline(pipeline);
+    pass.set_bind_group(0, bind_group, &[]);
+    pass.draw(0..3, 0..1);
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+use wgpu::naga;
+
+// ============================================================================
+// SHADER INTERFACE
+// ============================================================================
+
+/// Declarations every entity shader is compiled with. The comment at the top
+/// of `shaders/prelude.wgsl` documents the interface shader files follow.
+pub const SHADER_PRELUDE: &str = include_str!("shaders/prelude.wgsl");
+
+/// Shader of materials that don't name one
+pub const DEFAULT_SHADER: &str = include_str!("shaders/default.wgsl");
+
+/// Entry points a shader file has to define
+const ENTRY_POINTS: [(&str, naga::ShaderStage); 2] = [
+    ("vs_main", naga::ShaderStage::Vertex),
+    ("fs_main", naga::ShaderStage::Fragment),
+];
+
+/// Complete source of an entity shader: the prelude followed by the shader file
+pub fn compose(source: &str) -> String {
+    format!("{}\n{}", SHADER_PRELUDE, source)
+}
+
+/// Check a shader file against the engine interface without touching the
+/// GPU. Line numbers in errors refer to the shader file, not the prelude.
+pub fn validate(source: &str) -> Result<(), String> {
+    let composed = compose(source);
+
+    let module = naga::front::wgsl::parse_str(&composed)
+        .map_err(|e| located(e.message(), e.location(&composed)))?;
+
+    for (name, stage) in ENTRY_POINTS {
+        if !module
+            .entry_points
+            .iter()
+            .any(|ep| ep.name == name && ep.stage == stage)
+        {
+            return Err(format!("missing {:?} entry point '{}'", stage, name));
+        }
+    }
+
+    let mut validator = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::default(),
+    );
+    validator
+        .validate(&module)
+        .map_err(|e| located(&error_chain(e.as_inner()), e.location(&composed)))?;
+    Ok(())
+}
+
+/// Prefix an error with its line in the shader file
+fn located(message: &str, location: Option<naga::SourceLocation>) -> String {
+    let prelude_lines = SHADER_PRELUDE.matches('\n').count() as u32 + 1;
+    match location {
+        Some(loc) if loc.line_number > prelude_lines => format!(
+            "{}:{}: {}",
+            loc.line_number - prelude_lines,
+            loc.line_position,
+            message
+        ),
+        _ => message.to_string(),
+    }
+}
+
+/// Validation errors nest the useful part a few levels deep
+fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
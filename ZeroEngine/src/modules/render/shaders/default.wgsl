@@ -0,0 +1,12 @@
+// Shader used by materials without a custom one
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    return engine_vertex(vertex);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let surface = material_surface(in);
+    return vec4<f32>(shade(surface, in.world_position), surface.albedo.a);
+}
@@ -0,0 +1,66 @@
+// Debug render modes, compiled with the entity prelude so they read the same
+// frame and instance data as material shaders
+
+const WIRE_COLOR: vec3<f32> = vec3<f32>(0.55, 1.0, 0.6);
+// Light added by every surface covering a pixel in the overdraw heatmap
+const OVERDRAW_STEP: vec3<f32> = vec3<f32>(0.25, 0.1, 0.03);
+// Distance at which the depth view has faded to half brightness
+const DEPTH_HALF_DISTANCE: f32 = 10.0;
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    return engine_vertex(vertex);
+}
+
+// Wireframe drawn by the rasterizer with `PolygonMode::Line`
+@fragment
+fn fs_line(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(WIRE_COLOR, 1.0);
+}
+
+struct BarycentricOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) barycentric: vec3<f32>,
+}
+
+// Wireframe fallback for devices without line polygons. Needs meshes drawn
+// without an index buffer so every three vertices form one triangle.
+@vertex
+fn vs_barycentric(vertex: VertexInput, @builtin(vertex_index) vertex_index: u32) -> BarycentricOutput {
+    var out: BarycentricOutput;
+    out.clip_position = engine_vertex(vertex).clip_position;
+    let corner = vertex_index % 3u;
+    out.barycentric = vec3<f32>(f32(corner == 0u), f32(corner == 1u), f32(corner == 2u));
+    return out;
+}
+
+@fragment
+fn fs_barycentric(in: BarycentricOutput) -> @location(0) vec4<f32> {
+    // About one pixel wide at any distance
+    let width = fwidth(in.barycentric) * 1.5;
+    let edge = smoothstep(vec3<f32>(0.0), width, in.barycentric);
+    let coverage = 1.0 - min(edge.x, min(edge.y, edge.z));
+    if coverage < 0.01 {
+        discard;
+    }
+    return vec4<f32>(WIRE_COLOR * coverage, 1.0);
+}
+
+@fragment
+fn fs_normals(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(normalize(in.normal) * 0.5 + 0.5, 1.0);
+}
+
+// White at the camera, fading with distance
+@fragment
+fn fs_depth(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(frame.camera_position.xyz - in.world_position);
+    let brightness = 1.0 / (1.0 + distance / DEPTH_HALF_DISTANCE);
+    return vec4<f32>(vec3<f32>(brightness), 1.0);
+}
+
+// Added up without depth testing, so hot pixels are covered many times
+@fragment
+fn fs_overdraw(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(OVERDRAW_STEP, 1.0);
+}
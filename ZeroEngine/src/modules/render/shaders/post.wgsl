@@ -0,0 +1,196 @@
+// Post-process passes. Every pass draws one fullscreen triangle and reads
+// `source`; the composite pass also reads the bloom chain and grading LUT.
+
+struct PostSettings {
+    // x exposure, y tonemapper (0 none, 1 Reinhard, 2 ACES),
+    // z bloom intensity (0 when off), w bloom threshold
+    exposure_tonemap_bloom: vec4<f32>,
+    // x vignette intensity (0 when off), y vignette smoothness,
+    // z LUT strength (0 when off), w LUT size
+    vignette_grading: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> settings: PostSettings;
+@group(0) @binding(3)
+var bloom: texture_2d<f32>;
+@group(0) @binding(4)
+var lut: texture_2d<f32>;
+
+struct FullscreenOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    // One triangle covering the screen, uv (0, 0) in the top-left corner
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: FullscreenOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source, source_sampler, uv, 0.0).rgb;
+}
+
+// ============================================================================
+// BLOOM
+// ============================================================================
+
+// Four bilinear taps one texel apart average a 4x4 block of the source
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    return (sample_source(uv + texel * vec2<f32>(-1.0, -1.0))
+        + sample_source(uv + texel * vec2<f32>(1.0, -1.0))
+        + sample_source(uv + texel * vec2<f32>(-1.0, 1.0))
+        + sample_source(uv + texel * vec2<f32>(1.0, 1.0))) * 0.25;
+}
+
+@fragment
+fn fs_bloom_prefilter(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = downsample(in.uv) * settings.exposure_tonemap_bloom.x;
+    let threshold = settings.exposure_tonemap_bloom.w;
+
+    // Soft knee so pixels don't pop in right at the threshold
+    let brightness = max(color.r, max(color.g, color.b));
+    let knee = threshold * 0.5;
+    var soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 0.0001);
+    let contribution = max(soft, brightness - threshold) / max(brightness, 0.0001);
+    return vec4<f32>(color * contribution, 1.0);
+}
+
+@fragment
+fn fs_bloom_downsample(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.uv), 1.0);
+}
+
+// 3x3 tent filter, added on top of the next larger mip
+@fragment
+fn fs_bloom_upsample(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    var color = sample_source(in.uv) * 4.0;
+    color += (sample_source(in.uv + vec2<f32>(-texel.x, 0.0))
+        + sample_source(in.uv + vec2<f32>(texel.x, 0.0))
+        + sample_source(in.uv + vec2<f32>(0.0, -texel.y))
+        + sample_source(in.uv + vec2<f32>(0.0, texel.y))) * 2.0;
+    color += sample_source(in.uv + vec2<f32>(-texel.x, -texel.y))
+        + sample_source(in.uv + vec2<f32>(texel.x, -texel.y))
+        + sample_source(in.uv + vec2<f32>(-texel.x, texel.y))
+        + sample_source(in.uv + vec2<f32>(texel.x, texel.y));
+    return vec4<f32>(color / 16.0, 1.0);
+}
+
+// ============================================================================
+// COMPOSITE
+// ============================================================================
+
+fn tonemap_aces(x: vec3<f32>) -> vec3<f32> {
+    // Narkowicz's fit of the ACES filmic curve
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn tonemap(color: vec3<f32>) -> vec3<f32> {
+    let mode = u32(settings.exposure_tonemap_bloom.y);
+    if mode == 1u {
+        return color / (color + vec3<f32>(1.0));
+    }
+    if mode == 2u {
+        return tonemap_aces(color);
+    }
+    return clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// The LUT is authored in gamma space: N slices of N x N side by side, red
+// along x, green down and blue selecting the slice
+fn grade(color: vec3<f32>) -> vec3<f32> {
+    let size = settings.vignette_grading.w;
+    let c = pow(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / 2.2));
+
+    let slice = c.b * (size - 1.0);
+    let slice0 = floor(slice);
+    let slice1 = min(slice0 + 1.0, size - 1.0);
+    let x = c.r * (size - 1.0) + 0.5;
+    let y = (c.g * (size - 1.0) + 0.5) / size;
+    let a = textureSampleLevel(lut, source_sampler, vec2<f32>((slice0 * size + x) / (size * size), y), 0.0).rgb;
+    let b = textureSampleLevel(lut, source_sampler, vec2<f32>((slice1 * size + x) / (size * size), y), 0.0).rgb;
+
+    return pow(mix(a, b, slice - slice0), vec3<f32>(2.2));
+}
+
+@fragment
+fn fs_composite(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    var color = sample_source(in.uv) * settings.exposure_tonemap_bloom.x;
+
+    let bloom_intensity = settings.exposure_tonemap_bloom.z;
+    if bloom_intensity > 0.0 {
+        color += textureSampleLevel(bloom, source_sampler, in.uv, 0.0).rgb * bloom_intensity;
+    }
+
+    color = tonemap(color);
+
+    let lut_strength = settings.vignette_grading.z;
+    if lut_strength > 0.0 {
+        color = mix(color, grade(color), lut_strength);
+    }
+
+    let vignette = settings.vignette_grading.x;
+    if vignette > 0.0 {
+        // 0 in the center, 1 in the corners
+        let d = distance(in.uv, vec2<f32>(0.5)) * 1.41421356;
+        let falloff = smoothstep(1.0 - settings.vignette_grading.y, 1.0, d);
+        color *= 1.0 - vignette * falloff;
+    }
+
+    return vec4<f32>(color, 1.0);
+}
+
+// ============================================================================
+// FXAA
+// ============================================================================
+
+fn luma(color: vec3<f32>) -> f32 {
+    // sqrt brings the linear color close to perceptual brightness
+    return sqrt(dot(color, vec3<f32>(0.299, 0.587, 0.114)));
+}
+
+@fragment
+fn fs_fxaa(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    let rgb_m = sample_source(in.uv);
+    let luma_nw = luma(sample_source(in.uv + texel * vec2<f32>(-1.0, -1.0)));
+    let luma_ne = luma(sample_source(in.uv + texel * vec2<f32>(1.0, -1.0)));
+    let luma_sw = luma(sample_source(in.uv + texel * vec2<f32>(-1.0, 1.0)));
+    let luma_se = luma(sample_source(in.uv + texel * vec2<f32>(1.0, 1.0)));
+    let luma_m = luma(rgb_m);
+
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    // Blur along the edge, perpendicular to the luma gradient
+    var dir = vec2<f32>(-((luma_nw + luma_ne) - (luma_sw + luma_se)), (luma_nw + luma_sw) - (luma_ne + luma_se));
+    let dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * (1.0 / 8.0), 1.0 / 128.0);
+    let rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2<f32>(-8.0), vec2<f32>(8.0)) * texel;
+
+    let rgb_a = 0.5 * (sample_source(in.uv + dir * (1.0 / 3.0 - 0.5)) + sample_source(in.uv + dir * (2.0 / 3.0 - 0.5)));
+    let rgb_b = rgb_a * 0.5 + 0.25 * (sample_source(in.uv - dir * 0.5) + sample_source(in.uv + dir * 0.5));
+
+    let luma_b = luma(rgb_b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return vec4<f32>(rgb_a, 1.0);
+    }
+    return vec4<f32>(rgb_b, 1.0);
+}
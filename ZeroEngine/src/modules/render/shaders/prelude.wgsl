@@ -0,0 +1,322 @@
+// ============================================================================
+// ZERO ENGINE SHADER INTERFACE
+// ============================================================================
+//
+// Every entity shader is compiled with this prelude in front of it. A shader
+// file has to define two entry points:
+//
+//     @vertex fn vs_main(vertex: VertexInput) -> VertexOutput
+//     @fragment fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+//
+// The smallest valid shader hands both stages back to the engine:
+//
+//     @vertex
+//     fn vs_main(vertex: VertexInput) -> VertexOutput {
+//         return engine_vertex(vertex);
+//     }
+//     @fragment
+//     fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+//         let surface = material_surface(in);
+//         return vec4<f32>(shade(surface, in.world_position), surface.albedo.a);
+//     }
+//
+// Everything declared below is available to the shader. Names are reserved,
+// so don't redeclare them. Group 0 is per frame, group 1 holds the instances
+// of the current draw and group 2 the material textures. Entities sharing a
+// mesh and material are drawn as instances of one draw call, so per-entity
+// data is read from `instances`, never from a single uniform.
+
+const PI: f32 = 3.14159265;
+
+struct Frame {
+    view_proj: mat4x4<f32>,
+    // clip space back to world space, e.g. to turn pixels into view rays
+    inverse_view_proj: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    ambient: vec4<f32>,
+    // x number of entries in `lights`
+    light_count: vec4<u32>,
+    // x seconds since the renderer started
+    time: vec4<f32>,
+    // what the camera's viewport is cleared to
+    clear_color: vec4<f32>,
+    // xyz direction the camera looks in
+    camera_forward: vec4<f32>,
+    // rgb fog color, w fog floor height
+    fog_color: vec4<f32>,
+    // x linear start, y linear end, z density, w height falloff
+    fog_params: vec4<f32>,
+    // x 0 no fog, 1 linear, 2 exponential, 3 exponential squared, 4 height
+    fog_mode: vec4<u32>,
+}
+
+// kind in direction_kind.w: 0 directional, 1 point, 2 spot
+struct Light {
+    position_range: vec4<f32>,
+    direction_kind: vec4<f32>,
+    color_intensity: vec4<f32>,
+    // cosines of the inner and outer spot angles
+    cone: vec4<f32>,
+    // x first shadow map layer (negative for none), y layer count
+    shadow: vec4<f32>,
+}
+
+struct Instance {
+    transform: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+    base_color: vec4<f32>,
+    // rgb emissive factor, w normal map scale
+    emissive: vec4<f32>,
+    // x metallic, y roughness, z 1.0 if the entity receives shadows
+    params: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> frame: Frame;
+@group(0) @binding(1)
+var<storage, read> lights: array<Light>;
+@group(0) @binding(2)
+var<storage, read> shadow_matrices: array<mat4x4<f32>>;
+@group(0) @binding(3)
+var shadow_map: texture_depth_2d_array;
+@group(0) @binding(4)
+var shadow_sampler: sampler_comparison;
+@group(1) @binding(0)
+var<storage, read> instances: array<Instance>;
+@group(2) @binding(0)
+var base_color_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var metallic_roughness_texture: texture_2d<f32>;
+@group(2) @binding(2)
+var emissive_texture: texture_2d<f32>;
+@group(2) @binding(3)
+var normal_texture: texture_2d<f32>;
+@group(2) @binding(4)
+var material_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @builtin(instance_index) instance_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    // Index into `instances`
+    @location(3) @interpolate(flat) instance: u32,
+}
+
+// Material inputs of one fragment, ready for `shade`
+struct Surface {
+    albedo: vec4<f32>,
+    metallic: f32,
+    roughness: f32,
+    emissive: vec3<f32>,
+    // World space, normal map applied
+    normal: vec3<f32>,
+    receives_shadows: bool,
+}
+
+// Standard transform to world and clip space
+fn engine_vertex(vertex: VertexInput) -> VertexOutput {
+    let instance = instances[vertex.instance_index];
+    var out: VertexOutput;
+    let world_pos = instance.transform * vec4<f32>(vertex.position, 1.0);
+    out.clip_position = frame.view_proj * world_pos;
+    out.world_position = world_pos.xyz;
+    out.normal = (instance.normal_matrix * vec4<f32>(vertex.normal, 0.0)).xyz;
+    out.uv = vertex.uv;
+    out.instance = vertex.instance_index;
+    return out;
+}
+
+// Tangent frame from screen-space derivatives, so meshes don't need tangents
+fn perturb_normal(n: vec3<f32>, p: vec3<f32>, uv: vec2<f32>, tangent_normal: vec3<f32>) -> vec3<f32> {
+    let dp1 = dpdx(p);
+    let dp2 = dpdy(p);
+    let duv1 = dpdx(uv);
+    let duv2 = dpdy(uv);
+    let dp2perp = cross(dp2, n);
+    let dp1perp = cross(n, dp1);
+    let t = dp2perp * duv1.x + dp1perp * duv2.x;
+    let b = dp2perp * duv1.y + dp1perp * duv2.y;
+    let len = max(dot(t, t), dot(b, b));
+    if len < 1e-12 {
+        return n;
+    }
+    let inv = inverseSqrt(len);
+    return normalize(mat3x3<f32>(t * inv, b * inv, n) * tangent_normal);
+}
+
+// Material factors times textures. Samples with derivatives, so call it
+// from uniform control flow.
+fn material_surface(in: VertexOutput) -> Surface {
+    let instance = instances[in.instance];
+    let base_sample = textureSample(base_color_texture, material_sampler, in.uv);
+    let mr_sample = textureSample(metallic_roughness_texture, material_sampler, in.uv);
+    let emissive_sample = textureSample(emissive_texture, material_sampler, in.uv);
+    let normal_sample = textureSample(normal_texture, material_sampler, in.uv).xyz * 2.0 - 1.0;
+
+    var surface: Surface;
+    surface.albedo = instance.base_color * base_sample;
+    // glTF convention: roughness in G, metallic in B
+    surface.metallic = clamp(instance.params.x * mr_sample.b, 0.0, 1.0);
+    surface.roughness = clamp(instance.params.y * mr_sample.g, 0.04, 1.0);
+    surface.emissive = instance.emissive.rgb * emissive_sample.rgb;
+    let tangent_normal = vec3<f32>(normal_sample.xy * instance.emissive.w, normal_sample.z);
+    surface.normal = perturb_normal(normalize(in.normal), in.world_position, in.uv, tangent_normal);
+    surface.receives_shadows = instance.params.z > 0.5;
+    return surface;
+}
+
+// 3x3 PCF on one shadow map layer
+fn sample_shadow(layer: i32, uv: vec2<f32>, depth: f32) -> f32 {
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_map));
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, layer, depth);
+        }
+    }
+    return lit / 9.0;
+}
+
+// Fraction of the light reaching the surface. Cascades are ordered near to
+// far, so the first one containing the point is the sharpest.
+fn shadow_factor(light: Light, position: vec3<f32>, normal: vec3<f32>) -> f32 {
+    let first = i32(light.shadow.x);
+    if first < 0 {
+        return 1.0;
+    }
+    // Pushing the lookup out along the normal hides acne at grazing angles
+    let world = vec4<f32>(position + normal * 0.02, 1.0);
+    for (var i = 0; i < i32(light.shadow.y); i++) {
+        let clip = shadow_matrices[first + i] * world;
+        let ndc = clip.xyz / clip.w;
+        let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+        if all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0)) && ndc.z >= 0.0 && ndc.z <= 1.0 {
+            return sample_shadow(first + i, uv, ndc.z);
+        }
+    }
+    return 1.0;
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let k = r * r / 8.0;
+    let gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return gv * gl;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// How much fog covers a point, 0 to 1, by its view-space depth
+fn fog_factor(world_position: vec3<f32>) -> f32 {
+    let offset = world_position - frame.camera_position.xyz;
+    let depth = max(dot(offset, frame.camera_forward.xyz), 0.0);
+    let density = frame.fog_params.z;
+    switch frame.fog_mode.x {
+        case 1u: {
+            let range = max(frame.fog_params.y - frame.fog_params.x, 0.0001);
+            return clamp((depth - frame.fog_params.x) / range, 0.0, 1.0);
+        }
+        case 2u: {
+            return 1.0 - exp(-density * depth);
+        }
+        case 3u: {
+            let d = density * depth;
+            return 1.0 - exp(-d * d);
+        }
+        case 4u: {
+            // Density falls off exponentially with height, integrated along
+            // the view ray from the camera
+            let falloff = max(frame.fog_params.w, 0.0001);
+            let camera_height = frame.camera_position.y - frame.fog_color.w;
+            let climb = falloff * offset.y;
+            var spread = 1.0;
+            if abs(climb) > 0.0001 {
+                spread = (1.0 - exp(-climb)) / climb;
+            }
+            return 1.0 - exp(-density * exp(-falloff * camera_height) * spread * depth);
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+// Blend a shaded color into the scene fog. `shade` already does this;
+// unlit shaders can call it themselves.
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    return mix(color, frame.fog_color.rgb, fog_factor(world_position));
+}
+
+// Ambient plus every scene light (Cook-Torrance, shadowed) plus emission,
+// seen through the fog
+fn shade(surface: Surface, world_position: vec3<f32>) -> vec3<f32> {
+    let n = surface.normal;
+    let v = normalize(frame.camera_position.xyz - world_position);
+    let n_dot_v = max(dot(n, v), 0.0001);
+    let f0 = mix(vec3<f32>(0.04), surface.albedo.rgb, surface.metallic);
+
+    var lighting = vec3<f32>(0.0);
+    for (var i = 0u; i < frame.light_count.x; i++) {
+        let light = lights[i];
+        let kind = u32(light.direction_kind.w);
+        let direction = normalize(light.direction_kind.xyz);
+
+        var l = -direction;
+        var attenuation = 1.0;
+        if kind != 0u {
+            let to_light = light.position_range.xyz - world_position;
+            let dist = length(to_light);
+            l = to_light / max(dist, 0.0001);
+
+            // Inverse-square with a smooth cutoff at the light's range
+            let ratio = dist / light.position_range.w;
+            let window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+            attenuation = window * window / (dist * dist + 1.0);
+
+            if kind == 2u {
+                let cos_angle = dot(-l, direction);
+                attenuation *= smoothstep(light.cone.y, light.cone.x, cos_angle);
+            }
+        }
+
+        let n_dot_l = max(dot(n, l), 0.0);
+        if n_dot_l > 0.0 {
+            if surface.receives_shadows {
+                attenuation *= shadow_factor(light, world_position, n);
+            }
+            let h = normalize(l + v);
+            let n_dot_h = max(dot(n, h), 0.0);
+            let f = fresnel_schlick(max(dot(h, v), 0.0), f0);
+            let d = distribution_ggx(n_dot_h, surface.roughness);
+            let g = geometry_smith(n_dot_v, n_dot_l, surface.roughness);
+            let specular = d * g * f / (4.0 * n_dot_v * n_dot_l + 0.0001);
+            let k_d = (vec3<f32>(1.0) - f) * (1.0 - surface.metallic);
+
+            // Intensity is scaled by PI so a white light keeps its old brightness
+            let radiance = light.color_intensity.rgb * light.color_intensity.w * attenuation * PI;
+            lighting += (k_d * surface.albedo.rgb / PI + specular) * radiance * n_dot_l;
+        }
+    }
+
+    let color = frame.ambient.rgb * surface.albedo.rgb + lighting + surface.emissive;
+    return apply_fog(color, world_position);
+}
@@ -0,0 +1,453 @@
+use crate::modules::ecs::components::{Light, LightKind, Transform};
+use crate::modules::ecs::entity::{Camera, camera_view_proj};
+use glam::{Mat4, Vec3};
+
+pub const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+pub const SHADOW_MAP_SIZE: u32 = 2048;
+/// Layers of the shadow map array, shared by every shadow-casting light
+pub const MAX_SHADOW_LAYERS: usize = 8;
+/// Shadow map layers per directional light
+pub const CASCADE_COUNT: usize = 3;
+/// Directional shadows end this far from the camera
+pub const MAX_SHADOW_DISTANCE: f32 = 60.0;
+/// How far behind a cascade casters are still picked up
+const CASTER_DISTANCE: f32 = 50.0;
+/// Blend between uniform (0) and logarithmic (1) cascade splits
+const CASCADE_SPLIT_LAMBDA: f32 = 0.75;
+const SPOT_SHADOW_NEAR: f32 = 0.05;
+
+// ============================================================================
+// SHADOW PLANNING
+// ============================================================================
+
+/// Shadow map layers assigned to one light
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightShadow {
+    pub first_layer: usize,
+    pub layer_count: usize,
+}
+
+/// Which lights get shadow maps this frame and the view-projection of every
+/// shadow map layer
+#[derive(Default)]
+pub struct ShadowPlan {
+    /// One entry per light, in the order given to `plan_shadows`
+    pub lights: Vec<Option<LightShadow>>,
+    pub layers: Vec<Mat4>,
+}
+
+/// Hand out shadow map layers in light order until they run out. Directional
+/// lights need the camera to fit their cascades.
+pub fn plan_shadows(
+    lights: &[(Light, Vec3, Vec3)],
+    camera: Option<(&Camera, &Transform)>,
+    aspect: f32,
+) -> ShadowPlan {
+    let mut plan = ShadowPlan::default();
+
+    for (light, position, direction) in lights {
+        let free = MAX_SHADOW_LAYERS - plan.layers.len();
+        let matrices = match light.kind {
+            _ if !light.casts_shadows => None,
+            LightKind::Directional if free >= CASCADE_COUNT => {
+                camera.map(|(cam, t)| cascade_matrices(cam, t, aspect, *direction))
+            }
+            LightKind::Spot if free >= 1 => Some(vec![spot_shadow_matrix(
+                *position,
+                *direction,
+                light.outer_angle,
+                light.range,
+            )]),
+            _ => None,
+        };
+
+        plan.lights.push(matrices.map(|matrices| {
+            let first_layer = plan.layers.len();
+            plan.layers.extend(&matrices);
+            LightShadow {
+                first_layer,
+                layer_count: matrices.len(),
+            }
+        }));
+    }
+
+    plan
+}
+
+/// Far distance of each cascade, mixing logarithmic and uniform splits
+pub fn cascade_splits(near: f32, far: f32, count: usize) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let log = near * (far / near).powf(t);
+            let uniform = near + (far - near) * t;
+            CASCADE_SPLIT_LAMBDA * log + (1.0 - CASCADE_SPLIT_LAMBDA) * uniform
+        })
+        .collect()
+}
+
+fn cascade_matrices(
+    camera: &Camera,
+    transform: &Transform,
+    aspect: f32,
+    direction: Vec3,
+) -> Vec<Mat4> {
+    let near = camera.near.max(0.01);
+    let far = camera.far.min(MAX_SHADOW_DISTANCE).max(near * 2.0);
+
+    let mut slice_near = near;
+    cascade_splits(near, far, CASCADE_COUNT)
+        .into_iter()
+        .map(|slice_far| {
+            let corners = frustum_slice_corners(camera, transform, aspect, slice_near, slice_far);
+            slice_near = slice_far;
+            directional_shadow_matrix(&corners, direction, SHADOW_MAP_SIZE)
+        })
+        .collect()
+}
+
+/// World-space corners of the part of the camera frustum between `near` and `far`
+pub fn frustum_slice_corners(
+    camera: &Camera,
+    transform: &Transform,
+    aspect: f32,
+    near: f32,
+    far: f32,
+) -> [Vec3; 8] {
+    let slice = Camera {
+        fov: camera.fov,
+        near,
+        far,
+        projection: camera.projection,
+        ..Default::default()
+    };
+    let inverse = camera_view_proj(&slice, transform, aspect).inverse();
+
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        let ndc = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { 0.0 } else { 1.0 },
+        );
+        *corner = inverse.project_point3(ndc);
+    }
+    corners
+}
+
+/// Orthographic projection along `direction` that encloses `corners`. The
+/// bounds come from a bounding sphere and snap to whole texels, so shadow
+/// edges don't shimmer while the camera moves or turns.
+pub fn directional_shadow_matrix(corners: &[Vec3; 8], direction: Vec3, resolution: u32) -> Mat4 {
+    let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+    let radius = corners
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(0.0, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let direction = direction.try_normalize().unwrap_or(-Vec3::Y);
+    let eye = center - direction * (radius + CASTER_DISTANCE);
+    let view = Mat4::look_at_rh(eye, center, up_for(direction));
+    let proj = Mat4::orthographic_rh(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        2.0 * radius + CASTER_DISTANCE,
+    );
+    let matrix = proj * view;
+
+    // Shift by less than a texel so the world origin lands on a texel corner
+    let half = resolution as f32 / 2.0;
+    let origin = matrix.project_point3(Vec3::ZERO) * half;
+    let offset = (origin.round() - origin) / half;
+    Mat4::from_translation(Vec3::new(offset.x, offset.y, 0.0)) * matrix
+}
+
+/// Perspective projection covering a spot light's cone
+pub fn spot_shadow_matrix(position: Vec3, direction: Vec3, outer_angle: f32, range: f32) -> Mat4 {
+    let direction = direction.try_normalize().unwrap_or(-Vec3::Z);
+    let view = Mat4::look_at_rh(position, position + direction, up_for(direction));
+    // A little wider than the cone so PCF taps at the edge stay inside
+    let fov = (outer_angle * 2.0 + 2.0).clamp(1.0, 170.0).to_radians();
+    let far = if range.is_finite() { range } else { 100.0 };
+    let proj = Mat4::perspective_rh(fov, 1.0, SPOT_SHADOW_NEAR, far.max(SPOT_SHADOW_NEAR * 2.0));
+    proj * view
+}
+
+fn up_for(direction: Vec3) -> Vec3 {
+    if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    }
+}
+
+// ============================================================================
+// GPU RESOURCES
+// ============================================================================
+
+/// Depth-only pass data, one aligned slot per shadow map layer
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ShadowPassData {
+    view_proj: [[f32; 4]; 4],
+}
+unsafe impl bytemuck::Pod for ShadowPassData {}
+unsafe impl bytemuck::Zeroable for ShadowPassData {}
+
+/// Shadow map array, the comparison sampler used to read it and the
+/// depth-only pipeline that renders casters into it
+pub struct ShadowMaps {
+    pub array_view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    /// Light view-projection per layer, read by the lighting shader
+    pub matrix_buffer: wgpu::Buffer,
+    layer_views: Vec<wgpu::TextureView>,
+    pass_buffer: wgpu::Buffer,
+    pass_stride: u64,
+    pass_bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ShadowMaps {
+    pub fn new(
+        device: &wgpu::Device,
+        entity_bind_group_layout: &wgpu::BindGroupLayout,
+        vertex_stride: u64,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Maps"),
+            size: wgpu::Extent3d {
+                width: SHADOW_MAP_SIZE,
+                height: SHADOW_MAP_SIZE,
+                depth_or_array_layers: MAX_SHADOW_LAYERS as u32,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let array_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let layer_views = (0..MAX_SHADOW_LAYERS as u32)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Shadow Map Layer"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let matrix_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Matrix Buffer"),
+            size: (MAX_SHADOW_LAYERS * std::mem::size_of::<[[f32; 4]; 4]>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let pass_size = std::mem::size_of::<ShadowPassData>() as u64;
+        let pass_stride = pass_size.next_multiple_of(alignment);
+        let pass_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Pass Buffer"),
+            size: pass_stride * MAX_SHADOW_LAYERS as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let pass_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(pass_size),
+                    },
+                    count: None,
+                }],
+                label: Some("shadow_pass_bind_group_layout"),
+            });
+        let pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &pass_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &pass_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(pass_size),
+                }),
+            }],
+            label: Some("shadow_pass_bind_group"),
+        });
+
+        let pipeline = Self::create_pipeline(
+            device,
+            &pass_bind_group_layout,
+            entity_bind_group_layout,
+            vertex_stride,
+        );
+
+        Self {
+            array_view,
+            sampler,
+            matrix_buffer,
+            layer_views,
+            pass_buffer,
+            pass_stride,
+            pass_bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        pass_bind_group_layout: &wgpu::BindGroupLayout,
+        entity_bind_group_layout: &wgpu::BindGroupLayout,
+        vertex_stride: u64,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+                struct ShadowPass {
+                    view_proj: mat4x4<f32>,
+                }
+                // Same layout as the prelude's, only the transform is read
+                struct Instance {
+                    transform: mat4x4<f32>,
+                    normal_matrix: mat4x4<f32>,
+                    base_color: vec4<f32>,
+                    emissive: vec4<f32>,
+                    params: vec4<f32>,
+                }
+                @group(0) @binding(0)
+                var<uniform> shadow_pass: ShadowPass;
+                @group(1) @binding(0)
+                var<storage, read> instances: array<Instance>;
+                @vertex
+                fn vs_main(
+                    @location(0) position: vec3<f32>,
+                    @builtin(instance_index) instance: u32,
+                ) -> @builtin(position) vec4<f32> {
+                    let transform = instances[instance].transform;
+                    return shadow_pass.view_proj * transform * vec4<f32>(position, 1.0);
+                }
+                "#
+                .into(),
+            ),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[pass_bind_group_layout, entity_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        const ATTRIBUTES: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x3];
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: vertex_stride,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &ATTRIBUTES,
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // Single-sided geometry like planes still casts from behind
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Upload the layer matrices of this frame's plan
+    pub fn write(&self, queue: &wgpu::Queue, plan: &ShadowPlan) {
+        if plan.layers.is_empty() {
+            return;
+        }
+
+        let matrices: Vec<[[f32; 4]; 4]> =
+            plan.layers.iter().map(|m| m.to_cols_array_2d()).collect();
+        queue.write_buffer(&self.matrix_buffer, 0, bytemuck::cast_slice(&matrices));
+
+        let stride = self.pass_stride as usize;
+        let mut bytes = vec![0u8; plan.layers.len() * stride];
+        for (layer, view_proj) in matrices.into_iter().enumerate() {
+            let data = ShadowPassData { view_proj };
+            let data = bytemuck::bytes_of(&data);
+            bytes[layer * stride..layer * stride + data.len()].copy_from_slice(data);
+        }
+        queue.write_buffer(&self.pass_buffer, 0, &bytes);
+    }
+
+    /// Start the depth pass of one layer. The caller binds the instance
+    /// buffer (group 1) and draws each caster mesh over its instance range.
+    pub fn begin_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        layer: usize,
+    ) -> wgpu::RenderPass<'a> {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.layer_views[layer],
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        let offset = (layer as u64 * self.pass_stride) as u32;
+        pass.set_bind_group(0, &self.pass_bind_group, &[offset]);
+        pass
+    }
+}
@@ -0,0 +1,634 @@
+use crate::modules::assets::TextureHandle;
+use crate::modules::ecs::components::{BlendMode, SamplerSettings};
+use crate::modules::ecs::tilemap::Tilemap;
+use crate::modules::ecs::world::{EntityId, World};
+use crate::modules::render::material::MaterialBindings;
+use crate::modules::render::pipelines::blend_state;
+use crate::modules::render::shader::compose;
+use crate::modules::render::texture::{GpuTexture, create_sampler};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+
+// ============================================================================
+// SPRITE SHADER
+// ============================================================================
+
+/// One instanced quad per sprite. Composed with the prelude for the frame
+/// uniforms and fog; group 1 starts at binding 1 so it doesn't collide with
+/// `instances`.
+pub const SPRITE_SHADER: &str = r#"
+@group(1) @binding(1)
+var sprite_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var sprite_sampler: sampler;
+
+struct SpriteInput {
+    @location(0) origin: vec4<f32>,
+    @location(1) right: vec4<f32>,
+    @location(2) up: vec4<f32>,
+    @location(3) uv_rect: vec4<f32>,
+    @location(4) tint: vec4<f32>,
+}
+
+struct SpriteOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) tint: vec4<f32>,
+}
+
+@vertex
+fn vs_sprite(@builtin(vertex_index) vertex_index: u32, sprite: SpriteInput) -> SpriteOutput {
+    // Corners from the bottom left, (0, 0) to (1, 1)
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+    // origin.w and right.w carry the pivot
+    let local = corner - vec2<f32>(sprite.origin.w, sprite.right.w);
+    let world_position = sprite.origin.xyz + sprite.right.xyz * local.x + sprite.up.xyz * local.y;
+
+    var out: SpriteOutput;
+    out.clip_position = frame.view_proj * vec4<f32>(world_position, 1.0);
+    out.world_position = world_position;
+    // Texture rows run top to bottom
+    out.uv = mix(sprite.uv_rect.xy, sprite.uv_rect.zw, vec2<f32>(corner.x, 1.0 - corner.y));
+    out.tint = sprite.tint;
+    return out;
+}
+
+@fragment
+fn fs_sprite(in: SpriteOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(sprite_texture, sprite_sampler, in.uv) * in.tint;
+    if color.a <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(apply_fog(color.rgb, in.world_position), color.a);
+}
+"#;
+
+/// Per-sprite vertex data, see `SpriteInput`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpriteInstance {
+    /// xyz world position of the pivot, w pivot x
+    pub origin: [f32; 4],
+    /// xyz quad width along the entity's X axis, w pivot y
+    pub right: [f32; 4],
+    /// xyz quad height along the entity's Y axis
+    pub up: [f32; 4],
+    /// Texture coordinates at the quad's top left and bottom right
+    pub uv_rect: [f32; 4],
+    pub tint: [f32; 4],
+}
+unsafe impl bytemuck::Pod for SpriteInstance {}
+unsafe impl bytemuck::Zeroable for SpriteInstance {}
+
+/// Textures and sampler a run of sprites shares
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpriteKey {
+    pub texture: Option<TextureHandle>,
+    pub sampler: SamplerSettings,
+}
+
+/// Consecutive sprites drawn with one call
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteBatch {
+    pub key: SpriteKey,
+    pub instances: Range<u32>,
+}
+
+/// Instance data of every drawable sprite in draw order, and the batches
+/// they form. Sprites sort by layer, then world Z, so with the usual 2D
+/// camera looking down -Z nearer sprites draw last. `texture_size` returns
+/// `None` for textures still loading; those sprites are left out.
+pub fn collect_sprites(
+    world: &World,
+    texture_size: impl Fn(TextureHandle) -> Option<(u32, u32)>,
+) -> (Vec<SpriteInstance>, Vec<SpriteBatch>) {
+    let (instances, draws) = merge_draws(sorted_sprites(world, texture_size), Vec::new());
+    let batches = draws
+        .into_iter()
+        .filter_map(|draw| match draw {
+            SpriteDraw::Batch(batch) => Some(batch),
+            SpriteDraw::Chunk(_) => None,
+        })
+        .collect();
+    (instances, batches)
+}
+
+/// Sprites with their layer and world Z, in draw order
+fn sorted_sprites(
+    world: &World,
+    texture_size: impl Fn(TextureHandle) -> Option<(u32, u32)>,
+) -> Vec<(i32, f32, SpriteKey, SpriteInstance)> {
+    let mut sprites = Vec::new();
+    for (id, entity) in world.iter_entities() {
+        let Some(sprite) = &entity.sprite else {
+            continue;
+        };
+        let (width, height) = match sprite.texture {
+            Some(texture) => match texture_size(texture) {
+                Some(size) => size,
+                None => continue,
+            },
+            None => (1, 1),
+        };
+
+        let matrix = world.world_matrix(id);
+        let origin = matrix.w_axis.truncate();
+        let right = matrix.x_axis.truncate() * sprite.size.x;
+        let up = matrix.y_axis.truncate() * sprite.size.y;
+        let instance = SpriteInstance {
+            origin: origin.extend(sprite.pivot.x).to_array(),
+            right: right.extend(sprite.pivot.y).to_array(),
+            up: up.extend(0.0).to_array(),
+            uv_rect: sprite.uv_rect(width, height).to_array(),
+            tint: sprite.tint.to_array(),
+        };
+        let key = SpriteKey {
+            texture: sprite.texture,
+            sampler: sprite.sampler,
+        };
+        sprites.push((sprite.layer, origin.z, key, instance));
+    }
+    // Stable, so equal sprites keep world order
+    sprites.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    sprites
+}
+
+/// Interleave sorted sprites and tilemap chunks into draws. At the same
+/// layer and Z chunks go first, tilemaps being the usual backdrop.
+fn merge_draws(
+    sprites: Vec<(i32, f32, SpriteKey, SpriteInstance)>,
+    chunks: Vec<(i32, f32, ChunkId)>,
+) -> (Vec<SpriteInstance>, Vec<SpriteDraw>) {
+    let mut instances = Vec::with_capacity(sprites.len());
+    let mut draws: Vec<SpriteDraw> = Vec::new();
+    let mut chunks = chunks.into_iter().peekable();
+    for (layer, z, key, instance) in sprites {
+        while let Some(chunk) = chunks.next_if(|c| c.0.cmp(&layer).then(c.1.total_cmp(&z)).is_le())
+        {
+            draws.push(SpriteDraw::Chunk(chunk.2));
+        }
+        let index = instances.len() as u32;
+        instances.push(instance);
+        match draws.last_mut() {
+            Some(SpriteDraw::Batch(batch)) if batch.key == key => batch.instances.end = index + 1,
+            _ => draws.push(SpriteDraw::Batch(SpriteBatch {
+                key,
+                instances: index..index + 1,
+            })),
+        }
+    }
+    draws.extend(chunks.map(|chunk| SpriteDraw::Chunk(chunk.2)));
+    (instances, draws)
+}
+
+// ============================================================================
+// TILEMAP CHUNKS
+// ============================================================================
+
+/// Tilemap entity and chunk coordinates
+pub type ChunkId = (EntityId, u32, u32);
+
+/// Instances and batches of one tilemap chunk, tiles at their world
+/// position given the entity's `matrix`. Tiles are grouped by tileset.
+/// Returns `None` while a tileset texture is still loading.
+pub fn tilemap_chunk(
+    tilemap: &Tilemap,
+    matrix: Mat4,
+    cx: u32,
+    cy: u32,
+    texture_size: impl Fn(TextureHandle) -> Option<(u32, u32)>,
+) -> Option<(Vec<SpriteInstance>, Vec<SpriteBatch>)> {
+    let mut tiles = Vec::new();
+    for (x, y, tile) in tilemap.chunk_tiles(cx, cy) {
+        let Some((index, tileset)) = tilemap.tileset_for(tile.gid) else {
+            continue;
+        };
+        let (width, height) = match tileset.texture {
+            Some(texture) => texture_size(texture)?,
+            None => (1, 1),
+        };
+
+        // Anchored at the bottom left of the cell, larger tiles stick out
+        let cell = tilemap.cell_size;
+        let corner = Vec3::new(x as f32 * cell.x, -((y + 1) as f32) * cell.y, 0.0);
+        let size = Vec2::new(tileset.tile_width as f32, tileset.tile_height as f32)
+            / tilemap.cell_pixels.max(Vec2::ONE)
+            * cell;
+        let mut right = matrix.transform_vector3(Vec3::X * size.x);
+        let mut up = matrix.transform_vector3(Vec3::Y * size.y);
+
+        let uv = tileset
+            .region(tile.gid - tileset.first_gid)
+            .uv_rect(width, height);
+        // The diagonal flip transposes the tile before the other flips;
+        // swapping the quad's axes and both texture axes does that
+        let (flip_u, flip_v) = if tile.flip_diagonal {
+            std::mem::swap(&mut right, &mut up);
+            (!tile.flip_y, !tile.flip_x)
+        } else {
+            (tile.flip_x, tile.flip_y)
+        };
+        let (u0, u1) = if flip_u { (uv.z, uv.x) } else { (uv.x, uv.z) };
+        let (v0, v1) = if flip_v { (uv.w, uv.y) } else { (uv.y, uv.w) };
+
+        let instance = SpriteInstance {
+            origin: matrix.transform_point3(corner).extend(0.0).to_array(),
+            right: right.extend(0.0).to_array(),
+            up: up.extend(0.0).to_array(),
+            uv_rect: Vec4::new(u0, v0, u1, v1).to_array(),
+            tint: tilemap.tint.to_array(),
+        };
+        let key = SpriteKey {
+            texture: tileset.texture,
+            sampler: tilemap.sampler,
+        };
+        tiles.push((index, key, instance));
+    }
+    tiles.sort_by_key(|tile| tile.0);
+
+    let mut instances = Vec::with_capacity(tiles.len());
+    let mut batches: Vec<SpriteBatch> = Vec::new();
+    for (_, key, instance) in tiles {
+        let index = instances.len() as u32;
+        instances.push(instance);
+        match batches.last_mut() {
+            Some(batch) if batch.key == key => batch.instances.end = index + 1,
+            _ => batches.push(SpriteBatch {
+                key,
+                instances: index..index + 1,
+            }),
+        }
+    }
+    Some((instances, batches))
+}
+
+/// Uploaded chunk, rebuilt when its tiles or anything they are placed by
+/// changes
+struct TileChunk {
+    stamp: ChunkStamp,
+    /// `None` for chunks without tiles
+    buffer: Option<wgpu::Buffer>,
+    batches: Vec<SpriteBatch>,
+}
+
+/// Chunk revision, entity matrix, tint and cell size
+type ChunkStamp = (u32, Mat4, Vec4, Vec2);
+
+enum SpriteDraw {
+    Batch(SpriteBatch),
+    Chunk(ChunkId),
+}
+
+// ============================================================================
+// SPRITE RENDERER
+// ============================================================================
+
+/// Draws every `Sprite` as alpha-blended, instanced quads after the opaque
+/// pass, batched by texture and sampler. Tilemaps draw through the same
+/// pipeline from per-chunk buffers that are only rebuilt when the chunk
+/// changes. Textures come from the material bindings, so sprites and
+/// materials share uploads.
+pub struct SpriteRenderer {
+    layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// Bind groups with the view they were made for, rebuilt when the
+    /// texture behind a handle changes
+    bind_groups: HashMap<SpriteKey, (wgpu::TextureView, wgpu::BindGroup)>,
+    samplers: HashMap<SamplerSettings, wgpu::Sampler>,
+    white: GpuTexture,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: u64,
+    chunks: HashMap<ChunkId, TileChunk>,
+    draws: Vec<SpriteDraw>,
+}
+
+impl SpriteRenderer {
+    /// `frame_layout` is the main pass's group 0
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group_layout = Self::create_bind_group_layout(device);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sprite Pipeline Layout"),
+            bind_group_layouts: &[frame_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Sprite Shader"),
+            source: wgpu::ShaderSource::Wgsl(compose(SPRITE_SHADER).into()),
+        });
+        let pipeline =
+            Self::create_pipeline(device, &layout, &shader, format, depth_format, sample_count);
+
+        let instance_capacity = 64;
+        let instance_buffer = Self::create_instance_buffer(device, instance_capacity);
+
+        Self {
+            layout,
+            shader,
+            format,
+            depth_format,
+            pipeline,
+            bind_group_layout,
+            bind_groups: HashMap::new(),
+            samplers: HashMap::new(),
+            white: GpuTexture::solid(device, queue, [255; 4], "Sprite White Texture"),
+            instance_buffer,
+            instance_capacity,
+            chunks: HashMap::new(),
+            draws: Vec::new(),
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.pipeline = Self::create_pipeline(
+            device,
+            &self.layout,
+            &self.shader,
+            self.format,
+            self.depth_format,
+            sample_count,
+        );
+    }
+
+    /// Upload this frame's sprites and changed tilemap chunks, and make sure
+    /// every batch has its bind group
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        materials: &MaterialBindings,
+        world: &World,
+    ) {
+        let texture_size = |handle| {
+            let texture = &materials.texture(handle)?.texture;
+            Some((texture.width(), texture.height()))
+        };
+        let chunks = self.prepare_chunks(device, world, texture_size);
+        let (instances, draws) = merge_draws(sorted_sprites(world, texture_size), chunks);
+        self.draws = draws;
+        if self.draws.is_empty() {
+            self.bind_groups.clear();
+            return;
+        }
+
+        if instances.len() as u64 > self.instance_capacity {
+            self.instance_capacity = (instances.len() as u64).next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.instance_capacity);
+        }
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+        }
+
+        let mut keys = HashSet::new();
+        for draw in &self.draws {
+            match draw {
+                SpriteDraw::Batch(batch) => {
+                    keys.insert(batch.key);
+                }
+                SpriteDraw::Chunk(id) => {
+                    keys.extend(self.chunks[id].batches.iter().map(|b| b.key));
+                }
+            }
+        }
+        self.bind_groups.retain(|key, _| keys.contains(key));
+        for key in keys {
+            let view = match key.texture.and_then(|h| materials.texture(h)) {
+                Some(texture) => &texture.srgb_view,
+                None => &self.white.srgb_view,
+            };
+            if self
+                .bind_groups
+                .get(&key)
+                .is_some_and(|(bound, _)| bound == view)
+            {
+                continue;
+            }
+
+            let sampler = self
+                .samplers
+                .entry(key.sampler)
+                .or_insert_with(|| create_sampler(device, key.sampler));
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Sprite Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+            });
+            self.bind_groups.insert(key, (view.clone(), bind_group));
+        }
+    }
+
+    /// Rebuild chunks whose stamp changed and drop those of removed or
+    /// hidden tilemaps. Returns the chunks to draw, sorted like sprites.
+    /// Chunks waiting for a texture are skipped until it arrives.
+    fn prepare_chunks(
+        &mut self,
+        device: &wgpu::Device,
+        world: &World,
+        texture_size: impl Fn(TextureHandle) -> Option<(u32, u32)> + Copy,
+    ) -> Vec<(i32, f32, ChunkId)> {
+        let mut visible = Vec::new();
+        let mut seen = HashSet::new();
+        for (id, entity) in world.iter_entities() {
+            let Some(tilemap) = entity.tilemap.as_ref().filter(|t| t.visible) else {
+                continue;
+            };
+            let matrix = world.world_matrix(id);
+            let (columns, rows) = tilemap.chunks();
+            for (cx, cy) in (0..rows).flat_map(|cy| (0..columns).map(move |cx| (cx, cy))) {
+                let chunk_id = (id, cx, cy);
+                seen.insert(chunk_id);
+                let stamp = (
+                    tilemap.chunk_revision(cx, cy),
+                    matrix,
+                    tilemap.tint,
+                    tilemap.cell_size,
+                );
+                if self.chunks.get(&chunk_id).is_none_or(|c| c.stamp != stamp) {
+                    let Some((instances, batches)) =
+                        tilemap_chunk(tilemap, matrix, cx, cy, texture_size)
+                    else {
+                        self.chunks.remove(&chunk_id);
+                        continue;
+                    };
+                    let buffer = (!instances.is_empty()).then(|| {
+                        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Tilemap Chunk Buffer"),
+                            contents: bytemuck::cast_slice(&instances),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        })
+                    });
+                    self.chunks.insert(
+                        chunk_id,
+                        TileChunk {
+                            stamp,
+                            buffer,
+                            batches,
+                        },
+                    );
+                }
+                if self.chunks[&chunk_id].buffer.is_some() {
+                    visible.push((tilemap.layer, matrix.w_axis.z, chunk_id));
+                }
+            }
+        }
+        self.chunks.retain(|id, _| seen.contains(id));
+        visible.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        visible
+    }
+
+    /// Draw the prepared sprites into a pass that has the frame bind group
+    /// at group 0
+    pub fn draw(&self, pass: &mut wgpu::RenderPass) {
+        if self.draws.is_empty() {
+            return;
+        }
+        pass.set_pipeline(&self.pipeline);
+        let mut sprites_bound = false;
+        for draw in &self.draws {
+            match draw {
+                SpriteDraw::Batch(batch) => {
+                    if !sprites_bound {
+                        pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+                        sprites_bound = true;
+                    }
+                    if let Some((_, bind_group)) = self.bind_groups.get(&batch.key) {
+                        pass.set_bind_group(1, bind_group, &[]);
+                        pass.draw(0..6, batch.instances.clone());
+                    }
+                }
+                SpriteDraw::Chunk(id) => {
+                    let chunk = &self.chunks[id];
+                    let Some(buffer) = &chunk.buffer else {
+                        continue;
+                    };
+                    pass.set_vertex_buffer(0, buffer.slice(..));
+                    sprites_bound = false;
+                    for batch in &chunk.batches {
+                        if let Some((_, bind_group)) = self.bind_groups.get(&batch.key) {
+                            pass.set_bind_group(1, bind_group, &[]);
+                            pass.draw(0..6, batch.instances.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Group 1 of `SPRITE_SHADER`: the texture and its sampler
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sprite Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_instance_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite Instance Buffer"),
+            size: capacity * std::mem::size_of::<SpriteInstance>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Sprites test depth against the scene but don't write it, so
+    /// overlapping sprites rely on the draw order
+    pub fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+        ];
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sprite Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_sprite"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<SpriteInstance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &ATTRIBUTES,
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_sprite"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend_state(BlendMode::Alpha)),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::modules::assets::TextureHandle;
+use crate::modules::ecs::entity::RenderTarget;
+use crate::modules::render::material::MaterialBindings;
+use crate::modules::render::msaa::MsaaTargets;
+use crate::modules::render::texture::GpuTexture;
+use std::collections::HashMap;
+
+// ============================================================================
+// RENDER TARGETS
+// ============================================================================
+
+/// Depth and multisampled color of one offscreen target. Its resolved color
+/// lives in `MaterialBindings` under the target's texture handle, where
+/// materials find it.
+struct TargetBuffers {
+    size: (u32, u32),
+    msaa: MsaaTargets,
+}
+
+/// GPU side of every render target a camera in the world names. Targets are
+/// created on first use, recreated when their size or the sample count
+/// changes and released once no camera names them anymore.
+pub struct RenderTargets {
+    depth_format: wgpu::TextureFormat,
+    targets: HashMap<TextureHandle, TargetBuffers>,
+}
+
+impl RenderTargets {
+    pub fn new(depth_format: wgpu::TextureFormat) -> Self {
+        Self {
+            depth_format,
+            targets: HashMap::new(),
+        }
+    }
+
+    /// Bring the targets in line with the cameras' `targets` for a window of
+    /// the given size
+    pub fn sync(
+        &mut self,
+        device: &wgpu::Device,
+        materials: &mut MaterialBindings,
+        targets: &[RenderTarget],
+        window_size: (u32, u32),
+        sample_count: u32,
+    ) {
+        self.targets.retain(|handle, _| {
+            let used = targets.iter().any(|t| t.texture == *handle);
+            if !used {
+                materials.remove_render_target(*handle);
+            }
+            used
+        });
+
+        for target in targets {
+            let size = target.size.resolve(window_size.0, window_size.1);
+            if self.targets.get(&target.texture).is_some_and(|existing| {
+                existing.size == size && existing.msaa.sample_count == sample_count
+            }) {
+                continue;
+            }
+
+            let label = format!("Render Target {}", target.texture.id());
+            let texture = GpuTexture::render_target(device, size.0, size.1, &label);
+            materials.set_render_target(target.texture, texture);
+            let msaa = MsaaTargets::new(
+                device,
+                winit::dpi::PhysicalSize::new(size.0, size.1),
+                self.depth_format,
+                sample_count,
+            );
+            self.targets
+                .insert(target.texture, TargetBuffers { size, msaa });
+        }
+    }
+
+    /// Depth and multisampled color to draw into `handle`'s texture with
+    pub fn get(&self, handle: TextureHandle) -> Option<&MsaaTargets> {
+        self.targets.get(&handle).map(|target| &target.msaa)
+    }
+}
@@ -0,0 +1,697 @@
+use crate::modules::assets::font::Font;
+use crate::modules::assets::{Assets, DEFAULT_FONT, FontHandle};
+use crate::modules::ecs::components::{SamplerSettings, TextureFilter, TextureWrap};
+use crate::modules::ecs::text::Hud;
+use crate::modules::ecs::world::World;
+use crate::modules::render::shader::compose;
+use crate::modules::render::sprites::{SPRITE_SHADER, SpriteInstance, SpriteRenderer};
+use crate::modules::render::texture::create_sampler;
+use glam::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+use std::ops::Range;
+
+// ============================================================================
+// GLYPH ATLAS
+// ============================================================================
+
+/// Side of the glyph atlas texture in pixels
+pub const ATLAS_SIZE: u32 = 1024;
+
+/// Pixels per line world-space text is rasterized at, whatever its size
+pub const WORLD_TEXT_PIXELS: f32 = 64.0;
+
+/// Empty pixels around each glyph so filtering doesn't pick up neighbours
+const GLYPH_PADDING: u32 = 1;
+
+/// Where a glyph ended up in the atlas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasGlyph {
+    /// Texture coordinates of the top left and bottom right corner
+    pub uv_rect: Vec4,
+    /// Size in pixels
+    pub size: Vec2,
+    /// Top left corner relative to the pen position, +Y down
+    pub offset: Vec2,
+}
+
+/// Rasterized glyphs of every font and size in use, packed into one RGBA
+/// texture in rows ("shelves"). Color is white, coverage goes to alpha, so
+/// the sprite shader can tint it. When the atlas is full it starts over;
+/// `generation` tells users their glyphs moved.
+pub struct GlyphAtlas {
+    size: u32,
+    pixels: Vec<u8>,
+    /// By font id, glyph id and pixel size; `None` for glyphs with nothing
+    /// to draw
+    glyphs: HashMap<(u32, u16, u32), Option<AtlasGlyph>>,
+    cursor: (u32, u32),
+    shelf_height: u32,
+    /// Rows changed since the last upload
+    dirty: Option<Range<u32>>,
+    generation: u32,
+}
+
+impl GlyphAtlas {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            pixels: vec![0; (size * size * 4) as usize],
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            shelf_height: 0,
+            dirty: None,
+            generation: 0,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Bumped whenever the atlas was cleared to make room
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Rows changed since the last call
+    pub fn take_dirty_rows(&mut self) -> Option<Range<u32>> {
+        self.dirty.take()
+    }
+
+    /// Atlas entry of a glyph at `size` pixels per line, rasterizing it on
+    /// first use. Sizes are rounded to whole pixels.
+    pub fn glyph(
+        &mut self,
+        font_id: u32,
+        font: &Font,
+        glyph: u16,
+        size: f32,
+    ) -> Option<AtlasGlyph> {
+        let pixels = size.round().max(1.0) as u32;
+        let key = (font_id, glyph, pixels);
+        if let Some(entry) = self.glyphs.get(&key) {
+            return *entry;
+        }
+
+        let entry = font.rasterize(glyph, pixels as f32).and_then(|bitmap| {
+            let (x, y) = self.allocate(bitmap.width, bitmap.height)?;
+            for row in 0..bitmap.height {
+                for column in 0..bitmap.width {
+                    let index = (((y + row) * self.size + x + column) * 4) as usize;
+                    let coverage = bitmap.coverage[(row * bitmap.width + column) as usize];
+                    self.pixels[index..index + 4].copy_from_slice(&[255, 255, 255, coverage]);
+                }
+            }
+            let rows = y..y + bitmap.height;
+            self.dirty = Some(match self.dirty.take() {
+                Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+                None => rows,
+            });
+
+            let size = self.size as f32;
+            let min = Vec2::new(x as f32, y as f32) / size;
+            let max = Vec2::new((x + bitmap.width) as f32, (y + bitmap.height) as f32) / size;
+            Some(AtlasGlyph {
+                uv_rect: Vec4::new(min.x, min.y, max.x, max.y),
+                size: Vec2::new(bitmap.width as f32, bitmap.height as f32),
+                offset: bitmap.offset,
+            })
+        });
+        self.glyphs.insert(key, entry);
+        entry
+    }
+
+    /// Top left of a free `width` x `height` rectangle, clearing the atlas
+    /// if there is none. `None` if the glyph is too large for any atlas.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (width, height) = (width + GLYPH_PADDING * 2, height + GLYPH_PADDING * 2);
+        if width > self.size || height > self.size {
+            return None;
+        }
+        if self.cursor.0 + width > self.size {
+            self.cursor = (0, self.cursor.1 + self.shelf_height);
+            self.shelf_height = 0;
+        }
+        if self.cursor.1 + height > self.size {
+            self.clear();
+        }
+
+        let (x, y) = self.cursor;
+        self.cursor.0 += width;
+        self.shelf_height = self.shelf_height.max(height);
+        Some((x + GLYPH_PADDING, y + GLYPH_PADDING))
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.shelf_height = 0;
+        self.dirty = Some(0..self.size);
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+// ============================================================================
+// TEXT COLLECTION
+// ============================================================================
+
+/// Screen-space glyph quad, see `HUD_SHADER`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HudGlyph {
+    /// Top left corner and size in pixels, +Y down
+    pub rect: [f32; 4],
+    pub uv_rect: [f32; 4],
+    pub color: [f32; 4],
+}
+unsafe impl bytemuck::Pod for HudGlyph {}
+unsafe impl bytemuck::Zeroable for HudGlyph {}
+
+/// Font of a text and its id, the built-in one if its own failed to load
+fn font_for(fonts: &HashMap<FontHandle, Font>, handle: FontHandle) -> Option<(u32, &Font)> {
+    [handle, DEFAULT_FONT]
+        .into_iter()
+        .find_map(|h| Some((h.id(), fonts.get(&h)?)))
+}
+
+/// One sprite instance per visible glyph of every `Text`. Billboards use
+/// `camera_right` and `camera_up` instead of the entity's rotation.
+pub fn collect_world_text(
+    world: &World,
+    fonts: &HashMap<FontHandle, Font>,
+    atlas: &mut GlyphAtlas,
+    camera_right: Vec3,
+    camera_up: Vec3,
+) -> Vec<SpriteInstance> {
+    let mut instances = Vec::new();
+    for (id, entity) in world.iter_entities() {
+        let Some(text) = &entity.text else {
+            continue;
+        };
+        let Some((font_id, font)) = font_for(fonts, text.font) else {
+            continue;
+        };
+        let matrix = world.world_matrix(id);
+        let (right, up) = if text.billboard {
+            (
+                camera_right * matrix.x_axis.truncate().length(),
+                camera_up * matrix.y_axis.truncate().length(),
+            )
+        } else {
+            (matrix.x_axis.truncate(), matrix.y_axis.truncate())
+        };
+        let origin = matrix.w_axis.truncate();
+        let scale = text.size / WORLD_TEXT_PIXELS;
+
+        let layout = font.layout(&text.text, WORLD_TEXT_PIXELS, text.align);
+        let start = Vec2::new(layout.size.x * text.align.fraction(), layout.baseline);
+        for placed in &layout.glyphs {
+            let Some(glyph) = atlas.glyph(font_id, font, placed.glyph, WORLD_TEXT_PIXELS) else {
+                continue;
+            };
+            // Bottom left corner in pixels from the anchor, +Y down
+            let corner = placed.position + glyph.offset + Vec2::new(0.0, glyph.size.y) - start;
+            let position = origin + right * (corner.x * scale) - up * (corner.y * scale);
+            instances.push(SpriteInstance {
+                origin: position.extend(0.0).to_array(),
+                right: (right * glyph.size.x * scale).extend(0.0).to_array(),
+                up: (up * glyph.size.y * scale).extend(0.0).to_array(),
+                uv_rect: glyph.uv_rect.to_array(),
+                tint: text.color.to_array(),
+            });
+        }
+    }
+    instances
+}
+
+/// Glyph quads of every visible HUD label on a `screen` pixels large
+/// window
+pub fn collect_hud_text(
+    hud: &Hud,
+    screen: Vec2,
+    fonts: &HashMap<FontHandle, Font>,
+    atlas: &mut GlyphAtlas,
+) -> Vec<HudGlyph> {
+    let mut glyphs = Vec::new();
+    for (_, label) in hud.iter().filter(|(_, label)| label.visible) {
+        let Some((font_id, font)) = font_for(fonts, label.font) else {
+            continue;
+        };
+        let size = label.size.round().max(1.0);
+        let layout = font.layout(&label.text, size, label.align);
+        let top_left = (label.anchor * screen + label.offset - label.anchor * layout.size).round();
+        for placed in &layout.glyphs {
+            let Some(glyph) = atlas.glyph(font_id, font, placed.glyph, size) else {
+                continue;
+            };
+            let corner = top_left + placed.position + glyph.offset;
+            glyphs.push(HudGlyph {
+                rect: [corner.x, corner.y, glyph.size.x, glyph.size.y],
+                uv_rect: glyph.uv_rect.to_array(),
+                color: label.color.to_array(),
+            });
+        }
+    }
+    glyphs
+}
+
+// ============================================================================
+// HUD SHADER
+// ============================================================================
+
+/// Pixel-space glyph quads over the finished frame, self-contained
+pub const HUD_SHADER: &str = r#"
+struct Screen {
+    size: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> screen: Screen;
+@group(0) @binding(1)
+var atlas: texture_2d<f32>;
+@group(0) @binding(2)
+var atlas_sampler: sampler;
+
+struct GlyphInput {
+    @location(0) rect: vec4<f32>,
+    @location(1) uv_rect: vec4<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct GlyphOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_hud(@builtin(vertex_index) vertex_index: u32, glyph: GlyphInput) -> GlyphOutput {
+    // Corners from the top left, +Y down
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(1.0, 0.0),
+    );
+    let corner = corners[vertex_index];
+    let pixel = glyph.rect.xy + corner * glyph.rect.zw;
+    let ndc = pixel / screen.size * 2.0 - 1.0;
+
+    var out: GlyphOutput;
+    out.clip_position = vec4<f32>(ndc.x, -ndc.y, 0.0, 1.0);
+    out.uv = mix(glyph.uv_rect.xy, glyph.uv_rect.zw, corner);
+    out.color = glyph.color;
+    return out;
+}
+
+@fragment
+fn fs_hud(in: GlyphOutput) -> @location(0) vec4<f32> {
+    return textureSample(atlas, atlas_sampler, in.uv) * in.color;
+}
+"#;
+
+// ============================================================================
+// TEXT RENDERER
+// ============================================================================
+
+/// Draws world-space `Text` with the sprite pipeline, after sprites, and
+/// HUD labels straight onto the window after post-processing, so they are
+/// neither tonemapped nor blurred. Both share one glyph atlas.
+pub struct TextRenderer {
+    atlas: GlyphAtlas,
+    atlas_texture: wgpu::Texture,
+    fonts: HashMap<FontHandle, Font>,
+
+    world_layout: wgpu::PipelineLayout,
+    world_shader: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    world_pipeline: wgpu::RenderPipeline,
+    world_bind_group: wgpu::BindGroup,
+    world_buffer: wgpu::Buffer,
+    world_capacity: u64,
+    world_count: u32,
+
+    hud_pipeline: wgpu::RenderPipeline,
+    hud_bind_group: wgpu::BindGroup,
+    screen_buffer: wgpu::Buffer,
+    hud_buffer: wgpu::Buffer,
+    hud_capacity: u64,
+    hud_count: u32,
+}
+
+impl TextRenderer {
+    /// `format` and `depth_format` are the main pass's, `frame_layout` its
+    /// group 0; `surface_format` is the window's view format
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_layout: &wgpu::BindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+    ) -> Self {
+        let atlas = GlyphAtlas::new(ATLAS_SIZE);
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let atlas_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = create_sampler(
+            device,
+            SamplerSettings {
+                filter: TextureFilter::Linear,
+                wrap: TextureWrap::ClampToEdge,
+            },
+        );
+
+        // World text: sprite shader and pipeline, atlas as the texture
+        let sprite_layout = SpriteRenderer::create_bind_group_layout(device);
+        let world_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("World Text Pipeline Layout"),
+            bind_group_layouts: &[frame_layout, &sprite_layout],
+            push_constant_ranges: &[],
+        });
+        let world_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("World Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(compose(SPRITE_SHADER).into()),
+        });
+        let world_pipeline = SpriteRenderer::create_pipeline(
+            device,
+            &world_layout,
+            &world_shader,
+            format,
+            depth_format,
+            sample_count,
+        );
+        let world_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("World Text Bind Group"),
+            layout: &sprite_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        // HUD: own shader, no depth, one sample
+        let hud_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("HUD Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let screen_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("HUD Screen Buffer"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let hud_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("HUD Bind Group"),
+            layout: &hud_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let hud_pipeline = Self::create_hud_pipeline(device, &hud_layout, surface_format);
+
+        let world_capacity = 256;
+        let hud_capacity = 256;
+        Self {
+            atlas,
+            atlas_texture,
+            fonts: HashMap::new(),
+            world_layout,
+            world_shader,
+            format,
+            depth_format,
+            world_pipeline,
+            world_bind_group,
+            world_buffer: Self::create_buffer::<SpriteInstance>(
+                device,
+                "World Text",
+                world_capacity,
+            ),
+            world_capacity,
+            world_count: 0,
+            hud_pipeline,
+            hud_bind_group,
+            screen_buffer,
+            hud_buffer: Self::create_buffer::<HudGlyph>(device, "HUD Text", hud_capacity),
+            hud_capacity,
+            hud_count: 0,
+        }
+    }
+
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.world_pipeline = SpriteRenderer::create_pipeline(
+            device,
+            &self.world_layout,
+            &self.world_shader,
+            self.format,
+            self.depth_format,
+            sample_count,
+        );
+    }
+
+    /// Pick up fonts loaded since the last call
+    pub fn sync_fonts(&mut self, assets: &Assets) {
+        for (handle, entry) in assets.fonts.iter() {
+            if let Some(font) = &entry.data {
+                self.fonts.entry(handle).or_insert_with(|| font.clone());
+            }
+        }
+    }
+
+    /// Lay out this frame's text and upload it with any new glyphs.
+    /// `camera_right` and `camera_up` orient billboards, `screen` is the
+    /// window size in pixels.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        camera_right: Vec3,
+        camera_up: Vec3,
+        screen: Vec2,
+    ) {
+        let collect = |atlas: &mut GlyphAtlas| {
+            let world_text = collect_world_text(world, &self.fonts, atlas, camera_right, camera_up);
+            let hud_text = collect_hud_text(&world.hud, screen, &self.fonts, atlas);
+            (world_text, hud_text)
+        };
+        // A full atlas starts over, moving glyphs placed before
+        let generation = self.atlas.generation();
+        let (mut world_text, mut hud_text) = collect(&mut self.atlas);
+        if self.atlas.generation() != generation {
+            (world_text, hud_text) = collect(&mut self.atlas);
+        }
+
+        if let Some(rows) = self.atlas.take_dirty_rows() {
+            let size = self.atlas.size();
+            let start = (rows.start * size * 4) as usize;
+            let end = (rows.end * size * 4) as usize;
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &self.atlas_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: rows.start,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &self.atlas.pixels()[start..end],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size * 4),
+                    rows_per_image: Some(rows.end - rows.start),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: rows.end - rows.start,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        if world_text.len() as u64 > self.world_capacity {
+            self.world_capacity = (world_text.len() as u64).next_power_of_two();
+            self.world_buffer =
+                Self::create_buffer::<SpriteInstance>(device, "World Text", self.world_capacity);
+        }
+        if !world_text.is_empty() {
+            queue.write_buffer(&self.world_buffer, 0, bytemuck::cast_slice(&world_text));
+        }
+        self.world_count = world_text.len() as u32;
+
+        if hud_text.len() as u64 > self.hud_capacity {
+            self.hud_capacity = (hud_text.len() as u64).next_power_of_two();
+            self.hud_buffer =
+                Self::create_buffer::<HudGlyph>(device, "HUD Text", self.hud_capacity);
+        }
+        if !hud_text.is_empty() {
+            queue.write_buffer(&self.hud_buffer, 0, bytemuck::cast_slice(&hud_text));
+            queue.write_buffer(
+                &self.screen_buffer,
+                0,
+                bytemuck::cast_slice(&[screen.x, screen.y, 0.0, 0.0]),
+            );
+        }
+        self.hud_count = hud_text.len() as u32;
+    }
+
+    /// Draw world-space text into a pass that has the frame bind group at
+    /// group 0
+    pub fn draw_world(&self, pass: &mut wgpu::RenderPass) {
+        if self.world_count == 0 {
+            return;
+        }
+        pass.set_pipeline(&self.world_pipeline);
+        pass.set_bind_group(1, &self.world_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.world_buffer.slice(..));
+        pass.draw(0..6, 0..self.world_count);
+    }
+
+    /// Draw the HUD over whatever `view` shows
+    pub fn draw_hud(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.hud_count == 0 {
+            return;
+        }
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("HUD Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.hud_pipeline);
+        pass.set_bind_group(0, &self.hud_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.hud_buffer.slice(..));
+        pass.draw(0..6, 0..self.hud_count);
+    }
+
+    fn create_buffer<T>(device: &wgpu::Device, label: &str, capacity: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{} Buffer", label)),
+            size: capacity * std::mem::size_of::<T>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_hud_pipeline(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+        ];
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("HUD Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("HUD Shader"),
+            source: wgpu::ShaderSource::Wgsl(HUD_SHADER.into()),
+        });
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("HUD Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_hud"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<HudGlyph>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &ATTRIBUTES,
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_hud"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,149 @@
+use crate::modules::assets::TextureAsset;
+use crate::modules::ecs::components::{SamplerSettings, TextureFilter, TextureWrap};
+use crate::modules::render::post::HDR_FORMAT;
+
+// ============================================================================
+// GPU TEXTURES
+// ============================================================================
+
+/// RGBA8 texture with its full mip chain. Color textures (base color,
+/// emissive) are read through `srgb_view`, data textures (normals,
+/// metallic-roughness) through `linear_view`.
+pub struct GpuTexture {
+    pub texture: wgpu::Texture,
+    pub srgb_view: wgpu::TextureView,
+    pub linear_view: wgpu::TextureView,
+}
+
+impl GpuTexture {
+    /// Upload an image, generating mipmaps on the CPU
+    pub fn from_asset(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        asset: &TextureAsset,
+        label: &str,
+    ) -> Self {
+        let mip_level_count = asset.mip_level_count();
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: asset.width,
+                height: asset.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+        });
+
+        let mut level = None;
+        for mip_level in 0..mip_level_count {
+            let image = level.as_ref().unwrap_or(asset);
+            write_level(queue, &texture, mip_level, image);
+            level = Some(image.downsample());
+        }
+
+        Self::with_views(texture)
+    }
+
+    /// 1x1 texture of a single color, used when a material has no texture
+    pub fn solid(device: &wgpu::Device, queue: &wgpu::Queue, rgba: [u8; 4], label: &str) -> Self {
+        let asset = TextureAsset {
+            width: 1,
+            height: 1,
+            pixels: rgba.to_vec(),
+        };
+        Self::from_asset(device, queue, &asset, label)
+    }
+
+    /// HDR color texture cameras draw into. It holds linear color, so both
+    /// views read it as is.
+    pub fn render_target(device: &wgpu::Device, width: u32, height: u32, label: &str) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let linear_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            srgb_view: linear_view.clone(),
+            linear_view,
+            texture,
+        }
+    }
+
+    fn with_views(texture: wgpu::Texture) -> Self {
+        let srgb_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+            ..Default::default()
+        });
+        let linear_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            texture,
+            srgb_view,
+            linear_view,
+        }
+    }
+}
+
+fn write_level(queue: &wgpu::Queue, texture: &wgpu::Texture, mip_level: u32, image: &TextureAsset) {
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &image.pixels,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * image.width),
+            rows_per_image: Some(image.height),
+        },
+        wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+// ============================================================================
+// SAMPLERS
+// ============================================================================
+
+pub fn create_sampler(device: &wgpu::Device, settings: SamplerSettings) -> wgpu::Sampler {
+    let address_mode = match settings.wrap {
+        TextureWrap::Repeat => wgpu::AddressMode::Repeat,
+        TextureWrap::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        TextureWrap::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+    };
+    let (filter, anisotropy_clamp) = match settings.filter {
+        TextureFilter::Nearest => (wgpu::FilterMode::Nearest, 1),
+        TextureFilter::Linear => (wgpu::FilterMode::Linear, 16),
+    };
+
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Material Sampler"),
+        address_mode_u: address_mode,
+        address_mode_v: address_mode,
+        address_mode_w: address_mode,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter: filter,
+        anisotropy_clamp,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,296 @@
+use crate::modules::console::{LogLevel, LogLine, log_lines};
+use crate::modules::ecs::ui::{UiAnchor, UiEvent, UiLayer, UiPanel, UiWidgetKind};
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+// ============================================================================
+// UI LAYOUT
+// ============================================================================
+
+/// Width of progress bars in panels without a width, in points
+pub const DEFAULT_BAR_WIDTH: f32 = 160.0;
+
+fn align(anchor: UiAnchor) -> egui::Align2 {
+    match anchor {
+        UiAnchor::TopLeft => egui::Align2::LEFT_TOP,
+        UiAnchor::Top => egui::Align2::CENTER_TOP,
+        UiAnchor::TopRight => egui::Align2::RIGHT_TOP,
+        UiAnchor::Left => egui::Align2::LEFT_CENTER,
+        UiAnchor::Center => egui::Align2::CENTER_CENTER,
+        UiAnchor::Right => egui::Align2::RIGHT_CENTER,
+        UiAnchor::BottomLeft => egui::Align2::LEFT_BOTTOM,
+        UiAnchor::Bottom => egui::Align2::CENTER_BOTTOM,
+        UiAnchor::BottomRight => egui::Align2::RIGHT_BOTTOM,
+    }
+}
+
+/// Egui id of a panel's area, e.g. to look up where it was placed
+pub fn panel_id(name: &str) -> egui::Id {
+    egui::Id::new(("ui_panel", name))
+}
+
+/// Lay out every visible panel of `layer` for one egui frame, returning the
+/// buttons clicked in it
+pub fn show_ui(ctx: &egui::Context, layer: &UiLayer) -> Vec<UiEvent> {
+    let mut events = Vec::new();
+    for (name, panel) in layer.panels().filter(|(_, panel)| panel.visible) {
+        egui::Area::new(panel_id(name))
+            .anchor(
+                align(panel.anchor),
+                egui::vec2(panel.offset.x, panel.offset.y),
+            )
+            .show(ctx, |ui| {
+                egui::Frame::window(ui.style()).show(ui, |ui| {
+                    if let Some(width) = panel.width {
+                        ui.set_width(width);
+                    }
+                    if let Some(title) = &panel.title {
+                        ui.strong(title);
+                        ui.separator();
+                    }
+                    if panel.horizontal {
+                        ui.horizontal(|ui| show_widgets(ui, panel, &mut events));
+                    } else {
+                        show_widgets(ui, panel, &mut events);
+                    }
+                });
+            });
+    }
+    events
+}
+
+fn show_widgets(ui: &mut egui::Ui, panel: &UiPanel, events: &mut Vec<UiEvent>) {
+    for widget in &panel.widgets {
+        match &widget.kind {
+            UiWidgetKind::Label { text } => {
+                ui.label(text);
+            }
+            UiWidgetKind::Button { text, enabled } => {
+                if ui.add_enabled(*enabled, egui::Button::new(text)).clicked() {
+                    events.push(UiEvent::Clicked(widget.id.clone()));
+                }
+            }
+            UiWidgetKind::ProgressBar { value, text } => {
+                let mut bar = egui::ProgressBar::new(*value)
+                    .desired_width(panel.width.unwrap_or(DEFAULT_BAR_WIDTH));
+                if let Some(text) = text {
+                    bar = bar.text(text);
+                }
+                ui.add(bar);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// CONSOLE VIEW
+// ============================================================================
+
+/// Lines of log the console panel is tall
+pub const CONSOLE_ROWS: f32 = 16.0;
+
+/// Drop-down developer console: the captured log above a command line.
+/// Entered lines wait in `take_submitted` until the app runs them.
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleView {
+    pub open: bool,
+    pub input: String,
+    submitted: Vec<String>,
+}
+
+impl ConsoleView {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Command lines entered since the last call
+    pub fn take_submitted(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.submitted)
+    }
+}
+
+fn log_color(level: LogLevel) -> egui::Color32 {
+    match level {
+        LogLevel::Info => egui::Color32::LIGHT_GRAY,
+        LogLevel::Warn => egui::Color32::LIGHT_YELLOW,
+        LogLevel::Error => egui::Color32::LIGHT_RED,
+        LogLevel::Script => egui::Color32::LIGHT_BLUE,
+    }
+}
+
+/// Egui id of the console's command line
+pub fn console_input_id() -> egui::Id {
+    egui::Id::new("console_input")
+}
+
+/// Lay out the console at the top of the window if it is open
+pub fn show_console(ctx: &egui::Context, view: &mut ConsoleView, lines: &[LogLine]) {
+    if !view.open {
+        return;
+    }
+    egui::TopBottomPanel::top("console").show(ctx, |ui| {
+        let row = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .max_height(row * CONSOLE_ROWS)
+            .auto_shrink([false, true])
+            .stick_to_bottom(true)
+            .show_rows(ui, row, lines.len(), |ui, rows| {
+                for line in &lines[rows] {
+                    ui.label(
+                        egui::RichText::new(&line.text)
+                            .monospace()
+                            .color(log_color(line.level)),
+                    );
+                }
+            });
+        ui.separator();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut view.input)
+                .id(console_input_id())
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                .hint_text("help"),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut view.input);
+            if !line.trim().is_empty() {
+                view.submitted.push(line);
+            }
+        }
+        // Keep typing after a command, and from the moment it opens
+        response.request_focus();
+    });
+}
+
+// ============================================================================
+// UI OVERLAY
+// ============================================================================
+
+/// Draws the world's `UiLayer` and the console with egui as the last pass
+/// onto the window, after HUD text. The app forwards window events through
+/// `on_window_event`; clicks wait in `take_events` until the world picks
+/// them up.
+pub struct UiOverlay {
+    pub console: ConsoleView,
+    context: egui::Context,
+    input: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    events: Vec<UiEvent>,
+    /// Whether the last frame had panels or the console; input is ignored
+    /// while not
+    active: bool,
+}
+
+impl UiOverlay {
+    /// `format` is the window's view format
+    pub fn new(device: &wgpu::Device, window: &Window, format: wgpu::TextureFormat) -> Self {
+        let context = egui::Context::default();
+        let input = egui_winit::State::new(
+            context.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            None,
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer = egui_wgpu::Renderer::new(device, format, None, 1, false);
+        Self {
+            console: ConsoleView::default(),
+            context,
+            input,
+            renderer,
+            events: Vec::new(),
+            active: false,
+        }
+    }
+
+    pub fn context(&self) -> &egui::Context {
+        &self.context
+    }
+
+    /// Feed a window event to egui. `true` if egui used it, e.g. a click on
+    /// a panel, and the game should ignore it.
+    pub fn on_window_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        if !self.active {
+            return false;
+        }
+        self.input.on_window_event(window, event).consumed
+    }
+
+    /// Lay out and draw `layer` and the console over `view`. Returns command buffers to
+    /// submit before `encoder`'s.
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        window: &Window,
+        layer: &UiLayer,
+    ) -> Vec<wgpu::CommandBuffer> {
+        self.active = !layer.is_empty() || self.console.open;
+        if !self.active {
+            return Vec::new();
+        }
+
+        let raw_input = self.input.take_egui_input(window);
+        let mut events = Vec::new();
+        let lines = if self.console.open {
+            log_lines()
+        } else {
+            Vec::new()
+        };
+        let console = &mut self.console;
+        let output = self.context.run(raw_input, |ctx| {
+            show_console(ctx, console, &lines);
+            events = show_ui(ctx, layer);
+        });
+        self.events.extend(events);
+        self.input
+            .handle_platform_output(window, output.platform_output);
+
+        let jobs = self
+            .context
+            .tessellate(output.shapes, output.pixels_per_point);
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        let size = window.inner_size();
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+        let commands = self
+            .renderer
+            .update_buffers(device, queue, encoder, &jobs, &screen);
+
+        let mut pass = encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("UI Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .forget_lifetime();
+        self.renderer.render(&mut pass, &jobs, &screen);
+        drop(pass);
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+        commands
+    }
+
+    /// Clicks since the last call
+    pub fn take_events(&mut self) -> Vec<UiEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -1,5 +1,36 @@
+use crate::log_error;
+use crate::modules::assets::{
+    Assets, CUBE_MESH, MeshData, MeshHandle, TRIANGLE_MESH, TextureHandle,
+};
+use crate::modules::ecs::components::{
+    FogMode, Light, LightKind, Material, PostProcess, Tonemapper,
+};
+use crate::modules::ecs::particles::ParticleSimulation;
+use crate::modules::ecs::ui::UiEvent;
+use crate::modules::ecs::world::{EntityId, World};
+use crate::modules::render::background::BackgroundRenderer;
+use crate::modules::render::batching::{
+    BatchKey, DrawBatch, batch_by_key, batch_consecutive, sort_back_to_front,
+};
+use crate::modules::render::cameras::{CameraView, ViewportClear, camera_targets, camera_views};
+use crate::modules::render::culling::{Frustum, MeshBounds, RenderStats};
+use crate::modules::render::debug_draw::DebugRenderer;
+use crate::modules::render::material::MaterialBindings;
+use crate::modules::render::modes::{RenderMode, RenderModePipelines, unindex};
+use crate::modules::render::msaa::{MsaaTargets, supported_sample_counts, validate_sample_count};
+use crate::modules::render::particles::ParticleRenderer;
+use crate::modules::render::pipelines::PipelineCache;
+use crate::modules::render::post::{HDR_FORMAT, PostProcessor};
+use crate::modules::render::shadows::{LightShadow, ShadowMaps, plan_shadows};
+use crate::modules::render::sprites::SpriteRenderer;
+use crate::modules::render::targets::RenderTargets;
+use crate::modules::render::text::TextRenderer;
+use crate::modules::render::ui::UiOverlay;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Instant;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
 
@@ -11,27 +42,138 @@ pub struct Mesh {
     pub index_buffer: Option<wgpu::Buffer>,
     pub vertex_count: u32,
     pub index_count: Option<u32>,
+    pub index_format: wgpu::IndexFormat,
+    /// Local-space bounds for culling, `None` draws the mesh unconditionally
+    pub bounds: Option<MeshBounds>,
+    /// Indexed meshes expanded to one vertex per triangle corner, only kept
+    /// for the barycentric wireframe on devices without line polygons
+    pub unindexed_vertex_buffer: Option<wgpu::Buffer>,
 }
 
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Lights the storage buffer holds before it has to grow
+const MIN_LIGHT_CAPACITY: usize = 16;
+/// Instance buffer size before the first scene grows it
+const MIN_INSTANCE_CAPACITY: usize = 64;
+/// Cameras the frame buffer holds before it has to grow
+const MIN_CAMERA_CAPACITY: usize = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    uv: [f32; 2],
+}
+unsafe impl bytemuck::Pod for Vertex {}
+unsafe impl bytemuck::Zeroable for Vertex {}
+
+/// Per-frame data shared by every draw of one camera (group 0, binding 0).
+/// The buffer holds one per camera, picked with a dynamic offset.
 #[repr(C)]
 #[derive(Clone, Copy)]
-struct EntityUniformData {
+struct FrameUniformData {
     view_proj: [[f32; 4]; 4],
+    inverse_view_proj: [[f32; 4]; 4],
+    camera_position: [f32; 4],
+    ambient: [f32; 4],
+    light_count: [u32; 4],
+    time: [f32; 4],
+    clear_color: [f32; 4],
+    camera_forward: [f32; 4],
+    fog_color: [f32; 4],  // w fog floor height
+    fog_params: [f32; 4], // x start, y end, z density, w height falloff
+    fog_mode: [u32; 4],
+}
+unsafe impl bytemuck::Pod for FrameUniformData {}
+unsafe impl bytemuck::Zeroable for FrameUniformData {}
+
+/// One entry of the light storage buffer (group 0, binding 1)
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct GpuLight {
+    position_range: [f32; 4],
+    direction_kind: [f32; 4],
+    color_intensity: [f32; 4],
+    cone: [f32; 4],
+    shadow: [f32; 4], // x first shadow map layer (-1 for none), y layer count
+}
+unsafe impl bytemuck::Pod for GpuLight {}
+unsafe impl bytemuck::Zeroable for GpuLight {}
+
+impl GpuLight {
+    fn new(
+        light: &Light,
+        position: glam::Vec3,
+        direction: glam::Vec3,
+        shadow: Option<LightShadow>,
+    ) -> Self {
+        let kind = match light.kind {
+            LightKind::Directional => 0.0,
+            LightKind::Point => 1.0,
+            LightKind::Spot => 2.0,
+        };
+        Self {
+            position_range: position.extend(light.range).to_array(),
+            direction_kind: direction.extend(kind).to_array(),
+            color_intensity: light.color.extend(light.intensity).to_array(),
+            cone: [
+                light.inner_angle.to_radians().cos(),
+                light.outer_angle.to_radians().cos(),
+                0.0,
+                0.0,
+            ],
+            shadow: match shadow {
+                Some(s) => [s.first_layer as f32, s.layer_count as f32, 0.0, 0.0],
+                None => [-1.0, 0.0, 0.0, 0.0],
+            },
+        }
+    }
+}
+
+/// Per-entity data in the instance storage buffer (group 1). Each draw covers
+/// a contiguous range of it, one instance per entity.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct InstanceData {
     transform: [[f32; 4]; 4],
-    color: [f32; 4],
+    normal_matrix: [[f32; 4]; 4],
+    base_color: [f32; 4],
+    emissive: [f32; 4], // rgb emissive, w normal map scale
+    params: [f32; 4],   // x metallic, y roughness, z receives shadows
 }
 
-impl EntityUniformData {
-    pub fn new(view_proj: glam::Mat4, transform: glam::Mat4, color: glam::Vec4) -> Self {
+impl InstanceData {
+    pub fn new(transform: glam::Mat4, material: &Material, receives_shadows: bool) -> Self {
+        // Inverse-transpose keeps normals perpendicular under non-uniform scale
+        let normal_matrix = if transform.determinant().abs() > f32::EPSILON {
+            transform.inverse().transpose()
+        } else {
+            transform
+        };
         Self {
-            view_proj: view_proj.to_cols_array_2d(),
             transform: transform.to_cols_array_2d(),
-            color: color.to_array(),
+            normal_matrix: normal_matrix.to_cols_array_2d(),
+            base_color: material.color.to_array(),
+            emissive: material.emissive.extend(material.normal_scale).to_array(),
+            params: [
+                material.metallic,
+                material.roughness,
+                if receives_shadows { 1.0 } else { 0.0 },
+                0.0,
+            ],
         }
     }
 }
-unsafe impl bytemuck::Pod for EntityUniformData {}
-unsafe impl bytemuck::Zeroable for EntityUniformData {}
+unsafe impl bytemuck::Pod for InstanceData {}
+unsafe impl bytemuck::Zeroable for InstanceData {}
+
+/// One camera's draws, split where the background goes
+struct ViewBatches {
+    opaque: Vec<DrawBatch<BatchKey>>,
+    transparent: Vec<DrawBatch<BatchKey>>,
+}
 
 // ============================================================================
 // OPTIMIZED STATE STRUCTURE
@@ -43,13 +185,45 @@ pub struct State {
     size: winit::dpi::PhysicalSize<u32>,
     pub surface: wgpu::Surface<'static>,
     surface_format: wgpu::TextureFormat,
-    pub entity_pipeline: wgpu::RenderPipeline,
-    pub uniform_bind_group_layout: wgpu::BindGroupLayout,
-    pub meshes: HashMap<u32, Mesh>,
+    pub pipelines: PipelineCache,
+    pub frame_bind_group_layout: wgpu::BindGroupLayout,
+    pub instance_bind_group_layout: wgpu::BindGroupLayout,
+    pub materials: MaterialBindings,
+    pub shadows: ShadowMaps,
+    pub post: PostProcessor,
+    pub debug: DebugRenderer,
+    pub modes: RenderModePipelines,
+    pub viewport_clear: ViewportClear,
+    pub background: BackgroundRenderer,
+    pub particles: ParticleRenderer,
+    pub sprites: SpriteRenderer,
+    pub text: TextRenderer,
+    pub ui: UiOverlay,
+    /// Offscreen textures cameras draw into
+    pub render_targets: RenderTargets,
+    render_mode: RenderMode,
+    pub meshes: HashMap<MeshHandle, Mesh>,
+    /// Drawn and culled entity and draw call counts of the last frame
+    pub stats: RenderStats,
+    msaa: MsaaTargets,
+    /// MSAA sample counts the device supports for the main pass targets
+    pub supported_sample_counts: Vec<u32>,
+    /// Last sample count a world asked for that couldn't be used, so the
+    /// error is only reported once
+    rejected_sample_count: Option<u32>,
+    start_time: Instant,
 
-    // Optimization: Pre-allocated resources
-    uniform_buffer: wgpu::Buffer,
-    uniform_buffer_size: u64,
+    // Optimization: Pre-allocated resources, grown on demand
+    frame_buffer: wgpu::Buffer,
+    /// Bytes between the frame uniforms of consecutive cameras
+    frame_stride: u64,
+    camera_capacity: usize,
+    light_buffer: wgpu::Buffer,
+    light_capacity: usize,
+    frame_bind_group: wgpu::BindGroup,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+    instance_bind_group: wgpu::BindGroup,
     staging_belt: wgpu::util::StagingBelt,
 }
 
@@ -64,8 +238,15 @@ impl State {
             .request_adapter(&wgpu::RequestAdapterOptions::default())
             .await
             .unwrap();
+        // Sample counts beyond 1 and 4 need adapter-specific format features,
+        // the wireframe mode prefers line polygons
         let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default())
+            .request_device(&wgpu::DeviceDescriptor {
+                required_features: adapter.features()
+                    & (wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+                        | wgpu::Features::POLYGON_MODE_LINE),
+                ..Default::default()
+            })
             .await
             .unwrap();
         let size = window.inner_size();
@@ -73,22 +254,124 @@ impl State {
         let cap = surface.get_capabilities(&adapter);
         let surface_format = cap.formats[0];
 
-        // Bind group layout
-        let uniform_bind_group_layout = Self::create_uniform_bind_group_layout(&device);
+        // Bind group layouts
+        let frame_bind_group_layout = Self::create_frame_bind_group_layout(&device);
+        let instance_bind_group_layout = Self::create_instance_bind_group_layout(&device);
+        let materials = MaterialBindings::new(&device, &queue);
+        let shadows = ShadowMaps::new(
+            &device,
+            &instance_bind_group_layout,
+            std::mem::size_of::<Vertex>() as u64,
+        );
 
-        // Pipeline creation
-        let entity_pipeline =
-            Self::create_entity_pipeline(&device, surface_format, &uniform_bind_group_layout);
+        // Pipeline creation, single-sampled until a world asks for MSAA
+        let supported_sample_counts =
+            supported_sample_counts(&adapter, &device, &[HDR_FORMAT, DEPTH_FORMAT]);
+        let pipelines = PipelineCache::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &[
+                &frame_bind_group_layout,
+                &instance_bind_group_layout,
+                &materials.layout,
+            ],
+            Self::get_vertex_buffer_layout(),
+        );
+        let msaa = MsaaTargets::new(&device, size, DEPTH_FORMAT, 1);
+        let post = PostProcessor::new(&device, &queue, surface_format.add_srgb_suffix(), size);
+        let modes = RenderModePipelines::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &[
+                &frame_bind_group_layout,
+                &instance_bind_group_layout,
+                &materials.layout,
+            ],
+            Self::get_vertex_buffer_layout(),
+        );
+        let debug = DebugRenderer::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+        );
+        let viewport_clear = ViewportClear::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+        );
+        let background = BackgroundRenderer::new(
+            &device,
+            &queue,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+        );
+        // Particles simulate in compute shaders where the device has them
+        let particle_simulation = if adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            ParticleSimulation::Gpu
+        } else {
+            ParticleSimulation::Cpu
+        };
+        let particles = ParticleRenderer::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+            particle_simulation,
+        );
+        let sprites = SpriteRenderer::new(
+            &device,
+            &queue,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+        );
+        let text = TextRenderer::new(
+            &device,
+            HDR_FORMAT,
+            DEPTH_FORMAT,
+            1,
+            &frame_bind_group_layout,
+            surface_format.add_srgb_suffix(),
+        );
+        let ui = UiOverlay::new(&device, &window, surface_format.add_srgb_suffix());
+
+        // Frame uniforms, one per camera, and lights
+        let frame_stride = (std::mem::size_of::<FrameUniformData>() as u64)
+            .next_multiple_of(device.limits().min_uniform_buffer_offset_alignment as u64);
+        let frame_buffer = Self::create_frame_buffer(&device, frame_stride, MIN_CAMERA_CAPACITY);
+        let light_buffer = Self::create_light_buffer(&device, MIN_LIGHT_CAPACITY);
+        let frame_bind_group = Self::create_frame_bind_group(
+            &device,
+            &frame_bind_group_layout,
+            &frame_buffer,
+            &light_buffer,
+            &shadows,
+        );
 
-        // Initialize staging belt and uniform buffer
-        let uniform_buffer_size = std::mem::size_of::<EntityUniformData>() as u64;
+        // Instances: every entity drawn this frame, in batch order
         let staging_belt = wgpu::util::StagingBelt::new(1024);
-        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Entity Uniform Buffer"),
-            size: uniform_buffer_size,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let instance_buffer = Self::create_instance_buffer(&device, MIN_INSTANCE_CAPACITY);
+        let instance_bind_group = Self::create_instance_bind_group(
+            &device,
+            &instance_bind_group_layout,
+            &instance_buffer,
+        );
 
         let mut state = State {
             window,
@@ -97,11 +380,37 @@ impl State {
             size,
             surface,
             surface_format,
-            entity_pipeline,
-            uniform_bind_group_layout,
+            pipelines,
+            frame_bind_group_layout,
+            instance_bind_group_layout,
+            materials,
+            shadows,
+            post,
+            debug,
+            modes,
+            viewport_clear,
+            background,
+            particles,
+            sprites,
+            text,
+            ui,
+            render_targets: RenderTargets::new(DEPTH_FORMAT),
+            render_mode: RenderMode::default(),
             meshes: HashMap::new(),
-            uniform_buffer,
-            uniform_buffer_size,
+            stats: RenderStats::default(),
+            msaa,
+            supported_sample_counts,
+            rejected_sample_count: None,
+            start_time: Instant::now(),
+            frame_buffer,
+            frame_stride,
+            camera_capacity: MIN_CAMERA_CAPACITY,
+            light_buffer,
+            light_capacity: MIN_LIGHT_CAPACITY,
+            frame_bind_group,
+            instance_buffer,
+            instance_capacity: MIN_INSTANCE_CAPACITY,
+            instance_bind_group,
             staging_belt,
         };
 
@@ -113,112 +422,169 @@ impl State {
     // ============================================================================
     // INITIALIZATION HELPERS
     // ============================================================================
-    fn create_uniform_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    fn create_frame_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<FrameUniformData>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Shadow map matrices, array and comparison sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+            label: Some("frame_bind_group_layout"),
+        })
+    }
+
+    fn create_instance_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
                 visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
                     has_dynamic_offset: false,
-                    min_binding_size: None,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<InstanceData>() as u64
+                    ),
                 },
                 count: None,
             }],
-            label: Some("entity_uniform_bind_group_layout"),
+            label: Some("instance_bind_group_layout"),
         })
     }
 
-    fn create_entity_pipeline(
+    fn create_frame_bind_group(
         device: &wgpu::Device,
-        format: wgpu::TextureFormat,
-        uniform_bind_group_layout: &wgpu::BindGroupLayout,
-    ) -> wgpu::RenderPipeline {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("ECS Entity Shader"),
-            source: wgpu::ShaderSource::Wgsl(Self::get_shader_source()),
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("ECS Entity Pipeline Layout"),
-            bind_group_layouts: &[uniform_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("ECS Entity Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Self::get_vertex_buffer_layout()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
+        layout: &wgpu::BindGroupLayout,
+        frame_buffer: &wgpu::Buffer,
+        light_buffer: &wgpu::Buffer,
+        shadows: &ShadowMaps,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: frame_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<FrameUniformData>() as u64),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: shadows.matrix_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&shadows.array_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(&shadows.sampler),
+                },
+            ],
+            label: Some("frame_bind_group"),
         })
     }
 
-    fn get_shader_source() -> std::borrow::Cow<'static, str> {
-        r#"
-            struct Uniforms {
-                view_proj: mat4x4<f32>,
-                transform: mat4x4<f32>,
-                color: vec4<f32>,
-            }
-            @group(0) @binding(0)
-            var<uniform> uniforms: Uniforms;
-            struct VertexInput {
-                @location(0) position: vec3<f32>,
-            }
-            struct VertexOutput {
-                @builtin(position) clip_position: vec4<f32>,
-            }
-            @vertex
-            fn vs_main(vertex: VertexInput) -> VertexOutput {
-                var out: VertexOutput;
-                let world_pos = uniforms.transform * vec4<f32>(vertex.position, 1.0);
-                out.clip_position = uniforms.view_proj * world_pos;
-                return out;
-            }
-            @fragment
-            fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-                return uniforms.color;
-            }
-        "#
-        .into()
+    fn create_instance_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        instance_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: instance_buffer.as_entire_binding(),
+            }],
+            label: Some("instance_bind_group"),
+        })
+    }
+
+    fn create_frame_buffer(device: &wgpu::Device, stride: u64, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Uniform Buffer"),
+            size: stride * capacity as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_light_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Storage Buffer"),
+            size: (capacity * std::mem::size_of::<GpuLight>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Storage Buffer"),
+            size: (capacity * std::mem::size_of::<InstanceData>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
     }
 
     fn get_vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2];
         wgpu::VertexBufferLayout {
-            array_stride: 3 * 4, // 3 floats * 4 bytes each
+            array_stride: std::mem::size_of::<Vertex>() as u64, // position + normal + uv
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x3,
-            }],
+            attributes: &ATTRIBUTES,
         }
     }
 
@@ -232,9 +598,10 @@ impl State {
 
     fn load_triangle_mesh(&mut self) {
         const TRIANGLE_VERTICES: &[f32] = &[
-            0.0, 0.5, 0.0, // top
-            -0.5, -0.5, 0.0, // left
-            0.5, -0.5, 0.0, // right
+            // position      normal       uv
+            0.0, 0.5, 0.0, 0.0, 0.0, 1.0, 0.5, 0.0, // top
+            -0.5, -0.5, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, // left
+            0.5, -0.5, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, // right
         ];
 
         let vertex_buffer = self
@@ -246,38 +613,61 @@ impl State {
             });
 
         self.meshes.insert(
-            0,
+            TRIANGLE_MESH,
             Mesh {
                 vertex_buffer,
                 index_buffer: None,
                 vertex_count: 3,
                 index_count: None,
+                index_format: wgpu::IndexFormat::Uint16,
+                bounds: MeshBounds::from_positions(&interleaved_positions(TRIANGLE_VERTICES)),
+                unindexed_vertex_buffer: None,
             },
         );
     }
 
     fn load_cube_mesh(&mut self) {
+        // Four corners per face so every face gets its own flat normal and UVs
         const CUBE_VERTICES: &[f32] = &[
-            // Front face
-            -0.5, -0.5, 0.5, // 0
-            0.5, -0.5, 0.5, // 1
-            0.5, 0.5, 0.5, // 2
-            -0.5, 0.5, 0.5, // 3
-            // Back face
-            -0.5, -0.5, -0.5, // 4
-            0.5, -0.5, -0.5, // 5
-            0.5, 0.5, -0.5, // 6
-            -0.5, 0.5, -0.5, // 7
+            // Front face (+Z)
+            -0.5, -0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 1.0, //
+            0.5, -0.5, 0.5, 0.0, 0.0, 1.0, 1.0, 1.0, //
+            0.5, 0.5, 0.5, 0.0, 0.0, 1.0, 1.0, 0.0, //
+            -0.5, 0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, //
+            // Back face (-Z)
+            0.5, -0.5, -0.5, 0.0, 0.0, -1.0, 0.0, 1.0, //
+            -0.5, -0.5, -0.5, 0.0, 0.0, -1.0, 1.0, 1.0, //
+            -0.5, 0.5, -0.5, 0.0, 0.0, -1.0, 1.0, 0.0, //
+            0.5, 0.5, -0.5, 0.0, 0.0, -1.0, 0.0, 0.0, //
+            // Left face (-X)
+            -0.5, -0.5, -0.5, -1.0, 0.0, 0.0, 0.0, 1.0, //
+            -0.5, -0.5, 0.5, -1.0, 0.0, 0.0, 1.0, 1.0, //
+            -0.5, 0.5, 0.5, -1.0, 0.0, 0.0, 1.0, 0.0, //
+            -0.5, 0.5, -0.5, -1.0, 0.0, 0.0, 0.0, 0.0, //
+            // Right face (+X)
+            0.5, -0.5, 0.5, 1.0, 0.0, 0.0, 0.0, 1.0, //
+            0.5, -0.5, -0.5, 1.0, 0.0, 0.0, 1.0, 1.0, //
+            0.5, 0.5, -0.5, 1.0, 0.0, 0.0, 1.0, 0.0, //
+            0.5, 0.5, 0.5, 1.0, 0.0, 0.0, 0.0, 0.0, //
+            // Top face (+Y)
+            -0.5, 0.5, 0.5, 0.0, 1.0, 0.0, 0.0, 1.0, //
+            0.5, 0.5, 0.5, 0.0, 1.0, 0.0, 1.0, 1.0, //
+            0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 1.0, 0.0, //
+            -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 0.0, 0.0, //
+            // Bottom face (-Y)
+            -0.5, -0.5, -0.5, 0.0, -1.0, 0.0, 0.0, 1.0, //
+            0.5, -0.5, -0.5, 0.0, -1.0, 0.0, 1.0, 1.0, //
+            0.5, -0.5, 0.5, 0.0, -1.0, 0.0, 1.0, 0.0, //
+            -0.5, -0.5, 0.5, 0.0, -1.0, 0.0, 0.0, 0.0, //
         ];
 
         const CUBE_INDICES: &[u16] = &[
-            // Front face
-            0, 1, 2, 2, 3, 0, // Back face
-            4, 6, 5, 6, 4, 7, // Left face
-            4, 0, 3, 3, 7, 4, // Right face
-            1, 5, 6, 6, 2, 1, // Top face
-            3, 2, 6, 6, 7, 3, // Bottom face
-            4, 5, 1, 1, 0, 4,
+            0, 1, 2, 2, 3, 0, // Front
+            4, 5, 6, 6, 7, 4, // Back
+            8, 9, 10, 10, 11, 8, // Left
+            12, 13, 14, 14, 15, 12, // Right
+            16, 17, 18, 18, 19, 16, // Top
+            20, 21, 22, 22, 23, 20, // Bottom
         ];
 
         let vertex_buffer = self
@@ -297,16 +687,130 @@ impl State {
             });
 
         self.meshes.insert(
-            1,
+            CUBE_MESH,
             Mesh {
                 vertex_buffer,
                 index_buffer: Some(index_buffer),
-                vertex_count: 8,
+                vertex_count: 24,
                 index_count: Some(CUBE_INDICES.len() as u32),
+                index_format: wgpu::IndexFormat::Uint16,
+                bounds: MeshBounds::from_positions(&interleaved_positions(CUBE_VERTICES)),
+                unindexed_vertex_buffer: self.create_unindexed_buffer(
+                    bytemuck::cast_slice(CUBE_VERTICES),
+                    &CUBE_INDICES.iter().map(|&i| i as u32).collect::<Vec<_>>(),
+                ),
             },
         );
     }
 
+    /// Upload imported mesh data under the given handle, replacing any previous mesh
+    pub fn upload_mesh(&mut self, handle: MeshHandle, data: &MeshData) {
+        let normals = if data.normals.len() == data.positions.len() {
+            Cow::Borrowed(&data.normals)
+        } else {
+            Cow::Owned(data.smooth_normals())
+        };
+        let vertices: Vec<Vertex> = data
+            .positions
+            .iter()
+            .zip(normals.iter())
+            .enumerate()
+            .map(|(i, (&position, &normal))| Vertex {
+                position,
+                normal,
+                uv: data.uvs.get(i).copied().unwrap_or([0.0; 2]),
+            })
+            .collect();
+
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Imported Mesh VB"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let index_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Imported Mesh IB"),
+                contents: bytemuck::cast_slice(&data.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+
+        self.meshes.insert(
+            handle,
+            Mesh {
+                vertex_buffer,
+                index_buffer: Some(index_buffer),
+                vertex_count: data.vertex_count() as u32,
+                index_count: Some(data.indices.len() as u32),
+                index_format: wgpu::IndexFormat::Uint32,
+                bounds: MeshBounds::from_positions(&data.positions),
+                unindexed_vertex_buffer: self.create_unindexed_buffer(&vertices, &data.indices),
+            },
+        );
+    }
+
+    /// Unindexed copy of a mesh for the barycentric wireframe, `None` when
+    /// the device draws wireframes with line polygons
+    fn create_unindexed_buffer(
+        &self,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Option<wgpu::Buffer> {
+        if self.modes.line_polygons {
+            return None;
+        }
+        let vertices = unindex(vertices, indices);
+        Some(
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Unindexed Mesh VB"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+        )
+    }
+
+    /// Upload newly loaded meshes and release the buffers of unused ones
+    pub fn sync_meshes(&mut self, assets: &mut Assets) {
+        for handle in assets.take_freed_meshes() {
+            self.meshes.remove(&handle);
+        }
+
+        for handle in assets.take_mesh_uploads() {
+            let Some(mesh) = assets.meshes.get(handle) else {
+                continue;
+            };
+            if mesh.data.is_empty() {
+                log_error!("Skipping empty mesh {:?}", handle);
+                continue;
+            }
+            self.upload_mesh(handle, &mesh.data);
+        }
+    }
+
+    /// Upload newly loaded textures and release unused ones
+    pub fn sync_textures(&mut self, assets: &mut Assets) {
+        self.materials.sync(&self.device, &self.queue, assets);
+    }
+
+    /// Upload newly loaded skybox cubemaps and release unused ones
+    pub fn sync_cubemaps(&mut self, assets: &mut Assets) {
+        self.background.sync(&self.device, &self.queue, assets);
+    }
+
+    /// Build pipelines for newly loaded or edited shaders
+    pub fn sync_shaders(&mut self, assets: &mut Assets) {
+        self.pipelines.sync(&self.device, assets);
+    }
+
+    /// Pick up newly loaded fonts for text rendering
+    pub fn sync_fonts(&mut self, assets: &Assets) {
+        self.text.sync_fonts(assets);
+    }
+
     // ============================================================================
     // UTILITY FUNCTIONS
     // ============================================================================
@@ -314,6 +818,28 @@ impl State {
         &self.window
     }
 
+    /// Give a window event to the UI overlay first. `true` if a panel used
+    /// it and the game should ignore it.
+    pub fn handle_ui_event(&mut self, event: &winit::event::WindowEvent) -> bool {
+        self.ui.on_window_event(&self.window, event)
+    }
+
+    /// Buttons clicked since the last call
+    pub fn take_ui_events(&mut self) -> Vec<UiEvent> {
+        self.ui.take_events()
+    }
+
+    /// Show or hide the developer console
+    pub fn toggle_console(&mut self) {
+        self.ui.console.toggle();
+    }
+
+    /// Lines entered in the console since the last call, for
+    /// `Engine::run_console_command`
+    pub fn take_console_commands(&mut self) -> Vec<String> {
+        self.ui.console.take_submitted()
+    }
+
     fn configure_surface(&self) {
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -332,13 +858,70 @@ impl State {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
             self.configure_surface();
+            self.msaa =
+                MsaaTargets::new(&self.device, new_size, DEPTH_FORMAT, self.msaa.sample_count);
+            self.post.resize(&self.device, new_size);
+        }
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.msaa.sample_count
+    }
+
+    /// Switch MSAA on or off, rebuilding the targets and entity pipelines.
+    /// Fails for counts the device doesn't support.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<(), String> {
+        let sample_count = validate_sample_count(sample_count, &self.supported_sample_counts)?;
+        if sample_count != self.msaa.sample_count {
+            self.msaa = MsaaTargets::new(&self.device, self.size, DEPTH_FORMAT, sample_count);
+            self.pipelines.set_sample_count(&self.device, sample_count);
+            self.debug.set_sample_count(&self.device, sample_count);
+            self.modes.set_sample_count(&self.device, sample_count);
+            self.viewport_clear
+                .set_sample_count(&self.device, sample_count);
+            self.background.set_sample_count(&self.device, sample_count);
+            self.particles.set_sample_count(&self.device, sample_count);
+            self.sprites.set_sample_count(&self.device, sample_count);
+            self.text.set_sample_count(&self.device, sample_count);
+        }
+        Ok(())
+    }
+
+    /// Where particle emitters have to integrate their particles for this
+    /// device, see `update_particles`
+    pub fn particle_simulation(&self) -> ParticleSimulation {
+        self.particles.simulation()
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    /// Switch between lit rendering and the debug views
+    pub fn set_render_mode(&mut self, mode: RenderMode) {
+        self.render_mode = mode;
+    }
+
+    /// Follow the world's MSAA setting
+    fn apply_sample_count(&mut self, requested: u32) {
+        if requested == self.msaa.sample_count || self.rejected_sample_count == Some(requested) {
+            return;
+        }
+        match self.set_sample_count(requested) {
+            Ok(()) => self.rejected_sample_count = None,
+            Err(e) => {
+                log_error!("{}", e);
+                self.rejected_sample_count = Some(requested);
+            }
         }
     }
 
     // ============================================================================
     // OPTIMIZED RENDERING
     // ============================================================================
-    pub fn render(&mut self, world: &crate::modules::ecs::world::World) {
+    pub fn render(&mut self, world: &World) {
+        self.apply_sample_count(world.msaa_samples);
+
         let surface_texture = match self.surface.get_current_texture() {
             Ok(texture) => texture,
             Err(_) => return, // Skip frame if surface is unavailable
@@ -357,111 +940,486 @@ impl State {
                 label: Some("Render Encoder"),
             });
 
-        // Calculate view projection matrix once
-        let aspect = self.size.width as f32 / self.size.height as f32;
-        let view_proj = world
-            .active_camera_matrix(aspect)
-            .unwrap_or(glam::Mat4::IDENTITY);
+        // Every active camera draws into its own viewport, in order
+        let views = camera_views(world, self.size.width, self.size.height);
 
         // Collect renderable entities with mesh IDs and copy their data
-        let renderable_entities: Vec<_> = world.get_renderable_entities();
+        let renderable_entities = world.get_renderable_entities_with_ids();
 
-        if renderable_entities.is_empty() {
+        if renderable_entities.is_empty()
+            && world.debug_draw.is_empty()
+            && world.background.is_none()
+            && world.hud.is_empty()
+            && world.ui.is_empty()
+            && !self.ui.console.open
+            && world.iter_entities().all(|(_, e)| {
+                e.particle_emitter.is_none()
+                    && e.sprite.is_none()
+                    && e.tilemap.is_none()
+                    && e.text.is_none()
+            })
+        {
+            self.stats = RenderStats::default();
             // Early exit if nothing to render
             surface_texture.present();
             return;
         }
 
+        let shadow_layers = self.write_frame_uniforms(world, &views);
+        self.render_targets.sync(
+            &self.device,
+            &mut self.materials,
+            &camera_targets(world),
+            (self.size.width, self.size.height),
+            self.msaa.sample_count,
+        );
+        for (_, _, _, material) in &renderable_entities {
+            self.materials.prepare(&self.device, material);
+        }
+
+        // Per camera: frustum culling, then one instanced draw per mesh,
+        // material and shader among the opaque entities left. Transparent
+        // entities follow, back to front, and only neighbours in that order
+        // can share a draw. The background goes between the two. A camera
+        // can't see materials showing its own target, the texture can't be
+        // sampled while it is drawn into; those entities are neither drawn
+        // nor culled.
+        let mut stats = RenderStats::default();
+        let mut instance_order = Vec::new();
+        let mut view_batches: Vec<ViewBatches> = Vec::with_capacity(views.len());
+        for view in &views {
+            let frustum = Frustum::from_view_proj(&view.view_proj);
+            let visible: Vec<_> = renderable_entities
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, mesh_id, transform, _))| {
+                    self.is_visible(&frustum, mesh_id, transform)
+                })
+                .map(|(index, (_, mesh_id, _, material))| {
+                    (index, BatchKey::new(*mesh_id, material))
+                })
+                .collect();
+            stats.culled += renderable_entities.len() - visible.len();
+            let (mut transparent, opaque): (Vec<_>, Vec<_>) = visible
+                .into_iter()
+                .filter(|(_, key)| view.target.is_none_or(|t| !key.material.uses_texture(t)))
+                .partition(|(_, key)| key.pipeline.blend.is_transparent());
+            stats.drawn += opaque.len() + transparent.len();
+            stats.transparent += transparent.len();
+
+            let (order, opaque) = batch_by_key(opaque, instance_order.len() as u32);
+            instance_order.extend(order);
+            sort_back_to_front(&mut transparent, view.position, |(index, _)| {
+                renderable_entities[*index].2.w_axis.truncate()
+            });
+            let (order, transparent) = batch_consecutive(transparent, instance_order.len() as u32);
+            instance_order.extend(order);
+            view_batches.push(ViewBatches {
+                opaque,
+                transparent,
+            });
+        }
+
+        // Shadow casters are culled against each layer's own light frustum
+        // and only need to share a mesh
+        let mut shadow_batches = Vec::with_capacity(shadow_layers.len());
+        for light_view_proj in &shadow_layers {
+            let light_frustum = Frustum::from_view_proj(light_view_proj);
+            let casters = renderable_entities
+                .iter()
+                .enumerate()
+                .filter(|(_, (entity_id, mesh_id, transform, _))| {
+                    world
+                        .get_entity(*entity_id)
+                        .is_some_and(|e| e.casts_shadows)
+                        && self.is_visible(&light_frustum, mesh_id, transform)
+                })
+                .map(|(index, (_, mesh_id, _, _))| (index, *mesh_id));
+            let (order, batches) = batch_by_key(casters, instance_order.len() as u32);
+            instance_order.extend(order);
+            shadow_batches.push(batches);
+        }
+        self.write_instances(world, &renderable_entities, &instance_order);
+
+        // Debug text faces the first camera
+        let camera_rotation = views.first().map(|v| v.rotation).unwrap_or_default();
+        let debug_lines = world.debug_draw.vertices(
+            camera_rotation * glam::Vec3::X,
+            camera_rotation * glam::Vec3::Y,
+        );
+        self.debug.prepare(&self.device, &self.queue, &debug_lines);
+
+        // Debug views show geometry only
+        let background = match self.render_mode {
+            RenderMode::Lit => world.background.as_ref(),
+            _ => None,
+        };
+        self.background
+            .prepare(&self.device, &self.queue, background);
+        self.particles
+            .prepare(&self.device, &self.queue, &mut encoder, world);
+        self.sprites
+            .prepare(&self.device, &self.queue, &self.materials, world);
+        self.text.prepare(
+            &self.device,
+            &self.queue,
+            world,
+            camera_rotation * glam::Vec3::X,
+            camera_rotation * glam::Vec3::Y,
+            glam::Vec2::new(self.size.width as f32, self.size.height as f32),
+        );
+
+        // Shadow maps first, the main pass samples them
+        for (layer, batches) in shadow_batches.iter().enumerate() {
+            let mut shadow_pass = self.shadows.begin_pass(&mut encoder, layer);
+            shadow_pass.set_bind_group(1, &self.instance_bind_group, &[]);
+            for batch in batches {
+                if let Some(mesh) = self.meshes.get(&batch.key) {
+                    Self::draw_mesh(&mut shadow_pass, mesh, batch.instances.clone());
+                    stats.shadow_draws += 1;
+                }
+            }
+        }
+
+        // Render targets next, in the order their first camera is drawn, so
+        // the main pass can show them
+        let mut targets: Vec<TextureHandle> = Vec::new();
+        for target in views.iter().filter_map(|view| view.target) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for target in targets {
+            let (Some(buffers), Some(texture)) = (
+                self.render_targets.get(target),
+                self.materials.texture(target),
+            ) else {
+                continue;
+            };
+            let mut target_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Target Pass"),
+                color_attachments: &[Some(buffers.color_attachment(
+                    &texture.linear_view,
+                    wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                ))],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &buffers.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            self.draw_views(
+                &mut target_pass,
+                &views,
+                &view_batches,
+                Some(target),
+                &mut stats,
+            );
+        }
+
         // Begin render pass in a separate scope to avoid borrowing conflicts
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("ECS Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &texture_view,
-
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
+                color_attachments: &[Some(self.msaa.color_attachment(
+                    self.post.hdr_view(),
+                    wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                ))],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.msaa.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-
-            render_pass.set_pipeline(&self.entity_pipeline);
-
-            // Render all entities - look up meshes during rendering to avoid borrowing conflicts
-            for (mesh_id, transform, material) in &renderable_entities {
-                if let Some(mesh) = self.meshes.get(mesh_id) {
-                    Self::render_entity_in_pass(
-                        &self.queue,
-                        &self.device,
-                        &self.uniform_buffer,
-                        &self.uniform_bind_group_layout,
-                        &mut render_pass,
-                        mesh,
-                        transform,
-                        material,
-                        view_proj,
-                    );
-                }
-            }
+            self.draw_views(&mut render_pass, &views, &view_batches, None, &mut stats);
         } // render_pass is dropped here, freeing the encoder borrow
+        self.stats = stats;
+
+        // Resolve the HDR frame into the swapchain
+        // Debug views are shown as is, without effects or tonemapping
+        let post_process = match self.render_mode {
+            RenderMode::Lit => *world.active_post_process(),
+            _ => PostProcess {
+                tonemapper: Tonemapper::None,
+                fxaa: false,
+                ..PostProcess::default()
+            },
+        };
+        let lut = post_process
+            .color_grading
+            .lut
+            .and_then(|handle| Some((handle, self.materials.texture(handle)?)));
+        self.post.run(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &post_process,
+            lut,
+            &texture_view,
+        );
+        // HUD text over the finished, tonemapped frame
+        self.text.draw_hud(&mut encoder, &texture_view);
+        // Game UI on top of everything
+        let ui_commands = self.ui.draw(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &texture_view,
+            &self.window,
+            &world.ui,
+        );
 
         // Submit and present
-        self.queue.submit([encoder.finish()]);
+        self.queue
+            .submit(ui_commands.into_iter().chain([encoder.finish()]));
         self.window.pre_present_notify();
         surface_texture.present();
     }
 
-    fn render_entity_in_pass(
-        queue: &wgpu::Queue,
-        device: &wgpu::Device,
-        uniform_buffer: &wgpu::Buffer,
-        uniform_bind_group_layout: &wgpu::BindGroupLayout,
+    /// Cameras, ambient light, the light list and this frame's shadow maps.
+    /// Scenes without any lights get a default sun so unlit content stays
+    /// readable. Shadow cascades follow the first view. Returns the
+    /// view-projection of every shadow map layer that needs rendering.
+    fn write_frame_uniforms(&mut self, world: &World, views: &[CameraView]) -> Vec<glam::Mat4> {
+        let mut lights = world.get_lights();
+        if lights.is_empty() {
+            let mut sun = Light::directional(glam::Vec3::ONE, 1.0);
+            sun.casts_shadows = false;
+            let direction = glam::Vec3::new(-0.3, -1.0, -0.5).normalize();
+            lights.push((sun, glam::Vec3::ZERO, direction));
+        }
+
+        let camera = world.active_camera();
+        let aspect = views.first().map_or(1.0, |v| v.aspect);
+        let shadow_plan = plan_shadows(&lights, camera.as_ref().map(|(c, t)| (*c, t)), aspect);
+        self.shadows.write(&self.queue, &shadow_plan);
+
+        let lights: Vec<GpuLight> = lights
+            .iter()
+            .zip(&shadow_plan.lights)
+            .map(|((light, position, direction), shadow)| {
+                GpuLight::new(light, *position, *direction, *shadow)
+            })
+            .collect();
+
+        let mut rebind = false;
+        if lights.len() > self.light_capacity {
+            self.light_capacity = lights.len().next_power_of_two();
+            self.light_buffer = Self::create_light_buffer(&self.device, self.light_capacity);
+            rebind = true;
+        }
+        if views.len() > self.camera_capacity {
+            self.camera_capacity = views.len().next_power_of_two();
+            self.frame_buffer =
+                Self::create_frame_buffer(&self.device, self.frame_stride, self.camera_capacity);
+            rebind = true;
+        }
+        if rebind {
+            self.frame_bind_group = Self::create_frame_bind_group(
+                &self.device,
+                &self.frame_bind_group_layout,
+                &self.frame_buffer,
+                &self.light_buffer,
+                &self.shadows,
+            );
+        }
+
+        let fog = &world.fog;
+        let fog_mode = match fog.mode {
+            _ if !fog.enabled => 0,
+            FogMode::Linear => 1,
+            FogMode::Exponential => 2,
+            FogMode::ExponentialSquared => 3,
+            FogMode::Height => 4,
+        };
+
+        // Each camera's uniforms start at a multiple of the stride
+        let time = self.start_time.elapsed().as_secs_f32();
+        let mut frames = vec![0u8; self.frame_stride as usize * views.len()];
+        for (view, bytes) in views
+            .iter()
+            .zip(frames.chunks_exact_mut(self.frame_stride as usize))
+        {
+            let frame = FrameUniformData {
+                view_proj: view.view_proj.to_cols_array_2d(),
+                inverse_view_proj: view.view_proj.inverse().to_cols_array_2d(),
+                camera_position: view.position.extend(1.0).to_array(),
+                ambient: world.ambient_light.extend(1.0).to_array(),
+                light_count: [lights.len() as u32, 0, 0, 0],
+                time: [time, 0.0, 0.0, 0.0],
+                clear_color: view.clear_color.unwrap_or_default().to_array(),
+                camera_forward: (view.rotation * glam::Vec3::NEG_Z).extend(0.0).to_array(),
+                fog_color: fog.color.extend(fog.height).to_array(),
+                fog_params: [fog.start, fog.end, fog.density, fog.height_falloff],
+                fog_mode: [fog_mode, 0, 0, 0],
+            };
+            let frame = bytemuck::bytes_of(&frame);
+            bytes[..frame.len()].copy_from_slice(frame);
+        }
+
+        self.queue.write_buffer(&self.frame_buffer, 0, &frames);
+        self.queue
+            .write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&lights));
+
+        shadow_plan.layers
+    }
+
+    /// Draw the views into `target` (`None` for the window) with their
+    /// batches, each into its own viewport
+    fn draw_views(
+        &self,
         render_pass: &mut wgpu::RenderPass,
-        mesh: &Mesh,
-        transform: &crate::modules::ecs::components::Transform,
-        material: &crate::modules::ecs::components::Material,
-        view_proj: glam::Mat4,
+        views: &[CameraView],
+        view_batches: &[ViewBatches],
+        target: Option<TextureHandle>,
+        stats: &mut RenderStats,
     ) {
-        // Create transform matrix using glam
-        let transform_matrix = glam::Mat4::from_scale_rotation_translation(
-            transform.scale,
-            glam::Quat::IDENTITY, // or calculate from rotation if you implement it
-            transform.position,
-        );
+        for (index, (view, batches)) in views.iter().zip(view_batches).enumerate() {
+            if view.target != target {
+                continue;
+            }
+            let [x, y, width, height] = view.viewport;
+            render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            let offset = (index as u64 * self.frame_stride) as u32;
+            render_pass.set_bind_group(0, &self.frame_bind_group, &[offset]);
 
-        // Pack into uniform struct
-        let uniform = EntityUniformData {
-            view_proj: view_proj.to_cols_array_2d(),
-            transform: transform_matrix.to_cols_array_2d(),
-            color: material.color.into(),
-        };
+            // Earlier cameras may have drawn here already
+            self.viewport_clear
+                .draw(render_pass, view.clear_color.is_some());
+
+            // Cameras drawing over earlier ones keep what they left behind
+            self.draw_batches(render_pass, &batches.opaque, stats);
+            if view.clear_color.is_some() {
+                self.background.draw(render_pass);
+            }
+            if self.render_mode == RenderMode::Lit {
+                self.sprites.draw(render_pass);
+                self.text.draw_world(render_pass);
+            }
+            self.draw_batches(render_pass, &batches.transparent, stats);
+            if self.render_mode == RenderMode::Lit {
+                self.particles.draw(render_pass);
+            }
 
-        // Write to GPU
-        queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+            // Debug lines last, over everything they aren't behind. Only the
+            // window shows them.
+            if target.is_none() {
+                self.debug.draw(render_pass);
+            }
+        }
+    }
 
-        // Create bind group
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: uniform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-            label: Some("entity_uniform_bind_group"),
-        });
+    /// Draw entity batches of the current view. The background uses
+    /// groups 1 and 2 as well, so they are bound again for every call.
+    fn draw_batches(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        batches: &[DrawBatch<BatchKey>],
+        stats: &mut RenderStats,
+    ) {
+        if batches.is_empty() {
+            return;
+        }
+        render_pass.set_bind_group(1, &self.instance_bind_group, &[]);
+
+        // Debug views draw every batch with their own pipeline
+        let mode_pipeline = self.modes.get(self.render_mode);
+        let unindexed = self.modes.needs_unindexed(self.render_mode);
+        let mut current_pipeline = None;
+        if let Some(pipeline) = mode_pipeline {
+            render_pass.set_pipeline(pipeline);
+        }
 
-        render_pass.set_bind_group(0, &bind_group, &[]);
+        for batch in batches {
+            let Some(mesh) = self.meshes.get(&batch.key.mesh) else {
+                continue;
+            };
+            let Some(material) = self.materials.bind_group(&batch.key.material) else {
+                continue;
+            };
+            if mode_pipeline.is_none() && current_pipeline != Some(batch.key.pipeline) {
+                render_pass.set_pipeline(self.pipelines.get(batch.key.pipeline));
+                current_pipeline = Some(batch.key.pipeline);
+            }
+            render_pass.set_bind_group(2, material, &[]);
+            match (&mesh.unindexed_vertex_buffer, mesh.index_count) {
+                (Some(buffer), Some(count)) if unindexed => {
+                    render_pass.set_vertex_buffer(0, buffer.slice(..));
+                    render_pass.draw(0..count, batch.instances.clone());
+                }
+                _ => Self::draw_mesh(render_pass, mesh, batch.instances.clone()),
+            }
+            stats.draw_calls += 1;
+        }
+    }
+
+    /// Whether a mesh with the given world transform can show up in `frustum`
+    fn is_visible(&self, frustum: &Frustum, mesh: &MeshHandle, transform: &glam::Mat4) -> bool {
+        match self.meshes.get(mesh).and_then(|m| m.bounds) {
+            Some(bounds) => frustum.is_visible(&bounds, transform),
+            None => true,
+        }
+    }
+
+    /// Upload the instance data of `order`, indices into `renderables`, so
+    /// that batch instance ranges index straight into the buffer
+    fn write_instances(
+        &mut self,
+        world: &World,
+        renderables: &[(EntityId, MeshHandle, glam::Mat4, Material)],
+        order: &[usize],
+    ) {
+        if order.len() > self.instance_capacity {
+            self.instance_capacity = order.len().next_power_of_two();
+            self.instance_buffer =
+                Self::create_instance_buffer(&self.device, self.instance_capacity);
+            self.instance_bind_group = Self::create_instance_bind_group(
+                &self.device,
+                &self.instance_bind_group_layout,
+                &self.instance_buffer,
+            );
+        }
+
+        let instances: Vec<InstanceData> = order
+            .iter()
+            .map(|&index| {
+                let (entity_id, _, transform, material) = &renderables[index];
+                let receives_shadows = world
+                    .get_entity(*entity_id)
+                    .is_some_and(|e| e.receives_shadows);
+                InstanceData::new(*transform, material, receives_shadows)
+            })
+            .collect();
+        self.queue
+            .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+    }
+
+    fn draw_mesh(render_pass: &mut wgpu::RenderPass, mesh: &Mesh, instances: Range<u32>) {
         render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
 
         if let (Some(index_buffer), Some(index_count)) = (&mesh.index_buffer, mesh.index_count) {
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..index_count, 0, 0..1);
+            render_pass.set_index_buffer(index_buffer.slice(..), mesh.index_format);
+            render_pass.draw_indexed(0..index_count, 0, instances);
         } else {
-            render_pass.draw(0..mesh.vertex_count, 0..1);
+            render_pass.draw(0..mesh.vertex_count, instances);
         }
     }
 }
+
+/// Positions out of the position/normal/uv vertex arrays of the built-in meshes
+fn interleaved_positions(vertices: &[f32]) -> Vec<[f32; 3]> {
+    vertices
+        .chunks_exact(std::mem::size_of::<Vertex>() / std::mem::size_of::<f32>())
+        .map(|v| [v[0], v[1], v[2]])
+        .collect()
+}
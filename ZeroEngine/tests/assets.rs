@@ -0,0 +1,134 @@
+use ZeroEngine::modules::assets::loader::{AssetLoader, LoadKind, Loaded};
+use ZeroEngine::modules::assets::{AssetGroup, AssetId, AssetStatus, Assets, CUBE_MESH};
+use ZeroEngine::modules::ecs::entity::{MeshType, spawn_entity};
+use ZeroEngine::modules::ecs::world::World;
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+#[test]
+fn resolves_against_root_and_deduplicates() {
+    let mut assets = Assets::with_root(fixtures());
+
+    let a = assets.load_mesh("cube.obj");
+    let b = assets.load_mesh("./cube.obj");
+    let c = assets.load_mesh(&fixtures().join("cube.obj").to_string_lossy());
+
+    assert_eq!(a, b);
+    assert_eq!(a, c);
+    assets.wait_all();
+    assert!(assets.meshes.is_loaded(a));
+    assert_eq!(assets.take_mesh_uploads(), vec![a]);
+    assert_eq!(assets.load_mesh("Cube"), CUBE_MESH);
+}
+
+#[test]
+fn reports_failures_per_asset() {
+    let mut assets = Assets::with_root(fixtures());
+
+    let bad = assets.load_mesh("bad_index.obj");
+    let missing = assets.load_script("missing.wasm");
+    assets.wait_all();
+    assert_eq!(assets.in_flight(), 0);
+
+    match assets.meshes.status(bad) {
+        Some(AssetStatus::Failed(message)) => assert!(message.contains(":5:"), "{}", message),
+        other => panic!("expected failure, got {:?}", other),
+    }
+    assert!(!assets.scripts.is_loaded(missing));
+    assert_eq!(assets.errors().len(), 2);
+    assert!(assets.take_mesh_uploads().is_empty());
+}
+
+#[test]
+fn frees_meshes_once_unreferenced() {
+    let mut assets = Assets::with_root(fixtures());
+    let mut world = World::new();
+
+    let mesh = assets.load_mesh("groups.obj");
+    assets.wait_all();
+    let first = spawn_entity(
+        &mut world,
+        "a",
+        glam::Vec3::ZERO,
+        glam::Vec3::ONE,
+        MeshType::Custom(mesh.id()),
+        glam::Vec4::ONE,
+    );
+    spawn_entity(
+        &mut world,
+        "b",
+        glam::Vec3::ZERO,
+        glam::Vec3::ONE,
+        MeshType::Custom(mesh.id()),
+        glam::Vec4::ONE,
+    );
+
+    assets.update_ref_counts(&world);
+    assets.collect_unused();
+    assert_eq!(assets.meshes.ref_count(mesh), 2);
+    assert!(assets.take_freed_meshes().is_empty());
+
+    world.remove_entity(first);
+    assets.update_ref_counts(&world);
+    assert_eq!(assets.meshes.ref_count(mesh), 1);
+
+    let last = world.iter_entities().next().unwrap().0;
+    world.remove_entity(last);
+    assets.update_ref_counts(&world);
+    assets.collect_unused();
+    assert_eq!(assets.take_freed_meshes(), vec![mesh]);
+    assert!(assets.meshes.get(mesh).is_none());
+
+    // Built-ins are never freed
+    assert!(assets.meshes.is_loaded(CUBE_MESH));
+}
+
+#[test]
+fn loads_in_background_and_tracks_progress() {
+    let mut assets = Assets::with_root(fixtures());
+    let mut group = AssetGroup::new();
+
+    let cube = assets.load_mesh("cube.obj");
+    let bad = assets.load_mesh("bad_number.obj");
+    group.add(AssetId::Mesh(cube));
+    group.add(AssetId::Mesh(bad));
+    group.add(AssetId::Mesh(CUBE_MESH));
+
+    // Handles are usable right away, data arrives later
+    assert_eq!(assets.load_mesh("cube.obj"), cube);
+    assert_eq!(assets.progress(&group).total, 3);
+
+    assets.wait_for(&group);
+    let progress = assets.progress(&group);
+    assert!(progress.is_done());
+    assert_eq!(progress.loaded, 2);
+    assert_eq!(progress.failed, 1);
+    assert_eq!(progress.fraction(), 1.0);
+    assert_eq!(assets.in_flight(), 0);
+    assert_eq!(assets.take_mesh_uploads(), vec![cube]);
+}
+
+#[test]
+fn fails_jobs_that_panic() {
+    let mut loader = AssetLoader::new(1);
+    loader.submit(LoadKind::Texture, 7, || panic!("bad header"));
+    loader.submit(LoadKind::Script, 8, || {
+        Loaded::Script(Err("missing".into()))
+    });
+
+    // The worker survives the panic and keeps taking jobs
+    let mut results = Vec::new();
+    while let Some(result) = loader.recv_timeout(std::time::Duration::from_secs(10)) {
+        results.push(result);
+    }
+    assert_eq!(loader.in_flight(), 0);
+    match &results[..] {
+        [(7, Loaded::Texture(Err(e))), (8, Loaded::Script(Err(_)))] => {
+            assert!(e.contains("bad header"), "{}", e)
+        }
+        _ => panic!("unexpected results"),
+    }
+}
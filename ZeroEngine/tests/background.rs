@@ -0,0 +1,111 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::cubemap::{equirect_to_cube, face_direction, linear_pixels};
+use ZeroEngine::modules::ecs::components::Background;
+use ZeroEngine::modules::render::background::BACKGROUND_SHADER;
+use glam::{Vec3, Vec4};
+
+fn scene_file(name: &str, json: &str) -> String {
+    let path = std::env::temp_dir().join(format!("zero_{}_{}.json", name, std::process::id()));
+    std::fs::write(&path, json).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn resamples_panoramas_into_cube_faces() {
+    // Face centres look along the axes
+    assert_eq!(face_direction(0, 0.0, 0.0), Vec3::X);
+    assert_eq!(face_direction(3, 0.0, 0.0), -Vec3::Y);
+    assert_eq!(face_direction(5, 0.0, 0.0), -Vec3::Z);
+    // Down the image is down the world on the side faces
+    assert!(face_direction(4, 0.0, 0.9).y < 0.0);
+
+    // Top half of the panorama is sky, bottom half ground
+    let (width, height) = (16, 8);
+    let sky = Vec4::new(0.2, 0.4, 4.0, 1.0);
+    let ground = Vec4::new(0.3, 0.2, 0.1, 1.0);
+    let pixels: Vec<Vec4> = (0..width * height)
+        .map(|i| if i / width < height / 2 { sky } else { ground })
+        .collect();
+    let cube = equirect_to_cube(width, height, &pixels, 4);
+    assert_eq!(cube.size, 4);
+    assert_eq!(cube.pixels.len(), 6 * 4 * 4);
+
+    let face = |index: usize| &cube.pixels[index * 16..(index + 1) * 16];
+    let is = |color: Vec4| move |p: &Vec4| p.abs_diff_eq(color, 1e-5);
+    assert!(face(2).iter().all(is(sky)), "+Y is the sky");
+    assert!(face(3).iter().all(is(ground)), "-Y is the ground");
+    // Side faces keep HDR values and show both halves
+    assert!(is(sky)(&face(5)[0]));
+    assert!(is(ground)(&face(5)[15]));
+
+    // 8 bit panoramas are sRGB like skybox faces, float ones already linear
+    let ldr = image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 128, 0, 255]));
+    let linear = linear_pixels(&ldr.into());
+    assert!(linear[0].abs_diff_eq(Vec4::new(1.0, 0.2158, 0.0, 1.0), 1e-3));
+    let hdr = image::Rgb32FImage::from_pixel(1, 1, image::Rgb([0.5, 2.0, 0.0]));
+    assert_eq!(
+        linear_pixels(&hdr.into()),
+        vec![Vec4::new(0.5, 2.0, 0.0, 1.0)]
+    );
+}
+
+#[test]
+fn reads_background_from_scene() {
+    let load = |name: &str, background: &str| {
+        let path = scene_file(
+            name,
+            &format!(
+                r#"{{ "entities": [], "cameras": [], "background": {} }}"#,
+                background
+            ),
+        );
+        let mut engine = Engine::new();
+        let result = engine.load_scene(path.clone());
+        std::fs::remove_file(&path).ok();
+        result.map(|_| engine.world.background)
+    };
+
+    let background = load("bg_color", r#"{ "color": [0.1, 0.2, 0.3] }"#).unwrap();
+    assert_eq!(
+        background,
+        Some(Background::Color(Vec3::new(0.1, 0.2, 0.3)))
+    );
+
+    let background = load(
+        "bg_gradient",
+        r#"{ "gradient": { "top": [0, 0, 1], "bottom": [1, 1, 1] } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        background,
+        Some(Background::Gradient {
+            top: Vec3::Z,
+            bottom: Vec3::ONE
+        })
+    );
+
+    // Missing files fail in the background, the scene itself loads
+    let background = load("bg_skybox", r#"{ "skybox": "no_such_sky.hdr" }"#).unwrap();
+    assert!(matches!(background, Some(Background::Skybox(_))));
+
+    let err = load("bg_faces", r#"{ "skybox": ["a.png", "b.png"] }"#).unwrap_err();
+    assert!(err.contains("6 faces"), "{}", err);
+    let err = load(
+        "bg_both",
+        r#"{ "color": [0, 0, 0], "gradient": { "top": [0, 0, 0], "bottom": [0, 0, 0] } }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("exactly one"), "{}", err);
+}
+
+#[test]
+fn validates_background_shader() {
+    let module =
+        wgpu::naga::front::wgsl::parse_str(BACKGROUND_SHADER).expect("background shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("background shader validates");
+}
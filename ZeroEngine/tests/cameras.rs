@@ -0,0 +1,171 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::components::Transform;
+use ZeroEngine::modules::ecs::entity::{
+    Camera, Projection, Viewport, camera_view_proj, spawn_camera,
+};
+use ZeroEngine::modules::ecs::world::World;
+use ZeroEngine::modules::render::cameras::{CLEAR_SHADER, camera_views};
+use ZeroEngine::modules::render::shader::compose;
+use glam::{Vec3, Vec4};
+
+fn scene_file(name: &str, json: &str) -> String {
+    let path = std::env::temp_dir().join(format!("zero_{}_{}.json", name, std::process::id()));
+    std::fs::write(&path, json).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn orthographic_cameras_show_their_size() {
+    let camera = Camera {
+        near: 0.1,
+        far: 50.0,
+        projection: Projection::Orthographic { size: 10.0 },
+        ..Default::default()
+    };
+    let view_proj = camera_view_proj(&camera, &Transform::default(), 2.0);
+
+    // 10 units tall and 20 wide, independent of distance
+    for z in [-1.0, -20.0] {
+        let corner = view_proj.project_point3(Vec3::new(10.0, 5.0, z));
+        assert!((corner.x - 1.0).abs() < 1e-5 && (corner.y - 1.0).abs() < 1e-5);
+    }
+    let near = view_proj.project_point3(Vec3::new(0.0, 0.0, -0.1));
+    let far = view_proj.project_point3(Vec3::new(0.0, 0.0, -50.0));
+    assert!(near.z.abs() < 1e-5 && (far.z - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn draws_active_cameras_into_their_viewports_in_order() {
+    let mut world = World::new();
+    let minimap = spawn_camera(
+        &mut world,
+        "Minimap",
+        Vec3::Y * 20.0,
+        Vec3::ZERO,
+        60.0,
+        0.1,
+        100.0,
+    );
+    let left = spawn_camera(&mut world, "Left", Vec3::ZERO, Vec3::ZERO, 60.0, 0.1, 100.0);
+    let right = spawn_camera(&mut world, "Right", Vec3::X, Vec3::ZERO, 60.0, 0.1, 100.0);
+    let hidden = spawn_camera(
+        &mut world,
+        "Hidden",
+        Vec3::ZERO,
+        Vec3::ZERO,
+        60.0,
+        0.1,
+        100.0,
+    );
+
+    let mut set = |id, viewport: Viewport, order, clear_color| {
+        let camera = world.get_entity_mut(id).unwrap().camera.as_mut().unwrap();
+        camera.viewport = viewport;
+        camera.order = order;
+        camera.clear_color = clear_color;
+    };
+    set(minimap, Viewport::new(0.75, 0.0, 0.25, 0.25), 1, None);
+    set(left, Viewport::new(0.0, 0.0, 0.5, 1.0), 0, Some(Vec4::W));
+    set(right, Viewport::new(0.5, 0.0, 0.5, 1.0), 0, Some(Vec4::W));
+    set(hidden, Viewport::new(1.5, 0.0, 0.5, 1.0), 0, Some(Vec4::W));
+
+    let order: Vec<_> = world
+        .active_cameras()
+        .iter()
+        .map(|(id, _, _)| *id)
+        .collect();
+    assert_eq!(order, vec![left, right, hidden, minimap]);
+
+    // Off-screen viewports are skipped
+    let views = camera_views(&world, 800, 600);
+    let viewports: Vec<_> = views.iter().map(|v| v.viewport).collect();
+    assert_eq!(
+        viewports,
+        vec![
+            [0.0, 0.0, 400.0, 600.0],
+            [400.0, 0.0, 400.0, 600.0],
+            [600.0, 0.0, 200.0, 150.0],
+        ]
+    );
+    assert!((views[0].aspect - 400.0 / 600.0).abs() < 1e-6);
+    assert_eq!(views[1].position, Vec3::X);
+    assert_eq!(views[2].clear_color, None);
+
+    // Without any active camera the whole target is drawn once
+    for id in [minimap, left, right, hidden] {
+        world
+            .get_entity_mut(id)
+            .unwrap()
+            .camera
+            .as_mut()
+            .unwrap()
+            .is_active = false;
+    }
+    let views = camera_views(&world, 800, 600);
+    assert_eq!(views.len(), 1);
+    assert_eq!(views[0].viewport, [0.0, 0.0, 800.0, 600.0]);
+}
+
+#[test]
+fn reads_camera_projection_and_viewport_from_scene() {
+    let path = scene_file(
+        "cameras",
+        r#"{
+            "entities": [],
+            "cameras": [
+                {
+                    "name": "Player", "fov": 70.0, "near": 0.1, "far": 100.0,
+                    "active": true, "tags": [],
+                    "transform": { "position": [0, 1, 5], "rotation": [0, 0, 0], "scale": [1, 1, 1] }
+                },
+                {
+                    "name": "Map", "fov": 60.0, "near": 0.1, "far": 200.0,
+                    "active": true, "tags": [],
+                    "transform": { "position": [0, 50, 0], "rotation": [-1.5708, 0, 0], "scale": [1, 1, 1] },
+                    "projection": "orthographic", "size": 40,
+                    "viewport": [0.75, 0.0, 0.25, 0.25],
+                    "clear_color": [0.1, 0.2, 0.3, 1.0],
+                    "order": 1
+                }
+            ]
+        }"#,
+    );
+    let mut engine = Engine::new();
+    engine.load_scene(path.clone()).expect("scene should load");
+    std::fs::remove_file(&path).ok();
+
+    let cameras = engine.world.active_cameras();
+    assert_eq!(cameras.len(), 2, "every active camera stays active");
+    let (_, player, _) = cameras[0];
+    assert_eq!(player.projection, Projection::Perspective);
+    assert_eq!(player.viewport, Viewport::FULL);
+    let (_, map, _) = cameras[1];
+    assert_eq!(map.projection, Projection::Orthographic { size: 40.0 });
+    assert_eq!(map.viewport, Viewport::new(0.75, 0.0, 0.25, 0.25));
+    assert_eq!(map.clear_color, Some(Vec4::new(0.1, 0.2, 0.3, 1.0)));
+    assert_eq!(map.order, 1);
+
+    let bad = scene_file(
+        "bad_camera",
+        r#"{ "entities": [], "cameras": [{
+            "name": "C", "fov": 60.0, "near": 0.1, "far": 100.0, "active": true, "tags": [],
+            "transform": { "position": [0, 0, 0], "rotation": [0, 0, 0], "scale": [1, 1, 1] },
+            "projection": "fisheye"
+        }] }"#,
+    );
+    let err = Engine::new().load_scene(bad.clone()).unwrap_err();
+    std::fs::remove_file(&bad).ok();
+    assert!(err.contains("fisheye"), "{}", err);
+}
+
+#[test]
+fn validates_viewport_clear_shader() {
+    let module =
+        wgpu::naga::front::wgsl::parse_str(&compose(CLEAR_SHADER)).expect("clear shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("clear shader validates");
+}
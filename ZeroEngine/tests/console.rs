@@ -0,0 +1,176 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::console::{ConsoleContext, LogLevel, log_lines};
+use ZeroEngine::modules::render::ui::{ConsoleView, console_input_id, show_console};
+use ZeroEngine::{log_error, log_warn};
+use glam::{Vec3, Vec4};
+
+fn run(engine: &mut Engine, line: &str) -> Result<String, String> {
+    engine.run_console_command(None, line)
+}
+
+#[test]
+fn runs_builtin_commands() {
+    let dir = std::env::temp_dir().join(format!("zero_console_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let scene = dir.join("scene.json");
+    std::fs::write(
+        &scene,
+        r#"{
+            "entities": [
+                { "name": "Player", "mesh": "cube", "position": [0, 0, 0],
+                  "scale": [1, 1, 1], "color": [1, 1, 1, 1] }
+            ],
+            "cameras": []
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    engine
+        .load_scene(scene.to_string_lossy().into_owned())
+        .unwrap();
+
+    assert!(
+        run(&mut engine, "entities")
+            .unwrap()
+            .starts_with("1 entities")
+    );
+    run(&mut engine, "set Player position 1 2 3").unwrap();
+    run(&mut engine, "set Player color 1 0 0").unwrap();
+    let player = engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == "Player")
+        .map(|(_, e)| e)
+        .unwrap();
+    assert_eq!(
+        player.transform.as_ref().unwrap().position,
+        Vec3::new(1.0, 2.0, 3.0)
+    );
+    assert_eq!(
+        player.material.as_ref().unwrap().color,
+        Vec4::new(1.0, 0.0, 0.0, 1.0)
+    );
+    assert!(
+        run(&mut engine, "inspect Player")
+            .unwrap()
+            .contains("position: [1, 2, 3]")
+    );
+
+    run(&mut engine, "spawn cube 0 5 0").unwrap();
+    assert_eq!(engine.world.iter_entities().count(), 2);
+    assert_eq!(run(&mut engine, "timescale 0.5").unwrap(), "time scale 0.5");
+    assert_eq!(engine.world.time_scale, 0.5);
+
+    // Reloading starts the scene over
+    run(&mut engine, "reload").unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(engine.world.iter_entities().count(), 1);
+    assert_eq!(engine.world.time_scale, 1.0);
+}
+
+#[test]
+fn reports_command_errors() {
+    let mut engine = Engine::new();
+    let err = run(&mut engine, "teleport Player").unwrap_err();
+    assert!(err.contains("unknown command 'teleport'"), "{}", err);
+    assert_eq!(
+        run(&mut engine, "inspect Nobody").unwrap_err(),
+        "no entity 'Nobody'"
+    );
+    assert!(run(&mut engine, "timescale -1").is_err());
+    assert!(run(&mut engine, "rendermode").is_err(), "needs a renderer");
+    assert!(run(&mut engine, "reload").is_err(), "no scene loaded");
+
+    run(&mut engine, "spawn triangle").unwrap();
+    let err = run(&mut engine, "set Triangle mass 2").unwrap_err();
+    assert_eq!(err, "unknown field 'mass'");
+    let err = run(&mut engine, "set Triangle position 1 2").unwrap_err();
+    assert_eq!(err, "expected 3 numbers, got 2");
+
+    // Failures land in the log as errors
+    assert!(
+        log_lines()
+            .iter()
+            .any(|line| line.level == LogLevel::Error && line.text == "unknown field 'mass'")
+    );
+}
+
+#[test]
+fn registers_game_commands() {
+    let mut engine = Engine::new();
+    engine.console.register(
+        "slowmo",
+        "slowmo - quarter speed",
+        |_: &[&str], ctx: &mut ConsoleContext| {
+            ctx.engine.world.time_scale = 0.25;
+            Ok("slow".to_string())
+        },
+    );
+    assert_eq!(run(&mut engine, "slowmo").unwrap(), "slow");
+    assert_eq!(engine.world.time_scale, 0.25);
+    assert!(
+        run(&mut engine, "help")
+            .unwrap()
+            .contains("slowmo - quarter speed")
+    );
+
+    assert!(engine.console.unregister("slowmo"));
+    assert!(run(&mut engine, "slowmo").is_err());
+    assert!(engine.console.contains("spawn"));
+}
+
+#[test]
+fn submits_typed_lines() {
+    log_error!("console test marker");
+    log_warn!("console warning marker");
+    let lines = log_lines();
+    assert!(lines.iter().any(|line| line.text == "console test marker"));
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.level == LogLevel::Warn && line.text == "console warning marker")
+    );
+
+    let ctx = egui::Context::default();
+    let mut view = ConsoleView::default();
+    let frame = |events| egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(800.0, 600.0),
+        )),
+        events,
+        ..Default::default()
+    };
+
+    // Closed, nothing is laid out
+    let _ = ctx.run(frame(Vec::new()), |ctx| {
+        show_console(ctx, &mut view, &lines)
+    });
+    assert!(!ctx.memory(|m| m.has_focus(console_input_id())));
+
+    view.toggle();
+    let _ = ctx.run(frame(Vec::new()), |ctx| {
+        show_console(ctx, &mut view, &lines)
+    });
+    assert!(ctx.memory(|m| m.has_focus(console_input_id())));
+    let enter = egui::Event::Key {
+        key: egui::Key::Enter,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::default(),
+    };
+    for events in [
+        vec![egui::Event::Text("entities".into())],
+        vec![enter],
+        Vec::new(),
+    ] {
+        let _ = ctx.run(frame(events), |ctx| show_console(ctx, &mut view, &lines));
+    }
+    assert_eq!(view.take_submitted(), vec!["entities".to_string()]);
+    assert!(view.input.is_empty());
+    assert!(
+        ctx.memory(|m| m.has_focus(console_input_id())),
+        "keeps focus"
+    );
+}
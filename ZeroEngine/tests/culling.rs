@@ -0,0 +1,80 @@
+use ZeroEngine::modules::ecs::components::Transform;
+use ZeroEngine::modules::ecs::entity::{Camera, camera_view_proj};
+use ZeroEngine::modules::render::culling::{Aabb, Frustum, MeshBounds};
+use glam::{Mat4, Quat, Vec3};
+
+fn cube_bounds() -> MeshBounds {
+    let corners: Vec<[f32; 3]> = (0..8)
+        .map(|i| [0, 1, 2].map(|axis| if i & (1 << axis) == 0 { -0.5 } else { 0.5 }))
+        .collect();
+    MeshBounds::from_positions(&corners).unwrap()
+}
+
+/// Camera at the origin looking down -Z
+fn frustum() -> Frustum {
+    let camera = Camera {
+        fov: 60.0,
+        near: 0.1,
+        far: 50.0,
+        is_active: true,
+        ..Default::default()
+    };
+    Frustum::from_view_proj(&camera_view_proj(&camera, &Transform::default(), 1.0))
+}
+
+#[test]
+fn computes_mesh_bounds() {
+    let bounds = MeshBounds::from_positions(&[[0.0, 0.0, 0.0], [2.0, 4.0, -2.0]]).unwrap();
+    assert_eq!(bounds.aabb.min, Vec3::new(0.0, 0.0, -2.0));
+    assert_eq!(bounds.aabb.max, Vec3::new(2.0, 4.0, 0.0));
+    assert_eq!(bounds.sphere.center, Vec3::new(1.0, 2.0, -1.0));
+    assert!((bounds.sphere.radius - 6.0f32.sqrt()).abs() < 1e-5);
+
+    assert!(MeshBounds::from_positions(&[]).is_none());
+}
+
+#[test]
+fn transforms_bounds_into_world_space() {
+    let aabb = cube_bounds().aabb;
+    let rotated = Mat4::from_rotation_translation(
+        Quat::from_rotation_y(std::f32::consts::FRAC_PI_4),
+        Vec3::new(10.0, 0.0, 0.0),
+    );
+    let world = aabb.transformed(&rotated);
+    let half = 0.5 * std::f32::consts::SQRT_2;
+    assert!(
+        world
+            .min
+            .abs_diff_eq(Vec3::new(10.0 - half, -0.5, -half), 1e-5)
+    );
+    assert!(
+        world
+            .max
+            .abs_diff_eq(Vec3::new(10.0 + half, 0.5, half), 1e-5)
+    );
+
+    let sphere = cube_bounds()
+        .sphere
+        .transformed(&Mat4::from_scale(Vec3::new(1.0, 4.0, 1.0)));
+    assert!((sphere.radius - 4.0 * 0.75f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn culls_against_camera_frustum() {
+    let frustum = frustum();
+    let bounds = cube_bounds();
+    let at = |x: f32, y: f32, z: f32| Mat4::from_translation(Vec3::new(x, y, z));
+
+    assert!(frustum.is_visible(&bounds, &at(0.0, 0.0, -5.0)));
+    // Behind the camera, beyond the far plane and off to the side
+    assert!(!frustum.is_visible(&bounds, &at(0.0, 0.0, 5.0)));
+    assert!(!frustum.is_visible(&bounds, &at(0.0, 0.0, -60.0)));
+    assert!(!frustum.is_visible(&bounds, &at(20.0, 0.0, -5.0)));
+
+    // Partly inside still counts as visible
+    let edge = Aabb {
+        min: Vec3::new(-100.0, -0.5, -5.5),
+        max: Vec3::new(0.0, 0.5, -4.5),
+    };
+    assert!(frustum.intersects_aabb(&edge));
+}
@@ -0,0 +1,84 @@
+use ZeroEngine::modules::render::debug_draw::{DEBUG_SHADER, DebugDraw, glyph, text_segments};
+use glam::{Mat4, Vec3, Vec4};
+
+fn line_count(debug: &DebugDraw) -> usize {
+    debug.vertices(Vec3::X, Vec3::Y).len() / 2
+}
+
+#[test]
+fn tessellates_shapes_into_lines() {
+    let mut debug = DebugDraw::new();
+    debug.aabb(Vec3::ZERO, Vec3::ONE, Vec4::ONE, 0.0);
+    assert_eq!(line_count(&debug), 12);
+
+    // Every box edge is axis aligned and one unit long
+    let vertices = debug.vertices(Vec3::X, Vec3::Y);
+    for pair in vertices.chunks(2) {
+        let length = Vec3::from(pair[0].position).distance(Vec3::from(pair[1].position));
+        assert!((length - 1.0).abs() < 1e-6);
+    }
+
+    debug.clear();
+    debug.sphere(Vec3::ZERO, 2.0, Vec4::ONE, 0.0);
+    assert_eq!(line_count(&debug), 72);
+    for vertex in debug.vertices(Vec3::X, Vec3::Y) {
+        assert!((Vec3::from(vertex.position).length() - 2.0).abs() < 1e-5);
+    }
+
+    debug.clear();
+    debug.grid(Vec3::ZERO, 10.0, 4, Vec4::ONE, 0.0);
+    debug.axes(Mat4::from_translation(Vec3::Y), 1.0, 0.0);
+    assert_eq!(line_count(&debug), 10 + 3);
+    let x_axis = &debug.vertices(Vec3::X, Vec3::Y)[20..22];
+    assert_eq!(x_axis[0].position, [0.0, 1.0, 0.0]);
+    assert_eq!(x_axis[1].position, [1.0, 1.0, 0.0]);
+    assert_eq!(x_axis[1].color, [1.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn expires_shapes_and_toggles() {
+    let mut debug = DebugDraw::new();
+    debug.line(Vec3::ZERO, Vec3::X, Vec4::ONE, 0.0);
+    debug.line(Vec3::ZERO, Vec3::Y, Vec4::ONE, 0.5);
+    assert_eq!(line_count(&debug), 2);
+
+    // Zero duration lasts one frame, the rest until their time is up
+    debug.end_frame(0.016);
+    assert_eq!(line_count(&debug), 1);
+    debug.end_frame(0.5);
+    assert!(debug.is_empty());
+
+    debug.line(Vec3::ZERO, Vec3::X, Vec4::ONE, 10.0);
+    debug.toggle();
+    assert!(!debug.enabled && debug.is_empty());
+    debug.text_3d(Vec3::ZERO, "IGNORED", Vec4::ONE, 0.0);
+    assert!(debug.is_empty());
+    debug.toggle();
+    debug.text_3d(Vec3::ZERO, "1", Vec4::ONE, 0.0);
+    assert_eq!(line_count(&debug), 3);
+}
+
+#[test]
+fn lays_out_segment_text() {
+    assert_eq!(glyph('8').count_ones(), 10);
+    assert_eq!(glyph('a'), glyph('A'));
+    assert_eq!(glyph('~'), 0);
+
+    // A single character is centered on the origin
+    let segments = text_segments("8");
+    let xs = segments.iter().flat_map(|(a, b)| [a[0], b[0]]);
+    let ys = segments.iter().flat_map(|(a, b)| [a[1], b[1]]);
+    assert_eq!(xs.clone().fold(f32::MAX, f32::min), -0.25);
+    assert_eq!(xs.fold(f32::MIN, f32::max), 0.25);
+    assert_eq!(ys.clone().fold(f32::MAX, f32::min), -0.5);
+    assert_eq!(ys.fold(f32::MIN, f32::max), 0.5);
+    assert_eq!(text_segments("8 8").len(), 2 * segments.len());
+
+    let module = wgpu::naga::front::wgsl::parse_str(DEBUG_SHADER).expect("debug shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("debug shader validates");
+}
@@ -0,0 +1,14 @@
+// Scrolling scanlines on top of the regular lighting
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    return engine_vertex(vertex);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let surface = material_surface(in);
+    let lines = 0.5 + 0.5 * sin(in.world_position.y * 40.0 - frame.time.x * 4.0);
+    let color = shade(surface, in.world_position) + vec3<f32>(0.0, 0.4, 0.6) * lines;
+    return vec4<f32>(color, surface.albedo.a);
+}
@@ -0,0 +1,76 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::components::{Fog, FogMode};
+use ZeroEngine::modules::render::shader::validate;
+use glam::Vec3;
+
+fn load_fog(name: &str, fog: &str) -> Result<Fog, String> {
+    let path = std::env::temp_dir().join(format!("zero_{}_{}.json", name, std::process::id()));
+    std::fs::write(
+        &path,
+        format!(r#"{{ "entities": [], "cameras": [], "fog": {} }}"#, fog),
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(path.to_string_lossy().into_owned());
+    std::fs::remove_file(&path).ok();
+    result.map(|_| engine.world.fog)
+}
+
+#[test]
+fn reads_fog_from_scene() {
+    assert!(!Engine::new().world.fog.enabled, "fog is off by default");
+
+    let fog = load_fog(
+        "fog_linear",
+        r#"{ "mode": "linear", "color": [0.6, 0.7, 0.8], "start": 20, "end": 150 }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        fog,
+        Fog {
+            enabled: true,
+            mode: FogMode::Linear,
+            color: Vec3::new(0.6, 0.7, 0.8),
+            start: 20.0,
+            end: 150.0,
+            ..Fog::default()
+        }
+    );
+
+    let fog = load_fog(
+        "fog_height",
+        r#"{ "mode": "height", "density": 0.05, "height": 2, "height_falloff": 0.3, "enabled": false }"#,
+    )
+    .unwrap();
+    assert!(!fog.enabled);
+    assert_eq!(fog.mode, FogMode::Height);
+    assert_eq!(
+        (fog.density, fog.height, fog.height_falloff),
+        (0.05, 2.0, 0.3)
+    );
+}
+
+#[test]
+fn rejects_unknown_fog_modes() {
+    let err = load_fog("fog_bad", r#"{ "mode": "volumetric" }"#).unwrap_err();
+    assert!(err.contains("volumetric"), "{}", err);
+}
+
+#[test]
+fn unlit_shaders_can_apply_fog() {
+    validate(
+        r#"
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    return engine_vertex(vertex);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = instances[in.instance].base_color;
+    return vec4<f32>(apply_fog(color.rgb, in.world_position), color.a);
+}
+"#,
+    )
+    .unwrap();
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::gltf::load_gltf;
+use ZeroEngine::modules::assets::{AssetStatus, Assets};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn flattens_nodes_meshes_and_cameras() {
+    let scene = load_gltf(fixture("hierarchy.gltf")).expect("hierarchy.gltf should load");
+
+    assert_eq!(scene.meshes.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Tri");
+    assert_eq!(mesh.data.positions.len(), 3);
+    assert_eq!(mesh.data.indices, vec![0, 1, 2]);
+    assert_eq!(mesh.material.diffuse, glam::Vec4::new(1.0, 0.5, 0.0, 1.0));
+
+    let names: Vec<_> = scene.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["Root", "Triangle", "Eye"]);
+    assert_eq!(scene.nodes[1].parent, Some(0));
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+
+    let camera = scene.nodes[2].camera.as_ref().expect("Eye has a camera");
+    assert!((camera.fov - 60.0).abs() < 1e-3);
+    assert_eq!(camera.far, 50.0);
+}
+
+#[test]
+fn loads_gltf_as_scene_with_hierarchy() {
+    let mut engine = Engine::new();
+    engine
+        .load_scene(fixture("hierarchy.gltf").to_string_lossy().into_owned())
+        .expect("scene should load");
+    engine.wait_for_scene();
+    assert!(engine.scene_progress().is_done());
+
+    assert_eq!(engine.world.entity_count(), 3);
+
+    let (triangle_id, triangle) = engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == "Triangle")
+        .expect("Triangle entity");
+    assert!(triangle.parent.is_some());
+    assert_eq!(triangle.material.unwrap().color.y, 0.5);
+
+    let origin = engine
+        .world
+        .world_matrix(triangle_id)
+        .transform_point3(glam::Vec3::ZERO);
+    assert!(origin.abs_diff_eq(glam::Vec3::new(1.0, 2.0, 3.0), 1e-5));
+
+    let renderables = engine.world.get_renderable_entities();
+    assert_eq!(renderables.len(), 1);
+    assert!(engine.world.active_camera_matrix(1.0).is_some());
+}
+
+#[test]
+fn shares_meshes_picked_by_name_or_index() {
+    let mut assets = Assets::with_root(fixture(""));
+    let by_name = assets.load_mesh("hierarchy.gltf#Tri");
+    let missing = assets.load_mesh("hierarchy.gltf#Quad");
+    assets.wait_all();
+    assert!(assets.meshes.is_loaded(by_name));
+    assert_eq!(assets.load_mesh("hierarchy.gltf#0"), by_name);
+    assert_eq!(assets.load_mesh("hierarchy.gltf#Tri"), by_name);
+    match assets.meshes.status(missing) {
+        Some(AssetStatus::Failed(message)) => assert!(message.contains("no mesh 'Quad'")),
+        other => panic!("expected failure, got {:?}", other),
+    }
+
+    // The meshes of an imported scene are found by index
+    let mut engine = Engine::new();
+    let path = fixture("hierarchy.gltf").to_string_lossy().into_owned();
+    engine.load_scene(path.clone()).expect("scene should load");
+    engine.wait_for_scene();
+    let mesh = engine.assets.load_mesh(&format!("{}#0", path));
+    assert!(engine.assets.meshes.is_loaded(mesh));
+    let renderable = engine.world.get_renderable_entities();
+    assert_eq!(renderable[0].0, mesh);
+}
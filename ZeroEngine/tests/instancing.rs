@@ -0,0 +1,63 @@
+use ZeroEngine::modules::assets::{CUBE_MESH, TRIANGLE_MESH, TextureHandle};
+use ZeroEngine::modules::ecs::components::Material;
+use ZeroEngine::modules::render::batching::{BatchKey, DrawBatch, batch_by_key};
+use glam::Vec4;
+
+#[test]
+fn batches_by_mesh_and_textures_not_by_color() {
+    let red = Material::new(Vec4::new(1.0, 0.0, 0.0, 1.0));
+    let blue = Material::new(Vec4::new(0.0, 0.0, 1.0, 1.0));
+    let mut textured = Material::new(Vec4::ONE);
+    textured.base_color_texture = Some(TextureHandle::from_id(7));
+
+    let cube = CUBE_MESH;
+    let triangle = TRIANGLE_MESH;
+    assert_eq!(BatchKey::new(cube, &red), BatchKey::new(cube, &blue));
+    assert_ne!(BatchKey::new(cube, &red), BatchKey::new(triangle, &red));
+    assert_ne!(BatchKey::new(cube, &red), BatchKey::new(cube, &textured));
+
+    // A thousand cubes in two colors are still a single draw
+    let keys = (0..1000).map(|i| {
+        let material = if i % 2 == 0 { &red } else { &blue };
+        (i, BatchKey::new(cube, material))
+    });
+    let (order, batches) = batch_by_key(keys, 0);
+    assert_eq!(order.len(), 1000);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].instances, 0..1000);
+}
+
+#[test]
+fn orders_instances_contiguously_per_batch() {
+    let items = [(0, 'a'), (1, 'b'), (2, 'a'), (3, 'c'), (4, 'b'), (5, 'a')];
+    let (order, batches) = batch_by_key(items, 10);
+
+    // Keys keep the order they first appear in
+    assert_eq!(order, vec![0, 2, 5, 1, 4, 3]);
+    assert_eq!(
+        batches,
+        vec![
+            DrawBatch {
+                key: 'a',
+                instances: 10..13
+            },
+            DrawBatch {
+                key: 'b',
+                instances: 13..15
+            },
+            DrawBatch {
+                key: 'c',
+                instances: 15..16
+            },
+        ]
+    );
+    for batch in &batches {
+        for instance in batch.instances.clone() {
+            let item = order[(instance - 10) as usize];
+            assert_eq!(items[item].1, batch.key);
+        }
+    }
+
+    let (order, batches) = batch_by_key(std::iter::empty::<(usize, u8)>(), 0);
+    assert!(order.is_empty() && batches.is_empty());
+}
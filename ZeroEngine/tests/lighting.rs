@@ -0,0 +1,83 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::MeshData;
+use ZeroEngine::modules::ecs::components::LightKind;
+use glam::Vec3;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn loads_lights_and_ambient_from_scene() {
+    let mut engine = Engine::new();
+    engine
+        .load_scene(fixture("lit.json"))
+        .expect("scene should load");
+
+    assert_eq!(engine.world.ambient_light, Vec3::new(0.05, 0.05, 0.1));
+
+    let lights = engine.world.get_lights();
+    assert_eq!(lights.len(), 3);
+
+    let (point, position, _) = lights
+        .iter()
+        .find(|(l, _, _)| l.kind == LightKind::Point)
+        .expect("point light on the lamp");
+    assert_eq!(point.intensity, 8.0);
+    assert_eq!(point.range, 6.0);
+    assert!(position.abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-5));
+
+    let (sun, _, direction) = lights
+        .iter()
+        .find(|(l, _, _)| l.kind == LightKind::Directional)
+        .expect("sun");
+    assert_eq!(sun.color, Vec3::ONE);
+    assert!(direction.abs_diff_eq(Vec3::new(0.0, -1.0, -1.0).normalize(), 1e-5));
+
+    let (spot, _, direction) = lights
+        .iter()
+        .find(|(l, _, _)| l.kind == LightKind::Spot)
+        .expect("torch");
+    assert_eq!((spot.inner_angle, spot.outer_angle), (15.0, 25.0));
+    assert!(direction.abs_diff_eq(-Vec3::Y, 1e-5));
+
+    // The lamp keeps its mesh and is still drawn
+    assert_eq!(engine.world.get_renderable_entities().len(), 2);
+}
+
+#[test]
+fn rejects_unknown_light_types() {
+    let mut engine = Engine::new();
+    let err = engine
+        .load_scene(fixture("bad_light.json"))
+        .expect_err("area lights are not supported");
+    assert!(err.contains("Strobe") && err.contains("area"), "{}", err);
+}
+
+#[test]
+fn generates_smooth_normals_for_meshes_without_them() {
+    // Two triangles folded along the shared edge 0-1
+    let mesh = MeshData {
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ],
+        indices: vec![0, 1, 2, 1, 0, 3],
+        ..Default::default()
+    };
+
+    let normals = mesh.smooth_normals();
+    assert_eq!(normals.len(), 4);
+    assert_eq!(normals[2], [0.0, 0.0, 1.0]);
+    assert_eq!(normals[3], [0.0, 1.0, 0.0]);
+
+    let shared = Vec3::from(normals[0]);
+    assert!(shared.abs_diff_eq(Vec3::new(0.0, 1.0, 1.0).normalize(), 1e-5));
+}
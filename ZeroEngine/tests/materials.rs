@@ -0,0 +1,127 @@
+use ZeroEngine::modules::assets::material::parse_material;
+use ZeroEngine::modules::assets::obj::parse_mtl;
+use ZeroEngine::modules::assets::{AssetId, Assets, TextureAsset};
+use ZeroEngine::modules::ecs::components::{TextureFilter, TextureWrap};
+use ZeroEngine::modules::ecs::entity::{MeshType, spawn_entity};
+use ZeroEngine::modules::ecs::world::World;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+#[test]
+fn loads_material_file_and_its_textures() {
+    let mut assets = Assets::with_root(fixtures());
+
+    let handle = assets.load_material("brick.material");
+    assert_eq!(assets.load_material("./brick.material"), handle);
+    let material = *assets.materials.get(handle).unwrap();
+    assert_eq!(material.metallic, 0.25);
+    assert_eq!(material.roughness, 0.8);
+    assert_eq!(material.sampler.filter, TextureFilter::Nearest);
+    assert_eq!(material.sampler.wrap, TextureWrap::ClampToEdge);
+    assert!(material.emissive_texture.is_none());
+
+    // Texture paths are relative to the material file
+    let base = material.base_color_texture.unwrap();
+    assert_eq!(base, assets.load_texture("textures/checker.png"));
+
+    assets.wait_all();
+    let texture = assets.textures.get(base).unwrap();
+    assert_eq!((texture.width, texture.height), (2, 2));
+    assert_eq!(&texture.pixels[4..8], &[0, 0, 0, 255]);
+    assert!(assets.status(AssetId::Material(handle)).is_some());
+
+    let mut uploads = assets.take_texture_uploads();
+    uploads.sort_by_key(|h| h.id());
+    assert_eq!(uploads, vec![base, material.normal_texture.unwrap()]);
+}
+
+#[test]
+fn rejects_unknown_material_keys() {
+    let dir = Some(Path::new("assets"));
+
+    let err = parse_material(r#"{ "shininess": 32 }"#, dir).unwrap_err();
+    assert!(err.contains("shininess"), "{}", err);
+    let err = parse_material(r#"{ "sampler": { "filter": "cubic" } }"#, dir).unwrap_err();
+    assert!(err.contains("cubic"), "{}", err);
+
+    let material = parse_material(r#"{ "emissive_texture": "glow.png" }"#, dir).unwrap();
+    assert_eq!(material.roughness, 0.5);
+    assert_eq!(
+        material.emissive_texture,
+        Some(PathBuf::from("assets/glow.png"))
+    );
+}
+
+#[test]
+fn builds_mip_chain_by_averaging() {
+    let texture = TextureAsset {
+        width: 4,
+        height: 2,
+        pixels: [[255, 0, 0, 255], [0, 0, 255, 255]].repeat(4).concat(),
+    };
+    assert_eq!(texture.mip_level_count(), 3);
+
+    let level1 = texture.downsample();
+    assert_eq!((level1.width, level1.height), (2, 1));
+    assert_eq!(&level1.pixels[0..4], &[128, 0, 128, 255]);
+
+    let level2 = level1.downsample();
+    assert_eq!((level2.width, level2.height), (1, 1));
+    assert_eq!(level2.downsample().pixels.len(), 4);
+}
+
+#[test]
+fn reads_pbr_extensions_from_mtl() {
+    let source = "newmtl Metal\nKd 1 1 1\nNs 100\nPm 1.0\nKe 0.5 0.0 0.0\nmap_Ke glow.png\nnorm metal_n.png\n\
+                  newmtl Rough\nPr 0.75\nNs 1000\n";
+    let materials = parse_mtl(source, Some(Path::new("models"))).unwrap();
+
+    let metal = &materials["Metal"];
+    assert_eq!(metal.metallic, 1.0);
+    assert!((metal.roughness - (2.0f32 / 102.0).sqrt()).abs() < 1e-6);
+    assert_eq!(metal.emissive, glam::Vec3::new(0.5, 0.0, 0.0));
+    assert_eq!(
+        metal.emissive_texture,
+        Some(PathBuf::from("models/glow.png"))
+    );
+    assert_eq!(
+        metal.normal_texture,
+        Some(PathBuf::from("models/metal_n.png"))
+    );
+
+    assert_eq!(materials["Rough"].roughness, 0.75);
+}
+
+#[test]
+fn frees_textures_once_unreferenced() {
+    let mut assets = Assets::with_root(fixtures());
+    let mut world = World::new();
+
+    let handle = assets.load_material("brick.material");
+    assets.wait_all();
+    let material = *assets.materials.get(handle).unwrap();
+    let entity = spawn_entity(
+        &mut world,
+        "wall",
+        glam::Vec3::ZERO,
+        glam::Vec3::ONE,
+        MeshType::Cube,
+        glam::Vec4::ONE,
+    );
+    world.get_entity_mut(entity).unwrap().add_material(material);
+
+    assets.update_ref_counts(&world);
+    assets.collect_unused();
+    let base = material.base_color_texture.unwrap();
+    assert_eq!(assets.textures.ref_count(base), 1);
+    assert!(assets.take_freed_textures().is_empty());
+
+    world.remove_entity(entity);
+    assets.update_ref_counts(&world);
+    assets.collect_unused();
+    assert_eq!(assets.take_freed_textures().len(), 2);
+    assert!(assets.textures.get(base).is_none());
+}
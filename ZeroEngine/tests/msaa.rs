@@ -0,0 +1,41 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::world::World;
+use ZeroEngine::modules::render::msaa::{DEFAULT_SAMPLE_COUNT, validate_sample_count};
+
+fn load_scene_source(name: &str, source: &str) -> Result<Engine, String> {
+    let path = std::env::temp_dir().join(format!("zero_{}_{}.json", name, std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(path.to_string_lossy().into_owned());
+    std::fs::remove_file(&path).ok();
+    result.map(|_| engine)
+}
+
+#[test]
+fn validates_sample_counts_against_support() {
+    let supported = [1, 4];
+    assert_eq!(validate_sample_count(4, &supported), Ok(4));
+    assert_eq!(validate_sample_count(1, &supported), Ok(1));
+
+    let err = validate_sample_count(8, &supported).unwrap_err();
+    assert!(err.contains("isn't supported"), "{}", err);
+    let err = validate_sample_count(3, &[1, 2, 4, 8]).unwrap_err();
+    assert!(err.contains("1, 2, 4 or 8"), "{}", err);
+}
+
+#[test]
+fn reads_msaa_from_scene_file() {
+    assert_eq!(World::new().msaa_samples, DEFAULT_SAMPLE_COUNT);
+
+    let engine =
+        load_scene_source("msaa", r#"{ "entities": [], "cameras": [], "msaa": 8 }"#).unwrap();
+    assert_eq!(engine.world.msaa_samples, 8);
+
+    let err = load_scene_source(
+        "bad_msaa",
+        r#"{ "entities": [], "cameras": [], "msaa": 16 }"#,
+    )
+    .err()
+    .expect("16 samples are rejected");
+    assert!(err.contains("16"), "{}", err);
+}
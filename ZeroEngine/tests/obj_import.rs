@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+use ZeroEngine::modules::assets::obj::{load_obj, parse_obj};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn loads_cube_with_normals_and_material() {
+    let model = load_obj(fixture("cube.obj")).expect("cube.obj should load");
+
+    assert_eq!(model.meshes.len(), 1);
+    let mesh = &model.meshes[0];
+    assert_eq!(mesh.name, "Cube");
+    assert_eq!(mesh.material.as_deref(), Some("Red"));
+
+    // 6 quads with distinct normals: 4 unique corners each, 2 triangles each
+    assert_eq!(mesh.data.positions.len(), 24);
+    assert_eq!(mesh.data.normals.len(), 24);
+    assert!(mesh.data.uvs.is_empty());
+    assert_eq!(mesh.data.indices.len(), 36);
+
+    let red = &model.materials["Red"];
+    assert_eq!(red.diffuse, glam::Vec4::new(0.8, 0.1, 0.1, 0.5));
+    assert_eq!(red.shininess, 32.0);
+    assert_eq!(
+        red.diffuse_texture.as_deref(),
+        Some(fixture("textures/red.png").as_path())
+    );
+}
+
+#[test]
+fn splits_groups_and_resolves_negative_indices() {
+    let model = load_obj(fixture("groups.obj")).expect("groups.obj should load");
+
+    assert_eq!(model.meshes.len(), 2);
+    assert_eq!(model.meshes[0].material.as_deref(), Some("Blue"));
+    assert_eq!(model.meshes[0].data.indices, vec![0, 1, 2]);
+
+    let quad = &model.meshes[1];
+    assert_eq!(quad.name, "Quad");
+    assert_eq!(quad.material.as_deref(), Some("Green"));
+    assert_eq!(quad.data.positions.len(), 4);
+    // `vt 1 1` with V flipped to a top-left origin
+    assert_eq!(quad.data.uvs[2], [1.0, 0.0]);
+    assert_eq!(quad.data.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(model.materials["Green"].diffuse.w, 0.75);
+
+    let merged = model.merged();
+    assert_eq!(merged.positions.len(), 7);
+    assert_eq!(merged.uvs.len(), 7);
+    assert_eq!(&merged.indices[3..], &[3, 4, 5, 3, 5, 6]);
+}
+
+#[test]
+fn reports_line_of_out_of_range_index() {
+    let err = load_obj(fixture("bad_index.obj")).err().expect("should fail");
+    assert_eq!(err.line, 5);
+    assert!(err.message.contains("out of range"), "{}", err);
+    assert!(err.to_string().contains("bad_index.obj:5:"), "{}", err);
+}
+
+#[test]
+fn reports_line_of_invalid_number() {
+    let err = load_obj(fixture("bad_number.obj")).err().expect("should fail");
+    assert_eq!(err.line, 3);
+    assert!(err.message.contains("'zero'"), "{}", err);
+}
+
+#[test]
+fn reports_errors_inside_material_libraries() {
+    let err = load_obj(fixture("bad_material.obj")).err().expect("should fail");
+    assert_eq!(err.line, 2);
+    assert!(err.to_string().contains("bad.mtl:2:"), "{}", err);
+}
+
+#[test]
+fn rejects_degenerate_faces() {
+    let err = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n", None).err().expect("should fail");
+    assert_eq!(err.line, 3);
+}
@@ -0,0 +1,137 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::components::BlendMode;
+use ZeroEngine::modules::ecs::particles::{
+    ParticleBurst, ParticleEmitter, ParticleSimulation, update_particles,
+};
+use ZeroEngine::modules::render::particles::{PARTICLE_SHADER, PARTICLE_SIMULATE};
+use ZeroEngine::modules::render::shader::compose;
+use glam::{Mat4, Vec3};
+
+fn validate_wgsl(source: &str) {
+    let module = wgpu::naga::front::wgsl::parse_str(source).expect("shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("shader validates");
+}
+
+#[test]
+fn spawns_at_rate_and_in_bursts() {
+    let mut emitter = ParticleEmitter::default();
+    emitter.rate = 10.0;
+    emitter.lifetime = (5.0, 5.0);
+    emitter.max_particles = 50;
+    emitter.bursts = vec![ParticleBurst {
+        time: 0.25,
+        count: 20,
+    }];
+    let at = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+    emitter.update(&at, 0.2, ParticleSimulation::Cpu);
+    assert_eq!(emitter.alive_count(), 2);
+    emitter.update(&at, 0.1, ParticleSimulation::Cpu);
+    assert_eq!(emitter.alive_count(), 23, "scheduled burst fired");
+    assert_eq!(emitter.spawned().len(), 1);
+    assert_eq!(emitter.spawned()[0], 2..23);
+
+    // Script bursts come on top, the ring buffer caps the total
+    emitter.burst(100);
+    emitter.update(&at, 0.0, ParticleSimulation::Cpu);
+    assert_eq!(emitter.alive_count(), 50);
+
+    emitter.enabled = false;
+    emitter.update(&at, 10.0, ParticleSimulation::Cpu);
+    assert_eq!(emitter.alive_count(), 0, "everything outlived its lifetime");
+}
+
+#[test]
+fn cpu_simulation_applies_gravity_inside_the_cone() {
+    let mut emitter = ParticleEmitter::default();
+    emitter.rate = 0.0;
+    emitter.speed = (2.0, 2.0);
+    emitter.cone_angle = 10.0;
+    emitter.gravity = Vec3::new(0.0, -10.0, 0.0);
+    emitter.burst(64);
+    emitter.update(&Mat4::IDENTITY, 0.0, ParticleSimulation::Cpu);
+    let cos_cone = 10f32.to_radians().cos();
+    for particle in emitter.particles().iter().filter(|p| p.is_alive()) {
+        assert!(particle.velocity().normalize().dot(Vec3::Y) >= cos_cone - 1e-5);
+        assert!((particle.velocity().length() - 2.0).abs() < 1e-5);
+    }
+
+    // The GPU path leaves motion to the compute shader
+    emitter.update(&Mat4::IDENTITY, 0.5, ParticleSimulation::Gpu);
+    assert!(
+        emitter.particles()[0]
+            .position()
+            .abs_diff_eq(Vec3::ZERO, 1e-6)
+    );
+
+    let before = emitter.particles()[0];
+    emitter.update(&Mat4::IDENTITY, 0.1, ParticleSimulation::Cpu);
+    let after = emitter.particles()[0];
+    assert!((after.velocity().y - (before.velocity().y - 1.0)).abs() < 1e-5);
+    assert!((after.position_age[3] - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn reads_emitters_from_scene() {
+    let path = std::env::temp_dir().join(format!("zero_particles_{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "emitters": [{
+                "name": "Sparks",
+                "position": [0, 1, 0],
+                "rate": 0,
+                "bursts": [{ "time": 0.5, "count": 200 }, { "time": 0.1, "count": 5 }],
+                "speed": [4, 6],
+                "gravity": [0, -9.8, 0],
+                "end_color": [1, 0.2, 0, 0],
+                "blend": "additive"
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(path.to_string_lossy().into_owned());
+    std::fs::remove_file(&path).ok();
+    result.unwrap();
+
+    let (id, _) = engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == "Sparks")
+        .unwrap();
+    let emitter = engine
+        .world
+        .get_entity(id)
+        .unwrap()
+        .particle_emitter
+        .as_ref()
+        .unwrap();
+    assert_eq!(emitter.rate, 0.0);
+    assert_eq!(emitter.speed, (4.0, 6.0));
+    assert_eq!(emitter.blend, BlendMode::Additive);
+    assert_eq!(emitter.bursts[0].count, 5, "bursts are sorted by time");
+
+    update_particles(&mut engine.world, 0.2, ParticleSimulation::Cpu);
+    let emitter = engine
+        .world
+        .get_entity(id)
+        .unwrap()
+        .particle_emitter
+        .as_ref()
+        .unwrap();
+    assert_eq!(emitter.alive_count(), 5);
+    assert!(emitter.particles()[0].position().y > 1.0);
+}
+
+#[test]
+fn validates_particle_shaders() {
+    validate_wgsl(PARTICLE_SIMULATE);
+    validate_wgsl(&compose(PARTICLE_SHADER));
+}
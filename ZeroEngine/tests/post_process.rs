@@ -0,0 +1,109 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::AssetStatus;
+use ZeroEngine::modules::ecs::components::{PostProcess, Tonemapper};
+use ZeroEngine::modules::render::post::{
+    MAX_BLOOM_MIPS, POST_SHADER, PostUniformData, bloom_mip_count,
+};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn reads_scene_and_camera_post_processing() {
+    let mut engine = Engine::new();
+    engine
+        .load_scene(fixture("post_process.json"))
+        .expect("scene should load");
+    engine.wait_for_scene();
+
+    let scene = engine.world.post_process;
+    assert_eq!(scene.exposure, 1.5);
+    assert_eq!(scene.tonemapper, Tonemapper::Aces);
+    assert!(scene.bloom.enabled);
+    assert_eq!(scene.bloom.threshold, 0.8);
+    assert!(!scene.vignette.enabled);
+    assert_eq!(scene.vignette.intensity, 0.6);
+    assert!(scene.color_grading.enabled);
+    assert_eq!(scene.color_grading.strength, 0.5);
+    assert!(!scene.fxaa);
+
+    let lut = scene
+        .color_grading
+        .lut
+        .expect("LUT is loaded with the scene");
+    assert!(matches!(
+        engine.assets.textures.status(lut),
+        Some(AssetStatus::Loaded)
+    ));
+    assert_eq!(engine.assets.textures.get(lut).unwrap().width, 16);
+
+    // The active camera's own settings win until they are removed at runtime
+    let active = *engine.world.active_post_process();
+    assert_eq!(active.tonemapper, Tonemapper::Reinhard);
+    assert!(active.fxaa && !active.bloom.enabled);
+
+    for (_, entity) in engine.world.iter_entities_mut() {
+        if let Some(camera) = &mut entity.camera {
+            camera.post_process = None;
+        }
+    }
+    assert_eq!(*engine.world.active_post_process(), scene);
+}
+
+#[test]
+fn rejects_unknown_tonemapper() {
+    let path = std::env::temp_dir().join(format!("zero_bad_post_{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{ "entities": [], "cameras": [], "post_process": { "tonemapper": "filmic" } }"#,
+    )
+    .unwrap();
+
+    let err = Engine::new()
+        .load_scene(path.to_string_lossy().into_owned())
+        .unwrap_err();
+    assert!(err.contains("filmic"), "{}", err);
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn encodes_disabled_effects_as_zero() {
+    let mut settings = PostProcess::default();
+    settings.vignette.intensity = 0.7;
+    let off = PostUniformData::new(&settings, Some(16));
+    assert_eq!(off.exposure_tonemap_bloom, [1.0, 2.0, 0.0, 1.0]);
+    assert_eq!(off.vignette_grading[0], 0.0);
+    assert_eq!(off.vignette_grading[2], 0.0);
+
+    settings.bloom.enabled = true;
+    settings.vignette.enabled = true;
+    settings.color_grading.enabled = true;
+    let on = PostUniformData::new(&settings, Some(16));
+    assert_eq!(on.exposure_tonemap_bloom[2], settings.bloom.intensity);
+    assert_eq!(on.vignette_grading, [0.7, 0.5, 1.0, 16.0]);
+
+    // Grading waits for the LUT to reach the GPU
+    let pending = PostUniformData::new(&settings, None);
+    assert_eq!(pending.vignette_grading[2], 0.0);
+}
+
+#[test]
+fn sizes_bloom_chain_and_validates_shader() {
+    assert_eq!(bloom_mip_count(1, 1), 1);
+    assert_eq!(bloom_mip_count(64, 64), 3);
+    assert_eq!(bloom_mip_count(1920, 1080), MAX_BLOOM_MIPS);
+
+    let module = wgpu::naga::front::wgsl::parse_str(POST_SHADER).expect("post shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("post shader validates");
+}
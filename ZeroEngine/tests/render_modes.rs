@@ -0,0 +1,58 @@
+use ZeroEngine::modules::render::modes::{MODES_SHADER, RenderMode, unindex};
+use ZeroEngine::modules::render::shader::compose;
+
+#[test]
+fn cycles_through_modes_by_name() {
+    let mut mode = RenderMode::default();
+    assert_eq!(mode, RenderMode::Lit);
+    let mut seen = Vec::new();
+    for _ in 0..RenderMode::ALL.len() {
+        seen.push(mode.name());
+        mode = mode.next();
+    }
+    assert_eq!(mode, RenderMode::Lit);
+    assert_eq!(seen, ["lit", "wireframe", "normals", "depth", "overdraw"]);
+
+    for mode in RenderMode::ALL {
+        assert_eq!(RenderMode::from_name(mode.name()), Ok(mode));
+    }
+    let err = RenderMode::from_name("xray").unwrap_err();
+    assert!(err.contains("xray"), "{}", err);
+}
+
+#[test]
+fn expands_indexed_triangles_for_barycentric_wireframe() {
+    let quad = ['a', 'b', 'c', 'd'];
+    let expanded = unindex(&quad, &[0, 1, 2, 2, 3, 0]);
+    assert_eq!(expanded, vec!['a', 'b', 'c', 'c', 'd', 'a']);
+    assert!(unindex(&quad, &[]).is_empty());
+}
+
+#[test]
+fn validates_mode_shader_with_prelude() {
+    let module = wgpu::naga::front::wgsl::parse_str(&compose(MODES_SHADER))
+        .expect("render mode shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("render mode shader validates");
+
+    let entry_points: Vec<&str> = module
+        .entry_points
+        .iter()
+        .map(|ep| ep.name.as_str())
+        .collect();
+    for name in [
+        "vs_main",
+        "vs_barycentric",
+        "fs_line",
+        "fs_barycentric",
+        "fs_normals",
+        "fs_depth",
+        "fs_overdraw",
+    ] {
+        assert!(entry_points.contains(&name), "missing {}", name);
+    }
+}
@@ -0,0 +1,147 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::Assets;
+use ZeroEngine::modules::assets::material::parse_material;
+use ZeroEngine::modules::ecs::entity::{RenderTarget, TargetSize, Viewport, spawn_camera};
+use ZeroEngine::modules::ecs::world::World;
+use ZeroEngine::modules::render::cameras::{camera_targets, camera_views};
+use glam::Vec3;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn names_render_targets_like_textures() {
+    let mut assets = Assets::with_root(std::env::temp_dir());
+    let monitor = assets.render_target("monitor");
+    assert_eq!(assets.render_target("monitor"), monitor);
+    assert_eq!(assets.load_texture("target:monitor"), monitor);
+    assert_ne!(assets.render_target("mirror"), monitor);
+
+    // Nothing refers to it yet, but the renderer owns its contents
+    assets.collect_unused();
+    assert!(assets.textures.get(monitor).is_some());
+
+    // Material files name targets without a path
+    let file = parse_material(
+        r#"{ "emissive_texture": "target:monitor" }"#,
+        Some(Path::new("materials")),
+    )
+    .unwrap();
+    assert_eq!(file.emissive_texture, Some(PathBuf::from("target:monitor")));
+}
+
+#[test]
+fn sizes_target_cameras_by_their_target() {
+    assert_eq!(
+        TargetSize::Fixed {
+            width: 256,
+            height: 128
+        }
+        .resolve(1920, 1080),
+        (256, 128)
+    );
+    assert_eq!(
+        TargetSize::Window { scale: 0.5 }.resolve(1920, 1080),
+        (960, 540)
+    );
+    assert_eq!(TargetSize::Window { scale: 0.0 }.resolve(800, 600), (1, 1));
+
+    let mut world = World::new();
+    let mut assets = Assets::new();
+    let monitor = RenderTarget {
+        texture: assets.render_target("monitor"),
+        size: TargetSize::Fixed {
+            width: 256,
+            height: 128,
+        },
+    };
+    let security = spawn_camera(&mut world, "Security", Vec3::Y, Vec3::ZERO, 60.0, 0.1, 50.0);
+    let camera = world
+        .get_entity_mut(security)
+        .unwrap()
+        .camera
+        .as_mut()
+        .unwrap();
+    camera.target = Some(monitor);
+    camera.viewport = Viewport::new(0.0, 0.0, 0.5, 1.0);
+
+    // A target camera alone leaves the window to the fallback view
+    let views = camera_views(&world, 800, 600);
+    assert_eq!(views.len(), 2);
+    assert_eq!(views[0].target, Some(monitor.texture));
+    assert_eq!(views[0].viewport, [0.0, 0.0, 128.0, 128.0]);
+    assert_eq!(views[1].target, None);
+    assert_eq!(views[1].viewport, [0.0, 0.0, 800.0, 600.0]);
+
+    // Switched off cameras keep their target alive
+    let camera = world
+        .get_entity_mut(security)
+        .unwrap()
+        .camera
+        .as_mut()
+        .unwrap();
+    camera.is_active = false;
+    assert_eq!(camera_targets(&world), vec![monitor]);
+}
+
+#[test]
+fn reads_camera_targets_from_scene() {
+    let path = std::env::temp_dir().join(format!("zero_targets_{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{
+            "entities": [],
+            "cameras": [
+                {
+                    "name": "Security", "fov": 60.0, "near": 0.1, "far": 50.0,
+                    "active": true, "tags": [],
+                    "transform": { "position": [0, 3, 0], "rotation": [-0.5, 0, 0], "scale": [1, 1, 1] },
+                    "target": { "name": "monitor", "width": 320, "height": 240 }
+                },
+                {
+                    "name": "Mirror", "fov": 60.0, "near": 0.1, "far": 50.0,
+                    "active": true, "tags": [],
+                    "transform": { "position": [0, 1, 0], "rotation": [0, 3.14, 0], "scale": [1, 1, 1] },
+                    "target": { "name": "mirror", "scale": 0.5 }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    engine
+        .load_scene(path.to_string_lossy().into_owned())
+        .expect("scene should load");
+    std::fs::remove_file(&path).ok();
+
+    let targets = camera_targets(&engine.world);
+    assert_eq!(
+        targets,
+        vec![
+            RenderTarget {
+                texture: engine.assets.render_target("monitor"),
+                size: TargetSize::Fixed {
+                    width: 320,
+                    height: 240
+                },
+            },
+            RenderTarget {
+                texture: engine.assets.render_target("mirror"),
+                size: TargetSize::Window { scale: 0.5 },
+            },
+        ]
+    );
+
+    std::fs::write(
+        &path,
+        r#"{ "entities": [], "cameras": [{
+            "name": "C", "fov": 60.0, "near": 0.1, "far": 100.0, "active": true, "tags": [],
+            "transform": { "position": [0, 0, 0], "rotation": [0, 0, 0], "scale": [1, 1, 1] },
+            "target": { "name": "half", "width": 512 }
+        }] }"#,
+    )
+    .unwrap();
+    let err = Engine::new()
+        .load_scene(path.to_string_lossy().into_owned())
+        .unwrap_err();
+    std::fs::remove_file(&path).ok();
+    assert!(err.contains("half"), "{}", err);
+}
@@ -0,0 +1,206 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::console::log_lines;
+use ZeroEngine::modules::ecs::systems::{handle_scripts, init_scripts};
+use ZeroEngine::modules::ecs::ui::UiWidgetKind;
+use ZeroEngine::modules::render::debug_draw::DebugDraw;
+use glam::{Vec3, Vec4};
+use std::sync::Mutex;
+
+// Host functions reach the world through one global pointer, so scripts of
+// different tests must not run at the same time
+static SCRIPTS: Mutex<()> = Mutex::new(());
+
+/// Load `scene` next to `script.wat` holding `wat`, then run the scripts'
+/// `init`. AssemblyScript strings are laid out by hand in the modules: their
+/// size in bytes, then the UTF-16 text.
+fn run_script(name: &str, scene: &str, wat: &str) -> Engine {
+    let _running = SCRIPTS.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir().join(format!("zero_scripts_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("script.wat"), wat).unwrap();
+    std::fs::write(dir.join("scene.json"), scene).unwrap();
+
+    let mut engine = Engine::new();
+    engine
+        .load_scene(dir.join("scene.json").to_string_lossy().into_owned())
+        .unwrap();
+    engine.assets.load_script("script.wat");
+    engine.assets.wait_all();
+    init_scripts(&mut engine.world, &mut engine.scripts, &mut engine.assets).unwrap();
+    engine
+}
+
+fn update_scripts(engine: &mut Engine) {
+    let _running = SCRIPTS.lock().unwrap_or_else(|e| e.into_inner());
+    handle_scripts(
+        &mut engine.world,
+        &mut engine.scripts,
+        &mut engine.assets,
+        0.1,
+    )
+    .unwrap();
+}
+
+const RUNNER: &str = r#"{
+    "entities": [
+        { "name": "Runner", "mesh": "cube", "position": [0, 0, 0],
+          "scale": [1, 1, 1], "color": [1, 1, 1, 1], "scripts": ["script.wat"] }
+    ],
+    "cameras": []
+}"#;
+
+#[test]
+fn draws_debug_text_from_scripts() {
+    let engine = run_script(
+        "debug_text",
+        RUNNER,
+        r#"(module
+            (import "context" "debug_text_3d"
+                (func $text (param i32 f32 f32 f32 f32 f32 f32 f32 f32)))
+            (memory (export "memory") 1)
+            (data (i32.const 12) "\04\00\00\00H\00I\00")
+            (func (export "init")
+                (call $text (i32.const 16)
+                    (f32.const 0) (f32.const 1) (f32.const 0)
+                    (f32.const 1) (f32.const 0) (f32.const 0) (f32.const 1)
+                    (f32.const 0)))
+            (func (export "update") (param f32)))"#,
+    );
+
+    let mut expected = DebugDraw::new();
+    expected.text_3d(Vec3::Y, "HI", Vec4::new(1.0, 0.0, 0.0, 1.0), 0.0);
+    assert_eq!(
+        engine.world.debug_draw.vertices(Vec3::X, Vec3::Y),
+        expected.vertices(Vec3::X, Vec3::Y)
+    );
+}
+
+#[test]
+fn sets_text_and_hud_labels_from_scripts() {
+    let mut engine = run_script(
+        "text",
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "texts": [{ "name": "Sign", "text": "Score: 0", "scripts": ["script.wat"] }]
+        }"#,
+        r#"(module
+            (import "context" "text_set" (func $text_set (param i32)))
+            (import "context" "hud_set_text" (func $hud_set_text (param i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 12) "\10\00\00\00S\00c\00o\00r\00e\00:\00 \007\00")
+            (data (i32.const 36) "\0a\00\00\00s\00c\00o\00r\00e\00")
+            (data (i32.const 60) "\10\00\00\00L\00i\00v\00e\00s\00:\00 \003\00")
+            (func (export "init")
+                (call $text_set (i32.const 16))
+                (call $hud_set_text (i32.const 40) (i32.const 64)))
+            ;; Pointers before the header or past the end of memory
+            (func (export "update") (param f32)
+                (call $text_set (i32.const 2))
+                (call $text_set (i32.const 65534))))"#,
+    );
+
+    let sign = engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == "Sign")
+        .map(|(id, _)| id)
+        .unwrap();
+    let text = |engine: &Engine| {
+        let entity = engine.world.get_entity(sign).unwrap();
+        entity.text.as_ref().unwrap().text.clone()
+    };
+    assert_eq!(text(&engine), "Score: 7");
+    assert_eq!(engine.world.hud.get("score").unwrap().text, "Lives: 3");
+
+    // Bad strings trap the script instead of the engine
+    update_scripts(&mut engine);
+    assert_eq!(text(&engine), "Score: 7");
+    assert!(
+        log_lines()
+            .iter()
+            .any(|line| line.text.starts_with("Failed to update script"))
+    );
+}
+
+#[test]
+fn edits_scene_ui_widgets_from_scripts() {
+    let engine = run_script(
+        "ui",
+        r#"{
+            "entities": [
+                { "name": "Runner", "mesh": "cube", "position": [0, 0, 0],
+                  "scale": [1, 1, 1], "color": [1, 1, 1, 1], "scripts": ["script.wat"] }
+            ],
+            "cameras": [],
+            "ui": [{ "name": "status", "widgets": [
+                { "type": "label", "id": "score", "text": "Score: 0" }
+            ] }]
+        }"#,
+        r#"(module
+            (import "context" "ui_set_text" (func $ui_set_text (param i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 12) "\0a\00\00\00s\00c\00o\00r\00e\00")
+            (data (i32.const 36) "\10\00\00\00S\00c\00o\00r\00e\00:\00 \009\00")
+            (func (export "init")
+                (call $ui_set_text (i32.const 16) (i32.const 40)))
+            (func (export "update") (param f32)))"#,
+    );
+
+    assert_eq!(
+        engine.world.ui.widget("score").unwrap().kind,
+        UiWidgetKind::Label {
+            text: "Score: 9".into()
+        }
+    );
+}
+
+#[test]
+fn edits_tilemap_layers_by_name_from_scripts() {
+    let dir = std::env::temp_dir().join(format!("zero_scripts_tilemap_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("level.tmx"),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="8" columns="4">
+  <image source="terrain.png" width="64" height="32"/>
+ </tileset>
+ <layer id="1" name="Ground" width="3" height="2">
+  <data encoding="csv">4,0,0,0,0,0</data>
+ </layer>
+</map>"#,
+    )
+    .unwrap();
+    let engine = run_script(
+        "tilemap",
+        r#"{
+            "entities": [
+                { "name": "Runner", "mesh": "cube", "position": [0, 0, 0],
+                  "scale": [1, 1, 1], "color": [1, 1, 1, 1], "scripts": ["script.wat"] }
+            ],
+            "cameras": [],
+            "tilemaps": [{ "name": "Level", "path": "level.tmx" }]
+        }"#,
+        r#"(module
+            (import "context" "tilemap_get_tile" (func $get (param i32 i32 i32) (result i32)))
+            (import "context" "tilemap_set_tile" (func $set (param i32 i32 i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 12) "\0c\00\00\00G\00r\00o\00u\00n\00d\00")
+            (func (export "init")
+                (call $set (i32.const 16) (i32.const 2) (i32.const 1) (i32.const 3))
+                (call $set (i32.const 16) (i32.const 1) (i32.const 1)
+                    (i32.add (call $get (i32.const 16) (i32.const 0) (i32.const 0))
+                        (i32.const 1))))
+            (func (export "update") (param f32)))"#,
+    );
+
+    let tilemap = engine
+        .world
+        .iter_entities()
+        .find(|(_, e)| e.name == "Ground")
+        .and_then(|(_, e)| e.tilemap.as_ref())
+        .unwrap();
+    assert_eq!(tilemap.tile(2, 1).unwrap().gid, 3);
+    assert_eq!(tilemap.tile(1, 1).unwrap().gid, 5);
+}
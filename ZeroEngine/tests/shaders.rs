@@ -0,0 +1,94 @@
+use ZeroEngine::modules::assets::{AssetId, AssetStatus, Assets};
+use ZeroEngine::modules::render::shader::{DEFAULT_SHADER, validate};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+#[test]
+fn accepts_default_and_custom_shaders() {
+    validate(DEFAULT_SHADER).unwrap();
+    let hologram = std::fs::read_to_string(fixtures().join("shaders/hologram.wgsl")).unwrap();
+    validate(&hologram).unwrap();
+}
+
+#[test]
+fn reports_errors_with_lines_of_the_shader_file() {
+    let source = "@vertex\nfn vs_main(vertex: VertexInput) -> VertexOutput {\n    return engine_vertex(vertex)\n}\n";
+    let err = validate(source).unwrap_err();
+    assert!(err.starts_with("4:"), "{}", err);
+
+    let err = validate("@vertex\nfn vs_main(vertex: VertexInput) -> VertexOutput {\n    return engine_vertex(vertex);\n}\n")
+        .unwrap_err();
+    assert!(err.contains("fs_main"), "{}", err);
+}
+
+#[test]
+fn material_loads_its_shader() {
+    let mut assets = Assets::with_root(fixtures());
+
+    let handle = assets.load_material("hologram.material");
+    let shader = assets.materials.get(handle).unwrap().shader.unwrap();
+    assert_eq!(shader, assets.load_shader("shaders/hologram.wgsl"));
+
+    assets.wait_all();
+    assert!(matches!(
+        assets.status(AssetId::Shader(shader)),
+        Some(AssetStatus::Loaded)
+    ));
+    assert!(
+        assets
+            .shaders
+            .get(shader)
+            .unwrap()
+            .source
+            .contains("frame.time")
+    );
+    assert_eq!(assets.take_shader_changes(), vec![shader]);
+}
+
+#[test]
+fn reloads_edited_shaders_and_keeps_last_good_version() {
+    let dir = std::env::temp_dir().join(format!("zero_shader_reload_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("live.wgsl");
+    std::fs::write(&path, DEFAULT_SHADER).unwrap();
+
+    let mut assets = Assets::with_root(&dir);
+    let shader = assets.load_shader("live.wgsl");
+    assets.wait_all();
+    assert_eq!(assets.take_shader_changes(), vec![shader]);
+
+    let edit = |assets: &mut Assets, contents: &str, done: &dyn Fn(&Assets) -> bool| {
+        // Make sure the modification time moves even on coarse file systems
+        std::thread::sleep(Duration::from_millis(1100));
+        std::fs::write(&path, contents).unwrap();
+        let start = Instant::now();
+        while !done(assets) && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(100));
+            assets.reload_changed_shaders();
+            assets.wait_all();
+        }
+    };
+
+    // A broken edit is reported but the old source stays usable
+    let failed = |assets: &Assets| {
+        matches!(
+            assets.status(AssetId::Shader(shader)),
+            Some(AssetStatus::Failed(_))
+        )
+    };
+    edit(&mut assets, "fn broken( {", &failed);
+    assert!(failed(&assets));
+    assert_eq!(assets.shaders.get(shader).unwrap().source, DEFAULT_SHADER);
+    assert!(assets.take_shader_changes().is_empty());
+
+    let fixed = format!("// edited\n{}", DEFAULT_SHADER);
+    edit(&mut assets, &fixed, &|assets: &Assets| !failed(assets));
+    assert_eq!(assets.shaders.get(shader).unwrap().source, fixed);
+    assert_eq!(assets.take_shader_changes(), vec![shader]);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,149 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::components::{Light, Transform};
+use ZeroEngine::modules::ecs::entity::Camera;
+use ZeroEngine::modules::render::shadows::{
+    CASCADE_COUNT, LightShadow, MAX_SHADOW_LAYERS, cascade_splits, directional_shadow_matrix,
+    frustum_slice_corners, plan_shadows, spot_shadow_matrix,
+};
+use glam::{Vec3, Vec4};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn camera() -> (Camera, Transform) {
+    let camera = Camera {
+        fov: 60.0,
+        near: 0.1,
+        far: 100.0,
+        is_active: true,
+        ..Default::default()
+    };
+    let transform = Transform {
+        position: Vec3::new(0.0, 2.0, 5.0),
+        rotation: Vec3::new(-0.3, 0.4, 0.0),
+        scale: Vec3::ONE,
+    };
+    (camera, transform)
+}
+
+#[test]
+fn splits_cascades_between_near_and_far() {
+    let splits = cascade_splits(0.1, 60.0, 3);
+    assert_eq!(splits.len(), 3);
+    assert!(splits[0] > 0.1 && splits[0] < splits[1] && splits[1] < splits[2]);
+    assert!((splits[2] - 60.0).abs() < 1e-3);
+    // Near cascades are denser than a uniform split
+    assert!(splits[0] < 20.0);
+}
+
+#[test]
+fn cascade_encloses_its_frustum_slice() {
+    let (camera, transform) = camera();
+    let corners = frustum_slice_corners(&camera, &transform, 16.0 / 9.0, 0.1, 8.0);
+    let direction = Vec3::new(0.3, -1.0, -0.2);
+    let matrix = directional_shadow_matrix(&corners, direction, 2048);
+
+    for corner in corners {
+        let clip = matrix * corner.extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{:?}", ndc);
+        assert!((0.0..=1.0).contains(&ndc.z), "{:?}", ndc);
+    }
+
+    // Casters between the light and the slice still land in the depth range
+    let caster = corners[0] - direction.normalize() * 20.0;
+    let clip = matrix * caster.extend(1.0);
+    assert!(clip.z / clip.w >= 0.0);
+}
+
+#[test]
+fn spot_shadow_looks_down_the_cone() {
+    let matrix = spot_shadow_matrix(Vec3::new(0.0, 3.0, 0.0), -Vec3::Y, 25.0, 10.0);
+    let clip = matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let ndc = clip.truncate() / clip.w;
+    assert!(ndc.x.abs() < 1e-5 && ndc.y.abs() < 1e-5);
+    assert!(ndc.z > 0.0 && ndc.z < 1.0);
+
+    // Outside the cone is outside the map
+    let clip = matrix * Vec4::new(3.0, 2.0, 0.0, 1.0);
+    assert!((clip.x / clip.w).abs() > 1.0);
+}
+
+#[test]
+fn hands_out_layers_until_they_run_out() {
+    let (camera, transform) = camera();
+    let sun = Light::directional(Vec3::ONE, 1.0);
+    let spot = Light::spot(Vec3::ONE, 1.0, 10.0, 10.0, 20.0);
+    let mut unshadowed = sun;
+    unshadowed.casts_shadows = false;
+    let lights = vec![
+        (sun, Vec3::ZERO, -Vec3::Y),
+        (Light::point(Vec3::ONE, 1.0, 5.0), Vec3::ZERO, -Vec3::Z),
+        (unshadowed, Vec3::ZERO, -Vec3::Y),
+        (spot, Vec3::Y, -Vec3::Y),
+        (sun, Vec3::ZERO, -Vec3::X),
+        (sun, Vec3::ZERO, -Vec3::Z),
+        (spot, Vec3::Y, -Vec3::Y),
+    ];
+
+    let plan = plan_shadows(&lights, Some((&camera, &transform)), 1.5);
+    let cascades = |first_layer| {
+        Some(LightShadow {
+            first_layer,
+            layer_count: CASCADE_COUNT,
+        })
+    };
+    let single = |first_layer| {
+        Some(LightShadow {
+            first_layer,
+            layer_count: 1,
+        })
+    };
+    assert_eq!(
+        plan.lights,
+        vec![
+            cascades(0),
+            None,
+            None,
+            single(3),
+            cascades(4),
+            None,
+            single(7)
+        ]
+    );
+    assert_eq!(plan.layers.len(), MAX_SHADOW_LAYERS);
+
+    // Without a camera there is nothing to fit cascades to
+    let plan = plan_shadows(&lights[..1], None, 1.5);
+    assert_eq!(plan.lights, vec![None]);
+}
+
+#[test]
+fn reads_shadow_flags_from_scene() {
+    let mut engine = Engine::new();
+    engine
+        .load_scene(fixture("shadows.json"))
+        .expect("scene should load");
+
+    let flags = |name: &str| {
+        let (_, entity) = engine
+            .world
+            .iter_entities()
+            .find(|(_, e)| e.name == name)
+            .unwrap();
+        (entity.casts_shadows, entity.receives_shadows)
+    };
+    assert_eq!(flags("Ground"), (false, true));
+    assert_eq!(flags("Glass"), (true, false));
+    assert_eq!(flags("Crate"), (true, true));
+
+    let lights = engine.world.get_lights();
+    let casting = lights.iter().filter(|(l, _, _)| l.casts_shadows).count();
+    assert_eq!((lights.len(), casting), (2, 1));
+}
@@ -0,0 +1,138 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::sprite_sheet::parse_sprite_sheet;
+use ZeroEngine::modules::ecs::components::TextureFilter;
+use ZeroEngine::modules::ecs::sprites::{Sprite, SpriteRegion, update_sprite_animations};
+use ZeroEngine::modules::render::shader::compose;
+use ZeroEngine::modules::render::sprites::{SPRITE_SHADER, collect_sprites};
+use glam::{Vec2, Vec3, Vec4};
+
+const SHEET: &str = r#"{
+    "texture": "hero.png",
+    "grid": { "frame_size": [16, 16], "columns": 4, "count": 8 },
+    "frames": { "jump": [0, 32, 16, 24] },
+    "animations": {
+        "run": { "frames": [4, 5, 6, 7], "fps": 10 },
+        "jump": { "frames": ["jump", 0], "fps": 10, "loop": false }
+    }
+}"#;
+
+#[test]
+fn parses_sprite_sheets() {
+    let sheet = parse_sprite_sheet(SHEET, Some(std::path::Path::new("art"))).unwrap();
+    assert_eq!(sheet.texture.unwrap(), std::path::Path::new("art/hero.png"));
+    assert_eq!(sheet.filter, TextureFilter::Nearest);
+    assert_eq!(sheet.frames.len(), 9);
+    assert_eq!(sheet.frames["5"], SpriteRegion::new(16.0, 16.0, 16.0, 16.0));
+
+    let run = &sheet.animations["run"];
+    assert_eq!(run.frames[0], sheet.frames["4"]);
+    assert_eq!(
+        (run.frame_at(0.05), run.frame_at(0.35), run.frame_at(0.45)),
+        (0, 3, 0)
+    );
+    let jump = &sheet.animations["jump"];
+    assert_eq!(
+        jump.frame_at(5.0),
+        1,
+        "clips that don't loop hold the last frame"
+    );
+
+    let err = parse_sprite_sheet(
+        r#"{ "animations": { "idle": { "frames": ["missing"] } } }"#,
+        None,
+    )
+    .unwrap_err();
+    assert!(err.contains("missing"), "{}", err);
+}
+
+#[test]
+fn flips_and_regions_map_to_uvs() {
+    let mut sprite = Sprite {
+        region: Some(SpriteRegion::new(16.0, 0.0, 16.0, 32.0)),
+        ..Sprite::default()
+    };
+    assert_eq!(sprite.uv_rect(64, 32), Vec4::new(0.25, 0.0, 0.5, 1.0));
+    sprite.flip_x = true;
+    assert_eq!(sprite.uv_rect(64, 32), Vec4::new(0.5, 0.0, 0.25, 1.0));
+    sprite.flip_y = true;
+    assert_eq!(sprite.uv_rect(64, 32), Vec4::new(0.5, 1.0, 0.25, 0.0));
+}
+
+#[test]
+fn loads_animated_sprites_from_scene() {
+    let dir = std::env::temp_dir().join(format!("zero_sprites_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("hero.sprites"), SHEET).unwrap();
+    std::fs::write(
+        dir.join("scene.json"),
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "sprites": [
+                { "name": "Hero", "sheet": "hero.sprites", "animation": "run",
+                  "size": [1, 2], "pivot": [0.5, 0], "layer": 1, "tags": ["player"] },
+                { "name": "Ground", "position": [0, -1, 0], "tint": [0.2, 0.6, 0.2, 1] },
+                { "name": "Sky", "position": [0, 0, -5], "tint": [0.3, 0.5, 1, 1] }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(dir.join("scene.json").to_string_lossy().into_owned());
+    std::fs::remove_dir_all(&dir).ok();
+    result.unwrap();
+
+    let find = |name: &str| {
+        engine
+            .world
+            .iter_entities()
+            .find(|(_, e)| e.name == name)
+            .map(|(id, _)| id)
+            .unwrap()
+    };
+    let hero = find("Hero");
+    let sprite = engine.world.get_entity(hero).unwrap().sprite.unwrap();
+    assert!(sprite.texture.is_some());
+    assert_eq!(sprite.size, Vec2::new(1.0, 2.0));
+    assert_eq!(
+        sprite.region,
+        Some(SpriteRegion::new(0.0, 16.0, 16.0, 16.0))
+    );
+    assert!(engine.world.get_entity(hero).unwrap().has_tag("player"));
+
+    update_sprite_animations(&mut engine.world, &engine.assets, 0.25);
+    let sprite = engine.world.get_entity(hero).unwrap().sprite.unwrap();
+    assert_eq!(
+        sprite.region,
+        Some(SpriteRegion::new(32.0, 16.0, 16.0, 16.0))
+    );
+
+    // Untextured sprites batch together, the hero's texture is still loading
+    let (instances, batches) = collect_sprites(&engine.world, |_| None);
+    assert_eq!(instances.len(), 2);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(
+        Vec3::from_slice(&instances[0].origin),
+        Vec3::new(0.0, 0.0, -5.0)
+    );
+
+    // Sorted by layer first, so the hero draws last
+    let (instances, batches) = collect_sprites(&engine.world, |_| Some((64, 64)));
+    assert_eq!(instances.len(), 3);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].instances, 2..3);
+    assert_eq!(instances[2].uv_rect, [0.5, 0.25, 0.75, 0.5]);
+    assert_eq!(instances[2].up[1], 2.0);
+}
+
+#[test]
+fn validates_sprite_shader() {
+    let source = compose(SPRITE_SHADER);
+    let module = wgpu::naga::front::wgsl::parse_str(&source).expect("sprite shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("sprite shader validates");
+}
@@ -0,0 +1,134 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::DEFAULT_FONT;
+use ZeroEngine::modules::assets::font::Font;
+use ZeroEngine::modules::ecs::text::{HudText, TextAlign, parse_text_align};
+use ZeroEngine::modules::render::text::{
+    GlyphAtlas, HUD_SHADER, collect_hud_text, collect_world_text,
+};
+use glam::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+
+#[test]
+fn lays_out_aligned_lines() {
+    let font = Font::builtin();
+    let left = font.layout("Hello\nHi", 32.0, TextAlign::Left);
+    assert_eq!(left.glyphs.len(), 7);
+    assert_eq!(left.glyphs[0].position, Vec2::new(0.0, left.baseline));
+    assert_eq!(left.glyphs[5].position.x, 0.0);
+    assert_eq!(
+        left.glyphs[5].position.y - left.glyphs[0].position.y,
+        font.line_height(32.0)
+    );
+    assert!(left.glyphs[1].position.x > 0.0);
+
+    // Shorter lines move right by all or half of the space they leave
+    let right = font.layout("Hello\nHi", 32.0, TextAlign::Right);
+    let center = font.layout("Hello\nHi", 32.0, TextAlign::Center);
+    assert_eq!(right.size, left.size);
+    assert_eq!(right.glyphs[0].position.x, 0.0, "the widest line");
+    let shift = right.glyphs[5].position.x;
+    assert!(shift > 0.0);
+    assert!((center.glyphs[5].position.x - shift * 0.5).abs() < 1e-4);
+
+    assert_eq!(parse_text_align("center"), Ok(TextAlign::Center));
+    assert!(parse_text_align("justify").is_err());
+}
+
+#[test]
+fn caches_glyphs_in_atlas() {
+    let font = Font::builtin();
+    let layout = font.layout("A B", 24.0, TextAlign::Left);
+    let (a, space) = (layout.glyphs[0].glyph, layout.glyphs[1].glyph);
+
+    let mut atlas = GlyphAtlas::new(64);
+    let glyph = atlas.glyph(0, &font, a, 24.0).unwrap();
+    assert!(glyph.size.x > 0.0 && glyph.size.y > 0.0);
+    assert!(glyph.uv_rect.z <= 1.0 && glyph.uv_rect.w <= 1.0);
+    assert!(atlas.take_dirty_rows().is_some());
+    assert_eq!(atlas.glyph(0, &font, a, 24.2), Some(glyph), "cached");
+    assert!(atlas.take_dirty_rows().is_none());
+    assert_eq!(atlas.glyph(0, &font, space, 24.0), None, "nothing to draw");
+
+    // Filling the atlas starts it over
+    for size in 25..60 {
+        atlas.glyph(0, &font, a, size as f32);
+    }
+    assert!(atlas.generation() > 0);
+}
+
+#[test]
+fn loads_text_and_hud_from_scene() {
+    let dir = std::env::temp_dir().join(format!("zero_text_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("scene.json"),
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "texts": [
+                { "name": "Sign", "position": [1, 2, 0], "text": "Hi",
+                  "size": 0.5, "align": "center", "color": [1, 0, 0, 1] }
+            ],
+            "hud": [
+                { "name": "score", "text": "Score: 10", "size": 32,
+                  "anchor": [1, 0], "offset": [-10, 10], "align": "right" }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(dir.join("scene.json").to_string_lossy().into_owned());
+    std::fs::remove_dir_all(&dir).ok();
+    result.unwrap();
+
+    let fonts = HashMap::from([(DEFAULT_FONT, Font::builtin())]);
+    let mut atlas = GlyphAtlas::new(1024);
+
+    // Centered on the entity, standing on its baseline
+    let instances = collect_world_text(&engine.world, &fonts, &mut atlas, Vec3::X, Vec3::Y);
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0].tint, [1.0, 0.0, 0.0, 1.0]);
+    let left = instances[0].origin[0];
+    let right = instances[1].origin[0] + instances[1].right[0];
+    assert!(
+        ((left + right) * 0.5 - 1.0).abs() < 0.05,
+        "{} {}",
+        left,
+        right
+    );
+    assert!(instances.iter().all(|i| (i.origin[1] - 2.0).abs() < 0.02));
+
+    // Right aligned in the top right corner
+    let screen = Vec2::new(800.0, 600.0);
+    let glyphs = collect_hud_text(&engine.world.hud, screen, &fonts, &mut atlas);
+    assert_eq!(glyphs.len(), 8, "the space has no quad");
+    let end = glyphs
+        .iter()
+        .map(|g| g.rect[0] + g.rect[2])
+        .fold(0.0, f32::max);
+    assert!(end <= 790.0 && end > 780.0, "{}", end);
+    assert!(glyphs.iter().all(|g| g.rect[1] >= 10.0));
+
+    engine.world.hud.get_mut("score").unwrap().visible = false;
+    engine.world.hud.insert(
+        "hint",
+        HudText {
+            text: "ok".into(),
+            color: Vec4::ONE,
+            ..HudText::default()
+        },
+    );
+    let glyphs = collect_hud_text(&engine.world.hud, screen, &fonts, &mut atlas);
+    assert_eq!(glyphs.len(), 2);
+}
+
+#[test]
+fn validates_hud_shader() {
+    let module = wgpu::naga::front::wgsl::parse_str(HUD_SHADER).expect("hud shader parses");
+    wgpu::naga::valid::Validator::new(
+        wgpu::naga::valid::ValidationFlags::all(),
+        wgpu::naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .expect("hud shader validates");
+}
@@ -0,0 +1,266 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::Assets;
+use ZeroEngine::modules::assets::tiled::{TiledLayer, parse_tmj, parse_tmx};
+use ZeroEngine::modules::ecs::tilemap::{Tile, Tilemap, Tileset};
+use ZeroEngine::modules::render::sprites::tilemap_chunk;
+use glam::{Mat4, Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="8" columns="4">
+  <image source="terrain.png" width="64" height="32"/>
+  <tile id="1"><properties><property name="collision" type="int" value="3"/></properties></tile>
+  <tile id="2"><properties><property name="solid" type="bool" value="true"/></properties></tile>
+  <tile id="3"><objectgroup><object id="1" x="0" y="0" width="16" height="16"/></objectgroup></tile>
+ </tileset>
+ <layer id="1" name="Ground" width="3" height="2">
+  <data encoding="csv">
+1,2,3,
+4,0,0
+</data>
+ </layer>
+ <group id="2" name="Details" offsetx="8">
+  <layer id="3" name="Deco" width="3" height="2" opacity="0.5">
+   <data encoding="base64" compression="zlib">eJxjYGBgYARiJiBmZmBoAFIMLEAMAAUAAIs=</data>
+  </layer>
+ </group>
+ <objectgroup id="4" name="Spawns">
+  <object id="2" name="Player" type="spawn" x="16" y="32"/>
+ </objectgroup>
+</map>"#;
+
+#[test]
+fn parses_tmx_maps() {
+    let map = parse_tmx(TMX, Path::new("maps")).unwrap();
+    assert_eq!((map.width, map.height, map.tile_width), (3, 2, 16));
+
+    let tileset = &map.tilesets[0];
+    assert_eq!(
+        tileset.image.as_deref(),
+        Some(Path::new("maps/terrain.png"))
+    );
+    assert_eq!(
+        (
+            tileset.collision[&1],
+            tileset.collision[&2],
+            tileset.collision[&3]
+        ),
+        (3, 1, 1)
+    );
+    assert!(!tileset.collision.contains_key(&0));
+
+    assert_eq!(map.layers.len(), 3);
+    let TiledLayer::Tiles { name, data, .. } = &map.layers[0] else {
+        panic!("expected a tile layer");
+    };
+    assert_eq!(
+        (name.as_str(), data.as_slice()),
+        ("Ground", &[1, 2, 3, 4, 0, 0][..])
+    );
+    let TiledLayer::Tiles {
+        data,
+        offset,
+        opacity,
+        ..
+    } = &map.layers[1]
+    else {
+        panic!("expected a tile layer");
+    };
+    assert_eq!(data, &[0, 1, 2, 3 | 0x8000_0000, 0, 4]);
+    assert_eq!(
+        (*offset, *opacity),
+        ([8.0, 0.0], 0.5),
+        "group offsets add up"
+    );
+    let TiledLayer::Objects { objects, .. } = &map.layers[2] else {
+        panic!("expected an object layer");
+    };
+    assert_eq!(
+        (objects[0].name.as_str(), objects[0].class.as_str()),
+        ("Player", "spawn")
+    );
+
+    let err = parse_tmx(
+        &TMX.replace("infinite=\"0\"", "infinite=\"1\""),
+        Path::new(""),
+    )
+    .unwrap_err();
+    assert!(err.contains("infinite"), "{}", err);
+}
+
+#[test]
+fn parses_tmj_maps_with_flipped_tiles() {
+    let map = parse_tmj(
+        r#"{
+            "width": 2, "height": 1, "tilewidth": 8, "tileheight": 8,
+            "orientation": "orthogonal", "infinite": false,
+            "tilesets": [{
+                "firstgid": 5, "name": "walls", "image": "walls.png",
+                "tilewidth": 8, "tileheight": 8, "tilecount": 4, "columns": 2,
+                "tiles": [{ "id": 0, "properties": [
+                    { "name": "collision", "type": "int", "value": 2 }
+                ] }]
+            }],
+            "layers": [{
+                "type": "tilelayer", "name": "Walls", "width": 2, "height": 1,
+                "data": [5, 2684354566]
+            }]
+        }"#,
+        Path::new(""),
+    )
+    .unwrap();
+    assert_eq!(map.tilesets[0].first_gid, 5);
+    assert_eq!(map.tilesets[0].collision[&0], 2);
+
+    let TiledLayer::Tiles { data, .. } = &map.layers[0] else {
+        panic!("expected a tile layer");
+    };
+    let tile = Tile::from_raw(data[1]);
+    assert_eq!(tile.gid, 6);
+    assert!(tile.flip_x && !tile.flip_y && tile.flip_diagonal);
+
+    let tilesets: Arc<[Tileset]> = Arc::new([Tileset {
+        first_gid: 5,
+        tile_width: 8,
+        tile_height: 8,
+        columns: 2,
+        tile_count: 4,
+        collision: map.tilesets[0].collision.clone(),
+        ..Tileset::default()
+    }]);
+    let mut tilemap = Tilemap::new(2, 1, Vec2::splat(8.0), tilesets);
+    tilemap.set_tile(0, 0, Tile::from_raw(data[0]));
+    tilemap.set_tile(1, 0, tile);
+    assert_eq!((tilemap.collision(0, 0), tilemap.collision(1, 0)), (2, 0));
+    assert_eq!(tilemap.cell_at(Vec2::new(1.5, -0.5)), Some((1, 0)));
+    assert_eq!(tilemap.cell_at(Vec2::new(1.5, 0.5)), None, "above the map");
+
+    // 65536 x 65536 tiles would wrap to 0 in 32 bits
+    let err = parse_tmj(
+        r#"{
+            "width": 65536, "height": 65536, "tilewidth": 8, "tileheight": 8,
+            "tilesets": [],
+            "layers": [{
+                "type": "tilelayer", "name": "Huge", "width": 65536, "height": 65536,
+                "data": []
+            }]
+        }"#,
+        Path::new(""),
+    )
+    .unwrap_err();
+    assert!(err.contains("has 0 tiles"), "{}", err);
+}
+
+#[test]
+fn loads_tilemaps_from_scene() {
+    let dir = std::env::temp_dir().join(format!("zero_tilemap_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("level.tmx"), TMX).unwrap();
+    std::fs::write(
+        dir.join("scene.json"),
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "tilemaps": [
+                { "name": "Level", "path": "level.tmx", "position": [-1, 1, 0],
+                  "cell_size": [2, 2], "layer": 3 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(dir.join("scene.json").to_string_lossy().into_owned());
+    std::fs::remove_dir_all(&dir).ok();
+    result.unwrap();
+
+    let find = |name: &str| {
+        engine
+            .world
+            .iter_entities()
+            .find(|(_, e)| e.name == name)
+            .map(|(id, _)| id)
+            .unwrap()
+    };
+    let (level, ground, deco, player) =
+        (find("Level"), find("Ground"), find("Deco"), find("Player"));
+    let ground_entity = engine.world.get_entity(ground).unwrap();
+    assert_eq!(ground_entity.parent, Some(level));
+    let tilemap = ground_entity.tilemap.as_ref().unwrap();
+    assert_eq!((tilemap.layer, tilemap.cell_size), (3, Vec2::splat(2.0)));
+    assert!(tilemap.tilesets[0].texture.is_some());
+    assert_eq!(tilemap.collision(1, 0), 3);
+    assert_eq!(tilemap.collision(2, 0), 1);
+
+    let deco_entity = engine.world.get_entity(deco).unwrap();
+    let deco_map = deco_entity.tilemap.as_ref().unwrap();
+    assert_eq!((deco_map.layer, deco_map.tint.w), (4, 0.5));
+    assert!(deco_map.tile(0, 1).unwrap().flip_x);
+    assert_eq!(
+        deco_entity.transform.unwrap().position,
+        Vec3::new(1.0, 0.0, 0.0)
+    );
+
+    // Objects sit at their Tiled position, 16 pixels being one 2 unit cell
+    let spawn = engine.world.world_matrix(player).w_axis.truncate();
+    assert_eq!(spawn, Vec3::new(1.0, -3.0, 0.0));
+    assert!(engine.world.get_entity(player).unwrap().has_tag("spawn"));
+
+    let tilemap = engine
+        .world
+        .get_entity_mut(ground)
+        .unwrap()
+        .tilemap
+        .as_mut()
+        .unwrap();
+    let revision = tilemap.chunk_revision(0, 0);
+    assert!(tilemap.set_tile(2, 0, Tile::EMPTY));
+    assert!(!tilemap.set_tile(3, 0, Tile::new(1)), "outside the map");
+    assert_ne!(tilemap.chunk_revision(0, 0), revision);
+    assert_eq!(tilemap.collision(2, 0), 0);
+}
+
+#[test]
+fn builds_chunk_instances() {
+    let mut assets = Assets::with_root(".");
+    let texture = assets.load_texture("terrain.png");
+    let tilesets: Arc<[Tileset]> = Arc::new([Tileset {
+        first_gid: 1,
+        texture: Some(texture),
+        tile_width: 16,
+        tile_height: 32,
+        columns: 4,
+        tile_count: 8,
+        ..Tileset::default()
+    }]);
+    let mut tilemap = Tilemap::new(20, 2, Vec2::splat(16.0), tilesets);
+    tilemap.set_tile(1, 0, Tile::new(2));
+    tilemap.set_tile(0, 1, Tile::from_raw(1 | 0x2000_0000));
+    tilemap.set_tile(17, 1, Tile::new(1));
+    assert_eq!(tilemap.chunks(), (2, 1));
+
+    let matrix = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0));
+    assert!(
+        tilemap_chunk(&tilemap, matrix, 0, 0, |_| None).is_none(),
+        "waits for the texture"
+    );
+    let (instances, batches) = tilemap_chunk(&tilemap, matrix, 0, 0, |_| Some((64, 64))).unwrap();
+    assert_eq!((instances.len(), batches.len()), (2, 1));
+
+    // Row 0 is the top row, tiles anchored at their cell's bottom left and
+    // twice as tall as it
+    let tile = &instances[0];
+    assert_eq!(tile.origin, [11.0, -1.0, 0.0, 0.0]);
+    assert_eq!((tile.right[0], tile.up[1]), (1.0, 2.0));
+    assert_eq!(tile.uv_rect, [0.25, 0.0, 0.5, 0.5]);
+
+    // The diagonal flip swaps the quad's axes and both texture axes
+    let flipped = &instances[1];
+    assert_eq!(flipped.origin, [10.0, -2.0, 0.0, 0.0]);
+    assert_eq!((flipped.right[1], flipped.up[0]), (2.0, 1.0));
+    assert_eq!(flipped.uv_rect, [0.25, 0.5, 0.0, 0.0]);
+
+    let (instances, _) = tilemap_chunk(&tilemap, matrix, 1, 0, |_| Some((64, 64))).unwrap();
+    assert_eq!(instances.len(), 1);
+}
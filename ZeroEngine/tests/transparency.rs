@@ -0,0 +1,83 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::assets::material::parse_material;
+use ZeroEngine::modules::ecs::components::{BlendMode, Material};
+use ZeroEngine::modules::render::batching::{batch_consecutive, sort_back_to_front};
+use glam::{Vec3, Vec4};
+
+#[test]
+fn picks_blend_mode_from_alpha_and_files() {
+    assert_eq!(Material::new(Vec4::ONE).blend, BlendMode::Opaque);
+    assert_eq!(
+        Material::new(Vec4::new(0.0, 1.0, 0.0, 0.5)).blend,
+        BlendMode::Alpha
+    );
+
+    let file = parse_material(r#"{ "blend": "additive" }"#, None).unwrap();
+    assert_eq!(file.blend, Some(BlendMode::Additive));
+    assert_eq!(parse_material("{}", None).unwrap().blend, None);
+    let err = parse_material(r#"{ "blend": "screen" }"#, None).unwrap_err();
+    assert!(err.contains("screen"), "{}", err);
+}
+
+#[test]
+fn reads_blend_modes_from_scene() {
+    let path = std::env::temp_dir().join(format!("zero_blend_{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{
+            "entities": [
+                { "name": "Wall", "position": [0, 0, 0], "scale": [1, 1, 1], "mesh": "cube" },
+                { "name": "Glass", "position": [0, 0, 1], "scale": [1, 1, 1], "mesh": "cube",
+                  "color": [0.2, 0.6, 1.0, 0.4] },
+                { "name": "Glow", "position": [0, 0, 2], "scale": [1, 1, 1], "mesh": "cube",
+                  "blend": "additive" },
+                { "name": "Tint", "position": [0, 0, 3], "scale": [1, 1, 1], "mesh": "cube",
+                  "color": [1, 0, 0, 0.5], "blend": "multiply" }
+            ],
+            "cameras": []
+        }"#,
+    )
+    .unwrap();
+
+    let mut engine = Engine::new();
+    engine
+        .load_scene(path.to_string_lossy().into_owned())
+        .expect("scene should load");
+    std::fs::remove_file(&path).ok();
+
+    let blend = |name: &str| {
+        engine
+            .world
+            .iter_entities()
+            .find(|(_, e)| e.name == name)
+            .and_then(|(_, e)| e.material)
+            .map(|m| m.blend)
+    };
+    assert_eq!(blend("Wall"), Some(BlendMode::Opaque));
+    assert_eq!(blend("Glass"), Some(BlendMode::Alpha));
+    assert_eq!(blend("Glow"), Some(BlendMode::Additive));
+    assert_eq!(blend("Tint"), Some(BlendMode::Multiply));
+}
+
+#[test]
+fn draws_transparent_entities_back_to_front() {
+    // (entity, position, batch key)
+    let mut items = vec![
+        (0, Vec3::new(0.0, 0.0, -1.0), 'a'),
+        (1, Vec3::new(0.0, 0.0, -9.0), 'a'),
+        (2, Vec3::new(0.0, 0.0, -5.0), 'b'),
+        (3, Vec3::new(0.0, 0.0, -7.0), 'a'),
+    ];
+    sort_back_to_front(&mut items, Vec3::ZERO, |(_, position, _)| *position);
+    let sorted: Vec<usize> = items.iter().map(|(entity, _, _)| *entity).collect();
+    assert_eq!(sorted, vec![1, 3, 2, 0]);
+
+    // Neighbours with the same key share a draw, the order is kept
+    let (order, batches) = batch_consecutive(items.iter().map(|(e, _, key)| (*e, *key)), 4);
+    assert_eq!(order, vec![1, 3, 2, 0]);
+    let ranges: Vec<_> = batches
+        .iter()
+        .map(|b| (b.key, b.instances.clone()))
+        .collect();
+    assert_eq!(ranges, vec![('a', 4..6), ('b', 6..7), ('a', 7..8)]);
+}
@@ -0,0 +1,161 @@
+use ZeroEngine::Engine;
+use ZeroEngine::modules::ecs::ui::{
+    UiAnchor, UiEvent, UiLayer, UiPanel, UiWidget, UiWidgetKind, parse_ui_anchor,
+};
+use ZeroEngine::modules::render::ui::{panel_id, show_ui};
+use glam::Vec2;
+
+/// `name` must be unique per call, tests run in parallel
+fn load(name: &str, scene: &str) -> Result<Engine, String> {
+    let dir = std::env::temp_dir().join(format!("zero_ui_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("scene.json"), scene).unwrap();
+    let mut engine = Engine::new();
+    let result = engine.load_scene(dir.join("scene.json").to_string_lossy().into_owned());
+    std::fs::remove_dir_all(&dir).ok();
+    result.map(|_| engine)
+}
+
+fn frame(events: Vec<egui::Event>) -> egui::RawInput {
+    egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(800.0, 600.0),
+        )),
+        events,
+        ..Default::default()
+    }
+}
+
+fn click(pos: egui::Pos2, pressed: bool) -> egui::Event {
+    egui::Event::PointerButton {
+        pos,
+        button: egui::PointerButton::Primary,
+        pressed,
+        modifiers: egui::Modifiers::default(),
+    }
+}
+
+#[test]
+fn loads_ui_panels_from_scene() {
+    let engine = load(
+        "panels",
+        r#"{
+            "entities": [],
+            "cameras": [],
+            "ui": [
+                { "name": "status", "anchor": "top_right", "offset": [-10, 10],
+                  "title": "Player", "width": 180,
+                  "widgets": [
+                    { "type": "label", "id": "score", "text": "Score: 0" },
+                    { "type": "progress", "id": "health", "value": 0.75, "text": "HP" },
+                    { "type": "button", "id": "pause", "text": "Pause", "enabled": false }
+                  ] }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let panel = engine.world.ui.panel("status").unwrap();
+    assert_eq!(panel.anchor, UiAnchor::TopRight);
+    assert_eq!(
+        (panel.offset, panel.width, panel.visible),
+        (Vec2::new(-10.0, 10.0), Some(180.0), true)
+    );
+    assert_eq!(panel.title.as_deref(), Some("Player"));
+    assert_eq!(panel.widgets.len(), 3);
+    assert_eq!(
+        engine.world.ui.widget("health").unwrap().kind,
+        UiWidgetKind::ProgressBar {
+            value: 0.75,
+            text: Some("HP".into())
+        }
+    );
+    assert_eq!(
+        engine.world.ui.widget("pause").unwrap().kind,
+        UiWidgetKind::Button {
+            text: "Pause".into(),
+            enabled: false
+        }
+    );
+
+    let err = load(
+        "bad_widget",
+        r#"{ "entities": [], "cameras": [],
+             "ui": [{ "name": "menu", "widgets": [{ "type": "slider", "id": "volume" }] }] }"#,
+    )
+    .err()
+    .unwrap();
+    assert!(err.contains("unknown ui widget 'slider'"), "{}", err);
+    assert!(parse_ui_anchor("middle").is_err());
+}
+
+#[test]
+fn edits_widgets_by_id() {
+    let mut ui = UiLayer::new();
+    assert!(!ui.add_widget("menu", UiWidget::label("title", "Menu")));
+    ui.insert_panel("menu", UiPanel::new(UiAnchor::Center));
+    assert!(ui.add_widget("menu", UiWidget::label("title", "Menu")));
+    assert!(ui.add_widget("menu", UiWidget::progress_bar("loading", 2.0)));
+    assert!(ui.add_widget("menu", UiWidget::button("title", "Start")));
+    assert_eq!(
+        ui.panel("menu").unwrap().widgets.len(),
+        2,
+        "same id replaces"
+    );
+
+    ui.widget_mut("loading").unwrap().set_text("50%");
+    assert_eq!(
+        ui.widget("loading").unwrap().kind,
+        UiWidgetKind::ProgressBar {
+            value: 1.0,
+            text: Some("50%".into())
+        }
+    );
+    assert!(ui.remove_widget("loading"));
+    assert!(!ui.remove_widget("loading"));
+
+    ui.set_events(vec![UiEvent::Clicked("title".into())]);
+    assert!(ui.was_clicked("title"));
+    ui.set_events(Vec::new());
+    assert!(!ui.was_clicked("title"));
+}
+
+#[test]
+fn reports_button_clicks() {
+    let mut ui = UiLayer::new();
+    let mut panel = UiPanel::new(UiAnchor::TopRight);
+    panel.offset = Vec2::new(-10.0, 10.0);
+    panel.widgets.push(UiWidget::button("pause", "Pause"));
+    ui.insert_panel("menu", panel);
+    let mut hidden = UiPanel::new(UiAnchor::Center);
+    hidden.visible = false;
+    ui.insert_panel("hidden", hidden);
+
+    // Anchored areas settle after egui's sizing frame
+    let ctx = egui::Context::default();
+    for _ in 0..2 {
+        let _ = ctx.run(frame(Vec::new()), |ctx| {
+            assert!(show_ui(ctx, &ui).is_empty());
+        });
+    }
+    let rect = ctx.memory(|m| m.area_rect(panel_id("menu"))).unwrap();
+    assert!(
+        (rect.max.x - 790.0).abs() < 1.0 && (rect.min.y - 10.0).abs() < 1.0,
+        "{:?}",
+        rect
+    );
+    assert!(ctx.memory(|m| m.area_rect(panel_id("hidden"))).is_none());
+
+    // Press and release over the button
+    let pos = rect.center();
+    let mut events = Vec::new();
+    let _ = ctx.run(
+        frame(vec![egui::Event::PointerMoved(pos), click(pos, true)]),
+        |ctx| events.extend(show_ui(ctx, &ui)),
+    );
+    let _ = ctx.run(frame(vec![click(pos, false)]), |ctx| {
+        events.extend(show_ui(ctx, &ui))
+    });
+    assert_eq!(events, vec![UiEvent::Clicked("pause".into())]);
+}
@@ -1,12 +1,14 @@
 use ZeroEngine::Engine;
 use ZeroEngine::modules::state::State;
+use ZeroEngine::{log_error, log_info};
 use std::env;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, KeyEvent, WindowEvent},
     event_loop::ActiveEventLoop,
+    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
@@ -44,7 +46,12 @@ impl ApplicationHandler for App {
 
         // Initialize engine AFTER State
         
-self.engine.init_with_state(self.state.as_mut().unwrap(), args[1].clone());
+        if let Err(e) = self
+            .engine
+            .init_with_state(self.state.as_mut().unwrap(), args[1].clone())
+        {
+            log_error!("failed to load scene: {}", e);
+        }
 
         self.last_frame_time = Some(Instant::now());
         window.request_redraw();
@@ -52,6 +59,28 @@ self.engine.init_with_state(self.state.as_mut().unwrap(), args[1].clone());
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         if let Some(state) = self.state.as_mut() {
+            // ` opens and closes the console, before egui would type it
+            if let WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Backquote),
+                        state: ElementState::Pressed,
+                        repeat,
+                        ..
+                    },
+                ..
+            } = event
+            {
+                if !repeat {
+                    state.toggle_console();
+                }
+                return;
+            }
+            // Game UI panels and the console get input first, clicks and
+            // typing on them don't reach the game
+            if state.handle_ui_event(&event) {
+                return;
+            }
             match event {
                 WindowEvent::CloseRequested => event_loop.exit(),
                 WindowEvent::RedrawRequested => {
@@ -67,12 +96,41 @@ self.engine.init_with_state(self.state.as_mut().unwrap(), args[1].clone());
 
                     // Call the engine to update and render
                     if let Err(e) = self.engine.update_and_render(state, dt) {
-                        eprintln!("Update/render error: {}", e);
+                        log_error!("Update/render error: {}", e);
+                    }
+                    for line in state.take_console_commands() {
+                        let _ = self.engine.run_console_command(Some(state), &line);
                     }
 
                     state.get_window().request_redraw();
                 }
                 WindowEvent::Resized(size) => state.resize(size),
+                // F1 shows and hides debug drawing
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(KeyCode::F1),
+                            state: ElementState::Pressed,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                } => self.engine.world.debug_draw.toggle(),
+                // F2 cycles through the render modes
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(KeyCode::F2),
+                            state: ElementState::Pressed,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                } => {
+                    let mode = state.render_mode().next();
+                    state.set_render_mode(mode);
+                    log_info!("Render mode: {}", mode.name());
+                }
                 _ => (),
             }
         }
@@ -59,6 +59,149 @@ declare function get_entity_scale_z(id: u32): f32;
 @external("context", "set_entity_scale_z")
 declare function set_entity_scale_z(id: u32, val: f32): void;
 
+// Debug drawing: RGBA colors, durations in seconds (0 = one frame)
+// @ts-ignore
+@external("context", "debug_line")
+declare function debug_line(x1: f32, y1: f32, z1: f32, x2: f32, y2: f32, z2: f32, r: f32, g: f32, b: f32, a: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_aabb")
+declare function debug_aabb(minX: f32, minY: f32, minZ: f32, maxX: f32, maxY: f32, maxZ: f32, r: f32, g: f32, b: f32, a: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_sphere")
+declare function debug_sphere(x: f32, y: f32, z: f32, radius: f32, r: f32, g: f32, b: f32, a: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_axes")
+declare function debug_axes(size: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_grid")
+declare function debug_grid(x: f32, y: f32, z: f32, size: f32, divisions: u32, r: f32, g: f32, b: f32, a: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_text_3d")
+declare function debug_text_3d(text: string, x: f32, y: f32, z: f32, r: f32, g: f32, b: f32, a: f32, duration: f32): void;
+// @ts-ignore
+@external("context", "debug_set_enabled")
+declare function debug_set_enabled(enabled: bool): void;
+
+// Scene fog: linear RGB colors, distances in world units
+// @ts-ignore
+@external("context", "fog_set_enabled")
+declare function fog_set_enabled(enabled: bool): void;
+// @ts-ignore
+@external("context", "fog_set_color")
+declare function fog_set_color(r: f32, g: f32, b: f32): void;
+// @ts-ignore
+@external("context", "fog_get_density")
+declare function fog_get_density(): f32;
+// @ts-ignore
+@external("context", "fog_set_density")
+declare function fog_set_density(density: f32): void;
+// @ts-ignore
+@external("context", "fog_set_range")
+declare function fog_set_range(start: f32, end: f32): void;
+// @ts-ignore
+@external("context", "fog_set_height")
+declare function fog_set_height(height: f32, falloff: f32): void;
+
+// Particle emitter on the calling entity
+// @ts-ignore
+@external("context", "particles_burst")
+declare function particles_burst(count: i32): void;
+// @ts-ignore
+@external("context", "particles_set_enabled")
+declare function particles_set_enabled(enabled: bool): void;
+// @ts-ignore
+@external("context", "particles_set_rate")
+declare function particles_set_rate(rate: f32): void;
+
+// Sprite on the calling entity, tint is linear RGBA
+// @ts-ignore
+@external("context", "sprite_play")
+declare function sprite_play(name: string): void;
+// @ts-ignore
+@external("context", "sprite_stop")
+declare function sprite_stop(): void;
+// @ts-ignore
+@external("context", "sprite_set_flip")
+declare function sprite_set_flip(flipX: bool, flipY: bool): void;
+// @ts-ignore
+@external("context", "sprite_set_tint")
+declare function sprite_set_tint(r: f32, g: f32, b: f32, a: f32): void;
+
+// Tilemap layers by entity name, "" for the calling entity
+// @ts-ignore
+@external("context", "tilemap_get_tile")
+declare function tilemap_get_tile(layer: string, x: i32, y: i32): i32;
+// @ts-ignore
+@external("context", "tilemap_set_tile")
+declare function tilemap_set_tile(layer: string, x: i32, y: i32, gid: i32): void;
+// @ts-ignore
+@external("context", "tilemap_get_collision")
+declare function tilemap_get_collision(layer: string, x: i32, y: i32): i32;
+// @ts-ignore
+@external("context", "tilemap_collision_at")
+declare function tilemap_collision_at(layer: string, x: f32, y: f32): i32;
+
+// Text on the calling entity and HUD labels by name
+// @ts-ignore
+@external("context", "text_set")
+declare function text_set(text: string): void;
+// @ts-ignore
+@external("context", "text_set_color")
+declare function text_set_color(r: f32, g: f32, b: f32, a: f32): void;
+// @ts-ignore
+@external("context", "hud_set_text")
+declare function hud_set_text(name: string, text: string): void;
+// @ts-ignore
+@external("context", "hud_set_position")
+declare function hud_set_position(name: string, anchorX: f32, anchorY: f32, offsetX: f32, offsetY: f32): void;
+// @ts-ignore
+@external("context", "hud_set_color")
+declare function hud_set_color(name: string, r: f32, g: f32, b: f32, a: f32): void;
+// @ts-ignore
+@external("context", "hud_set_size")
+declare function hud_set_size(name: string, size: f32): void;
+// @ts-ignore
+@external("context", "hud_set_visible")
+declare function hud_set_visible(name: string, visible: bool): void;
+// @ts-ignore
+@external("context", "hud_remove")
+declare function hud_remove(name: string): void;
+
+// Game UI panels and widgets by name
+// @ts-ignore
+@external("context", "ui_add_panel")
+declare function ui_add_panel(name: string, anchor: string, offsetX: f32, offsetY: f32): void;
+// @ts-ignore
+@external("context", "ui_remove_panel")
+declare function ui_remove_panel(name: string): void;
+// @ts-ignore
+@external("context", "ui_set_panel_visible")
+declare function ui_set_panel_visible(name: string, visible: bool): void;
+// @ts-ignore
+@external("context", "ui_add_label")
+declare function ui_add_label(panel: string, id: string, text: string): void;
+// @ts-ignore
+@external("context", "ui_add_button")
+declare function ui_add_button(panel: string, id: string, text: string): void;
+// @ts-ignore
+@external("context", "ui_add_progress")
+declare function ui_add_progress(panel: string, id: string, value: f32): void;
+// @ts-ignore
+@external("context", "ui_set_text")
+declare function ui_set_text(id: string, text: string): void;
+// @ts-ignore
+@external("context", "ui_set_progress")
+declare function ui_set_progress(id: string, value: f32): void;
+// @ts-ignore
+@external("context", "ui_set_enabled")
+declare function ui_set_enabled(id: string, enabled: bool): void;
+// @ts-ignore
+@external("context", "ui_remove_widget")
+declare function ui_remove_widget(id: string): void;
+// @ts-ignore
+@external("context", "ui_was_clicked")
+declare function ui_was_clicked(id: string): bool;
+
 // =========================================================
 // Global state
 // =========================================================
@@ -139,6 +282,115 @@ class Entity {
   }
 }
 
+// =========================================================
+// Debug drawing
+// =========================================================
+
+export class Debug {
+  static line(x1: f32, y1: f32, z1: f32, x2: f32, y2: f32, z2: f32, r: f32 = 1, g: f32 = 1, b: f32 = 1, a: f32 = 1, duration: f32 = 0): void {
+    debug_line(x1, y1, z1, x2, y2, z2, r, g, b, a, duration);
+  }
+
+  static aabb(minX: f32, minY: f32, minZ: f32, maxX: f32, maxY: f32, maxZ: f32, r: f32 = 1, g: f32 = 1, b: f32 = 1, a: f32 = 1, duration: f32 = 0): void {
+    debug_aabb(minX, minY, minZ, maxX, maxY, maxZ, r, g, b, a, duration);
+  }
+
+  static sphere(x: f32, y: f32, z: f32, radius: f32, r: f32 = 1, g: f32 = 1, b: f32 = 1, a: f32 = 1, duration: f32 = 0): void {
+    debug_sphere(x, y, z, radius, r, g, b, a, duration);
+  }
+
+  // Axes of the entity running the script
+  static axes(size: f32 = 1, duration: f32 = 0): void {
+    debug_axes(size, duration);
+  }
+
+  static grid(x: f32, y: f32, z: f32, size: f32, divisions: u32, r: f32 = 0.5, g: f32 = 0.5, b: f32 = 0.5, a: f32 = 1, duration: f32 = 0): void {
+    debug_grid(x, y, z, size, divisions, r, g, b, a, duration);
+  }
+
+  static text3d(text: string, x: f32, y: f32, z: f32, r: f32 = 1, g: f32 = 1, b: f32 = 1, a: f32 = 1, duration: f32 = 0): void {
+    debug_text_3d(text, x, y, z, r, g, b, a, duration);
+  }
+
+  static setEnabled(enabled: bool): void {
+    debug_set_enabled(enabled);
+  }
+}
+
+// =========================================================
+// Fog
+// =========================================================
+
+export class Fog {
+  static setEnabled(enabled: bool): void {
+    fog_set_enabled(enabled);
+  }
+
+  static setColor(r: f32, g: f32, b: f32): void {
+    fog_set_color(r, g, b);
+  }
+
+  static get density(): f32 { return fog_get_density(); }
+  static set density(val: f32) { fog_set_density(val); }
+
+  // Where linear fog starts and reaches full strength
+  static setRange(start: f32, end: f32): void {
+    fog_set_range(start, end);
+  }
+
+  // Fog floor height and how quickly height fog thins above it
+  static setHeight(height: f32, falloff: f32): void {
+    fog_set_height(height, falloff);
+  }
+}
+
+// =========================================================
+// Particles
+// =========================================================
+
+// Controls the emitter on the entity running the script
+export class Particles {
+  // Release count particles on the next frame
+  static burst(count: i32): void {
+    particles_burst(count);
+  }
+
+  static setEnabled(enabled: bool): void {
+    particles_set_enabled(enabled);
+  }
+
+  // Particles per second
+  static setRate(rate: f32): void {
+    particles_set_rate(rate);
+  }
+}
+
+// =========================================================
+// Sprite
+// =========================================================
+
+// Controls the sprite on the entity running the script
+export class Sprite {
+  // Start a clip from the sprite sheet; calling it again while the clip
+  // plays doesn't restart it
+  static play(name: string): void {
+    sprite_play(name);
+  }
+
+  // Hold the current frame
+  static stop(): void {
+    sprite_stop();
+  }
+
+  static setFlip(flipX: bool, flipY: bool): void {
+    sprite_set_flip(flipX, flipY);
+  }
+
+  static setTint(r: f32, g: f32, b: f32, a: f32): void {
+    sprite_set_tint(r, g, b, a);
+  }
+}
+
 // =========================================================
 // self() accessor
 // =========================================================
@@ -163,3 +415,150 @@ export function init(): void {
 export function update(deltaTime: f32): void {
   // Called every frame with delta time in seconds
 }
+
+// =========================================================
+// Tilemap
+// =========================================================
+
+// Reads and edits tilemap layers. Cells count from the top left, gids
+// number tiles across the map's tilesets like in Tiled, 0 is empty.
+export class Tilemap {
+  // Gid of a cell, -1 outside the layer
+  static getTile(layer: string, x: i32, y: i32): i32 {
+    return tilemap_get_tile(layer, x, y);
+  }
+
+  // 0 clears the cell
+  static setTile(layer: string, x: i32, y: i32, gid: i32): void {
+    tilemap_set_tile(layer, x, y, gid);
+  }
+
+  // Collision flags of the tile in a cell, 0 for none
+  static getCollision(layer: string, x: i32, y: i32): i32 {
+    return tilemap_get_collision(layer, x, y);
+  }
+
+  // Collision flags of the cell under a world position
+  static collisionAt(layer: string, x: f32, y: f32): i32 {
+    return tilemap_collision_at(layer, x, y);
+  }
+}
+
+// =========================================================
+// Text
+// =========================================================
+
+// World-space text on this entity
+export class Text {
+  static set(text: string): void {
+    text_set(text);
+  }
+
+  // Linear RGBA
+  static setColor(r: f32, g: f32, b: f32, a: f32): void {
+    text_set_color(r, g, b, a);
+  }
+}
+
+// Screen-space labels by name, e.g. Hud.setText("score", "Score: " + score.toString())
+export class Hud {
+  // Adds the label if there is none of that name
+  static setText(name: string, text: string): void {
+    hud_set_text(name, text);
+  }
+
+  // Anchor from (0, 0) top left to (1, 1) bottom right, offset in pixels
+  static setPosition(name: string, anchorX: f32, anchorY: f32, offsetX: f32 = 0, offsetY: f32 = 0): void {
+    hud_set_position(name, anchorX, anchorY, offsetX, offsetY);
+  }
+
+  static setColor(name: string, r: f32, g: f32, b: f32, a: f32 = 1): void {
+    hud_set_color(name, r, g, b, a);
+  }
+
+  // Line height in pixels
+  static setSize(name: string, size: f32): void {
+    hud_set_size(name, size);
+  }
+
+  static setVisible(name: string, visible: bool): void {
+    hud_set_visible(name, visible);
+  }
+
+  static remove(name: string): void {
+    hud_remove(name);
+  }
+}
+
+// =========================================================
+// UI
+// =========================================================
+
+const uiClickIds: string[] = [];
+const uiClickHandlers: (() => void)[] = [];
+
+// Game UI drawn over the frame. Panels stick to an anchor such as
+// "top_left", "center" or "bottom_right"; widgets are found by id.
+export class Ui {
+  static addPanel(name: string, anchor: string, offsetX: f32 = 0, offsetY: f32 = 0): void {
+    ui_add_panel(name, anchor, offsetX, offsetY);
+  }
+
+  static removePanel(name: string): void {
+    ui_remove_panel(name);
+  }
+
+  static setPanelVisible(name: string, visible: bool): void {
+    ui_set_panel_visible(name, visible);
+  }
+
+  static addLabel(panel: string, id: string, text: string): void {
+    ui_add_label(panel, id, text);
+  }
+
+  static addButton(panel: string, id: string, text: string): void {
+    ui_add_button(panel, id, text);
+  }
+
+  // value from 0 to 1
+  static addProgress(panel: string, id: string, value: f32): void {
+    ui_add_progress(panel, id, value);
+  }
+
+  static setText(id: string, text: string): void {
+    ui_set_text(id, text);
+  }
+
+  static setProgress(id: string, value: f32): void {
+    ui_set_progress(id, value);
+  }
+
+  static setEnabled(id: string, enabled: bool): void {
+    ui_set_enabled(id, enabled);
+  }
+
+  static removeWidget(id: string): void {
+    ui_remove_widget(id);
+  }
+
+  // True during the update after the button was clicked
+  static wasClicked(id: string): bool {
+    return ui_was_clicked(id);
+  }
+
+  // Run handler after the update following a click on the button. Needs
+  // dispatchUiEvents exported from the script.
+  static onClick(id: string, handler: () => void): void {
+    uiClickIds.push(id);
+    uiClickHandlers.push(handler);
+  }
+}
+
+// Called by the engine after update when buttons were clicked
+export function dispatchUiEvents(): void {
+  for (let i = 0; i < uiClickIds.length; i++) {
+    if (ui_was_clicked(uiClickIds[i])) {
+      uiClickHandlers[i]();
+    }
+  }
+}
@@ -1,4 +1,4 @@
 // assembly/index.ts
 export { init, update } from "./mover";
-export { setCurrentEntity } from "./context";
+export { setCurrentEntity, dispatchUiEvents } from "./context";
 